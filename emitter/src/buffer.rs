@@ -1,5 +1,8 @@
 use crate::log_entry::LogEntry;
+use crate::metrics;
+use crate::processor::{self, Processor};
 use crate::sink::Sink;
+use futures::future::join_all;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::info;
@@ -7,8 +10,15 @@ use tracing::info;
 pub struct Buffer {
     rx: mpsc::Receiver<LogEntry>,
     sinks: Vec<Box<dyn Sink>>,
+    processors: Vec<Box<dyn Processor>>,
     capacity: usize,
     flush_interval: Duration,
+    /// Upper bound on a single sink's write, so one slow backend can't stall a flush
+    sink_timeout: Option<Duration>,
+    /// Flush once the batch's estimated size reaches this many bytes
+    max_batch_bytes: Option<usize>,
+    /// Entries removed by the processors so far
+    dropped_by_processors: u64,
 }
 
 impl Buffer {
@@ -17,17 +27,31 @@ impl Buffer {
         sinks: Vec<Box<dyn Sink>>,
         capacity: usize,
         flush_interval: Duration,
+        sink_timeout: Option<Duration>,
+        max_batch_bytes: Option<usize>,
     ) -> Self {
         Self {
             rx,
             sinks,
+            processors: Vec::new(),
             capacity,
             flush_interval,
+            sink_timeout,
+            max_batch_bytes,
+            dropped_by_processors: 0,
         }
     }
 
+    /// Run each batch through `processors` before writing it out
+    pub fn with_processors(mut self, processors: Vec<Box<dyn Processor>>) -> Self {
+        self.processors = processors;
+        self
+    }
+
     pub async fn run(&mut self) {
         let mut entries = Vec::with_capacity(self.capacity);
+
+        let mut batch_bytes = 0;
         let mut last_flush = Instant::now();
 
         loop {
@@ -35,9 +59,14 @@ impl Buffer {
 
             match tokio::time::timeout(timeout, self.rx.recv()).await {
                 Ok(Some(entry)) => {
+                    batch_bytes += entry.estimated_size();
                     entries.push(entry);
-                    if entries.len() >= self.capacity {
+                    metrics::set_buffered(entries.len());
+                    let full = entries.len() >= self.capacity
+                        || self.max_batch_bytes.is_some_and(|max| batch_bytes >= max);
+                    if full {
                         self.flush(&mut entries).await;
+                        batch_bytes = 0;
                         last_flush = Instant::now();
                     }
                 }
@@ -46,26 +75,64 @@ impl Buffer {
                     if !entries.is_empty() {
                         self.flush(&mut entries).await;
                     }
+                    self.close().await;
                     break;
                 }
                 Err(_) => {
                     // Timer expired — flush whatever we have
                     if !entries.is_empty() {
                         self.flush(&mut entries).await;
-                        last_flush = Instant::now();
+                        batch_bytes = 0;
                     }
+                    // restart the timer even with nothing to flush, or an
+                    // idle (e.g. paused) run spins on a zero timeout
+                    last_flush = Instant::now();
                 }
             }
         }
     }
 
-    async fn flush(&self, entries: &mut Vec<LogEntry>) {
+    pub fn dropped_by_processors(&self) -> u64 {
+        self.dropped_by_processors
+    }
+
+    async fn flush(&mut self, entries: &mut Vec<LogEntry>) {
         let batch = std::mem::replace(entries, Vec::with_capacity(self.capacity));
-        for sink in &self.sinks {
-            if let Err(e) = sink.write(&batch).await {
-                eprintln!("Sink error: {e}");
+        metrics::set_buffered(0);
+        let received = batch.len();
+        let batch = processor::apply(&self.processors, batch);
+        metrics::entries_dropped("processor", received - batch.len());
+        self.dropped_by_processors += (received - batch.len()) as u64;
+        if batch.is_empty() {
+            return;
+        }
+
+        // write to every sink concurrently so a slow one doesn't delay the rest
+        let results = join_all(self.sinks.iter().map(|sink| async {
+            match self.sink_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, sink.write(&batch))
+                    .await
+                    .unwrap_or_else(|_| Err(format!("write timed out after {timeout:?}").into())),
+                None => sink.write(&batch).await,
+            }
+        }))
+        .await;
+        for (i, result) in results.into_iter().enumerate() {
+            if let Err(e) = result {
+                eprintln!("Sink {i} error: {e}");
             }
         }
         info!("Flushed {} logs to {} sinks", batch.len(), self.sinks.len());
     }
+
+    async fn close(&self) {
+        for sink in &self.sinks {
+            if let Err(e) = sink.flush().await {
+                eprintln!("Sink flush error: {e}");
+            }
+            if let Err(e) = sink.close().await {
+                eprintln!("Sink close error: {e}");
+            }
+        }
+    }
 }
@@ -0,0 +1,141 @@
+use std::collections::{HashMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ServiceConfig;
+use crate::emitter::MessagePool;
+use crate::log_entry::LogLevel;
+
+/// Most services an error travels through, the first included
+pub const MAX_CHAIN: usize = 3;
+
+fn default_rate() -> f64 {
+    0.5
+}
+
+fn default_within_ms() -> u64 {
+    2000
+}
+
+fn default_escalate() -> f64 {
+    0.3
+}
+
+/// Services whose own entries follow from another's errors: each ERROR
+/// `from` logs may be echoed, shortly after, by a WARN or ERROR in each of
+/// `to` that quotes it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CascadeConfig {
+    pub from: String,
+    /// The services calling `from`, which see its failures
+    pub to: Vec<String>,
+    /// Share of `from`'s errors that cascade (0.0 - 1.0)
+    #[serde(default = "default_rate")]
+    pub rate: f64,
+    /// Longest delay between the error and each entry it sets off
+    #[serde(default = "default_within_ms")]
+    pub within_ms: u64,
+    /// Share of those entries that are errors themselves (and may cascade
+    /// further) rather than warnings
+    #[serde(default = "default_escalate")]
+    pub escalate: f64,
+}
+
+/// The message a dependent logs at `level` when `from` fails with `message`
+pub fn message(level: &LogLevel, from: &str, message: &str) -> String {
+    match level {
+        LogLevel::Error => format!("Call to {from} failed: {message}"),
+        _ => format!("Retrying call to {from} after error: {message}"),
+    }
+}
+
+/// Every message the cascades may log, given the errors each service may
+/// log on its own (from its pool, or `scenario_messages`), which need
+/// embedding up front
+pub fn messages(
+    cascades: &[CascadeConfig],
+    pool: &MessagePool,
+    scenario_messages: &[&str],
+) -> HashSet<String> {
+    let own_errors = |service: &str| -> Vec<String> {
+        pool.for_service(service)
+            .for_level(&LogLevel::Error)
+            .iter()
+            .map(String::as_str)
+            .chain(scenario_messages.iter().copied())
+            .map(str::to_string)
+            .collect()
+    };
+    let mut all = HashSet::new();
+    // errors that cascaded into a service in the previous step, which may
+    // cascade on from there
+    let mut cascaded: HashMap<&str, Vec<String>> = HashMap::new();
+    for step in 0..MAX_CHAIN - 1 {
+        let mut next: HashMap<&str, Vec<String>> = HashMap::new();
+        for cascade in cascades {
+            let errors = match step {
+                0 => own_errors(&cascade.from),
+                _ => cascaded
+                    .get(cascade.from.as_str())
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            for error in &errors {
+                all.insert(message(&LogLevel::Warn, &cascade.from, error));
+                let escalated = message(&LogLevel::Error, &cascade.from, error);
+                for to in &cascade.to {
+                    next.entry(to.as_str()).or_default().push(escalated.clone());
+                }
+                all.insert(escalated);
+            }
+        }
+        cascaded = next;
+    }
+    all
+}
+
+/// A pipeline's cascades, looked up by the service they start from
+#[derive(Debug)]
+pub struct Cascades {
+    by_source: HashMap<String, Vec<CascadeConfig>>,
+    /// Every service a cascade reaches
+    services: HashMap<String, ServiceConfig>,
+}
+
+impl Cascades {
+    /// Cascades between `services`; those reaching a service the pipeline
+    /// doesn't emit for give it typical settings
+    pub fn new(cascades: Vec<CascadeConfig>, services: &[ServiceConfig]) -> Self {
+        let mut by_source: HashMap<String, Vec<CascadeConfig>> = HashMap::new();
+        let mut reached = HashMap::new();
+        for cascade in cascades {
+            for to in &cascade.to {
+                let service = services
+                    .iter()
+                    .find(|service| &service.name == to)
+                    .cloned()
+                    .unwrap_or_else(|| ServiceConfig::passive(to));
+                reached.insert(to.clone(), service);
+            }
+            by_source
+                .entry(cascade.from.clone())
+                .or_default()
+                .push(cascade);
+        }
+        Self {
+            by_source,
+            services: reached,
+        }
+    }
+
+    /// The cascades starting from `service`
+    pub fn starting_at(&self, service: &str) -> &[CascadeConfig] {
+        self.by_source.get(service).map_or(&[], Vec::as_slice)
+    }
+
+    /// A service some cascade reaches
+    pub fn service(&self, name: &str) -> Option<&ServiceConfig> {
+        self.services.get(name)
+    }
+}
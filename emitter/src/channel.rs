@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tracing::warn;
+
+use crate::log_entry::LogEntry;
+use crate::metrics;
+
+/// What emitters do when the channel to the buffer is full
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Wait for room, delaying the emitter (backpressure)
+    #[default]
+    Block,
+    /// Evict the oldest queued entry to make room
+    DropOldest,
+    /// Discard the entry being sent
+    DropNewest,
+    /// Shed a growing share of entries once the channel is half full
+    Sample,
+}
+
+/// The sending half of the emitter channel, applying the overflow policy so
+/// emitters never wait unless the policy is `block`
+#[derive(Clone)]
+pub struct EntrySender {
+    tx: mpsc::Sender<LogEntry>,
+    policy: OverflowPolicy,
+    generated: Arc<AtomicU64>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// The channel has been closed by the buffer
+#[derive(Debug)]
+pub struct Closed;
+
+impl EntrySender {
+    pub async fn send(&self, entry: LogEntry) -> Result<(), Closed> {
+        self.generated.fetch_add(1, Ordering::Relaxed);
+        match self.policy {
+            // the relay task behind drop_oldest never stops reading, so a
+            // plain send only waits if it falls behind
+            OverflowPolicy::Block | OverflowPolicy::DropOldest => {
+                self.tx.send(entry).await.map_err(|_| Closed)
+            }
+            OverflowPolicy::DropNewest => self.try_send(entry),
+            OverflowPolicy::Sample => {
+                let free = self.tx.capacity() as f64 / self.tx.max_capacity() as f64;
+                if free < 0.5 && !rand::thread_rng().gen_bool(free * 2.0) {
+                    self.drop_entry();
+                    return Ok(());
+                }
+                self.try_send(entry)
+            }
+        }
+    }
+
+    fn try_send(&self, entry: LogEntry) -> Result<(), Closed> {
+        match self.tx.try_send(entry) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.drop_entry();
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => Err(Closed),
+        }
+    }
+
+    fn drop_entry(&self) {
+        metrics::entries_dropped("overflow", 1);
+        if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+            warn!("Emitter channel full, dropping entries ({:?})", self.policy);
+        }
+    }
+
+    /// Counter of entries dropped by the overflow policy, readable without
+    /// holding a sender (which would keep the channel open)
+    pub fn dropped(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.dropped)
+    }
+
+    /// Counter of every entry sent, dropped or not
+    pub fn generated(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.generated)
+    }
+}
+
+/// Create the channel between the emitters and the buffer, holding up to
+/// `capacity` entries
+pub fn channel(capacity: usize, policy: OverflowPolicy) -> (EntrySender, mpsc::Receiver<LogEntry>) {
+    let capacity = capacity.max(1);
+    let dropped = Arc::new(AtomicU64::new(0));
+
+    let (tx, rx) = mpsc::channel(capacity);
+    let rx = match policy {
+        OverflowPolicy::DropOldest => {
+            // the queued entries live in the relay, which can evict from
+            // the front; mpsc can only refuse new ones
+            let (out_tx, out_rx) = mpsc::channel(1);
+            tokio::spawn(relay(rx, out_tx, capacity, Arc::clone(&dropped)));
+            out_rx
+        }
+        _ => rx,
+    };
+    (
+        EntrySender {
+            tx,
+            policy,
+            generated: Arc::new(AtomicU64::new(0)),
+            dropped,
+        },
+        rx,
+    )
+}
+
+async fn relay(
+    mut rx: mpsc::Receiver<LogEntry>,
+    tx: mpsc::Sender<LogEntry>,
+    capacity: usize,
+    dropped: Arc<AtomicU64>,
+) {
+    let mut queue: VecDeque<LogEntry> = VecDeque::with_capacity(capacity);
+    loop {
+        if queue.is_empty() {
+            let Some(entry) = rx.recv().await else {
+                return;
+            };
+            queue.push_back(entry);
+            continue;
+        }
+
+        tokio::select! {
+            entry = rx.recv() => match entry {
+                Some(entry) => {
+                    if queue.len() >= capacity {
+                        queue.pop_front();
+                        metrics::entries_dropped("overflow", 1);
+                        if dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                            warn!("Emitter channel full, dropping entries (DropOldest)");
+                        }
+                    }
+                    queue.push_back(entry);
+                }
+                None => break,
+            },
+            permit = tx.reserve() => match permit {
+                Ok(permit) => permit.send(queue.pop_front().expect("queue is not empty")),
+                Err(_) => return,
+            },
+        }
+    }
+
+    // the emitters are done; hand over what's left
+    for entry in queue {
+        if tx.send(entry).await.is_err() {
+            return;
+        }
+    }
+}
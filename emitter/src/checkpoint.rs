@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::PipelineConfig;
+use crate::emitter::{LivePool, MessagePool};
+use crate::replay;
+
+fn default_interval_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckpointConfig {
+    /// File the run's progress is saved to, and resumed from if present
+    pub path: PathBuf,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+/// A snapshot of a run, enough to carry on with the same dataset after a
+/// crash or restart
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Run time covered so far, across every resumed run
+    pub elapsed_secs: u64,
+    /// The message pools, so resumed runs draw from the same messages
+    pub pool: MessagePool,
+    /// Entries generated so far, by pipeline and then by service or flow
+    pub generated: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl Checkpoint {
+    /// Read the checkpoint at `path`, if there is one
+    pub fn load(path: &Path) -> std::io::Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Total entries generated across all pipelines
+    pub fn total_generated(&self) -> u64 {
+        self.generated
+            .values()
+            .flat_map(|services| services.values())
+            .sum()
+    }
+}
+
+/// Live per-service (and per-flow and replay) generated counts, seeded from a
+/// checkpoint when resuming
+pub struct Progress {
+    counters: BTreeMap<String, BTreeMap<String, Arc<AtomicU64>>>,
+    resumed_secs: u64,
+    started: Instant,
+}
+
+impl Progress {
+    pub fn new(pipelines: &[PipelineConfig], resumed: Option<&Checkpoint>) -> Arc<Self> {
+        let counters = pipelines
+            .iter()
+            .map(|pipeline| {
+                let saved = resumed.and_then(|cp| cp.generated.get(&pipeline.name));
+                let services = pipeline
+                    .services
+                    .iter()
+                    .map(|service| service.name.as_str())
+                    .chain(pipeline.flows.iter().map(|flow| flow.name.as_str()))
+                    .chain(pipeline.replay.as_ref().map(|_| replay::SOURCE))
+                    .map(|name| {
+                        let count = saved.and_then(|s| s.get(name)).copied().unwrap_or(0);
+                        (name.to_string(), Arc::new(AtomicU64::new(count)))
+                    })
+                    .collect();
+                (pipeline.name.clone(), services)
+            })
+            .collect();
+        Arc::new(Self {
+            counters,
+            resumed_secs: resumed.map_or(0, |cp| cp.elapsed_secs),
+            started: Instant::now(),
+        })
+    }
+
+    /// The counter a service's (or flow's) emitter bumps for each entry it
+    /// generates
+    pub fn counter(&self, pipeline: &str, source: &str) -> Arc<AtomicU64> {
+        self.counters
+            .get(pipeline)
+            .and_then(|services| services.get(source))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Run time covered so far, including that of the runs resumed from
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs(self.resumed_secs) + self.started.elapsed()
+    }
+
+    fn snapshot(&self, pool: &MessagePool) -> Checkpoint {
+        Checkpoint {
+            elapsed_secs: self.elapsed().as_secs(),
+            pool: pool.clone(),
+            generated: self
+                .counters
+                .iter()
+                .map(|(pipeline, services)| {
+                    let counts = services
+                        .iter()
+                        .map(|(service, count)| (service.clone(), count.load(Ordering::Relaxed)))
+                        .collect();
+                    (pipeline.clone(), counts)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Write the checkpoint every `interval_secs` until the task is aborted.
+/// Each save goes to a temporary file first so a crash mid-write never
+/// leaves a torn checkpoint behind.
+pub async fn run(config: CheckpointConfig, progress: Arc<Progress>, pool: Arc<LivePool>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.max(1)));
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if let Err(e) = save(&config.path, &progress.snapshot(&pool.current().pool)).await {
+            warn!(
+                "Failed to write checkpoint to {}: {e}",
+                config.path.display()
+            );
+        }
+    }
+}
+
+async fn save(path: &Path, checkpoint: &Checkpoint) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(checkpoint)?).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+/// Drop the checkpoint once a run has finished, so the next one starts fresh
+pub fn clear(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -1,18 +1,93 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::cascade::CascadeConfig;
+use crate::channel::OverflowPolicy;
+use crate::checkpoint::CheckpointConfig;
+use crate::control::ControlConfig;
+use crate::drift::DriftConfig;
+use crate::duplicate::DuplicateConfig;
+use crate::embedding::EmbeddingProvider;
+use crate::embedding::lazy::LazyConfig;
+use crate::jitter::JitterConfig;
+use crate::language::MultilingualConfig;
+use crate::log_format::LogFormat;
+use crate::metadata::MetadataConfig;
+use crate::metrics::MetricsConfig;
+use crate::payload::PayloadConfig;
+use crate::pii::PiiConfig;
+use crate::popularity::Popularity;
+use crate::processor::EntryMatch;
+use crate::processor::dedup::DedupConfig;
+use crate::processor::enrich::EnrichConfig;
+use crate::processor::filter::FilterConfig;
+use crate::processor::redact::RedactConfig;
+use crate::processor::sample::SampleConfig;
+use crate::processor::sparse::SparseConfig;
+use crate::processor::transform::TransformConfig;
+use crate::replay::ReplayConfig;
+use crate::scenario::ScenarioConfig;
+use crate::sink::adaptive::AdaptiveBatchConfig;
+use crate::sink::null::NullConfig;
+use crate::sink::rate_limit::RateLimitConfig;
+use crate::sink::retry::RetryConfig;
+use crate::skew::SkewConfig;
+use crate::stack_trace::StackTraceConfig;
+use crate::traffic::{BackfillConfig, TrafficShape};
+use crate::vocabulary::{VocabularyConfig, VocabularyRef};
+use crate::wal::WalConfig;
+
+#[cfg(feature = "cassandra")]
+use crate::sink::cassandra::CassandraConfig;
+#[cfg(feature = "cloudwatch")]
+use crate::sink::cloudwatch::CloudWatchConfig;
 #[cfg(feature = "dashboard")]
 use crate::sink::dashboard::DashboardConfig;
+#[cfg(feature = "datadog")]
+use crate::sink::datadog::DatadogConfig;
 #[cfg(feature = "elasticsearch")]
 use crate::sink::elasticsearch::ElasticSearchConfig;
+#[cfg(feature = "exec")]
+use crate::sink::exec::ExecConfig;
+#[cfg(feature = "fluentd")]
+use crate::sink::fluentd::FluentdConfig;
+#[cfg(feature = "gelf")]
+use crate::sink::gelf::GelfConfig;
+#[cfg(feature = "http")]
+use crate::sink::http::HttpConfig;
+#[cfg(feature = "lancedb")]
+use crate::sink::lancedb::LanceDbConfig;
+#[cfg(feature = "otlp")]
+use crate::sink::otlp::OtlpConfig;
 #[cfg(feature = "pgvector")]
 use crate::sink::pgvector::PgvectorConfig;
 #[cfg(feature = "qdrant")]
 use crate::sink::qdrant::QdrantConfig;
+#[cfg(feature = "socket")]
+use crate::sink::socket::SocketConfig;
+#[cfg(feature = "splunk")]
+use crate::sink::splunk::SplunkConfig;
+#[cfg(feature = "syslog")]
+use crate::sink::syslog::SyslogConfig;
+#[cfg(feature = "vespa")]
+use crate::sink::vespa::VespaConfig;
+
+/// 0 runs until stopped
+fn default_run_duration_secs() -> u64 {
+    0
+}
 
 fn default_message_pool_size() -> usize {
     10_000
 }
 
+fn default_channel_capacity() -> usize {
+    10_000
+}
+
 fn default_embedding_model() -> String {
     "text-embedding-3-small".to_string()
 }
@@ -21,19 +96,74 @@ fn default_embedding_dimensions() -> u32 {
     1536
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_embedding_concurrency() -> usize {
+    4
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EmbeddingConfig {
+    /// openai (the default), cohere, tei or ollama; or none, to leave
+    /// entries without embeddings
+    #[serde(default)]
+    pub provider: EmbeddingProvider,
+    /// Not needed by local TEI and Ollama servers
+    #[serde(default)]
     pub api_key: String,
+    /// The provider's address, if not its usual one (an OpenAI-compatible
+    /// API, or a TEI or Ollama server elsewhere)
+    pub url: Option<String>,
     #[serde(default = "default_embedding_model")]
     pub model: String,
+    /// Must match what the model returns, as sinks are set up with it
     #[serde(default = "default_embedding_dimensions")]
     pub dimensions: u32,
+    /// Messages per request, below the provider's limit: smaller requests
+    /// for a self-hosted server with little memory, or one request per GPU
+    /// batch
+    pub batch_size: Option<usize>,
+    /// Requests in flight at once while embedding the pool at startup
+    #[serde(default = "default_embedding_concurrency")]
+    pub concurrency: usize,
+    /// Cut embeddings down to their first N dimensions (renormalized) for
+    /// every sink, for models trained to allow it; sinks can cut further
+    pub truncate_dim: Option<u32>,
+    /// Scale embeddings to unit length, as some models don't, so cosine,
+    /// dot product and Euclidean distance rank them alike
+    #[serde(default)]
+    pub normalize: bool,
+    /// Embed messages as they're generated, through a cache, instead of
+    /// the whole pool at startup
+    pub lazy: Option<LazyConfig>,
+    /// Keep embeddings on disk here, so later runs with the same model
+    /// only embed messages they haven't seen
+    pub cache_dir: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl EmbeddingConfig {
+    /// Whether entries are embedded at all
+    pub fn enabled(&self) -> bool {
+        self.provider != EmbeddingProvider::None
+    }
+
+    /// Dimensions of the embeddings sinks receive, after any truncation;
+    /// 0 with embedding switched off
+    pub fn output_dim(&self) -> usize {
+        if !self.enabled() {
+            return 0;
+        }
+        self.truncate_dim
+            .unwrap_or(self.dimensions)
+            .min(self.dimensions) as usize
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum SinkConfig {
     Stdout {},
+    /// `type: null` would be read as a YAML null, so this is tagged `blackhole`
+    #[serde(rename = "blackhole")]
+    Null(NullConfig),
     #[cfg(feature = "qdrant")]
     Qdrant(QdrantConfig),
     #[cfg(feature = "elasticsearch")]
@@ -41,30 +171,367 @@ pub enum SinkConfig {
     ElasticSearch(ElasticSearchConfig),
     #[cfg(feature = "pgvector")]
     Pgvector(PgvectorConfig),
+    #[cfg(feature = "lancedb")]
+    Lancedb(LanceDbConfig),
+    #[cfg(feature = "vespa")]
+    Vespa(VespaConfig),
+    #[cfg(feature = "fluentd")]
+    Fluentd(FluentdConfig),
+    #[cfg(feature = "syslog")]
+    Syslog(SyslogConfig),
+    #[cfg(feature = "otlp")]
+    Otlp(OtlpConfig),
+    #[cfg(feature = "splunk")]
+    Splunk(SplunkConfig),
+    #[cfg(feature = "datadog")]
+    Datadog(DatadogConfig),
+    #[cfg(feature = "gelf")]
+    Gelf(GelfConfig),
+    #[cfg(feature = "cloudwatch")]
+    Cloudwatch(CloudWatchConfig),
+    #[cfg(feature = "cassandra")]
+    Cassandra(CassandraConfig),
+    #[cfg(feature = "socket")]
+    Socket(SocketConfig),
+    #[cfg(feature = "http")]
+    Http(HttpConfig),
+    #[cfg(feature = "exec")]
+    Exec(ExecConfig),
     #[cfg(feature = "dashboard")]
     Dashboard(DashboardConfig),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EmitterConfig {
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProcessorConfig {
+    Filter(FilterConfig),
+    Transform(TransformConfig),
+    Enrich(EnrichConfig),
+    Redact(RedactConfig),
+    Sample(SampleConfig),
+    Dedup(DedupConfig),
+    Sparse(SparseConfig),
+}
+
+impl SinkConfig {
+    /// The sink's `type` tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SinkConfig::Stdout {} => "stdout",
+            SinkConfig::Null(_) => "blackhole",
+            #[cfg(feature = "qdrant")]
+            SinkConfig::Qdrant(_) => "qdrant",
+            #[cfg(feature = "elasticsearch")]
+            SinkConfig::ElasticSearch(_) => "elasticsearch",
+            #[cfg(feature = "pgvector")]
+            SinkConfig::Pgvector(_) => "pgvector",
+            #[cfg(feature = "lancedb")]
+            SinkConfig::Lancedb(_) => "lancedb",
+            #[cfg(feature = "vespa")]
+            SinkConfig::Vespa(_) => "vespa",
+            #[cfg(feature = "fluentd")]
+            SinkConfig::Fluentd(_) => "fluentd",
+            #[cfg(feature = "syslog")]
+            SinkConfig::Syslog(_) => "syslog",
+            #[cfg(feature = "otlp")]
+            SinkConfig::Otlp(_) => "otlp",
+            #[cfg(feature = "splunk")]
+            SinkConfig::Splunk(_) => "splunk",
+            #[cfg(feature = "datadog")]
+            SinkConfig::Datadog(_) => "datadog",
+            #[cfg(feature = "gelf")]
+            SinkConfig::Gelf(_) => "gelf",
+            #[cfg(feature = "cloudwatch")]
+            SinkConfig::Cloudwatch(_) => "cloudwatch",
+            #[cfg(feature = "cassandra")]
+            SinkConfig::Cassandra(_) => "cassandra",
+            #[cfg(feature = "socket")]
+            SinkConfig::Socket(_) => "socket",
+            #[cfg(feature = "http")]
+            SinkConfig::Http(_) => "http",
+            #[cfg(feature = "exec")]
+            SinkConfig::Exec(_) => "exec",
+            #[cfg(feature = "dashboard")]
+            SinkConfig::Dashboard(_) => "dashboard",
+        }
+    }
+
+    /// The URL of the service the sink writes to, for sinks with one, along
+    /// with the key it's set under
+    pub fn url(&self) -> Option<(&'static str, &str)> {
+        match self {
+            #[cfg(feature = "qdrant")]
+            SinkConfig::Qdrant(config) => Some(("url", &config.url)),
+            #[cfg(feature = "elasticsearch")]
+            SinkConfig::ElasticSearch(config) if config.cloud_id.is_none() => {
+                Some(("url", &config.url))
+            }
+            #[cfg(feature = "vespa")]
+            SinkConfig::Vespa(config) => Some(("url", &config.url)),
+            #[cfg(feature = "otlp")]
+            SinkConfig::Otlp(config) => Some(("endpoint", &config.endpoint)),
+            #[cfg(feature = "splunk")]
+            SinkConfig::Splunk(config) => Some(("url", &config.url)),
+            #[cfg(feature = "datadog")]
+            SinkConfig::Datadog(config) => config.url.as_deref().map(|url| ("url", url)),
+            #[cfg(feature = "http")]
+            SinkConfig::Http(config) => Some(("url", &config.url)),
+            _ => None,
+        }
+    }
+}
+
+/// A sink along with the options applied around it, independent of its type
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SinkSpec {
+    #[serde(flatten)]
+    pub sink: SinkConfig,
+    /// Name used in logs and metrics, defaulting to the sink's type
+    pub name: Option<String>,
+    /// Retry failed writes instead of dropping the batch
+    pub retry: Option<RetryConfig>,
+    /// Only write the entries matching these conditions to this sink
+    pub route: Option<EntryMatch>,
+    /// Throttle writes to simulate an ingestion quota
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Split flushes into batches sized by observed write latency
+    pub adaptive_batch: Option<AdaptiveBatchConfig>,
+    /// Applied to batches on their way to this sink only, after the
+    /// pipeline's processors
+    #[serde(default)]
+    pub processors: Vec<ProcessorConfig>,
+    /// Store only the first N dimensions of embeddings (renormalized), so
+    /// one run can fill collections of several sizes
+    pub truncate_dim: Option<usize>,
+    /// Embed entries just before this sink with this model, instead of
+    /// (or as well as) in the emitter; pair with `embedding.provider: none`
+    /// to ship only text to the others
+    pub embedding: Option<EmbeddingConfig>,
+}
+
+impl SinkSpec {
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.sink.kind())
+    }
+}
+
+fn default_pipeline_name() -> String {
+    "default".to_string()
+}
+
+fn default_buffer_size() -> usize {
+    1000
+}
+
+fn default_flush_interval_ms() -> u64 {
+    5000
+}
+
+/// Services generating entries, and the buffer and sinks they're written to
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PipelineConfig {
+    #[serde(default = "default_pipeline_name")]
+    pub name: String,
+    #[serde(default = "default_buffer_size")]
     pub buffer_size: usize,
+    #[serde(default = "default_flush_interval_ms")]
     pub flush_interval_ms: u64,
+    #[serde(default)]
+    pub services: Vec<ServiceConfig>,
+    /// Simulated requests passing through several services, logged with a
+    /// shared trace id
+    #[serde(default)]
+    pub flows: Vec<FlowConfig>,
+    /// Anomalies injected at set times during the run
+    #[serde(default)]
+    pub scenarios: Vec<ScenarioConfig>,
+    /// Errors in one service echoed by the services depending on it
+    #[serde(default)]
+    pub cascades: Vec<CascadeConfig>,
+    /// Entries read from an existing log file, embedded as they're read
+    pub replay: Option<ReplayConfig>,
+    #[serde(default)]
+    pub sinks: Vec<SinkSpec>,
+    /// Applied in order to each batch before it's written to the sinks
+    #[serde(default)]
+    pub processors: Vec<ProcessorConfig>,
+    /// Per-sink timeout for each flush; a sink exceeding it has the batch
+    /// reported as failed while the others carry on
+    pub sink_timeout_ms: Option<u64>,
+    /// Also flush once a batch's estimated size reaches this many bytes,
+    /// embeddings included, to keep bulk requests within backend limits
+    pub max_batch_bytes: Option<usize>,
+    /// Entries queued between the emitters and the buffer
+    #[serde(default = "default_channel_capacity")]
+    pub channel_capacity: usize,
+    /// What emitters do when that queue is full
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+    /// Spill entries the buffer can't keep up with to disk
+    pub wal: Option<WalConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmitterConfig {
+    /// Config files merged under this one as it's loaded, e.g. shared sinks
+    /// or services: paths relative to this file, each over the ones before
+    /// it, and this file over them all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+    /// 0 (the default) runs until stopped
+    #[serde(default = "default_run_duration_secs")]
     pub run_duration_secs: u64,
     #[serde(default = "default_message_pool_size")]
     pub message_pool_size: usize,
-    pub services: Vec<ServiceConfig>,
-    pub sinks: Vec<SinkConfig>,
+    /// Slot lists and templates the message pool is generated from, on top
+    /// of the built-in ones
+    #[serde(default)]
+    pub vocabulary: VocabularyConfig,
+    /// Vocabularies services can refer to by name
+    #[serde(default)]
+    pub vocabularies: HashMap<String, VocabularyConfig>,
+    /// Generate a share of messages in other languages and scripts
+    pub multilingual: Option<MultilingualConfig>,
+    /// How often each pool message is picked
+    #[serde(default)]
+    pub popularity: Popularity,
+    /// How entries logging the same message get embeddings of their own:
+    /// noise (the default), none, or a paraphrase embedded for real
+    #[serde(default)]
+    pub jitter: JitterConfig,
+    /// Retire part of the pool now and then for new messages, so what's
+    /// logged changes over a long run
+    pub drift: Option<DriftConfig>,
+    /// Generate a past window as fast as the sinks take it, instead of
+    /// running in real time
+    pub backfill: Option<BackfillConfig>,
+    /// Seed for every generator RNG, so runs with the same config repeat
+    /// the same entries
+    pub seed: Option<u64>,
+    /// Re-send some entries under the same id, like an at-least-once
+    /// shipper
+    pub duplicates: Option<DuplicateConfig>,
+    /// Kubernetes and host attributes on every entry
+    pub metadata: Option<MetadataConfig>,
+    /// Emails, IPs and card numbers in some entries, for redaction demos
+    pub pii: Option<PiiConfig>,
+    /// Very long messages and large JSON fields in some entries, for
+    /// testing sinks' size limits
+    pub payload: Option<PayloadConfig>,
+    /// Attach synthetic stack traces to some ERROR entries
+    pub stack_traces: Option<StackTraceConfig>,
+    /// The pipeline configured at the top level, used when `pipelines` is empty
+    #[serde(flatten)]
+    pub pipeline: PipelineConfig,
+    /// Independent pipelines run side by side, sharing the message pool
+    #[serde(default)]
+    pub pipelines: Vec<PipelineConfig>,
+    /// Carry on without sinks that fail to initialize instead of aborting
+    #[serde(default)]
+    pub fail_open: bool,
+    /// Also write the end-of-run report to this JSON file
+    pub report_path: Option<PathBuf>,
+    /// Serve Prometheus metrics (needs the `metrics` feature)
+    pub metrics: Option<MetricsConfig>,
+    /// Serve an API for changing rates and stopping the run (needs the
+    /// `control` feature)
+    pub control: Option<ControlConfig>,
+    /// Periodically save progress so an interrupted run can pick up where it
+    /// left off
+    pub checkpoint: Option<CheckpointConfig>,
     pub embedding: EmbeddingConfig,
+    /// Further models embedding every entry alongside `embedding`, by the
+    /// name their vectors are stored under (a plain identifier, as it
+    /// becomes a pgvector column)
+    #[serde(default)]
+    pub extra_embeddings: BTreeMap<String, EmbeddingConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl EmitterConfig {
+    /// The pipelines to run
+    pub fn pipelines(&self) -> Vec<PipelineConfig> {
+        if self.pipelines.is_empty() {
+            vec![self.pipeline.clone()]
+        } else {
+            self.pipelines.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ServiceConfig {
     pub name: String,
     pub rate_per_sec: f64,
     pub level_weights: LogLevelWeights,
+    /// Diurnal swings, bursts and ramps around `rate_per_sec`
+    #[serde(flatten)]
+    pub traffic: TrafficShape,
+    /// Messages from this vocabulary, named or its own, layered over the
+    /// shared one
+    pub vocabulary: Option<VocabularyRef>,
+    /// Share of messages about each of these components, e.g. mostly
+    /// `AuthManager` and `SessionStore` for an auth service; the rest are
+    /// spread over the vocabulary's other components
+    #[serde(default)]
+    pub component_affinity: BTreeMap<String, f64>,
+    /// How messages are rendered: as they are, or as access, syslog,
+    /// logfmt or JSON lines
+    #[serde(default)]
+    pub format: LogFormat,
+    /// Clock offset and late, out-of-order entries
+    #[serde(flatten)]
+    pub skew: SkewConfig,
+    /// Emitter tasks sharing the rate, for rates in the thousands per
+    /// second
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+}
+
+fn default_workers() -> usize {
+    1
+}
+
+impl ServiceConfig {
+    /// A service that only logs when something else involves it (a flow
+    /// passing through, a cascading error), with typical level weights
+    pub fn passive(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rate_per_sec: 0.0,
+            level_weights: LogLevelWeights {
+                debug: 0.1,
+                info: 0.7,
+                warn: 0.15,
+                error: 0.05,
+            },
+            traffic: TrafficShape::default(),
+            vocabulary: None,
+            component_affinity: BTreeMap::new(),
+            format: LogFormat::default(),
+            skew: SkewConfig::default(),
+            workers: default_workers(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_hop_delay_ms() -> u64 {
+    20
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FlowConfig {
+    pub name: String,
+    /// Requests started per second
+    pub rate_per_sec: f64,
+    /// Services each request passes through, in order; each logs one entry
+    /// in a child span of the one before
+    pub path: Vec<String>,
+    /// Mean delay between one service's entry and the next
+    #[serde(default = "default_hop_delay_ms")]
+    pub hop_delay_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LogLevelWeights {
     pub debug: f64,
     pub info: f64,
@@ -75,58 +542,138 @@ pub struct LogLevelWeights {
 impl Default for EmitterConfig {
     fn default() -> Self {
         Self {
-            buffer_size: 1000,
-            flush_interval_ms: 5000,
-            run_duration_secs: 30,
+            include: Vec::new(),
+            run_duration_secs: default_run_duration_secs(),
             message_pool_size: default_message_pool_size(),
-            sinks: vec![SinkConfig::Stdout {}],
+            vocabulary: VocabularyConfig::default(),
+            vocabularies: HashMap::new(),
+            multilingual: None,
+            popularity: Popularity::default(),
+            jitter: JitterConfig::default(),
+            drift: None,
+            backfill: None,
+            seed: None,
+            duplicates: None,
+            metadata: None,
+            pii: None,
+            payload: None,
+            stack_traces: None,
+            pipeline: PipelineConfig {
+                name: default_pipeline_name(),
+                buffer_size: default_buffer_size(),
+                flush_interval_ms: default_flush_interval_ms(),
+                services: default_services(),
+                flows: Vec::new(),
+                scenarios: Vec::new(),
+                cascades: Vec::new(),
+                replay: None,
+                sinks: vec![SinkSpec {
+                    sink: SinkConfig::Stdout {},
+                    name: None,
+                    retry: None,
+                    route: None,
+                    rate_limit: None,
+                    adaptive_batch: None,
+                    processors: Vec::new(),
+                    truncate_dim: None,
+                    embedding: None,
+                }],
+                processors: Vec::new(),
+                sink_timeout_ms: None,
+                max_batch_bytes: None,
+                channel_capacity: default_channel_capacity(),
+                overflow: OverflowPolicy::default(),
+                wal: None,
+            },
+            pipelines: Vec::new(),
+            fail_open: false,
+            report_path: None,
+            metrics: None,
+            control: None,
+            checkpoint: None,
             embedding: EmbeddingConfig {
+                provider: EmbeddingProvider::default(),
                 api_key: std::env::var("OPENAI_API_KEY").unwrap_or_default(),
+                url: None,
                 model: default_embedding_model(),
                 dimensions: default_embedding_dimensions(),
+                batch_size: None,
+                concurrency: default_embedding_concurrency(),
+                truncate_dim: None,
+                normalize: false,
+                lazy: None,
+                cache_dir: None,
             },
-            services: vec![
-                ServiceConfig {
-                    name: "api-gateway".into(),
-                    rate_per_sec: 100.0,
-                    level_weights: LogLevelWeights {
-                        debug: 0.1,
-                        info: 0.7,
-                        warn: 0.15,
-                        error: 0.05,
-                    },
-                },
-                ServiceConfig {
-                    name: "auth-service".into(),
-                    rate_per_sec: 50.0,
-                    level_weights: LogLevelWeights {
-                        debug: 0.05,
-                        info: 0.6,
-                        warn: 0.2,
-                        error: 0.15,
-                    },
-                },
-                ServiceConfig {
-                    name: "payment-service".into(),
-                    rate_per_sec: 30.0,
-                    level_weights: LogLevelWeights {
-                        debug: 0.05,
-                        info: 0.5,
-                        warn: 0.25,
-                        error: 0.2,
-                    },
-                },
-                ServiceConfig {
-                    name: "user-service".into(),
-                    rate_per_sec: 40.0,
-                    level_weights: LogLevelWeights {
-                        debug: 0.1,
-                        info: 0.65,
-                        warn: 0.15,
-                        error: 0.1,
-                    },
-                },
-            ],
+            extra_embeddings: BTreeMap::new(),
         }
     }
 }
+
+fn default_services() -> Vec<ServiceConfig> {
+    vec![
+        ServiceConfig {
+            name: "api-gateway".into(),
+            rate_per_sec: 100.0,
+            level_weights: LogLevelWeights {
+                debug: 0.1,
+                info: 0.7,
+                warn: 0.15,
+                error: 0.05,
+            },
+            traffic: TrafficShape::default(),
+            vocabulary: None,
+            component_affinity: BTreeMap::new(),
+            format: LogFormat::default(),
+            skew: SkewConfig::default(),
+            workers: default_workers(),
+        },
+        ServiceConfig {
+            name: "auth-service".into(),
+            rate_per_sec: 50.0,
+            level_weights: LogLevelWeights {
+                debug: 0.05,
+                info: 0.6,
+                warn: 0.2,
+                error: 0.15,
+            },
+            traffic: TrafficShape::default(),
+            vocabulary: None,
+            component_affinity: BTreeMap::new(),
+            format: LogFormat::default(),
+            skew: SkewConfig::default(),
+            workers: default_workers(),
+        },
+        ServiceConfig {
+            name: "payment-service".into(),
+            rate_per_sec: 30.0,
+            level_weights: LogLevelWeights {
+                debug: 0.05,
+                info: 0.5,
+                warn: 0.25,
+                error: 0.2,
+            },
+            traffic: TrafficShape::default(),
+            vocabulary: None,
+            component_affinity: BTreeMap::new(),
+            format: LogFormat::default(),
+            skew: SkewConfig::default(),
+            workers: default_workers(),
+        },
+        ServiceConfig {
+            name: "user-service".into(),
+            rate_per_sec: 40.0,
+            level_weights: LogLevelWeights {
+                debug: 0.1,
+                info: 0.65,
+                warn: 0.15,
+                error: 0.1,
+            },
+            traffic: TrafficShape::default(),
+            vocabulary: None,
+            component_affinity: BTreeMap::new(),
+            format: LogFormat::default(),
+            skew: SkewConfig::default(),
+            workers: default_workers(),
+        },
+    ]
+}
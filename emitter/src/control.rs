@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::LogLevelWeights;
+use crate::processor::sample::SampleConfig;
+use crate::scenario::{ScenarioConfig, ScenarioStatus, Scenarios};
+
+/// How often a paused emitter looks whether it's been resumed
+const PAUSE_POLL: Duration = Duration::from_millis(100);
+
+/// An HTTP endpoint for changing rates, pausing, firing scenarios and
+/// stopping a run while it's going (needs the `control` feature)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ControlConfig {
+    pub port: u16,
+}
+
+/// A rate in entries (or requests) per second that can be changed while
+/// an emitter runs on it
+#[derive(Debug)]
+pub struct Rate(AtomicU64);
+
+impl Rate {
+    fn new(rate: f64) -> Self {
+        Self(AtomicU64::new(rate.to_bits()))
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, rate: f64) {
+        self.0.store(rate.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// What can be changed about a run while it's going: every emitter's rate,
+/// by pipeline and by service (or `flow:<name>`), services' level weights,
+/// each pipeline's scenarios and sampling, whether it's paused, and
+/// whether it's stopping
+#[derive(Debug, Default)]
+pub struct Control {
+    rates: Mutex<BTreeMap<String, BTreeMap<String, Arc<Rate>>>>,
+    /// Level weights changed since the run started, by pipeline and service
+    level_weights: RwLock<BTreeMap<String, BTreeMap<String, LogLevelWeights>>>,
+    scenarios: Mutex<BTreeMap<String, Arc<Scenarios>>>,
+    /// The settings of each pipeline's sample processors, in order
+    samples: Mutex<BTreeMap<String, Vec<Arc<RwLock<SampleConfig>>>>>,
+    paused: AtomicBool,
+    stopping: AtomicBool,
+}
+
+impl Control {
+    /// The rate `source` of `pipeline` emits at, starting from `initial`
+    pub fn rate(&self, pipeline: &str, source: &str, initial: f64) -> Arc<Rate> {
+        let mut rates = self.rates.lock().unwrap();
+        let rate = rates
+            .entry(pipeline.to_string())
+            .or_default()
+            .entry(source.to_string())
+            .or_insert_with(|| Arc::new(Rate::new(initial)));
+        Arc::clone(rate)
+    }
+
+    /// Every emitter's current rate, by pipeline
+    pub fn rates(&self) -> BTreeMap<String, BTreeMap<String, f64>> {
+        self.rates
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(pipeline, rates)| {
+                let rates = rates
+                    .iter()
+                    .map(|(source, rate)| (source.clone(), rate.get()))
+                    .collect();
+                (pipeline.clone(), rates)
+            })
+            .collect()
+    }
+
+    /// Change the rate of `source`, in `pipeline` or in every pipeline that
+    /// has it. Returns how many emitters were changed.
+    pub fn set_rate(&self, pipeline: Option<&str>, source: &str, rate: f64) -> usize {
+        let rates = self.rates.lock().unwrap();
+        let mut changed = 0;
+        for (name, rates) in rates.iter() {
+            if pipeline.is_some_and(|pipeline| pipeline != name) {
+                continue;
+            }
+            if let Some(current) = rates.get(source) {
+                current.set(rate);
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// The level weights `service` of `pipeline` was changed to, if it was
+    pub fn level_weights(&self, pipeline: &str, service: &str) -> Option<LogLevelWeights> {
+        self.level_weights
+            .read()
+            .unwrap()
+            .get(pipeline)?
+            .get(service)
+            .cloned()
+    }
+
+    pub fn set_level_weights(&self, pipeline: &str, service: &str, weights: LogLevelWeights) {
+        self.level_weights
+            .write()
+            .unwrap()
+            .entry(pipeline.to_string())
+            .or_default()
+            .insert(service.to_string(), weights);
+    }
+
+    /// The scenario schedule of `pipeline`, starting from `initial`
+    pub fn scenarios(
+        &self,
+        pipeline: &str,
+        initial: Vec<ScenarioConfig>,
+        offset: Duration,
+    ) -> Arc<Scenarios> {
+        let scenarios = Arc::new(Scenarios::new(initial, offset));
+        self.scenarios
+            .lock()
+            .unwrap()
+            .insert(pipeline.to_string(), Arc::clone(&scenarios));
+        scenarios
+    }
+
+    /// Replace the scenario schedule of `pipeline`. Returns false if it has
+    /// none to replace.
+    pub fn set_scenarios(&self, pipeline: &str, configs: Vec<ScenarioConfig>) -> bool {
+        match self.scenarios.lock().unwrap().get(pipeline) {
+            Some(scenarios) => {
+                scenarios.replace(configs);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start the scenario called `name` now, in `pipeline` or in every
+    /// pipeline that has one, for `duration` or as long as it's configured
+    /// to last. Returns how many pipelines it was started in.
+    pub fn trigger_scenario(
+        &self,
+        pipeline: Option<&str>,
+        name: &str,
+        duration: Option<Duration>,
+    ) -> usize {
+        self.each_scenarios(pipeline, |scenarios| scenarios.trigger(name, duration))
+    }
+
+    /// End the scenario called `name` now, wherever it's running, in
+    /// `pipeline` or in every pipeline. Returns how many pipelines it was
+    /// running in.
+    pub fn end_scenario(&self, pipeline: Option<&str>, name: &str) -> usize {
+        self.each_scenarios(pipeline, |scenarios| scenarios.end(name))
+    }
+
+    /// Every pipeline's scenarios, and whether each is running
+    pub fn scenario_status(&self) -> BTreeMap<String, Vec<ScenarioStatus>> {
+        self.scenarios
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(pipeline, scenarios)| (pipeline.clone(), scenarios.status()))
+            .collect()
+    }
+
+    /// Apply `change` to the scenarios of `pipeline`, or of every pipeline,
+    /// counting those it applied to
+    fn each_scenarios(&self, pipeline: Option<&str>, change: impl Fn(&Scenarios) -> bool) -> usize {
+        self.scenarios
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| pipeline.is_none_or(|pipeline| pipeline == name.as_str()))
+            .filter(|(_, scenarios)| change(scenarios))
+            .count()
+    }
+
+    /// The settings of the next sample processor of `pipeline`, starting
+    /// from `initial`
+    pub fn sample(&self, pipeline: &str, initial: SampleConfig) -> Arc<RwLock<SampleConfig>> {
+        let sample = Arc::new(RwLock::new(initial));
+        self.samples
+            .lock()
+            .unwrap()
+            .entry(pipeline.to_string())
+            .or_default()
+            .push(Arc::clone(&sample));
+        sample
+    }
+
+    /// Change the settings of the `index`-th sample processor of
+    /// `pipeline`. Returns false if there's no such processor.
+    pub fn set_sample(&self, pipeline: &str, index: usize, config: SampleConfig) -> bool {
+        let samples = self.samples.lock().unwrap();
+        match samples.get(pipeline).and_then(|samples| samples.get(index)) {
+            Some(sample) => {
+                *sample.write().unwrap() = config;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Have the emitters generate nothing until resumed; the run's duration
+    /// keeps counting meanwhile
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Wait until emission is resumed, or the run is stopping, returning
+    /// how long that took
+    pub async fn resumed(&self) -> Duration {
+        let started = Instant::now();
+        while self.is_paused() && !self.is_stopping() {
+            tokio::time::sleep(PAUSE_POLL).await;
+        }
+        started.elapsed()
+    }
+
+    /// Have the emitters wind down, as if the run's duration were up, so
+    /// what they've generated is flushed and reported
+    pub fn stop(&self) {
+        self.stopping.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::Relaxed)
+    }
+}
+
+/// The control API, for its own server or nested under the dashboard's:
+/// - `GET /status`: whether the run is paused or stopping, every emitter's
+///   rate and every pipeline's scenarios
+/// - `GET /rates`: every emitter's rate, by pipeline
+/// - `PUT /rates/{source}`: set a service's (or `flow:<name>`'s) rate from
+///   `{"rate_per_sec": 50.0}`, in every pipeline or in `?pipeline=<name>`
+/// - `POST /pause`, `POST /resume`: stop and restart generating entries
+/// - `POST /scenarios/{name}/trigger`: start a configured scenario now, for
+///   `{"duration_secs": 30}` or as long as configured, in every pipeline
+///   or in `?pipeline=<name>`
+/// - `POST /scenarios/{name}/end`: end a running scenario now
+/// - `POST /stop`: stop the run, flushing what's been generated
+#[cfg(any(feature = "control", feature = "dashboard"))]
+pub fn router(control: Arc<Control>) -> axum::Router {
+    use axum::extract::{Path, Query, State};
+    use axum::http::StatusCode;
+    use axum::routing::{get, post, put};
+    use axum::{Json, Router};
+    use tracing::info;
+
+    #[derive(Deserialize)]
+    struct SetRate {
+        rate_per_sec: f64,
+    }
+
+    #[derive(Deserialize)]
+    struct Scope {
+        pipeline: Option<String>,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct Trigger {
+        duration_secs: Option<u64>,
+    }
+
+    #[derive(Serialize)]
+    struct Status {
+        paused: bool,
+        stopping: bool,
+        rates: BTreeMap<String, BTreeMap<String, f64>>,
+        scenarios: BTreeMap<String, Vec<ScenarioStatus>>,
+    }
+
+    async fn get_status(State(control): State<Arc<Control>>) -> Json<Status> {
+        Json(Status {
+            paused: control.is_paused(),
+            stopping: control.is_stopping(),
+            rates: control.rates(),
+            scenarios: control.scenario_status(),
+        })
+    }
+
+    async fn get_rates(
+        State(control): State<Arc<Control>>,
+    ) -> Json<BTreeMap<String, BTreeMap<String, f64>>> {
+        Json(control.rates())
+    }
+
+    async fn put_rate(
+        State(control): State<Arc<Control>>,
+        Path(source): Path<String>,
+        Query(scope): Query<Scope>,
+        Json(body): Json<SetRate>,
+    ) -> (StatusCode, String) {
+        if !body.rate_per_sec.is_finite() || body.rate_per_sec < 0.0 {
+            return (
+                StatusCode::BAD_REQUEST,
+                "rate_per_sec must be a non-negative number".to_string(),
+            );
+        }
+        match control.set_rate(scope.pipeline.as_deref(), &source, body.rate_per_sec) {
+            0 => (StatusCode::NOT_FOUND, format!("No emitter named {source}")),
+            changed => {
+                info!("Rate of {source} set to {}/s", body.rate_per_sec);
+                (StatusCode::OK, format!("Changed {changed} emitter(s)"))
+            }
+        }
+    }
+
+    async fn post_pause(State(control): State<Arc<Control>>) -> StatusCode {
+        info!("Emission paused through the control API");
+        control.pause();
+        StatusCode::ACCEPTED
+    }
+
+    async fn post_resume(State(control): State<Arc<Control>>) -> StatusCode {
+        info!("Emission resumed through the control API");
+        control.resume();
+        StatusCode::ACCEPTED
+    }
+
+    async fn post_trigger(
+        State(control): State<Arc<Control>>,
+        Path(name): Path<String>,
+        Query(scope): Query<Scope>,
+        body: Option<Json<Trigger>>,
+    ) -> (StatusCode, String) {
+        let Json(body) = body.unwrap_or_default();
+        if body.duration_secs == Some(0) {
+            return (
+                StatusCode::BAD_REQUEST,
+                "duration_secs must be at least 1".to_string(),
+            );
+        }
+        let duration = body.duration_secs.map(Duration::from_secs);
+        match control.trigger_scenario(scope.pipeline.as_deref(), &name, duration) {
+            0 => (StatusCode::NOT_FOUND, format!("No scenario named {name}")),
+            started => {
+                info!("Scenario {name} triggered through the control API");
+                (StatusCode::OK, format!("Started in {started} pipeline(s)"))
+            }
+        }
+    }
+
+    async fn post_end(
+        State(control): State<Arc<Control>>,
+        Path(name): Path<String>,
+        Query(scope): Query<Scope>,
+    ) -> (StatusCode, String) {
+        match control.end_scenario(scope.pipeline.as_deref(), &name) {
+            0 => (
+                StatusCode::NOT_FOUND,
+                format!("Scenario {name} isn't running"),
+            ),
+            ended => {
+                info!("Scenario {name} ended through the control API");
+                (StatusCode::OK, format!("Ended in {ended} pipeline(s)"))
+            }
+        }
+    }
+
+    async fn post_stop(State(control): State<Arc<Control>>) -> StatusCode {
+        info!("Stop requested through the control API");
+        control.stop();
+        StatusCode::ACCEPTED
+    }
+
+    Router::new()
+        .route("/status", get(get_status))
+        .route("/rates", get(get_rates))
+        .route("/rates/{source}", put(put_rate))
+        .route("/pause", post(post_pause))
+        .route("/resume", post(post_resume))
+        .route("/scenarios/{name}/trigger", post(post_trigger))
+        .route("/scenarios/{name}/end", post(post_end))
+        .route("/stop", post(post_stop))
+        .with_state(control)
+}
+
+/// Serve the control API ([`router`]) on the configured port
+#[cfg(feature = "control")]
+pub async fn serve(config: ControlConfig, control: Arc<Control>) {
+    use tracing::{error, info};
+
+    let app = router(control);
+    let addr = format!("0.0.0.0:{}", config.port);
+    info!("Control API available at http://localhost:{}", config.port);
+
+    match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!("Control server error: {e}");
+            }
+        }
+        Err(e) => error!("Failed to bind control server to {addr}: {e}"),
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::EmitterConfig;
+use crate::embedding::EmbeddingService;
+use crate::emitter::{LivePool, PoolSnapshot};
+
+/// Most messages embedded in one request
+const EMBED_BATCH: usize = 2048;
+
+fn default_interval_secs() -> u64 {
+    600
+}
+
+fn default_fraction() -> f64 {
+    0.1
+}
+
+/// The message pool changing as a run goes on, like services being
+/// redeployed with new log lines
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DriftConfig {
+    /// How often the pool changes
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// Share of each pool's messages retired each time, and replaced by
+    /// new ones from the same vocabulary (0.0 - 1.0)
+    #[serde(default = "default_fraction")]
+    pub fraction: f64,
+}
+
+/// Every `interval_secs`, retire part of the pool and embed its replacements,
+/// until the task is aborted. The emitters pick up each new pool for their
+/// next entry.
+pub async fn run(
+    drift: DriftConfig,
+    config: EmitterConfig,
+    pool: Arc<LivePool>,
+    embedder: Arc<EmbeddingService>,
+    mut rng: StdRng,
+) {
+    let pipelines = config.pipelines();
+    let mut interval = tokio::time::interval(Duration::from_secs(drift.interval_secs.max(1)));
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let current = pool.current();
+        let drifted = match current.pool.drift(
+            &mut rng,
+            &config.vocabulary,
+            &config.vocabularies,
+            config.multilingual.as_ref(),
+            pipelines.iter().flat_map(|pipeline| &pipeline.services),
+            drift.fraction,
+        ) {
+            Ok(drifted) => drifted,
+            Err(e) => {
+                warn!("Failed to drift the message pool: {e}");
+                continue;
+            }
+        };
+
+        // embedding lazily, new messages are embedded once they're logged
+        if config.embedding.lazy.is_some() {
+            pool.replace(PoolSnapshot {
+                pool: drifted,
+                embeddings: HashMap::new(),
+            });
+            info!("Message pool drifted");
+            continue;
+        }
+        let needed = drifted.to_embed(&pipelines, &config.jitter);
+        let unseen: Vec<String> = needed
+            .iter()
+            .filter(|message| !current.embeddings.contains_key(*message))
+            .cloned()
+            .collect();
+        let mut fresh = HashMap::with_capacity(unseen.len());
+        for batch in unseen.chunks(EMBED_BATCH) {
+            match embedder.embed_batch(batch).await {
+                Ok(embeddings) => fresh.extend(batch.iter().cloned().zip(embeddings)),
+                Err(e) => {
+                    warn!("Failed to embed drifted messages, keeping the pool as it was: {e}");
+                    break;
+                }
+            }
+        }
+        if fresh.len() < unseen.len() {
+            continue;
+        }
+
+        // retired messages' embeddings go with them
+        let embeddings = needed
+            .into_iter()
+            .filter_map(|message| {
+                let embedding = match current.embeddings.get(&message) {
+                    Some(embedding) => embedding.clone(),
+                    None => fresh.remove(&message)?,
+                };
+                Some((message, embedding))
+            })
+            .collect();
+        pool.replace(PoolSnapshot {
+            pool: drifted,
+            embeddings,
+        });
+        info!("Message pool drifted: {} new messages", unseen.len());
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::LogEntry;
+
+fn default_window() -> usize {
+    100
+}
+
+fn default_keep_timestamp() -> bool {
+    true
+}
+
+/// Re-sends of recent entries under the same id, as an at-least-once
+/// shipper retrying a batch would produce
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateConfig {
+    /// Share of entries followed by a re-send of a recent one (0.0 - 1.0)
+    pub rate: f64,
+    /// How many of an emitter's most recent entries a re-send is picked from
+    #[serde(default = "default_window")]
+    pub window: usize,
+    /// Re-send with the original timestamp; false stamps it with the time
+    /// of the re-send
+    #[serde(default = "default_keep_timestamp")]
+    pub keep_timestamp: bool,
+}
+
+/// An emitter's recent entries, for picking duplicates from
+pub struct Recent<'a> {
+    config: &'a DuplicateConfig,
+    entries: VecDeque<LogEntry>,
+}
+
+impl<'a> Recent<'a> {
+    pub fn new(config: &'a DuplicateConfig) -> Self {
+        Self {
+            config,
+            entries: VecDeque::with_capacity(config.window.max(1)),
+        }
+    }
+
+    /// Remember `entry`, and maybe return a recent entry to send again,
+    /// re-sent at `now`
+    pub fn record(
+        &mut self,
+        entry: &LogEntry,
+        now: DateTime<Utc>,
+        rng: &mut impl Rng,
+    ) -> Option<LogEntry> {
+        if self.entries.len() >= self.config.window.max(1) {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry.clone());
+
+        if !rng.gen_bool(self.config.rate.clamp(0.0, 1.0)) {
+            return None;
+        }
+        let mut duplicate = self.entries[rng.gen_range(0..self.entries.len())].clone();
+        if !self.config.keep_timestamp {
+            duplicate.timestamp = now;
+        }
+        Some(duplicate)
+    }
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{EmbedError, Embedder, post_json};
+use crate::config::EmbeddingConfig;
+
+const DEFAULT_URL: &str = "https://api.cohere.com";
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Embeddings,
+}
+
+#[derive(Deserialize)]
+struct Embeddings {
+    float: Vec<Vec<f32>>,
+}
+
+/// Cohere's v2 embed API. Messages are embedded as documents, the way
+/// they're stored; `dimensions` is passed on for models that can shorten
+/// their embeddings.
+pub struct CohereEmbedder {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    dimensions: u32,
+}
+
+impl CohereEmbedder {
+    pub fn new(config: &EmbeddingConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.as_deref().unwrap_or(DEFAULT_URL).to_string(),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+            dimensions: config.dimensions,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for CohereEmbedder {
+    async fn embed(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let request = self
+            .client
+            .post(format!("{}/v2/embed", self.url.trim_end_matches('/')))
+            .bearer_auth(&self.api_key)
+            .json(&json!({
+                "model": self.model,
+                "texts": batch,
+                "input_type": "search_document",
+                "embedding_types": ["float"],
+                "output_dimension": self.dimensions,
+            }));
+        let response: EmbedResponse = post_json(request, "Cohere").await?;
+        Ok(response.embeddings.float)
+    }
+
+    fn batch_size(&self) -> usize {
+        96
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use tracing::{info, warn};
+
+use crate::config::EmbeddingConfig;
+use crate::emitter::fnv1a;
+
+/// Embeddings kept between runs, one file per provider, model and
+/// dimensions, so a pool embedded once isn't paid for again. Each record is
+/// a message's hash followed by its embedding, appended as they're made.
+pub struct DiskCache {
+    dimensions: usize,
+    entries: Mutex<HashMap<u64, Vec<f32>>>,
+    file: Mutex<File>,
+}
+
+impl DiskCache {
+    /// Open (or start) the cache file for `config`'s model in `dir`,
+    /// dropping any record cut short by an earlier run being killed
+    pub fn open(dir: &Path, config: &EmbeddingConfig) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let name = format!(
+            "{:?}-{}-{}.bin",
+            config.provider, config.model, config.dimensions
+        )
+        .to_lowercase()
+        .replace(['/', '\\', ':'], "_");
+        let path = dir.join(name);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        let dimensions = config.dimensions as usize;
+        let record = 8 + 4 * dimensions;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let whole = bytes.len() / record * record;
+        if whole < bytes.len() {
+            warn!("Dropping a partial record at the end of {}", path.display());
+            file.set_len(whole as u64)?;
+        }
+        let entries: HashMap<u64, Vec<f32>> = bytes[..whole]
+            .chunks_exact(record)
+            .map(|record| {
+                let hash = u64::from_le_bytes(record[..8].try_into().unwrap());
+                let embedding = record[8..]
+                    .chunks_exact(4)
+                    .map(|v| f32::from_le_bytes(v.try_into().unwrap()))
+                    .collect();
+                (hash, embedding)
+            })
+            .collect();
+        info!(
+            "Loaded {} cached embeddings from {}",
+            entries.len(),
+            path.display()
+        );
+        Ok(Self {
+            dimensions,
+            entries: Mutex::new(entries),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The cached embeddings of those of `messages` there are any for
+    pub fn get(&self, messages: &[String]) -> HashMap<String, Vec<f32>> {
+        let entries = self.entries.lock().unwrap();
+        messages
+            .iter()
+            .filter_map(|message| {
+                let embedding = entries.get(&fnv1a(0, message))?;
+                Some((message.clone(), embedding.clone()))
+            })
+            .collect()
+    }
+
+    /// Keep newly made embeddings. Failing to write only costs embedding
+    /// them again next run, so it's a warning.
+    pub fn put(&self, messages: &[String], embeddings: &[Vec<f32>]) {
+        let mut bytes = Vec::with_capacity(messages.len() * (8 + 4 * self.dimensions));
+        let mut entries = self.entries.lock().unwrap();
+        for (message, embedding) in messages.iter().zip(embeddings) {
+            if embedding.len() != self.dimensions {
+                continue;
+            }
+            let hash = fnv1a(0, message);
+            bytes.extend_from_slice(&hash.to_le_bytes());
+            bytes.extend(embedding.iter().flat_map(|v| v.to_le_bytes()));
+            entries.insert(hash, embedding.clone());
+        }
+        if let Err(e) = self.file.lock().unwrap().write_all(&bytes) {
+            warn!("Failed to write to the embedding cache: {e}");
+        }
+    }
+}
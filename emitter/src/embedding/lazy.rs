@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use lru::LruCache;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+use super::{EmbedError, EmbeddingService};
+
+fn default_cache_entries() -> usize {
+    100_000
+}
+
+fn default_batch_size() -> usize {
+    256
+}
+
+fn default_max_wait_ms() -> u64 {
+    20
+}
+
+/// Embedding messages as they're generated rather than the whole pool up
+/// front, the way a streaming ingestion path would
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LazyConfig {
+    /// Most embeddings kept, the least recently used going first
+    #[serde(default = "default_cache_entries")]
+    pub cache_entries: usize,
+    /// Most messages the worker gathers into one request
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// How long the worker waits for more messages before sending a
+    /// request that isn't full
+    #[serde(default = "default_max_wait_ms")]
+    pub max_wait_ms: u64,
+}
+
+impl Default for LazyConfig {
+    fn default() -> Self {
+        Self {
+            cache_entries: default_cache_entries(),
+            batch_size: default_batch_size(),
+            max_wait_ms: default_max_wait_ms(),
+        }
+    }
+}
+
+type Cache = Arc<Mutex<LruCache<String, Vec<f32>>>>;
+
+/// Messages waiting on the worker, and where their embeddings go
+struct Request {
+    messages: Vec<String>,
+    reply: oneshot::Sender<Result<HashMap<String, Vec<f32>>, String>>,
+}
+
+/// Embeddings made on demand: served from an LRU cache, with misses from
+/// every emitter gathered into batches by a background worker
+pub struct LazyEmbeddings {
+    cache: Cache,
+    requests: mpsc::Sender<Request>,
+}
+
+impl LazyEmbeddings {
+    /// Start the worker, which runs until this is dropped
+    pub fn start(config: LazyConfig, embedder: Arc<EmbeddingService>) -> Arc<Self> {
+        let capacity = NonZeroUsize::new(config.cache_entries).unwrap_or(NonZeroUsize::MIN);
+        let cache = Arc::new(Mutex::new(LruCache::new(capacity)));
+        let (requests, rx) = mpsc::channel(1024);
+        tokio::spawn(work(config, rx, Arc::clone(&cache), embedder));
+        Arc::new(Self { cache, requests })
+    }
+
+    /// Fill the cache with embeddings made up front
+    pub fn warm(&self, embeddings: HashMap<String, Vec<f32>>) {
+        let mut cache = self.cache.lock().unwrap();
+        for (message, embedding) in embeddings {
+            cache.put(message, embedding);
+        }
+    }
+
+    /// The embeddings of `messages`, in the same order, waiting on the
+    /// worker for any not in the cache
+    pub async fn embed(&self, messages: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let mut embeddings: Vec<Option<Vec<f32>>> = {
+            let mut cache = self.cache.lock().unwrap();
+            messages
+                .iter()
+                .map(|message| cache.get(message).cloned())
+                .collect()
+        };
+        let mut misses: Vec<String> = messages
+            .iter()
+            .zip(&embeddings)
+            .filter(|(_, embedding)| embedding.is_none())
+            .map(|(message, _)| message.clone())
+            .collect();
+        if !misses.is_empty() {
+            misses.sort();
+            misses.dedup();
+            let (reply, answer) = oneshot::channel();
+            self.requests
+                .send(Request {
+                    messages: misses,
+                    reply,
+                })
+                .await
+                .map_err(|_| "embedding worker stopped")?;
+            let fresh = answer.await.map_err(|_| "embedding worker stopped")??;
+            for (message, embedding) in messages.iter().zip(&mut embeddings) {
+                if embedding.is_none() {
+                    *embedding = fresh.get(message).cloned();
+                }
+            }
+        }
+        Ok(embeddings
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect())
+    }
+}
+
+/// Gather requests until `batch_size` messages or `max_wait_ms` have gone
+/// by, embed those the cache still lacks in one go, and answer each
+async fn work(
+    config: LazyConfig,
+    mut rx: mpsc::Receiver<Request>,
+    cache: Cache,
+    embedder: Arc<EmbeddingService>,
+) {
+    let max_wait = Duration::from_millis(config.max_wait_ms);
+    while let Some(first) = rx.recv().await {
+        let mut queued = first.messages.len();
+        let mut requests = vec![first];
+        let deadline = tokio::time::Instant::now() + max_wait;
+        while queued < config.batch_size {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(request)) => {
+                    queued += request.messages.len();
+                    requests.push(request);
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        // another request may have brought some in meanwhile
+        let mut found = HashMap::new();
+        let mut unseen = Vec::new();
+        {
+            let mut cache = cache.lock().unwrap();
+            for message in requests.iter().flat_map(|request| &request.messages) {
+                if found.contains_key(message) {
+                    continue;
+                }
+                match cache.get(message) {
+                    Some(embedding) => {
+                        found.insert(message.clone(), embedding.clone());
+                    }
+                    None => unseen.push(message.clone()),
+                }
+            }
+        }
+        unseen.sort();
+        unseen.dedup();
+
+        let result = if unseen.is_empty() {
+            Ok(())
+        } else {
+            debug!("Embedding {} messages on demand", unseen.len());
+            match embedder.embed_batch(&unseen).await {
+                Ok(embeddings) => {
+                    let mut cache = cache.lock().unwrap();
+                    for (message, embedding) in unseen.into_iter().zip(embeddings) {
+                        cache.put(message.clone(), embedding.clone());
+                        found.insert(message, embedding);
+                    }
+                    Ok(())
+                }
+                Err(e) => {
+                    warn!("Failed to embed messages on demand: {e}");
+                    Err(e.to_string())
+                }
+            }
+        };
+
+        for request in requests {
+            let answer = result.clone().map(|()| {
+                request
+                    .messages
+                    .iter()
+                    .filter_map(|message| Some((message.clone(), found.get(message)?.clone())))
+                    .collect()
+            });
+            let _ = request.reply.send(answer);
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+use indicatif::{ProgressBar, ProgressStyle};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config::EmbeddingConfig;
+use crate::embedding::disk::DiskCache;
+
+pub mod cohere;
+pub mod disk;
+pub mod lazy;
+pub mod ollama;
+pub mod openai;
+pub mod quantize;
+pub mod tei;
+
+pub type EmbedError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where embeddings come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProvider {
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    Cohere,
+    /// A HuggingFace text-embeddings-inference server
+    Tei,
+    Ollama,
+    /// No embeddings at all: entries carry an empty one, and sinks leave
+    /// out their dense vector, for measuring keyword ingestion on its own
+    None,
+}
+
+/// How a sink compares embeddings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Distance {
+    #[default]
+    Cosine,
+    /// Inner product, ranking like cosine only for normalized embeddings
+    Dot,
+    Euclidean,
+}
+
+/// A backend turning messages into embeddings
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Embed a batch of at most [`Embedder::batch_size`] messages in one
+    /// request, returning the embeddings in the same order
+    async fn embed(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError>;
+
+    /// Most messages the backend takes per request
+    fn batch_size(&self) -> usize;
+}
+
+/// Stands in for a model when embedding is switched off
+struct NoEmbedder;
+
+#[async_trait]
+impl Embedder for NoEmbedder {
+    async fn embed(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        Ok(vec![Vec::new(); batch.len()])
+    }
+
+    fn batch_size(&self) -> usize {
+        usize::MAX
+    }
+}
+
+/// The first `dim` dimensions of `embedding`, renormalized to unit length,
+/// as models trained with Matryoshka representation learning allow
+pub fn truncate(embedding: &[f32], dim: usize) -> Vec<f32> {
+    let mut truncated = embedding[..dim.min(embedding.len())].to_vec();
+    normalize(&mut truncated);
+    truncated
+}
+
+/// Scale `embedding` to unit length
+pub fn normalize(embedding: &mut [f32]) {
+    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in embedding {
+            *v /= norm;
+        }
+    }
+}
+
+/// Send `request`, reading a successful response as JSON and turning any
+/// other into an error naming `provider`
+async fn post_json<T: serde::de::DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    provider: &str,
+) -> Result<T, EmbedError> {
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{provider} embedding request failed: {status} {body}").into());
+    }
+    Ok(response.json().await?)
+}
+
+/// How long embedding messages up front should take, going by a trial
+/// request of a few of them
+#[derive(Debug, Clone)]
+pub struct Estimate {
+    /// Messages not in the embedding cache
+    pub to_embed: usize,
+    pub trial_size: usize,
+    pub trial_time: Duration,
+    pub requests: usize,
+    pub total: Duration,
+}
+
+/// Embeds messages with the configured provider
+pub struct EmbeddingService {
+    config: EmbeddingConfig,
+    embedder: Box<dyn Embedder>,
+    /// Embeddings from earlier runs, when `cache_dir` is set
+    disk: Option<DiskCache>,
+    /// Most messages per request: `batch_size` if set, within what the
+    /// provider takes
+    batch_size: usize,
+}
+
+impl EmbeddingService {
+    pub fn from_config(config: EmbeddingConfig) -> Self {
+        let embedder: Box<dyn Embedder> = match config.provider {
+            EmbeddingProvider::OpenAi => Box::new(openai::OpenAiEmbedder::new(&config)),
+            EmbeddingProvider::Cohere => Box::new(cohere::CohereEmbedder::new(&config)),
+            EmbeddingProvider::Tei => Box::new(tei::TeiEmbedder::new(&config)),
+            EmbeddingProvider::Ollama => Box::new(ollama::OllamaEmbedder::new(&config)),
+            EmbeddingProvider::None => Box::new(NoEmbedder),
+        };
+        let disk = config
+            .cache_dir
+            .as_deref()
+            .filter(|_| config.enabled())
+            .and_then(|dir| match DiskCache::open(dir, &config) {
+                Ok(disk) => Some(disk),
+                Err(e) => {
+                    warn!(
+                        "Failed to open the embedding cache in {}, embedding everything: {e}",
+                        dir.display()
+                    );
+                    None
+                }
+            });
+        let batch_size = match config.batch_size {
+            Some(batch_size) => batch_size.min(embedder.batch_size()),
+            None => embedder.batch_size(),
+        }
+        .max(1);
+        Self {
+            config,
+            embedder,
+            disk,
+            batch_size,
+        }
+    }
+
+    /// Embed all messages with the configured provider. Returns a map from
+    /// message text to its embedding vector. Call this once at startup.
+    pub async fn embed_all(
+        &self,
+        messages: &[String],
+    ) -> Result<HashMap<String, Vec<f32>>, EmbedError> {
+        info!(
+            "Embedding {} messages with provider={:?} model={} dims={}",
+            messages.len(),
+            self.config.provider,
+            self.config.model,
+            self.config.dimensions,
+        );
+
+        let mut map = self.cached(messages);
+        let messages: Vec<String> = messages
+            .iter()
+            .filter(|message| !map.contains_key(*message))
+            .cloned()
+            .collect();
+        if !map.is_empty() {
+            info!(
+                "{} messages found in the embedding cache, {} to embed",
+                map.len(),
+                messages.len()
+            );
+        }
+        let total = messages.len();
+        let concurrency = self.config.concurrency.max(1);
+        if total > 0 {
+            info!(
+                "{} requests of up to {} messages, {} at a time",
+                total.div_ceil(self.batch_size),
+                self.batch_size,
+                concurrency
+            );
+        }
+
+        let pb = ProgressBar::new(total as u64);
+        pb.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} messages (ETA {eta})",
+            )
+            .unwrap()
+            .progress_chars("#>-"),
+        );
+
+        // the bar only shows on a terminal, so progress is logged too, every
+        // tenth of the way
+        let started = Instant::now();
+        let mut done = 0;
+        let mut logged = 0;
+        let mut requests = stream::iter(messages.chunks(self.batch_size))
+            .map(|batch| async move { (batch, self.embed_request(batch).await) })
+            .buffer_unordered(concurrency);
+        while let Some((batch, embeddings)) = requests.next().await {
+            map.extend(batch.iter().cloned().zip(embeddings?));
+            done += batch.len();
+            pb.inc(batch.len() as u64);
+
+            let tenths = done * 10 / total;
+            if tenths > logged && done < total {
+                logged = tenths;
+                let eta = started
+                    .elapsed()
+                    .mul_f64((total - done) as f64 / done as f64);
+                info!("Embedded {done}/{total} messages, ETA {}s", eta.as_secs());
+            }
+        }
+
+        pb.finish_and_clear();
+        info!("Embedded {} messages successfully", map.len());
+        for embedding in map.values_mut() {
+            *embedding = self.output(embedding);
+        }
+        Ok(map)
+    }
+
+    /// Embed up to `trial_size` of `messages` (those not cached first) in
+    /// one request, checking the provider answers with the configured
+    /// dimensions, and from how long it took estimate how long
+    /// [`embed_all`](Self::embed_all) would take over all of them
+    pub async fn estimate(
+        &self,
+        messages: &[String],
+        trial_size: usize,
+    ) -> Result<Estimate, EmbedError> {
+        let cached = self.cached(messages);
+        let unseen: Vec<&String> = messages
+            .iter()
+            .filter(|message| !cached.contains_key(*message))
+            .collect();
+        let trial: Vec<String> = unseen
+            .iter()
+            .copied()
+            .chain(messages)
+            .take(trial_size.clamp(1, self.batch_size))
+            .cloned()
+            .collect();
+
+        let started = Instant::now();
+        self.embed_request(&trial).await?;
+        let trial_time = started.elapsed();
+
+        // a full request takes longer than the trial, by about how many
+        // more messages it carries
+        let requests = unseen.len().div_ceil(self.batch_size);
+        let per_request = trial_time
+            .mul_f64((unseen.len().min(self.batch_size) as f64 / trial.len() as f64).max(1.0));
+        let rounds = requests.div_ceil(self.config.concurrency.max(1));
+        Ok(Estimate {
+            to_embed: unseen.len(),
+            trial_size: trial.len(),
+            trial_time,
+            requests,
+            total: per_request.mul_f64(rounds as f64),
+        })
+    }
+
+    /// Embed a batch of messages, in as many requests as the provider
+    /// needs, returning the embeddings in the same order
+    pub async fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let mut cached = self.cached(batch);
+        let unseen: Vec<String> = batch
+            .iter()
+            .filter(|message| !cached.contains_key(*message))
+            .cloned()
+            .collect();
+        for chunk in unseen.chunks(self.batch_size) {
+            let embeddings = self.embed_request(chunk).await?;
+            cached.extend(chunk.iter().cloned().zip(embeddings));
+        }
+        Ok(batch
+            .iter()
+            .map(|message| match cached.get(message) {
+                Some(embedding) => self.output(embedding),
+                None => Vec::new(),
+            })
+            .collect())
+    }
+
+    /// What's handed on of the model's `embedding`: truncated and
+    /// normalized as configured
+    fn output(&self, embedding: &[f32]) -> Vec<f32> {
+        match self.config.truncate_dim {
+            Some(dim) => truncate(embedding, dim as usize),
+            None if self.config.normalize => {
+                let mut normalized = embedding.to_vec();
+                normalize(&mut normalized);
+                normalized
+            }
+            None => embedding.to_vec(),
+        }
+    }
+
+    /// Embeddings of `messages` kept from earlier runs
+    fn cached(&self, messages: &[String]) -> HashMap<String, Vec<f32>> {
+        match &self.disk {
+            Some(disk) => disk.get(messages),
+            None => HashMap::new(),
+        }
+    }
+
+    /// One request's worth, checked against the configured dimensions the
+    /// sinks are set up with
+    async fn embed_request(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        debug!("Embedding batch of {} messages", batch.len());
+        debug!("Batch messages: {:?}", batch);
+
+        let embeddings = self.embedder.embed(batch).await?;
+        if embeddings.len() != batch.len() {
+            return Err(format!(
+                "{:?} returned {} embeddings for {} messages",
+                self.config.provider,
+                embeddings.len(),
+                batch.len()
+            )
+            .into());
+        }
+        if let Some(embedding) = embeddings.first()
+            && self.config.enabled()
+            && embedding.len() != self.config.dimensions as usize
+        {
+            return Err(format!(
+                "{} returns {}-dimensional embeddings, but embedding.dimensions is {}",
+                self.config.model,
+                embedding.len(),
+                self.config.dimensions
+            )
+            .into());
+        }
+        if let Some(disk) = &self.disk {
+            disk.put(batch, &embeddings);
+        }
+        Ok(embeddings)
+    }
+}
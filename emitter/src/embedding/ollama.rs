@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{EmbedError, Embedder, post_json};
+use crate::config::EmbeddingConfig;
+
+const DEFAULT_URL: &str = "http://localhost:11434";
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// An Ollama server's embed API, with `model` pulled there beforehand
+pub struct OllamaEmbedder {
+    client: reqwest::Client,
+    url: String,
+    model: String,
+}
+
+impl OllamaEmbedder {
+    pub fn new(config: &EmbeddingConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.as_deref().unwrap_or(DEFAULT_URL).to_string(),
+            model: config.model.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OllamaEmbedder {
+    async fn embed(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let request = self
+            .client
+            .post(format!("{}/api/embed", self.url.trim_end_matches('/')))
+            .json(&json!({ "model": self.model, "input": batch }));
+        let response: EmbedResponse = post_json(request, "Ollama").await?;
+        Ok(response.embeddings)
+    }
+
+    fn batch_size(&self) -> usize {
+        256
+    }
+}
@@ -0,0 +1,52 @@
+use async_openai::Client as OpenAiClient;
+use async_openai::config::OpenAIConfig;
+use async_openai::types::embeddings::{CreateEmbeddingRequestArgs, EmbeddingInput};
+use async_trait::async_trait;
+
+use super::{EmbedError, Embedder};
+use crate::config::EmbeddingConfig;
+
+/// OpenAI's embeddings API, or a compatible one at `url`
+pub struct OpenAiEmbedder {
+    client: OpenAiClient<OpenAIConfig>,
+    model: String,
+    dimensions: u32,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(config: &EmbeddingConfig) -> Self {
+        let mut oai_config = OpenAIConfig::new().with_api_key(&config.api_key);
+        if let Some(url) = &config.url {
+            oai_config = oai_config.with_api_base(url);
+        }
+        Self {
+            client: OpenAiClient::with_config(oai_config),
+            model: config.model.clone(),
+            dimensions: config.dimensions,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAiEmbedder {
+    async fn embed(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let mut request = CreateEmbeddingRequestArgs::default();
+        request
+            .model(&self.model)
+            .input(EmbeddingInput::StringArray(batch.to_vec()))
+            .dimensions(self.dimensions);
+
+        let request = request.build()?;
+        let response = self.client.embeddings().create(request).await?;
+        Ok(response
+            .data
+            .into_iter()
+            .map(|embedding| embedding.embedding)
+            .collect())
+    }
+
+    fn batch_size(&self) -> usize {
+        // OpenAI's typical batch limit
+        2048
+    }
+}
@@ -0,0 +1,47 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Embeddings stored in a smaller representation, quantized before they're
+/// sent rather than by the store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientQuantization {
+    /// One signed byte per dimension, scaled so the largest component of
+    /// each embedding is ±127
+    Int8,
+    /// One bit per dimension: whether it's positive
+    Binary,
+}
+
+/// `embedding` as int8, scaled per embedding; cosine similarity doesn't
+/// mind the scale
+pub fn int8(embedding: &[f32]) -> Vec<i8> {
+    let max = embedding.iter().fold(0.0f32, |max, v| max.max(v.abs()));
+    if max == 0.0 {
+        return vec![0; embedding.len()];
+    }
+    embedding
+        .iter()
+        .map(|v| (v / max * 127.0).round() as i8)
+        .collect()
+}
+
+/// `embedding` as one bit per dimension
+pub fn bits(embedding: &[f32]) -> Vec<bool> {
+    embedding.iter().map(|v| *v > 0.0).collect()
+}
+
+/// The bits of `embedding` packed eight to a byte, first dimension in the
+/// highest bit
+pub fn packed_bits(embedding: &[f32]) -> Vec<u8> {
+    embedding
+        .chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| **v > 0.0)
+                .fold(0u8, |byte, (i, _)| byte | (0x80 >> i))
+        })
+        .collect()
+}
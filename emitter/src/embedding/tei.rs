@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{EmbedError, Embedder, post_json};
+use crate::config::EmbeddingConfig;
+
+const DEFAULT_URL: &str = "http://localhost:8080";
+
+/// A HuggingFace text-embeddings-inference server, which serves the one
+/// model it was started with (`model` is only reported)
+pub struct TeiEmbedder {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+}
+
+impl TeiEmbedder {
+    pub fn new(config: &EmbeddingConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.as_deref().unwrap_or(DEFAULT_URL).to_string(),
+            api_key: config.api_key.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for TeiEmbedder {
+    async fn embed(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let mut request = self
+            .client
+            .post(format!("{}/embed", self.url.trim_end_matches('/')))
+            .json(&json!({ "inputs": batch, "truncate": true }));
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+        post_json(request, "text-embeddings-inference").await
+    }
+
+    fn batch_size(&self) -> usize {
+        // the server's default --max-client-batch-size
+        32
+    }
+}
@@ -1,246 +1,1060 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
-use tokio::sync::mpsc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use uuid::Uuid;
 
-use crate::config::{LogLevelWeights, ServiceConfig};
-use crate::log_entry::{LogEntry, LogLevel};
+use crate::cascade::{self, Cascades};
+use crate::channel::EntrySender;
+use crate::config::{FlowConfig, LogLevelWeights, PipelineConfig, ServiceConfig};
+use crate::control::Control;
+use crate::duplicate::{DuplicateConfig, Recent};
+use crate::embedding::lazy::LazyEmbeddings;
+use crate::jitter::JitterConfig;
+use crate::language::{MultilingualConfig, Phrasing};
+use crate::log_entry::{LogEntry, LogLevel, TraceContext};
+use crate::metadata::Metadata;
+use crate::metrics;
+use crate::payload::PayloadConfig;
+use crate::pii::PiiConfig;
+use crate::popularity::Popularity;
+use crate::scenario::{Modifiers, Scenarios};
+use crate::stack_trace::StackTraceConfig;
+use crate::traffic::{Arrival, Backfill};
+use crate::vocabulary::{VocabularyConfig, VocabularyRef};
 
-// ---------------------------------------------------------------------------
-// Slot-based combinatorial message generation
-// 20 items per slot × 4 patterns × 20^4..20^5 = ~640K–12.8M unique messages
-// ---------------------------------------------------------------------------
-
-const COMPONENTS: &[&str] = &[
-    "ConnectionPool",
-    "QueryExecutor",
-    "AuthManager",
-    "CacheLayer",
-    "LoadBalancer",
-    "RateLimiter",
-    "SchemaValidator",
-    "EventBus",
-    "HealthMonitor",
-    "SessionStore",
-    "CircuitBreaker",
-    "JobScheduler",
-    "ReplicaManager",
-    "PartitionConsumer",
-    "TLSHandler",
-    "GarbageCollector",
-    "DiskMonitor",
-    "DeadLetterQueue",
-    "IdempotencyFilter",
-    "TraceContext",
+const REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-west-2",
+    "eu-west-1",
+    "eu-central-1",
+    "ap-southeast-1",
+    "ap-northeast-1",
 ];
 
-const ACTIONS: &[&str] = &[
-    "detected threshold breach",
-    "completed successfully",
-    "failed after retries",
-    "initiated graceful recovery",
-    "rejected invalid request",
-    "triggered rebalance",
-    "exceeded soft limit",
-    "evicted stale entry",
-    "propagated context",
-    "acquired resource handle",
-    "flushed pending writes",
-    "rotated credentials",
-    "promoted fallback path",
-    "checkpointed at offset",
-    "enqueued background task",
-    "resolved after backoff",
-    "timed out waiting",
-    "received reset signal",
-    "applied migration",
-    "scheduled maintenance",
-];
+fn pick<'a>(list: &[&'a str], rng: &mut impl Rng) -> &'a str {
+    list[rng.gen_range(0..list.len())]
+}
 
-const METRICS: &[&str] = &[
-    "latency=2340ms",
-    "count=184302",
-    "ratio=0.94",
-    "depth=500",
-    "attempt=3/5",
-    "usage=85%",
-    "lag=500ms",
-    "size=1.2MB",
-    "rate=120/s",
-    "ttl=3600s",
-    "connections=980/1024",
-    "duration=30s",
-    "retries=3",
-    "offset=48291",
-    "queue_depth=1247",
-    "p99=450ms",
-    "batch_size=500",
-    "memory=2.4GB",
-    "iops=12000",
-    "threads=48",
-];
+/// The RNG for one stream of generated data (the pool, a service's
+/// emitter, a flow): derived from `seed` and the stream's name when runs
+/// should be reproducible, from entropy otherwise
+pub fn stream_rng(seed: Option<u64>, stream: &str) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(fnv1a(seed, stream)),
+        None => StdRng::from_entropy(),
+    }
+}
 
-const TARGETS: &[&str] = &[
-    "on orders table",
-    "for payments-api",
-    "from upstream host",
-    "in consumer group",
-    "on /data volume",
-    "for tenant af923c",
-    "to downstream service",
-    "across service boundary",
-    "in container cgroup",
-    "from replica node-3",
-    "on topic order.completed",
-    "for client session",
-    "in write-ahead log",
-    "on port 8443",
-    "from discovery endpoint",
-    "in ring buffer",
-    "for user session",
-    "on primary shard",
-    "to dead letter queue",
-    "from environment config",
-];
+/// FNV-1a of `text` mixed with `seed`: unlike std's hasher, fixed across
+/// builds
+pub(crate) fn fnv1a(seed: u64, text: &str) -> u64 {
+    text.bytes()
+        .fold(0xcbf2_9ce4_8422_2325 ^ seed, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
 
-const CONTEXTS: &[&str] = &[
-    "(retrying)",
-    "(non-blocking)",
-    "(scheduled)",
-    "(cached response)",
-    "(dark_launch=true)",
-    "(read-only)",
-    "(best-effort)",
-    "(idempotent)",
-    "(correlation_id=missing)",
-    "(circuit=open)",
-    "(degraded mode)",
-    "(cold start)",
-    "(warm path)",
-    "(fallback)",
-    "(async)",
-    "(batched)",
-    "(compressed)",
-    "(encrypted)",
-    "(sampled)",
-    "(throttled)",
-];
+/// A v4 UUID drawn from `rng`, so seeded runs repeat their ids
+fn random_uuid(rng: &mut impl Rng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.r#gen()).into_uuid()
+}
 
-fn pick<'a>(list: &[&'a str], rng: &mut impl Rng) -> &'a str {
-    list[rng.gen_range(0..list.len())]
+/// One vocabulary's messages: a general pool, plus one for each level with
+/// phrasing of its own
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Messages {
+    pub general: Vec<String>,
+    #[serde(default)]
+    pub levels: BTreeMap<LogLevel, Vec<String>>,
 }
 
-pub fn generate_message(rng: &mut impl Rng) -> String {
-    let pattern = rng.gen_range(0..4u8);
-    match pattern {
-        0 => format!(
-            "{}: {} {} {}",
-            pick(COMPONENTS, rng),
-            pick(ACTIONS, rng),
-            pick(TARGETS, rng),
-            pick(CONTEXTS, rng),
-        ),
-        1 => format!(
-            "{}: {} [{}] {}",
-            pick(COMPONENTS, rng),
-            pick(ACTIONS, rng),
-            pick(METRICS, rng),
-            pick(TARGETS, rng),
-        ),
-        2 => format!(
-            "{}: {} [{}]",
-            pick(COMPONENTS, rng),
-            pick(ACTIONS, rng),
-            pick(METRICS, rng),
-        ),
-        _ => format!(
-            "{}: {} {} [{}] {}",
-            pick(COMPONENTS, rng),
-            pick(ACTIONS, rng),
-            pick(TARGETS, rng),
-            pick(METRICS, rng),
-            pick(CONTEXTS, rng),
-        ),
+impl Messages {
+    fn build(rng: &mut impl Rng, phrasing: &Phrasing, size: usize) -> Self {
+        let general = phrasing.build_pool(rng, size);
+        let levels = phrasing
+            .levels()
+            .map(|level| {
+                let messages = phrasing.for_level(level).build_pool(rng, size);
+                (level.clone(), messages)
+            })
+            .collect();
+        Self { general, levels }
+    }
+
+    /// The messages to draw from for an entry at `level`
+    pub fn for_level(&self, level: &LogLevel) -> &[String] {
+        match self.levels.get(level) {
+            Some(messages) if !messages.is_empty() => messages,
+            _ => &self.general,
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &String> {
+        self.general.iter().chain(self.levels.values().flatten())
+    }
+
+    /// A copy with `fraction` of each list's messages replaced by new ones
+    /// drawn from `phrasing`
+    fn drift(&self, rng: &mut impl Rng, phrasing: &Phrasing, fraction: f64) -> Self {
+        let general = drift_list(&self.general, phrasing, fraction, rng);
+        let levels = self
+            .levels
+            .iter()
+            .map(|(level, messages)| {
+                let messages = drift_list(messages, &phrasing.for_level(level), fraction, rng);
+                (level.clone(), messages)
+            })
+            .collect();
+        Self { general, levels }
     }
 }
 
-/// Pre-generate a pool of unique messages for embedding at startup.
-pub fn build_message_pool(rng: &mut impl Rng, size: usize) -> Vec<String> {
-    let mut pool = std::collections::HashSet::with_capacity(size);
-    while pool.len() < size {
-        pool.insert(generate_message(rng));
+/// `messages` with `fraction` of them, picked at random, replaced in place
+/// (keeping their popularity rank) by messages not in the list before
+fn drift_list(
+    messages: &[String],
+    phrasing: &Phrasing,
+    fraction: f64,
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    let retire = (messages.len() as f64 * fraction.clamp(0.0, 1.0)).round() as usize;
+    let mut seen: HashSet<String> = messages.iter().cloned().collect();
+    let mut drifted = messages.to_vec();
+    let mut misses = 0;
+    for index in rand::seq::index::sample(rng, messages.len(), retire) {
+        // a vocabulary with nothing new left to say keeps the rest
+        while misses < 1000 {
+            let message = phrasing.generate(rng);
+            if seen.insert(message.clone()) {
+                drifted[index] = message;
+                misses = 0;
+                break;
+            }
+            misses += 1;
+        }
     }
-    pool.into_iter().collect()
+    drifted
 }
 
-/// Add small noise to an embedding to prevent degenerate HNSW clusters
-/// from duplicate vectors while preserving semantic locality.
-fn jitter_embedding(embedding: &[f32], rng: &mut impl Rng, scale: f32) -> Vec<f32> {
-    embedding
-        .iter()
-        .map(|&v| {
-            let noise = rng.gen_range(-1.0f32..1.0) * scale * v.abs().max(0.01);
-            v + noise
-        })
-        .collect()
+/// The messages emitters draw from: a shared pool, plus one for each
+/// service with a vocabulary (or component affinity) of its own
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessagePool {
+    pub shared: Messages,
+    #[serde(default)]
+    pub services: HashMap<String, Messages>,
+}
+
+impl MessagePool {
+    /// Pre-generate pools of `size` unique messages for embedding at startup.
+    /// `vocabulary` is layered over the built-in one, and service
+    /// vocabularies (inline or from `named`) over that, each translated as
+    /// `multilingual` asks. Services are matched by name, so same-named
+    /// services in different pipelines share the first one's vocabulary.
+    pub fn build<'a>(
+        rng: &mut impl Rng,
+        vocabulary: &VocabularyConfig,
+        named: &HashMap<String, VocabularyConfig>,
+        multilingual: Option<&MultilingualConfig>,
+        services: impl IntoIterator<Item = &'a ServiceConfig>,
+        size: usize,
+    ) -> Result<Self, String> {
+        let base = vocabulary.over(&VocabularyConfig::builtin());
+        let mut pool = Self {
+            shared: Messages::build(rng, &Phrasing::new(base.clone(), multilingual), size),
+            services: HashMap::new(),
+        };
+        for service in services {
+            if pool.services.contains_key(&service.name) {
+                continue;
+            }
+            if let Some(own) = service_vocabulary(service, named, &base)? {
+                let messages = Messages::build(rng, &Phrasing::new(own, multilingual), size);
+                pool.services.insert(service.name.clone(), messages);
+            }
+        }
+        Ok(pool)
+    }
+
+    /// A copy with `fraction` of every pool's messages replaced by new ones,
+    /// from the same vocabularies [`MessagePool::build`] was given, like a
+    /// deployment changing what services log
+    pub fn drift<'a>(
+        &self,
+        rng: &mut impl Rng,
+        vocabulary: &VocabularyConfig,
+        named: &HashMap<String, VocabularyConfig>,
+        multilingual: Option<&MultilingualConfig>,
+        services: impl IntoIterator<Item = &'a ServiceConfig>,
+        fraction: f64,
+    ) -> Result<Self, String> {
+        let base = vocabulary.over(&VocabularyConfig::builtin());
+        let mut drifted = Self {
+            shared: self
+                .shared
+                .drift(rng, &Phrasing::new(base.clone(), multilingual), fraction),
+            services: HashMap::new(),
+        };
+        // in a fixed order, so seeded runs drift alike
+        let mut services: Vec<_> = services
+            .into_iter()
+            .filter(|service| self.services.contains_key(&service.name))
+            .collect();
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        for service in services {
+            if drifted.services.contains_key(&service.name) {
+                continue;
+            }
+            if let Some(own) = service_vocabulary(service, named, &base)? {
+                let phrasing = Phrasing::new(own, multilingual);
+                let messages = self.services[&service.name].drift(rng, &phrasing, fraction);
+                drifted.services.insert(service.name.clone(), messages);
+            }
+        }
+        Ok(drifted)
+    }
+
+    pub fn for_service(&self, service: &str) -> &Messages {
+        self.services.get(service).unwrap_or(&self.shared)
+    }
+
+    /// Every message across the pools
+    pub fn messages(&self) -> impl Iterator<Item = &String> {
+        self.shared
+            .iter()
+            .chain(self.services.values().flat_map(Messages::iter))
+    }
+
+    /// Every message the pipelines may log from this pool, which need
+    /// embedding up front: the pool's, those scenarios and cascades log
+    /// instead, and the variants `jitter` may log of any of them
+    pub fn to_embed(&self, pipelines: &[PipelineConfig], jitter: &JitterConfig) -> HashSet<String> {
+        let mut messages: HashSet<String> = self.messages().cloned().collect();
+        for pipeline in pipelines {
+            let scenario_messages: Vec<&str> = pipeline
+                .scenarios
+                .iter()
+                .flat_map(|scenario| scenario.vocabulary())
+                .collect();
+            messages.extend(scenario_messages.iter().map(|message| message.to_string()));
+            messages.extend(cascade::messages(
+                &pipeline.cascades,
+                self,
+                &scenario_messages,
+            ));
+        }
+        let variants: Vec<String> = messages
+            .iter()
+            .flat_map(|message| jitter.variants(message))
+            .collect();
+        messages.extend(variants);
+        messages
+    }
+}
+
+/// `service`'s own vocabulary layered over `base`, if it has one or
+/// weighs its components
+fn service_vocabulary(
+    service: &ServiceConfig,
+    named: &HashMap<String, VocabularyConfig>,
+    base: &VocabularyConfig,
+) -> Result<Option<VocabularyConfig>, String> {
+    let mut own = match &service.vocabulary {
+        None if service.component_affinity.is_empty() => return Ok(None),
+        None => base.clone(),
+        Some(VocabularyRef::Named(name)) => named
+            .get(name)
+            .ok_or_else(|| {
+                format!(
+                    "Service '{}' uses unknown vocabulary '{name}'",
+                    service.name
+                )
+            })?
+            .over(base),
+        Some(VocabularyRef::Inline(own)) => own.over(base),
+    };
+    if !service.component_affinity.is_empty() {
+        own.shares
+            .insert("component".to_string(), service.component_affinity.clone());
+    }
+    Ok(Some(own))
+}
+
+/// A message pool along with the embedding of every message that may be
+/// logged from it
+pub struct PoolSnapshot {
+    pub pool: MessagePool,
+    pub embeddings: HashMap<String, Vec<f32>>,
+}
+
+/// The pool emitters draw from, replaced whole as it drifts
+pub struct LivePool {
+    current: RwLock<Arc<PoolSnapshot>>,
+}
+
+impl LivePool {
+    pub fn new(pool: MessagePool, embeddings: HashMap<String, Vec<f32>>) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(PoolSnapshot { pool, embeddings })),
+        }
+    }
+
+    pub fn current(&self) -> Arc<PoolSnapshot> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    pub fn replace(&self, snapshot: PoolSnapshot) {
+        *self.current.write().unwrap() = Arc::new(snapshot);
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Log generation + emission
 // ---------------------------------------------------------------------------
 
+/// What a pipeline's emitters generate entries from
+#[derive(Clone)]
+pub struct MessageSource {
+    /// The pools, with the embeddings of their messages and of every
+    /// scenario and cascade message
+    pub pool: Arc<LivePool>,
+    pub scenarios: Arc<Scenarios>,
+    pub cascades: Arc<Cascades>,
+    /// The pipeline this is generating for, to look up live rates by
+    pub pipeline: Arc<str>,
+    pub control: Arc<Control>,
+    pub stack_traces: Option<Arc<StackTraceConfig>>,
+    pub popularity: Popularity,
+    /// Generate this past window instead of running in real time
+    pub backfill: Option<Backfill>,
+    pub duplicates: Option<Arc<DuplicateConfig>>,
+    pub metadata: Option<Arc<Metadata>>,
+    pub pii: Option<Arc<PiiConfig>>,
+    pub payload: Option<Arc<PayloadConfig>>,
+    /// How entries logging the same message are told apart
+    pub jitter: JitterConfig,
+    /// Embed messages as entries are generated, rather than looking them
+    /// up in the pool's embeddings
+    pub lazy: Option<Arc<LazyEmbeddings>>,
+    /// The `extra_embeddings` models, each made on demand through its cache
+    pub extras: Arc<BTreeMap<String, Arc<LazyEmbeddings>>>,
+}
+
+/// An entry waiting to be stamped and rendered by the service logging it,
+/// once it has its embedding
+struct Unfinished<'a> {
+    log: LogEntry,
+    service: &'a ServiceConfig,
+    at: Moment,
+}
+
+/// A point on an emitter's timeline
+#[derive(Debug, Clone, Copy)]
+struct Moment {
+    /// Time into the run, as traffic shapes and scenarios see it
+    elapsed: Duration,
+    timestamp: DateTime<Utc>,
+}
+
+impl Moment {
+    fn later(self, by: Duration) -> Self {
+        Self {
+            elapsed: self.elapsed + by,
+            timestamp: self.timestamp + chrono::Duration::from_std(by).unwrap_or_default(),
+        }
+    }
+
+    fn earlier(self, by: Duration) -> Self {
+        Self {
+            elapsed: self.elapsed.saturating_sub(by),
+            timestamp: self.timestamp - chrono::Duration::from_std(by).unwrap_or_default(),
+        }
+    }
+}
+
+/// Where an emitter is in time: following the wall clock, or, backfilling,
+/// stepping through a past window as fast as the pipeline takes entries
+#[derive(Debug, Clone)]
+struct Timeline {
+    started: Instant,
+    /// When the live emitter is next due to wake
+    due: Instant,
+    backfill: Option<Backfill>,
+    /// Time into the backfill window
+    simulated: Duration,
+}
+
+impl Timeline {
+    fn new(backfill: Option<Backfill>) -> Self {
+        Self {
+            started: Instant::now(),
+            due: Instant::now(),
+            backfill,
+            simulated: Duration::ZERO,
+        }
+    }
+
+    /// The emitter's own run time, or how far into the window it is
+    fn elapsed(&self) -> Duration {
+        match self.backfill {
+            Some(_) => self.simulated,
+            None => self.started.elapsed(),
+        }
+    }
+
+    /// Time left, out of `duration` (zero: indefinitely) when live, or of
+    /// the window
+    fn remaining(&self, duration: Duration) -> Option<Duration> {
+        match self.backfill {
+            Some(backfill) => Some(backfill.span.saturating_sub(self.simulated)),
+            None => (!duration.is_zero()).then(|| duration.saturating_sub(self.elapsed())),
+        }
+    }
+
+    fn finished(&self, duration: Duration) -> bool {
+        self.remaining(duration).is_some_and(|left| left.is_zero())
+    }
+
+    /// Move `by` along: sleeping when live, instantly when backfilling
+    async fn advance(&mut self, by: Duration) {
+        match self.backfill {
+            Some(_) => self.simulated += by,
+            None => {
+                // sleeping until a running deadline rather than for `by`,
+                // so gaps shorter than the timer's millisecond resolution
+                // add up to the right rate
+                self.due += by;
+                let now = Instant::now();
+                if self.due > now {
+                    tokio::time::sleep_until(self.due.into()).await;
+                } else if now - self.due > MAX_WAIT {
+                    // too far behind (a slow pipeline) to catch up on
+                    self.due = now;
+                }
+            }
+        }
+    }
+
+    fn now(&self, scenarios: &Scenarios) -> Moment {
+        match self.backfill {
+            Some(backfill) => Moment {
+                elapsed: self.simulated,
+                timestamp: backfill.start
+                    + chrono::Duration::from_std(self.simulated).unwrap_or_default(),
+            },
+            None => Moment {
+                elapsed: scenarios.elapsed(),
+                timestamp: Utc::now(),
+            },
+        }
+    }
+}
+
+impl MessageSource {
+    /// How much faster (or slower) than its base rate `service` emits at
+    /// this point of `timeline`, from its traffic shape and any active
+    /// scenarios
+    fn rate_multiplier(
+        &self,
+        service: &ServiceConfig,
+        timeline: &Timeline,
+        duration: Duration,
+    ) -> f64 {
+        // paused emitters wait on at a rate of nothing
+        if self.control.is_paused() {
+            return 0.0;
+        }
+        let at = timeline.now(&self.scenarios).elapsed;
+        service.traffic.multiplier(at, timeline.remaining(duration))
+            * self
+                .scenarios
+                .schedule()
+                .modifiers(&service.name, at)
+                .rate_multiplier
+    }
+
+    /// Generate an entry for `service` at `at`, under whatever scenarios are
+    /// active then, rendered in the service's format; along with the
+    /// entries it sets off in the services depending on it. None if lazily
+    /// embedding them failed.
+    async fn generate(
+        &self,
+        service: &ServiceConfig,
+        trace: Option<TraceContext>,
+        at: Moment,
+        rng: &mut (impl Rng + Send),
+    ) -> Option<(LogEntry, Vec<LogEntry>)> {
+        let mut entries = {
+            let pool = self.pool.current();
+            let schedule = self.scenarios.schedule();
+            let weights = self
+                .control
+                .level_weights(&self.pipeline, &service.name)
+                .unwrap_or_else(|| service.level_weights.clone());
+            let mut log = {
+                let modifiers = schedule.modifiers(&service.name, at.elapsed);
+                generate_log(
+                    service,
+                    &weights,
+                    rng,
+                    pool.pool.for_service(&service.name),
+                    &pool.embeddings,
+                    &modifiers,
+                    &self.popularity,
+                )
+            };
+            log.trace = trace;
+            let mut cascaded = Vec::new();
+            self.cascade(&log, at, 1, &pool.embeddings, rng, &mut cascaded);
+            self.jitter.apply(
+                &mut log,
+                |message| self.embedding(&pool.embeddings, message),
+                rng,
+            );
+            cascaded.insert(0, Unfinished { log, service, at });
+            cascaded
+        };
+
+        let messages: Vec<String> = entries
+            .iter()
+            .map(|entry| entry.log.message.clone())
+            .collect();
+        if let Some(lazy) = &self.lazy {
+            let embeddings = lazy.embed(&messages).await.ok()?;
+            for (entry, mut embedding) in entries.iter_mut().zip(embeddings) {
+                self.jitter.perturb(&mut embedding, rng);
+                entry.log.embedding = embedding;
+            }
+        }
+        for (name, extra) in self.extras.iter() {
+            let embeddings = extra.embed(&messages).await.ok()?;
+            for (entry, embedding) in entries.iter_mut().zip(embeddings) {
+                entry.log.extra_embeddings.insert(name.clone(), embedding);
+            }
+        }
+
+        let mut finished = entries
+            .drain(..)
+            .map(|entry| self.finish(entry.service, entry.log, entry.at, rng));
+        let log = finished.next()?;
+        Some((log, finished.collect()))
+    }
+
+    /// The embedding an entry logging `message` starts with: the pool's, or
+    /// none yet when embedding lazily. None if the message can't be logged,
+    /// not having been embedded up front.
+    fn embedding(&self, embeddings: &HashMap<String, Vec<f32>>, message: &str) -> Option<Vec<f32>> {
+        match self.lazy {
+            Some(_) => Some(Vec::new()),
+            None => embeddings.get(message).cloned(),
+        }
+    }
+
+    /// If `log` is an ERROR that cascades, an entry for each service
+    /// depending on its own: a WARN or ERROR quoting it a little later, the
+    /// errors cascading on in turn. `log` is the `depth`-th service of the
+    /// chain.
+    fn cascade<'a>(
+        &'a self,
+        log: &LogEntry,
+        at: Moment,
+        depth: usize,
+        embeddings: &HashMap<String, Vec<f32>>,
+        rng: &mut impl Rng,
+        cascaded: &mut Vec<Unfinished<'a>>,
+    ) {
+        if log.level != LogLevel::Error || depth >= cascade::MAX_CHAIN {
+            return;
+        }
+        for rule in self.cascades.starting_at(&log.service) {
+            if !rng.gen_bool(rule.rate.clamp(0.0, 1.0)) {
+                continue;
+            }
+            for to in &rule.to {
+                let level = if rng.gen_bool(rule.escalate.clamp(0.0, 1.0)) {
+                    LogLevel::Error
+                } else {
+                    LogLevel::Warn
+                };
+                let message = cascade::message(&level, &log.service, &log.message);
+                let (Some(embedding), Some(service)) = (
+                    self.embedding(embeddings, &message),
+                    self.cascades.service(to),
+                ) else {
+                    continue;
+                };
+                let later = at.later(Duration::from_millis(rng.gen_range(0..=rule.within_ms)));
+                let latency_multiplier = self
+                    .scenarios
+                    .schedule()
+                    .modifiers(to, later.elapsed)
+                    .latency_multiplier;
+                let mut fields = generate_fields(&level, latency_multiplier, rng);
+                fields.insert("cause_id".to_string(), json!(log.id));
+                let mut entry = LogEntry {
+                    id: random_uuid(rng).to_string(),
+                    timestamp: later.timestamp,
+                    service: to.clone(),
+                    level,
+                    message,
+                    embedding,
+                    sparse: None,
+                    extra_embeddings: BTreeMap::new(),
+                    fields,
+                    trace: log.trace.as_ref().map(|trace| TraceContext {
+                        trace_id: trace.trace_id.clone(),
+                        span_id: format!("{:016x}", rng.r#gen::<u64>()),
+                        parent_span_id: Some(trace.span_id.clone()),
+                    }),
+                    sample_rate: None,
+                    repeat_count: None,
+                };
+                // queued after the entry that sets them off
+                let mut further = Vec::new();
+                self.cascade(&entry, later, depth + 1, embeddings, rng, &mut further);
+                self.jitter.apply(
+                    &mut entry,
+                    |message| self.embedding(embeddings, message),
+                    rng,
+                );
+                cascaded.push(Unfinished {
+                    log: entry,
+                    service,
+                    at: later,
+                });
+                cascaded.append(&mut further);
+            }
+        }
+    }
+
+    /// Stamp an entry `service` logs at `at`, and render it the way the
+    /// service does
+    fn finish(
+        &self,
+        service: &ServiceConfig,
+        mut log: LogEntry,
+        at: Moment,
+        rng: &mut impl Rng,
+    ) -> LogEntry {
+        log.timestamp = service.skew.apply(at.timestamp, rng);
+        if let Some(metadata) = &self.metadata {
+            metadata.apply(&mut log, at.elapsed, rng);
+        }
+        if let Some(pii) = &self.pii {
+            pii.apply(&mut log, rng);
+        }
+        service.format.apply(&mut log, rng);
+        if let Some(payload) = &self.payload {
+            payload.apply(&mut log, rng);
+        }
+        if let Some(stack_traces) = &self.stack_traces {
+            stack_traces.apply(&mut log, rng);
+        }
+        log
+    }
+}
+
+/// Request attributes for an entry, with status codes and latencies that
+/// follow its level (errors are 5xx and slow, warnings 4xx)
+fn generate_fields(
+    level: &LogLevel,
+    latency_multiplier: f64,
+    rng: &mut impl Rng,
+) -> HashMap<String, Value> {
+    let (status_codes, slowdown): (&[u16], f64) = match level {
+        LogLevel::Error => (&[500, 502, 503, 504], 4.0),
+        LogLevel::Warn => (&[400, 401, 403, 404, 409, 429], 2.0),
+        LogLevel::Debug | LogLevel::Info => (&[200, 200, 200, 201, 204], 1.0),
+    };
+    // long-tailed: most requests are quick, a few take seconds
+    let duration_ms =
+        (rng.gen_range(0.0f64..1.0).powi(3) * 1500.0 * slowdown * latency_multiplier) as u64 + 1;
+
+    HashMap::from([
+        (
+            "request_id".to_string(),
+            json!(random_uuid(rng).to_string()),
+        ),
+        (
+            "user_id".to_string(),
+            json!(format!("user-{:05}", rng.gen_range(0..10_000))),
+        ),
+        ("duration_ms".to_string(), json!(duration_ms)),
+        (
+            "status_code".to_string(),
+            json!(status_codes[rng.gen_range(0..status_codes.len())]),
+        ),
+        ("region".to_string(), json!(pick(REGIONS, rng))),
+    ])
+}
+
+/// Generate an entry for `service`, its level drawn by `weights` (the
+/// service's own, unless changed while running)
 pub fn generate_log(
     service: &ServiceConfig,
+    weights: &LogLevelWeights,
     rng: &mut impl Rng,
-    pool: &[String],
+    pool: &Messages,
     embeddings: &HashMap<String, Vec<f32>>,
+    modifiers: &Modifiers,
+    popularity: &Popularity,
 ) -> LogEntry {
-    let level = pick_level(&service.level_weights, rng);
-    let message = &pool[rng.gen_range(0..pool.len())];
-    let base_embedding = embeddings.get(message).cloned().unwrap_or_default();
-    let embedding = jitter_embedding(&base_embedding, rng, 0.01);
+    let level = match modifiers.error_rate {
+        Some(rate) if rng.gen_bool(rate.clamp(0.0, 1.0)) => LogLevel::Error,
+        Some(_) => pick_level(
+            &LogLevelWeights {
+                error: 0.0,
+                ..weights.clone()
+            },
+            rng,
+        ),
+        None => pick_level(weights, rng),
+    };
+    let message = if modifiers.messages.is_empty() {
+        let pool = pool.for_level(&level);
+        pool[popularity.pick(pool.len(), rng)].as_str()
+    } else {
+        modifiers.messages[rng.gen_range(0..modifiers.messages.len())]
+    };
+    let embedding = embeddings.get(message).cloned().unwrap_or_default();
+    let fields = generate_fields(&level, modifiers.latency_multiplier, rng);
 
     LogEntry {
-        id: Uuid::new_v4().to_string(),
+        id: random_uuid(rng).to_string(),
         timestamp: Utc::now(),
         service: service.name.clone(),
         level,
-        message: message.clone(),
+        message: message.to_string(),
         embedding,
+        sparse: None,
+        extra_embeddings: BTreeMap::new(),
+        fields,
+        trace: None,
+        sample_rate: None,
+        repeat_count: None,
     }
 }
 
 pub async fn emit_logs(
     service: ServiceConfig,
-    tx: mpsc::Sender<LogEntry>,
+    tx: EntrySender,
     duration: Duration,
-    pool: Arc<Vec<String>>,
-    embeddings: Arc<HashMap<String, Vec<f32>>>,
+    source: MessageSource,
+    generated: Arc<AtomicU64>,
+    mut rng: StdRng,
 ) {
-    let mut rng = StdRng::from_entropy();
-    let start = Instant::now();
-    let mean_interval_ms = 1000.0 / service.rate_per_sec;
+    let mut timeline = Timeline::new(source.backfill);
+    let mut recent = source.duplicates.as_deref().map(Recent::new);
+    let base_rate = source
+        .control
+        .rate(&source.pipeline, &service.name, service.rate_per_sec);
+
+    let mut arrivals = Arrivals::new(service.traffic.arrival, &mut rng);
+    // each of the service's workers takes its share of the rate
+    let workers = service.workers.max(1) as f64;
 
-    while duration.is_zero() || start.elapsed() < duration {
-        let log = generate_log(&service, &mut rng, &pool, &embeddings);
+    while !timeline.finished(duration) && !source.control.is_stopping() {
+        let rate =
+            base_rate.get() * source.rate_multiplier(&service, &timeline, duration) / workers;
+        let due = arrivals.wait(rate, &mut rng, &mut timeline).await;
+
+        let now = timeline.now(&source.scenarios);
+        for age in due {
+            let at = now.earlier(age);
+            let Some((log, cascaded)) = source.generate(&service, None, at, &mut rng).await else {
+                continue;
+            };
+            let duplicate = recent
+                .as_mut()
+                .and_then(|recent| recent.record(&log, at.timestamp, &mut rng));
+            let entries = std::iter::once(log).chain(duplicate).chain(cascaded);
+            if !send(&tx, &generated, entries).await {
+                return;
+            }
+        }
+    }
+}
+
+/// Hand `entries` to the pipeline, counting each. Returns false once the
+/// pipeline has shut down.
+async fn send(
+    tx: &EntrySender,
+    generated: &AtomicU64,
+    entries: impl IntoIterator<Item = LogEntry>,
+) -> bool {
+    for log in entries {
+        metrics::entry_generated(&log.service);
+        generated.fetch_add(1, Ordering::Relaxed);
         if tx.send(log).await.is_err() {
-            break;
+            return false;
+        }
+    }
+    true
+}
+
+/// Exponential delay with the given mean, for Poisson arrivals
+fn exponential_delay(mean_ms: f64, rng: &mut impl Rng) -> Duration {
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+    Duration::from_secs_f64(-mean_ms * u.ln() / 1000.0)
+}
+
+/// Longest wait before a changing rate is looked at again
+const MAX_WAIT: Duration = Duration::from_secs(1);
+
+/// Wait until the next arrival at `rate` per second (exponential
+/// inter-arrival time, i.e. a Poisson process). Waits longer than
+/// [`MAX_WAIT`] are cut short, returning false, so the caller can pick up
+/// a new rate; exponential waits are memoryless, so drawing again from
+/// there doesn't skew the arrivals.
+async fn wait_for_arrival(rate: f64, rng: &mut impl Rng, timeline: &mut Timeline) -> bool {
+    if rate <= 0.0 {
+        timeline.advance(MAX_WAIT).await;
+        return false;
+    }
+    let delay = exponential_delay(1000.0 / rate, rng);
+    if delay > MAX_WAIT {
+        timeline.advance(MAX_WAIT).await;
+        return false;
+    }
+    timeline.advance(delay).await;
+    true
+}
+
+/// Where a service's emitter is between one arrival (or batch) and the
+/// next
+struct Arrivals {
+    arrival: Arrival,
+    /// Waiting done toward the next arrival, in arrivals' worth at the
+    /// rates that applied meanwhile; in seconds for batches
+    progress: f64,
+    /// Arrivals' worth of waiting the next one takes
+    gap: f64,
+    /// Entries owed to the next batch, fractions included
+    owed: f64,
+}
+
+impl Arrivals {
+    fn new(arrival: Arrival, rng: &mut impl Rng) -> Self {
+        Self {
+            arrival,
+            progress: 0.0,
+            gap: Self::draw_gap(arrival, rng),
+            owed: 0.0,
+        }
+    }
+
+    fn draw_gap(arrival: Arrival, rng: &mut impl Rng) -> f64 {
+        match arrival {
+            Arrival::Gaussian { stddev } => {
+                // Box-Muller
+                let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+                let z = (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * rng.r#gen::<f64>()).cos();
+                (1.0 + stddev.max(0.0) * z).max(0.0)
+            }
+            Arrival::Poisson | Arrival::Fixed | Arrival::Batch { .. } => 1.0,
         }
+    }
 
-        // Exponential inter-arrival time (Poisson process)
-        let u: f64 = rng.gen_range(f64::EPSILON..1.0);
-        let delay_ms = (-mean_interval_ms * u.ln()) as u64;
-        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    /// Wait for whatever arrives next at `rate` per second, returning how
+    /// long ago each entry arriving now was due: one entry due now, or a
+    /// batch's worth spread over its interval. Like [`wait_for_arrival`],
+    /// waits longer than [`MAX_WAIT`] are cut short so a new rate is picked
+    /// up, with nothing arriving.
+    async fn wait(
+        &mut self,
+        rate: f64,
+        rng: &mut impl Rng,
+        timeline: &mut Timeline,
+    ) -> Vec<Duration> {
+        let max_wait = MAX_WAIT.as_secs_f64();
+        match self.arrival {
+            Arrival::Poisson => {
+                if wait_for_arrival(rate, rng, timeline).await {
+                    vec![Duration::ZERO]
+                } else {
+                    Vec::new()
+                }
+            }
+            Arrival::Fixed | Arrival::Gaussian { .. } => {
+                if rate <= 0.0 {
+                    timeline.advance(MAX_WAIT).await;
+                    return Vec::new();
+                }
+                let secs = (self.gap - self.progress) / rate;
+                if secs > max_wait {
+                    timeline.advance(MAX_WAIT).await;
+                    self.progress += rate * max_wait;
+                    return Vec::new();
+                }
+                timeline
+                    .advance(Duration::from_secs_f64(secs.max(0.0)))
+                    .await;
+                self.progress = 0.0;
+                self.gap = Self::draw_gap(self.arrival, rng);
+                vec![Duration::ZERO]
+            }
+            Arrival::Batch { every_secs } => {
+                let every = every_secs.max(0.001);
+                let step = (every - self.progress).clamp(0.0, max_wait);
+                timeline.advance(Duration::from_secs_f64(step)).await;
+                self.progress += step;
+                self.owed += rate.max(0.0) * step;
+                if self.progress + 1e-9 < every {
+                    return Vec::new();
+                }
+                self.progress = 0.0;
+                let due = self.owed.floor();
+                self.owed -= due;
+                let mut ages: Vec<Duration> = (0..due as usize)
+                    .map(|_| Duration::from_secs_f64(rng.gen_range(0.0..every)))
+                    .collect();
+                // oldest first
+                ages.sort_unstable_by(|a, b| b.cmp(a));
+                ages
+            }
+        }
+    }
+}
+
+/// The services a flow's requests pass through
+struct Route {
+    hops: Vec<ServiceConfig>,
+    /// Mean delay between one hop and the next
+    mean_hop_ms: f64,
+}
+
+impl Route {
+    /// Take one request along the route, logging an entry per hop under a
+    /// new trace id. Returns false once the pipeline has shut down.
+    async fn request(
+        &self,
+        source: &MessageSource,
+        mut timeline: Timeline,
+        tx: &EntrySender,
+        generated: &AtomicU64,
+        rng: &mut (impl Rng + Send),
+    ) -> bool {
+        let trace_id = format!("{:032x}", rng.r#gen::<u128>());
+        let mut parent_span_id = None;
+        let mut recent = source.duplicates.as_deref().map(Recent::new);
+        for (i, service) in self.hops.iter().enumerate() {
+            if i > 0 {
+                timeline
+                    .advance(exponential_delay(self.mean_hop_ms, rng))
+                    .await;
+            }
+            let span_id = format!("{:016x}", rng.r#gen::<u64>());
+            let trace = TraceContext {
+                trace_id: trace_id.clone(),
+                span_id: span_id.clone(),
+                parent_span_id: parent_span_id.replace(span_id),
+            };
+            let at = timeline.now(&source.scenarios);
+            let Some((log, cascaded)) = source.generate(service, Some(trace), at, rng).await else {
+                continue;
+            };
+            let duplicate = recent
+                .as_mut()
+                .and_then(|recent| recent.record(&log, at.timestamp, rng));
+            let entries = std::iter::once(log).chain(duplicate).chain(cascaded);
+            if !send(tx, generated, entries).await {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Start requests along the flow's path at its rate, each logging one entry
+/// per service it passes through, all under the same trace id
+pub async fn emit_flow(
+    flow: FlowConfig,
+    services: Vec<ServiceConfig>,
+    tx: EntrySender,
+    duration: Duration,
+    source: MessageSource,
+    generated: Arc<AtomicU64>,
+    mut rng: StdRng,
+) {
+    // services on the path the pipeline doesn't emit for get typical weights
+    let hops = flow
+        .path
+        .iter()
+        .map(|name| {
+            services
+                .iter()
+                .find(|service| &service.name == name)
+                .cloned()
+                .unwrap_or_else(|| ServiceConfig::passive(name))
+        })
+        .collect();
+    let route = Arc::new(Route {
+        hops,
+        mean_hop_ms: flow.hop_delay_ms as f64,
+    });
+    let mut timeline = Timeline::new(source.backfill);
+    let base_rate = source.control.rate(
+        &source.pipeline,
+        &format!("flow:{}", flow.name),
+        flow.rate_per_sec,
+    );
+
+    while !timeline.finished(duration) && !source.control.is_stopping() {
+        // requests follow the traffic shape and scenarios of the service
+        // they enter through
+        let rate = base_rate.get()
+            * route.hops.first().map_or(1.0, |entry| {
+                source.rate_multiplier(entry, &timeline, duration)
+            });
+        if !wait_for_arrival(rate, &mut rng, &mut timeline).await {
+            continue;
+        }
+
+        let mut request_rng = StdRng::seed_from_u64(rng.r#gen());
+        if source.backfill.is_some() {
+            // nothing to overlap with when backfilling: take each request
+            // through in turn, its hops stamped along the simulated clock
+            if !route
+                .request(&source, timeline.clone(), &tx, &generated, &mut request_rng)
+                .await
+            {
+                return;
+            }
+            continue;
+        }
+
+        let route = Arc::clone(&route);
+        let tx = tx.clone();
+        let request_source = source.clone();
+        let generated = Arc::clone(&generated);
+        let request_timeline = timeline.clone();
+        // each request runs on its own so slow ones overlap like real traffic
+        tokio::spawn(async move {
+            route
+                .request(
+                    &request_source,
+                    request_timeline,
+                    &tx,
+                    &generated,
+                    &mut request_rng,
+                )
+                .await;
+        });
     }
 }
 
 fn pick_level(weights: &LogLevelWeights, rng: &mut impl Rng) -> LogLevel {
     let total = weights.debug + weights.info + weights.warn + weights.error;
+    if total <= 0.0 {
+        return LogLevel::Info;
+    }
     let roll: f64 = rng.gen_range(0.0..total);
 
     if roll < weights.debug {
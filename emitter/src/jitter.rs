@@ -0,0 +1,164 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::emitter::fnv1a;
+use crate::log_entry::LogEntry;
+
+/// Words rewritten by paraphrasing, and what they become
+const REWORDINGS: &[(&str, &str)] = &[
+    ("failed", "did not succeed"),
+    ("completed", "finished"),
+    ("successfully", "ok"),
+    ("initiated", "started"),
+    ("detected", "observed"),
+    ("rejected", "refused"),
+    ("received", "got"),
+    ("exceeded", "went over"),
+    ("after", "following"),
+    ("retries", "retry attempts"),
+    ("request", "req"),
+    ("connection", "conn"),
+    ("session", "sess"),
+    ("timed out", "hit timeout"),
+    ("waiting", "while waiting"),
+    ("error", "err"),
+];
+
+fn default_scale() -> f64 {
+    0.01
+}
+
+fn default_variants() -> usize {
+    3
+}
+
+/// How entries logging the same message are kept from having identical
+/// embeddings, which bunch up in HNSW graphs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JitterConfig {
+    /// Every entry carries its message's exact embedding
+    None,
+    /// Uniform noise of up to `scale` times each component
+    Noise {
+        #[serde(default = "default_scale")]
+        scale: f64,
+    },
+    /// Log one of `variants` slightly reworded versions of the message (or
+    /// the message itself), each embedded for real up front
+    Paraphrase {
+        #[serde(default = "default_variants")]
+        variants: usize,
+    },
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        JitterConfig::Noise {
+            scale: default_scale(),
+        }
+    }
+}
+
+impl JitterConfig {
+    /// The reworded versions of `message` an entry may log instead, which
+    /// need embedding along with it
+    pub fn variants(&self, message: &str) -> Vec<String> {
+        match *self {
+            JitterConfig::Paraphrase { variants } => (1..=variants)
+                .map(|variant| paraphrase(message, variant))
+                .filter(|paraphrased| paraphrased != message)
+                .collect(),
+            JitterConfig::None | JitterConfig::Noise { .. } => Vec::new(),
+        }
+    }
+
+    /// Set `entry` apart from others logging its message. Paraphrases'
+    /// embeddings come from `embedding`; a message whose paraphrases weren't
+    /// embedded is left as it is.
+    pub fn apply(
+        &self,
+        entry: &mut LogEntry,
+        embedding: impl Fn(&str) -> Option<Vec<f32>>,
+        rng: &mut impl Rng,
+    ) {
+        match *self {
+            JitterConfig::None => {}
+            JitterConfig::Noise { .. } => self.perturb(&mut entry.embedding, rng),
+            JitterConfig::Paraphrase { variants } => {
+                let variant = rng.gen_range(0..=variants);
+                if variant == 0 {
+                    return;
+                }
+                let paraphrased = paraphrase(&entry.message, variant);
+                if let Some(embedding) = embedding(&paraphrased) {
+                    entry.message = paraphrased;
+                    entry.embedding = embedding;
+                }
+            }
+        }
+    }
+
+    /// Add noise to an embedding, when that's the jitter, keeping its
+    /// length so normalized embeddings stay so. Lazily made embeddings get
+    /// theirs once they arrive.
+    pub fn perturb(&self, embedding: &mut [f32], rng: &mut impl Rng) {
+        if let JitterConfig::Noise { scale } = *self {
+            let scale = scale.max(0.0) as f32;
+            if scale > 0.0 {
+                let norm = length(embedding);
+                for v in embedding.iter_mut() {
+                    *v += rng.gen_range(-1.0f32..1.0) * scale * v.abs().max(0.01);
+                }
+                let perturbed = length(embedding);
+                if perturbed > 0.0 {
+                    for v in embedding.iter_mut() {
+                        *v *= norm / perturbed;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Euclidean length of `embedding`
+fn length(embedding: &[f32]) -> f32 {
+    embedding.iter().map(|v| v * v).sum::<f32>().sqrt()
+}
+
+/// The `variant`-th rewording of `message`: a word or two swapped for a
+/// synonym or abbreviation, or a small change in punctuation or case. The
+/// same for every run, so it can be embedded ahead of time.
+fn paraphrase(message: &str, variant: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(fnv1a(variant as u64, message));
+    let mut paraphrased = message.to_string();
+    let mut rewordings: Vec<_> = REWORDINGS
+        .iter()
+        .filter(|(from, _)| paraphrased.contains(from))
+        .collect();
+    rewordings.shuffle(&mut rng);
+    for (from, to) in rewordings.into_iter().take(rng.gen_range(1..=2)) {
+        paraphrased = paraphrased.replacen(from, to, 1);
+    }
+    // with nothing to reword, each variant makes a different change
+    let unchanged = paraphrased == message;
+    if unchanged || rng.gen_bool(0.3) {
+        let change = if unchanged {
+            (variant - 1) % 3
+        } else {
+            rng.gen_range(0..3)
+        };
+        paraphrased = match change {
+            0 => format!("{paraphrased}."),
+            1 => paraphrased.replacen(": ", " - ", 1),
+            _ => paraphrased.to_lowercase(),
+        };
+    }
+    if paraphrased == message {
+        paraphrased.push('.');
+    }
+    paraphrased
+}
@@ -0,0 +1,757 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::LogLevel;
+use crate::vocabulary::{VocabularyConfig, slots, strings};
+
+/// Translations of the built-in vocabulary's phrasing. Component names and
+/// metrics stay as they are, like identifiers in real localized logs.
+struct Pack {
+    actions: &'static [&'static str],
+    targets: &'static [&'static str],
+    contexts: &'static [&'static str],
+    templates: &'static [&'static str],
+    failures: &'static [&'static str],
+    causes: &'static [&'static str],
+    error_templates: &'static [&'static str],
+    degradations: &'static [&'static str],
+    warn_templates: &'static [&'static str],
+}
+
+const GERMAN: Pack = Pack {
+    actions: &[
+        "erfolgreich abgeschlossen",
+        "nach Wiederholungen fehlgeschlagen",
+        "Wiederherstellung eingeleitet",
+        "ungültige Anfrage abgelehnt",
+        "Neuverteilung ausgelöst",
+        "veralteten Eintrag entfernt",
+        "ausstehende Schreibvorgänge übertragen",
+        "Zugangsdaten rotiert",
+        "Migration angewendet",
+        "Wartung geplant",
+    ],
+    targets: &[
+        "in Tabelle bestellungen",
+        "für Mandant af923c",
+        "vom Upstream-Host",
+        "in der Verbrauchergruppe",
+        "auf Volume /data",
+        "zum nachgelagerten Dienst",
+        "auf Port 8443",
+        "für Benutzersitzung",
+        "auf dem primären Shard",
+        "in der Dead-Letter-Queue",
+    ],
+    contexts: &[
+        "(wird wiederholt)",
+        "(nicht blockierend)",
+        "(geplant)",
+        "(Antwort aus dem Cache)",
+        "(schreibgeschützt)",
+        "(gedrosselt)",
+        "(eingeschränkter Modus)",
+        "(Kaltstart)",
+    ],
+    templates: &[
+        "{component}: {action} {target} {context}",
+        "{component}: {action} [{metric}] {target}",
+        "{component}: {action} [{metric}]",
+    ],
+    failures: &[
+        "Anfrage fehlgeschlagen",
+        "Schreibvorgang abgebrochen",
+        "Verbindung verloren",
+        "Transaktion zurückgerollt",
+        "Zeitüberschreitung beim Aufruf",
+        "Wiederholungen erschöpft",
+    ],
+    causes: &[
+        "Verbindung abgelehnt",
+        "Frist überschritten",
+        "Prüfsumme stimmt nicht überein",
+        "kein Quorum erreicht",
+        "Speicher erschöpft",
+        "Zugriff verweigert",
+        "Zertifikat abgelaufen",
+        "Upstream antwortete mit 503",
+    ],
+    error_templates: &[
+        "{component}: {failure} {target}: {cause}",
+        "{component}: {failure} [{metric}], Ursache: {cause}",
+    ],
+    degradations: &[
+        "langsame Antworten",
+        "Anfrage wird wiederholt",
+        "erhöhte Fehlerrate",
+        "Kapazitätsgrenze fast erreicht",
+        "Last wird abgeworfen",
+        "Warteschlange wächst",
+    ],
+    warn_templates: &[
+        "{component}: {degradation} {target} [{metric}]",
+        "{component}: {degradation} {context}",
+    ],
+};
+
+const SPANISH: Pack = Pack {
+    actions: &[
+        "completado correctamente",
+        "falló tras varios reintentos",
+        "inició la recuperación",
+        "rechazó una solicitud no válida",
+        "activó el reequilibrio",
+        "expulsó una entrada obsoleta",
+        "vació las escrituras pendientes",
+        "rotó las credenciales",
+        "aplicó la migración",
+        "programó el mantenimiento",
+    ],
+    targets: &[
+        "en la tabla pedidos",
+        "para el inquilino af923c",
+        "desde el host de origen",
+        "en el grupo de consumidores",
+        "en el volumen /data",
+        "hacia el servicio dependiente",
+        "en el puerto 8443",
+        "para la sesión de usuario",
+        "en el fragmento primario",
+        "en la cola de mensajes fallidos",
+    ],
+    contexts: &[
+        "(reintentando)",
+        "(no bloqueante)",
+        "(programado)",
+        "(respuesta en caché)",
+        "(solo lectura)",
+        "(limitado)",
+        "(modo degradado)",
+        "(arranque en frío)",
+    ],
+    templates: &[
+        "{component}: {action} {target} {context}",
+        "{component}: {action} [{metric}] {target}",
+        "{component}: {action} [{metric}]",
+    ],
+    failures: &[
+        "la solicitud falló",
+        "escritura abortada",
+        "conexión perdida",
+        "transacción revertida",
+        "la llamada agotó el tiempo de espera",
+        "reintentos agotados",
+    ],
+    causes: &[
+        "conexión rechazada",
+        "plazo excedido",
+        "suma de verificación incorrecta",
+        "sin quórum",
+        "memoria agotada",
+        "permiso denegado",
+        "certificado caducado",
+        "el servicio de origen devolvió 503",
+    ],
+    error_templates: &[
+        "{component}: {failure} {target}: {cause}",
+        "{component}: {failure} [{metric}], causa: {cause}",
+    ],
+    degradations: &[
+        "respuestas lentas",
+        "reintentando la solicitud",
+        "tasa de errores elevada",
+        "cerca del límite de capacidad",
+        "descartando carga",
+        "la cola está creciendo",
+    ],
+    warn_templates: &[
+        "{component}: {degradation} {target} [{metric}]",
+        "{component}: {degradation} {context}",
+    ],
+};
+
+const RUSSIAN: Pack = Pack {
+    actions: &[
+        "успешно завершено",
+        "сбой после повторных попыток",
+        "запущено восстановление",
+        "отклонён некорректный запрос",
+        "запущена перебалансировка",
+        "удалена устаревшая запись",
+        "сброшены отложенные записи",
+        "обновлены учётные данные",
+        "применена миграция",
+        "запланировано обслуживание",
+    ],
+    targets: &[
+        "в таблице заказов",
+        "для арендатора af923c",
+        "от вышестоящего хоста",
+        "в группе потребителей",
+        "на томе /data",
+        "к нижестоящему сервису",
+        "на порту 8443",
+        "для сессии пользователя",
+        "на основном шарде",
+        "в очереди недоставленных сообщений",
+    ],
+    contexts: &[
+        "(повтор)",
+        "(неблокирующий)",
+        "(по расписанию)",
+        "(ответ из кэша)",
+        "(только чтение)",
+        "(ограничено)",
+        "(деградированный режим)",
+        "(холодный старт)",
+    ],
+    templates: &[
+        "{component}: {action} {target} {context}",
+        "{component}: {action} [{metric}] {target}",
+        "{component}: {action} [{metric}]",
+    ],
+    failures: &[
+        "запрос не выполнен",
+        "запись прервана",
+        "соединение потеряно",
+        "транзакция отменена",
+        "истекло время ожидания вызова",
+        "попытки исчерпаны",
+    ],
+    causes: &[
+        "в соединении отказано",
+        "превышен срок ожидания",
+        "несовпадение контрольной суммы",
+        "кворум не достигнут",
+        "недостаточно памяти",
+        "доступ запрещён",
+        "срок действия сертификата истёк",
+        "вышестоящий сервис вернул 503",
+    ],
+    error_templates: &[
+        "{component}: {failure} {target}: {cause}",
+        "{component}: {failure} [{metric}], причина: {cause}",
+    ],
+    degradations: &[
+        "медленные ответы",
+        "повтор запроса",
+        "повышенная доля ошибок",
+        "близко к пределу ёмкости",
+        "сброс нагрузки",
+        "очередь растёт",
+    ],
+    warn_templates: &[
+        "{component}: {degradation} {target} [{metric}]",
+        "{component}: {degradation} {context}",
+    ],
+};
+
+const JAPANESE: Pack = Pack {
+    actions: &[
+        "正常に完了しました",
+        "リトライ後に失敗しました",
+        "復旧を開始しました",
+        "不正なリクエストを拒否しました",
+        "リバランスを開始しました",
+        "古いエントリを削除しました",
+        "保留中の書き込みをフラッシュしました",
+        "認証情報をローテーションしました",
+        "マイグレーションを適用しました",
+        "メンテナンスを予約しました",
+    ],
+    targets: &[
+        "ordersテーブルで",
+        "テナントaf923cの",
+        "上流ホストから",
+        "コンシューマーグループ内で",
+        "/dataボリュームで",
+        "下流サービスへ",
+        "ポート8443で",
+        "ユーザーセッションの",
+        "プライマリシャードで",
+        "デッドレターキューへ",
+    ],
+    contexts: &[
+        "（リトライ中）",
+        "（ノンブロッキング）",
+        "（スケジュール実行）",
+        "（キャッシュ応答）",
+        "（読み取り専用）",
+        "（スロットリング中）",
+        "（縮退運転）",
+        "（コールドスタート）",
+    ],
+    templates: &[
+        "{component}: {target}{action} {context}",
+        "{component}: {target}{action} [{metric}]",
+        "{component}: {action} [{metric}]",
+    ],
+    failures: &[
+        "リクエストが失敗しました",
+        "書き込みが中断されました",
+        "接続が切断されました",
+        "トランザクションがロールバックされました",
+        "呼び出しがタイムアウトしました",
+        "リトライ回数の上限に達しました",
+    ],
+    causes: &[
+        "接続が拒否されました",
+        "期限を超過しました",
+        "チェックサムが一致しません",
+        "クォーラムに達しません",
+        "メモリ不足",
+        "権限がありません",
+        "証明書の有効期限切れ",
+        "上流が503を返しました",
+    ],
+    error_templates: &[
+        "{component}: {target}{failure}: {cause}",
+        "{component}: {failure} [{metric}] 原因: {cause}",
+    ],
+    degradations: &[
+        "応答が遅延しています",
+        "リクエストを再試行しています",
+        "エラー率が上昇しています",
+        "容量の上限に近づいています",
+        "負荷を制限しています",
+        "キューが滞留しています",
+    ],
+    warn_templates: &[
+        "{component}: {target}{degradation} [{metric}]",
+        "{component}: {degradation} {context}",
+    ],
+};
+
+const CHINESE: Pack = Pack {
+    actions: &[
+        "成功完成",
+        "重试后失败",
+        "开始优雅恢复",
+        "拒绝了无效请求",
+        "触发了重新平衡",
+        "清除了过期条目",
+        "刷新了待写入数据",
+        "轮换了凭据",
+        "应用了迁移",
+        "安排了维护",
+    ],
+    targets: &[
+        "在订单表上",
+        "租户 af923c 的",
+        "来自上游主机",
+        "在消费者组中",
+        "在 /data 卷上",
+        "发往下游服务",
+        "在端口 8443 上",
+        "用户会话的",
+        "在主分片上",
+        "到死信队列",
+    ],
+    contexts: &[
+        "（重试中）",
+        "（非阻塞）",
+        "（定时）",
+        "（缓存响应）",
+        "（只读）",
+        "（已限流）",
+        "（降级模式）",
+        "（冷启动）",
+    ],
+    templates: &[
+        "{component}：{target}{action} {context}",
+        "{component}：{target}{action} [{metric}]",
+        "{component}：{action} [{metric}]",
+    ],
+    failures: &[
+        "请求失败",
+        "写入中止",
+        "连接丢失",
+        "事务已回滚",
+        "调用超时",
+        "重试次数已用尽",
+    ],
+    causes: &[
+        "连接被拒绝",
+        "超过截止时间",
+        "校验和不匹配",
+        "未达到法定人数",
+        "内存不足",
+        "权限被拒绝",
+        "证书已过期",
+        "上游返回 503",
+    ],
+    error_templates: &[
+        "{component}：{target}{failure}，原因：{cause}",
+        "{component}：{failure} [{metric}]，原因：{cause}",
+    ],
+    degradations: &[
+        "响应缓慢",
+        "正在重试请求",
+        "错误率升高",
+        "接近容量上限",
+        "正在削减负载",
+        "队列积压",
+    ],
+    warn_templates: &[
+        "{component}：{target}{degradation} [{metric}]",
+        "{component}：{degradation} {context}",
+    ],
+};
+
+const KOREAN: Pack = Pack {
+    actions: &[
+        "성공적으로 완료됨",
+        "재시도 후 실패함",
+        "복구를 시작함",
+        "잘못된 요청을 거부함",
+        "리밸런싱을 시작함",
+        "오래된 항목을 제거함",
+        "대기 중인 쓰기를 플러시함",
+        "자격 증명을 교체함",
+        "마이그레이션을 적용함",
+        "유지 보수를 예약함",
+    ],
+    targets: &[
+        "orders 테이블에서",
+        "테넌트 af923c에 대해",
+        "업스트림 호스트로부터",
+        "컨슈머 그룹에서",
+        "/data 볼륨에서",
+        "다운스트림 서비스로",
+        "포트 8443에서",
+        "사용자 세션에 대해",
+        "기본 샤드에서",
+        "데드 레터 큐로",
+    ],
+    contexts: &[
+        "(재시도 중)",
+        "(논블로킹)",
+        "(예약됨)",
+        "(캐시된 응답)",
+        "(읽기 전용)",
+        "(제한됨)",
+        "(성능 저하 모드)",
+        "(콜드 스타트)",
+    ],
+    templates: &[
+        "{component}: {target} {action} {context}",
+        "{component}: {target} {action} [{metric}]",
+        "{component}: {action} [{metric}]",
+    ],
+    failures: &[
+        "요청 실패",
+        "쓰기 중단",
+        "연결 끊김",
+        "트랜잭션 롤백",
+        "호출 시간 초과",
+        "재시도 횟수 초과",
+    ],
+    causes: &[
+        "연결 거부됨",
+        "기한 초과",
+        "체크섬 불일치",
+        "쿼럼 미달",
+        "메모리 부족",
+        "권한 거부됨",
+        "인증서 만료",
+        "업스트림이 503 반환",
+    ],
+    error_templates: &[
+        "{component}: {target} {failure}: {cause}",
+        "{component}: {failure} [{metric}], 원인: {cause}",
+    ],
+    degradations: &[
+        "응답 지연",
+        "요청 재시도 중",
+        "오류율 상승",
+        "용량 한계 근접",
+        "부하 차단 중",
+        "큐 적체",
+    ],
+    warn_templates: &[
+        "{component}: {target} {degradation} [{metric}]",
+        "{component}: {degradation} {context}",
+    ],
+};
+
+const ARABIC: Pack = Pack {
+    actions: &[
+        "اكتمل بنجاح",
+        "فشل بعد عدة محاولات",
+        "بدأ الاسترداد",
+        "رفض طلبًا غير صالح",
+        "أطلق إعادة التوازن",
+        "أزال إدخالًا قديمًا",
+        "أفرغ عمليات الكتابة المعلقة",
+        "دوّر بيانات الاعتماد",
+        "طبّق الترحيل",
+        "جدول الصيانة",
+    ],
+    targets: &[
+        "في جدول الطلبات",
+        "للمستأجر af923c",
+        "من المضيف الرئيسي",
+        "في مجموعة المستهلكين",
+        "على وحدة التخزين /data",
+        "إلى الخدمة التابعة",
+        "على المنفذ 8443",
+        "لجلسة المستخدم",
+        "على الجزء الأساسي",
+        "إلى قائمة الرسائل الميتة",
+    ],
+    contexts: &[
+        "(إعادة المحاولة)",
+        "(غير حاجب)",
+        "(مجدول)",
+        "(استجابة مخزنة)",
+        "(للقراءة فقط)",
+        "(مقيد)",
+        "(وضع متدهور)",
+        "(بدء بارد)",
+    ],
+    templates: &[
+        "{component}: {action} {target} {context}",
+        "{component}: {action} [{metric}] {target}",
+        "{component}: {action} [{metric}]",
+    ],
+    failures: &[
+        "فشل الطلب",
+        "أُلغيت الكتابة",
+        "انقطع الاتصال",
+        "تم التراجع عن المعاملة",
+        "انتهت مهلة الاستدعاء",
+        "استُنفدت المحاولات",
+    ],
+    causes: &[
+        "تم رفض الاتصال",
+        "تم تجاوز المهلة",
+        "عدم تطابق المجموع الاختباري",
+        "لم يتحقق النصاب",
+        "نفدت الذاكرة",
+        "تم رفض الإذن",
+        "انتهت صلاحية الشهادة",
+        "أعاد الخادم الرئيسي 503",
+    ],
+    error_templates: &[
+        "{component}: {failure} {target}: {cause}",
+        "{component}: {failure} [{metric}]، السبب: {cause}",
+    ],
+    degradations: &[
+        "استجابات بطيئة",
+        "إعادة محاولة الطلب",
+        "ارتفاع معدل الأخطاء",
+        "قرب الحد الأقصى للسعة",
+        "تخفيف الحمل",
+        "تراكم قائمة الانتظار",
+    ],
+    warn_templates: &[
+        "{component}: {degradation} {target} [{metric}]",
+        "{component}: {degradation} {context}",
+    ],
+};
+
+/// English phrasing dressed up with emoji, as chat-ops bots and some
+/// frontends log
+const EMOJI: Pack = Pack {
+    actions: &[],
+    targets: &[],
+    contexts: &[],
+    templates: &[
+        "✅ {component}: {action} {target} {context}",
+        "🚀 {component}: {action} [{metric}]",
+        "📦 {component}: {action} {target} [{metric}] 👀",
+    ],
+    failures: &[],
+    causes: &[],
+    error_templates: &[
+        "❌ {component}: {failure} {target}: {cause} 💥",
+        "🔥🔥 {component}: {failure} [{metric}], cause: {cause}",
+    ],
+    degradations: &[],
+    warn_templates: &[
+        "⚠️ {component}: {degradation} {target} [{metric}] 🐢",
+        "🟡 {component}: {degradation} {context}",
+    ],
+};
+
+fn default_share() -> f64 {
+    0.2
+}
+
+fn default_languages() -> Vec<Language> {
+    vec![
+        Language::De,
+        Language::Es,
+        Language::Ru,
+        Language::Ja,
+        Language::Zh,
+        Language::Ko,
+        Language::Ar,
+        Language::Emoji,
+    ]
+}
+
+/// A language (or script) pool messages can be generated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    De,
+    Es,
+    Ru,
+    Ja,
+    Zh,
+    Ko,
+    /// Right-to-left
+    Ar,
+    /// English with emoji
+    Emoji,
+}
+
+impl Language {
+    fn pack(self) -> &'static Pack {
+        match self {
+            Language::De => &GERMAN,
+            Language::Es => &SPANISH,
+            Language::Ru => &RUSSIAN,
+            Language::Ja => &JAPANESE,
+            Language::Zh => &CHINESE,
+            Language::Ko => &KOREAN,
+            Language::Ar => &ARABIC,
+            Language::Emoji => &EMOJI,
+        }
+    }
+
+    /// This language's phrasing, to be layered over a vocabulary
+    pub fn vocabulary(self) -> VocabularyConfig {
+        let pack = self.pack();
+        let present = |lists: &[(&str, &[&str])]| {
+            let lists: Vec<_> = lists
+                .iter()
+                .filter(|(_, items)| !items.is_empty())
+                .copied()
+                .collect();
+            slots(&lists)
+        };
+        VocabularyConfig {
+            slots: present(&[
+                ("action", pack.actions),
+                ("target", pack.targets),
+                ("context", pack.contexts),
+            ]),
+            templates: strings(pack.templates),
+            shares: BTreeMap::new(),
+            levels: BTreeMap::from([
+                (
+                    LogLevel::Error,
+                    VocabularyConfig {
+                        slots: present(&[("failure", pack.failures), ("cause", pack.causes)]),
+                        templates: strings(pack.error_templates),
+                        shares: BTreeMap::new(),
+                        levels: BTreeMap::new(),
+                    },
+                ),
+                (
+                    LogLevel::Warn,
+                    VocabularyConfig {
+                        slots: present(&[("degradation", pack.degradations)]),
+                        templates: strings(pack.warn_templates),
+                        shares: BTreeMap::new(),
+                        levels: BTreeMap::new(),
+                    },
+                ),
+            ]),
+        }
+    }
+}
+
+/// A share of pool messages in other languages and scripts, for exercising
+/// analyzers, tokenizers and multilingual embedding models
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MultilingualConfig {
+    /// Share of each pool's messages not in English (0.0 - 1.0)
+    #[serde(default = "default_share")]
+    pub share: f64,
+    /// Languages to spread that share over, evenly
+    #[serde(default = "default_languages")]
+    pub languages: Vec<Language>,
+}
+
+/// A vocabulary along with its translations, which a share of messages are
+/// drawn from instead
+#[derive(Debug, Clone)]
+pub struct Phrasing {
+    vocabulary: VocabularyConfig,
+    translations: Vec<VocabularyConfig>,
+    share: f64,
+}
+
+impl Phrasing {
+    /// `vocabulary`, translated as `multilingual` asks. Translations take
+    /// whatever a language leaves alone (component names, metrics) from
+    /// `vocabulary`.
+    pub fn new(vocabulary: VocabularyConfig, multilingual: Option<&MultilingualConfig>) -> Self {
+        let (translations, share) = match multilingual {
+            Some(multilingual) => (
+                multilingual
+                    .languages
+                    .iter()
+                    .map(|language| language.vocabulary().over(&vocabulary))
+                    .collect(),
+                multilingual.share.clamp(0.0, 1.0),
+            ),
+            None => (Vec::new(), 0.0),
+        };
+        Self {
+            vocabulary,
+            translations,
+            share,
+        }
+    }
+
+    /// The levels with phrasing of their own
+    pub fn levels(&self) -> impl Iterator<Item = &LogLevel> {
+        self.vocabulary.levels.keys()
+    }
+
+    /// The phrasing for entries at `level`; see [`VocabularyConfig::for_level`]
+    pub fn for_level(&self, level: &LogLevel) -> Self {
+        Self {
+            vocabulary: self.vocabulary.for_level(level),
+            translations: self
+                .translations
+                .iter()
+                .map(|translation| translation.for_level(level))
+                .collect(),
+            share: self.share,
+        }
+    }
+
+    /// A message in English, or a translation picked at random
+    pub fn generate(&self, rng: &mut impl Rng) -> String {
+        match self.translations.choose(rng) {
+            Some(translation) if rng.gen_bool(self.share) => translation.generate(rng),
+            _ => self.vocabulary.generate(rng),
+        }
+    }
+
+    /// `size` unique messages, `share` of them split evenly between the
+    /// translations and shuffled in among the rest
+    pub fn build_pool(&self, rng: &mut impl Rng, size: usize) -> Vec<String> {
+        if self.translations.is_empty() {
+            return self.vocabulary.build_pool(rng, size);
+        }
+        let translated = (size as f64 * self.share).round() as usize;
+        let mut pool = self.vocabulary.build_pool(rng, size - translated);
+        let languages = self.translations.len();
+        for (i, translation) in self.translations.iter().enumerate() {
+            let count = translated / languages + usize::from(i < translated % languages);
+            pool.extend(translation.build_pool(rng, count));
+        }
+        pool.shuffle(rng);
+        pool
+    }
+}
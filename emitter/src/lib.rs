@@ -1,6 +1,36 @@
 pub mod buffer;
+pub mod cascade;
+pub mod channel;
+pub mod checkpoint;
 pub mod config;
+pub mod control;
+pub mod drift;
+pub mod duplicate;
 pub mod embedding;
 pub mod emitter;
+pub mod jitter;
+pub mod language;
 pub mod log_entry;
+pub mod log_format;
+pub mod metadata;
+pub mod metrics;
+pub mod overrides;
+pub mod payload;
+pub mod pii;
+pub mod plan;
+pub mod popularity;
+pub mod preset;
+pub mod processor;
+pub mod reload;
+pub mod replay;
+pub mod report;
+pub mod scenario;
+pub mod search;
+pub mod secret;
 pub mod sink;
+pub mod skew;
+pub mod stack_trace;
+pub mod traffic;
+pub mod validate;
+pub mod vocabulary;
+pub mod wal;
@@ -1,12 +1,19 @@
 use chrono::{DateTime, Utc};
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum LogLevel {
+    #[serde(alias = "DEBUG", alias = "debug")]
     Debug,
+    #[serde(alias = "INFO", alias = "info")]
     Info,
+    #[serde(alias = "WARN", alias = "warn")]
     Warn,
+    #[serde(alias = "ERROR", alias = "error")]
     Error,
 }
 
@@ -21,6 +28,56 @@ impl fmt::Display for LogLevel {
     }
 }
 
+/// Levels are read in any of their spellings, as the aliases allow
+impl JsonSchema for LogLevel {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "LogLevel".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "enum": [
+                "Debug", "DEBUG", "debug", "Info", "INFO", "info",
+                "Warn", "WARN", "warn", "Error", "ERROR", "error",
+            ],
+        })
+    }
+}
+
+/// Where an entry sits in a distributed trace, as W3C trace context hex ids
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceContext {
+    /// 32 hex digits, shared by every entry of one request
+    pub trace_id: String,
+    /// 16 hex digits
+    pub span_id: String,
+    /// The span of the service that called this one, unless it's the root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<String>,
+}
+
+impl TraceContext {
+    /// The ids that are set, by name
+    pub fn ids(&self) -> Vec<(&'static str, &str)> {
+        let mut ids = vec![
+            ("trace_id", self.trace_id.as_str()),
+            ("span_id", self.span_id.as_str()),
+        ];
+        if let Some(parent) = &self.parent_span_id {
+            ids.push(("parent_span_id", parent));
+        }
+        ids
+    }
+}
+
+/// Term weights by dimension, indices ascending
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SparseEmbedding {
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub id: String,
@@ -29,4 +86,65 @@ pub struct LogEntry {
     pub level: LogLevel,
     pub message: String,
     pub embedding: Vec<f32>,
+    /// Term weights of the message, when a `sparse` processor made them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse: Option<SparseEmbedding>,
+    /// Embeddings by the models under `extra_embeddings`, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_embeddings: BTreeMap<String, Vec<f32>>,
+    /// Structured attributes, e.g. `request_id`, `status_code`, `region`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, Value>,
+    /// Set on entries generated as part of a simulated request flow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceContext>,
+    /// Fraction of similar entries kept by sampling, if sampled (each kept
+    /// entry stands for `1 / sample_rate` of them)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+    /// Number of identical entries this one stands for, if deduplicated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_count: Option<u32>,
+}
+
+impl LogEntry {
+    /// Rough size of the entry once serialized for a sink, counting the
+    /// embedding at ~10 bytes per float as it would be written in JSON
+    pub fn estimated_size(&self) -> usize {
+        const OVERHEAD: usize = 128; // field names, timestamp, level
+        OVERHEAD
+            + self.id.len()
+            + self.service.len()
+            + self.message.len()
+            + self.embedding.len() * 10
+            + self
+                .extra_embeddings
+                .iter()
+                .map(|(name, embedding)| name.len() + embedding.len() * 10)
+                .sum::<usize>()
+            + self
+                .fields
+                .iter()
+                .map(|(key, value)| match value {
+                    Value::String(s) => key.len() + s.len() + 8,
+                    _ => key.len() + 24,
+                })
+                .sum::<usize>()
+            + if self.trace.is_some() { 120 } else { 0 }
+    }
+
+    /// The structured fields with every value as text, for sinks whose
+    /// schema only holds strings
+    pub fn fields_as_text(&self) -> HashMap<String, String> {
+        self.fields
+            .iter()
+            .map(|(key, value)| {
+                let text = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (key.clone(), text)
+            })
+            .collect()
+    }
 }
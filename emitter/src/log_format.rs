@@ -0,0 +1,239 @@
+use std::fmt::Write;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::log_entry::{LogEntry, LogLevel};
+
+const METHODS: &[&str] = &["GET", "GET", "GET", "POST", "POST", "PUT", "DELETE"];
+
+const RESOURCES: &[&str] = &[
+    "orders", "users", "sessions", "payments", "carts", "search", "health",
+];
+
+const USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_4) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_4 like Mac OS X) AppleWebKit/605.1.15 Mobile/15E148",
+    "okhttp/4.12.0",
+    "python-requests/2.31.0",
+    "curl/8.5.0",
+];
+
+/// How an entry's message is rendered, the way a real agent would read it
+/// from a file
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// The vocabulary's message as it is
+    #[default]
+    Template,
+    /// nginx `combined` access lines, and error-log lines for WARN/ERROR
+    Nginx,
+    /// Apache `combined` access lines, and error-log lines for WARN/ERROR
+    Apache,
+    /// BSD syslog lines, as written to /var/log/syslog
+    Syslog,
+    /// `key=value` pairs
+    Logfmt,
+    /// One JSON object per line
+    Json,
+}
+
+/// Request details an access or error line is made of, taken from the
+/// entry's fields where it has them
+struct Request {
+    client_ip: String,
+    method: &'static str,
+    path: String,
+    status: u64,
+    bytes: u32,
+    seconds: f64,
+    user: Option<String>,
+    user_agent: &'static str,
+    pid: u32,
+    /// The server's connection serial (nginx) or client port (Apache)
+    connection: u32,
+}
+
+impl Request {
+    fn new(entry: &LogEntry, rng: &mut impl Rng) -> Self {
+        let field_u64 = |key: &str| entry.fields.get(key).and_then(Value::as_u64);
+        let resource = RESOURCES.choose(rng).unwrap();
+        let random_ip = format!(
+            "{}.{}.{}.{}",
+            rng.gen_range(11..224),
+            rng.gen_range(0..256),
+            rng.gen_range(0..256),
+            rng.gen_range(1..255)
+        );
+        Self {
+            client_ip: entry
+                .fields
+                .get("client_ip")
+                .and_then(Value::as_str)
+                .map_or(random_ip, str::to_string),
+            method: METHODS.choose(rng).unwrap(),
+            path: format!("/api/v1/{resource}/{}", rng.gen_range(1000..100_000)),
+            status: field_u64("status_code").unwrap_or(200),
+            bytes: rng.gen_range(64..16_384),
+            seconds: field_u64("duration_ms").unwrap_or(1) as f64 / 1000.0,
+            user: entry
+                .fields
+                .get("user_id")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            user_agent: USER_AGENTS.choose(rng).unwrap(),
+            pid: rng.gen_range(1000..32_768),
+            connection: rng.gen_range(1024..65_536),
+        }
+    }
+}
+
+impl LogFormat {
+    /// Rewrite `entry`'s message in this format. Fields, level and
+    /// embedding are left as they are.
+    pub fn apply(self, entry: &mut LogEntry, rng: &mut impl Rng) {
+        entry.message = match self {
+            LogFormat::Template => return,
+            LogFormat::Nginx => nginx(entry, &Request::new(entry, rng)),
+            LogFormat::Apache => apache(entry, &Request::new(entry, rng)),
+            LogFormat::Syslog => syslog(entry, rng),
+            LogFormat::Logfmt => logfmt(entry),
+            LogFormat::Json => json_line(entry),
+        };
+    }
+}
+
+fn nginx(entry: &LogEntry, request: &Request) -> String {
+    let time = entry.timestamp;
+    match entry.level {
+        LogLevel::Warn | LogLevel::Error => format!(
+            "{} [{}] {}#0: *{} {}, client: {}, server: {}, request: \"{} {} HTTP/1.1\"",
+            time.format("%Y/%m/%d %H:%M:%S"),
+            if entry.level == LogLevel::Error {
+                "error"
+            } else {
+                "warn"
+            },
+            request.pid,
+            request.connection,
+            entry.message,
+            request.client_ip,
+            entry.service,
+            request.method,
+            request.path,
+        ),
+        LogLevel::Debug | LogLevel::Info => format!(
+            "{} - {} [{}] \"{} {} HTTP/1.1\" {} {} \"-\" \"{}\" rt={:.3}",
+            request.client_ip,
+            request.user.as_deref().unwrap_or("-"),
+            time.format("%d/%b/%Y:%H:%M:%S %z"),
+            request.method,
+            request.path,
+            request.status,
+            request.bytes,
+            request.user_agent,
+            request.seconds,
+        ),
+    }
+}
+
+fn apache(entry: &LogEntry, request: &Request) -> String {
+    let time = entry.timestamp;
+    match entry.level {
+        LogLevel::Warn | LogLevel::Error => format!(
+            "[{}] [proxy:{}] [pid {}] [client {}:{}] {}",
+            time.format("%a %b %d %H:%M:%S%.6f %Y"),
+            if entry.level == LogLevel::Error {
+                "error"
+            } else {
+                "warn"
+            },
+            request.pid,
+            request.client_ip,
+            request.connection,
+            entry.message,
+        ),
+        LogLevel::Debug | LogLevel::Info => format!(
+            "{} - {} [{}] \"{} {} HTTP/1.1\" {} {} \"-\" \"{}\"",
+            request.client_ip,
+            request.user.as_deref().unwrap_or("-"),
+            time.format("%d/%b/%Y:%H:%M:%S %z"),
+            request.method,
+            request.path,
+            request.status,
+            request.bytes,
+            request.user_agent,
+        ),
+    }
+}
+
+fn syslog(entry: &LogEntry, rng: &mut impl Rng) -> String {
+    format!(
+        "{} ip-10-0-{}-{} {}[{}]: {}: {}",
+        entry.timestamp.format("%b %e %H:%M:%S"),
+        rng.gen_range(0..16),
+        rng.gen_range(1..255),
+        entry.service,
+        rng.gen_range(1000..32_768),
+        entry.level,
+        entry.message,
+    )
+}
+
+fn logfmt(entry: &LogEntry) -> String {
+    let mut line = format!(
+        "ts={} level={} service={} msg={}",
+        entry.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+        entry.level.to_string().to_lowercase(),
+        entry.service,
+        logfmt_value(&entry.message),
+    );
+    if let Some(trace) = &entry.trace {
+        for (key, id) in trace.ids() {
+            let _ = write!(line, " {key}={id}");
+        }
+    }
+    let mut fields: Vec<_> = entry.fields_as_text().into_iter().collect();
+    fields.sort();
+    for (key, value) in fields {
+        let _ = write!(line, " {key}={}", logfmt_value(&value));
+    }
+    line
+}
+
+/// Quote values with spaces, quotes or `=` in them
+fn logfmt_value(value: &str) -> String {
+    if value.is_empty() || value.contains([' ', '"', '=', '\n']) {
+        format!("{value:?}")
+    } else {
+        value.to_string()
+    }
+}
+
+fn json_line(entry: &LogEntry) -> String {
+    let mut object = Map::new();
+    object.insert(
+        "ts".to_string(),
+        json!(entry.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+    );
+    object.insert(
+        "level".to_string(),
+        json!(entry.level.to_string().to_lowercase()),
+    );
+    object.insert("service".to_string(), json!(entry.service));
+    object.insert("msg".to_string(), json!(entry.message));
+    if let Some(trace) = &entry.trace {
+        for (key, id) in trace.ids() {
+            object.insert(key.to_string(), json!(id));
+        }
+    }
+    for (key, value) in &entry.fields {
+        object.insert(key.clone(), value.clone());
+    }
+    Value::Object(object).to_string()
+}
@@ -1,115 +1,1010 @@
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
-use clap::Parser;
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use rand::rngs::StdRng;
 use tokio::sync::mpsc;
-use tracing::info;
+use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
 
 use logstorm::buffer::Buffer;
-use logstorm::config::{EmitterConfig, SinkConfig};
+use logstorm::cascade::Cascades;
+use logstorm::channel;
+use logstorm::checkpoint::{self, Checkpoint, Progress};
+use logstorm::config::{EmitterConfig, PipelineConfig, ProcessorConfig, SinkConfig, SinkSpec};
+use logstorm::control::Control;
+use logstorm::drift;
+use logstorm::duplicate::DuplicateConfig;
 use logstorm::embedding::EmbeddingService;
-use logstorm::emitter::{build_message_pool, emit_logs};
+use logstorm::embedding::lazy::LazyEmbeddings;
+use logstorm::emitter::{LivePool, MessagePool, MessageSource, emit_flow, emit_logs, stream_rng};
+use logstorm::jitter::JitterConfig;
+use logstorm::metadata::Metadata;
+use logstorm::overrides;
+use logstorm::payload::PayloadConfig;
+use logstorm::pii::PiiConfig;
+use logstorm::plan::PipelinePlan;
+use logstorm::popularity::Popularity;
+use logstorm::preset::{self, Preset};
+use logstorm::processor::Processor;
+use logstorm::processor::dedup::DedupProcessor;
+use logstorm::processor::enrich::EnrichProcessor;
+use logstorm::processor::filter::FilterProcessor;
+use logstorm::processor::redact::RedactProcessor;
+use logstorm::processor::sample::SampleProcessor;
+use logstorm::processor::sparse::SparseProcessor;
+use logstorm::processor::transform::TransformProcessor;
+use logstorm::reload;
+use logstorm::replay;
+use logstorm::report::{self, RunReport, SinkTally};
+use logstorm::search::{SearchBackend, Searchable};
+use logstorm::secret;
+use logstorm::sink::adaptive::AdaptiveBatchSink;
+use logstorm::sink::embed::EmbedSink;
+use logstorm::sink::metered::MeteredSink;
+use logstorm::sink::null::NullSink;
+use logstorm::sink::process::ProcessSink;
+use logstorm::sink::rate_limit::RateLimitSink;
+use logstorm::sink::retry::RetrySink;
+use logstorm::sink::route::RouteSink;
+use logstorm::sink::truncate::TruncateSink;
 use logstorm::sink::{Sink, StdoutSink};
+use logstorm::stack_trace::StackTraceConfig;
+use logstorm::traffic::Backfill;
+use logstorm::validate;
+use logstorm::wal;
 
 #[derive(Parser)]
-#[command(name = "logstorm", about = "Synthetic log emitter")]
-struct Args {
-    /// Path to config file
+#[command(
+    name = "logstorm",
+    about = "Synthetic log emitter",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// `run`'s options, for running without naming the subcommand
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate entries and write them to the configured sinks (the default)
+    Run(RunArgs),
+    /// Check a config file, with any overrides, and summarize what it
+    /// would run; the same as `run --validate`
+    ValidateConfig(RunArgs),
+    /// Write a starter config file from a preset, with the sinks of your
+    /// choice
+    Init(InitArgs),
+    /// Print a JSON Schema of the config file, for editors to validate and
+    /// complete it with; it covers the sinks this build has features for
+    Schema,
+}
+
+#[derive(clap::Args, Clone)]
+struct RunArgs {
+    /// Path to config file: YAML, or TOML or JSON by its extension
     #[arg(short, long, default_value = "config.yaml")]
     config: String,
 
-    /// Override run_duration_secs from config
+    /// Run a built-in preset instead of the config file: quickstart-stdout,
+    /// es-vs-qdrant, high-volume-soak or incident-demo
+    #[arg(long, conflicts_with = "watch")]
+    preset: Option<String>,
+
+    /// Override run_duration_secs from config (0 runs until stopped)
+    #[arg(long, visible_alias = "duration-secs")]
+    duration: Option<u64>,
+
+    /// Override the seed from config
     #[arg(long)]
-    duration_secs: Option<u64>,
+    seed: Option<u64>,
+
+    /// Set a config value by its dotted path, e.g. `embedding.model=...` or
+    /// `sinks.0.url=...`, over the file and `EMITTER__` variables; repeatable
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    sets: Vec<String>,
+
+    /// Check the config and exit without running, listing every problem
+    /// found
+    #[arg(long)]
+    validate: bool,
+
+    /// Connect to every sink, embed a few sample messages and print what the
+    /// run would do, then exit without creating or writing anything
+    #[arg(long, conflicts_with = "validate")]
+    dry_run: bool,
+
+    /// Watch the config file, applying changes to rates, level weights,
+    /// sampling and scenarios as it's saved
+    #[arg(long)]
+    watch: bool,
+
+    /// Write to sinks of these types (e.g. stdout, blackhole) instead of
+    /// the configured ones, in every pipeline; repeatable
+    #[arg(long = "sink")]
+    sinks: Vec<String>,
 }
 
-/// Expand `${VAR_NAME}` patterns in a string with environment variable values.
-/// Unknown vars become empty strings.
-fn expand_env_vars(input: &str) -> String {
-    let mut result = input.to_string();
-    while let Some(start) = result.find("${") {
-        let Some(end) = result[start..].find('}') else {
-            break;
+impl RunArgs {
+    /// What the config is read from, for messages about it
+    fn source(&self) -> String {
+        match &self.preset {
+            Some(name) => format!("preset {name}"),
+            None => self.config.clone(),
+        }
+    }
+
+    /// The config to run: the file or preset, overridden by the environment
+    /// and these args
+    fn load(&self) -> Result<EmitterConfig, String> {
+        let mut config = match &self.preset {
+            Some(name) => load_preset(preset::find(name)?, &self.sets)?,
+            None => load_config(&self.config, &self.sets)?,
         };
-        let var_name = &result[start + 2..start + end];
-        let value = std::env::var(var_name).unwrap_or_default();
-        result = format!(
-            "{}{}{}",
-            &result[..start],
-            value,
-            &result[start + end + 1..]
+        self.apply(&mut config)?;
+        Ok(config)
+    }
+
+    /// Apply the overrides to `config`
+    fn apply(&self, config: &mut EmitterConfig) -> Result<(), String> {
+        if let Some(duration) = self.duration {
+            config.run_duration_secs = duration;
+        }
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+        if !self.sinks.is_empty() {
+            let sinks = self
+                .sinks
+                .iter()
+                .map(|kind| {
+                    serde_yaml::from_str::<SinkSpec>(&format!("type: {kind}"))
+                        .map_err(|e| format!("--sink {kind}: {e}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            config.pipeline.sinks = sinks.clone();
+            for pipeline in &mut config.pipelines {
+                pipeline.sinks = sinks.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The config at `path` (YAML, TOML or JSON by its extension; the defaults
+/// if there's none) with `${VAR}`s expanded, then `EMITTER__` environment variables and `--set`
+/// assignments layered over it
+fn load_config(path: &str, sets: &[String]) -> Result<EmitterConfig, String> {
+    let value = if std::path::Path::new(path).exists() {
+        overrides::read_file(std::path::Path::new(path))?
+    } else {
+        info!("No {path} found, using defaults");
+        serde_yaml::to_value(EmitterConfig::default()).map_err(|e| e.to_string())?
+    };
+    layer(path, value, sets)
+}
+
+/// A preset's config, with the same layers over it as a file's
+fn load_preset(preset: &Preset, sets: &[String]) -> Result<EmitterConfig, String> {
+    let value = overrides::parse_file(std::path::Path::new("preset.yaml"), preset.source)
+        .map_err(|e| format!("preset {}: {e}", preset.name))?;
+    layer(&format!("preset {}", preset.name), value, sets)
+}
+
+/// `EMITTER__` environment variables and `--set` assignments layered over
+/// the config read from `source`, and then its `file:` and `cmd:`
+/// credentials read
+fn layer(
+    source: &str,
+    mut value: serde_yaml::Value,
+    sets: &[String],
+) -> Result<EmitterConfig, String> {
+    overrides::apply_env(&mut value, std::env::vars());
+    for set in sets {
+        overrides::apply_set(&mut value, set)?;
+    }
+    secret::resolve(&mut value).map_err(|e| format!("{source}: {e}"))?;
+    serde_path_to_error::deserialize(value).map_err(|e| match e.path().to_string().as_str() {
+        "." => format!("{source}: {}", e.inner()),
+        at => format!("{source}: {at}: {}", e.inner()),
+    })
+}
+
+#[derive(clap::Args)]
+struct InitArgs {
+    /// The preset to start from
+    #[arg(long, default_value = "quickstart-stdout")]
+    preset: String,
+
+    /// Write to an example sink of this type (e.g. qdrant, elasticsearch,
+    /// pgvector) instead of the preset's; repeatable
+    #[arg(long = "sink")]
+    sinks: Vec<String>,
+
+    /// Where to write the config
+    #[arg(short, long, default_value = "config.yaml")]
+    output: std::path::PathBuf,
+
+    /// Overwrite the file if it's there
+    #[arg(long)]
+    force: bool,
+
+    /// List the presets instead
+    #[arg(long)]
+    list: bool,
+}
+
+/// Write the starter config `args` ask for
+fn init(args: &InitArgs) -> Result<(), String> {
+    if args.list {
+        println!("{}", preset::list());
+        return Ok(());
+    }
+    let chosen = preset::find(&args.preset)?;
+    let config = preset::starter(chosen, &args.sinks)?;
+    let output = args.output.display();
+    if args.output.exists() && !args.force {
+        return Err(format!(
+            "{output} already exists; pass --force to overwrite it"
+        ));
+    }
+    std::fs::write(&args.output, config).map_err(|e| format!("{output}: {e}"))?;
+    println!("Wrote {output} from preset {}", chosen.name);
+    println!(
+        "Check it with `logstorm validate-config -c {output}`, then run it with `logstorm -c {output}`"
+    );
+    Ok(())
+}
+
+/// Print what `config`, found valid, would run
+fn summarize(path: &str, config: &EmitterConfig) {
+    println!("{path} is valid");
+    for pipeline in config.pipelines() {
+        let services: Vec<&str> = pipeline.services.iter().map(|s| s.name.as_str()).collect();
+        let sinks: Vec<&str> = pipeline.sinks.iter().map(|spec| spec.name()).collect();
+        println!(
+            "  pipeline {}: services [{}], sinks [{}]",
+            pipeline.name,
+            services.join(", "),
+            sinks.join(", ")
         );
     }
-    result
 }
 
-fn load_config(path: &str) -> EmitterConfig {
-    match std::fs::read_to_string(path) {
-        Ok(contents) => {
-            let expanded = expand_env_vars(&contents);
-            serde_yaml::from_str(&expanded).unwrap_or_else(|e| panic!("Invalid {path}: {e}"))
+/// How many sample messages to embed when estimating how long embedding
+/// the pool up front takes
+const DRY_RUN_SAMPLES: usize = 8;
+
+/// Check `config`'s run can go ahead without running it: connect to every
+/// sink and initialize it (creating collections, indices and tables as a
+/// run would), time embedding a few of `messages`, and print the plan.
+/// Returns whether everything checked out.
+async fn dry_run(
+    config: &EmitterConfig,
+    pipelines: &[PipelineConfig],
+    messages: &[String],
+    duration: Duration,
+) -> bool {
+    let mut ok = true;
+    println!("Dry run: nothing will be written");
+    println!("  message pool: {} messages", messages.len());
+    if config.embedding.lazy.is_some() {
+        println!("  embedding: lazily, as messages are first logged");
+    }
+    let mut models = vec![("embedding".to_string(), config.embedding.clone())];
+    models.extend(
+        config
+            .extra_embeddings
+            .iter()
+            .map(|(name, extra)| (format!("extra_embeddings.{name}"), extra.clone())),
+    );
+    for (name, model_cfg) in models {
+        if !model_cfg.enabled() {
+            println!("  {name}: switched off");
+            continue;
         }
-        Err(_) => {
-            info!("No {path} found, using defaults");
-            EmitterConfig::default()
+        let model = model_cfg.model.clone();
+        let service = EmbeddingService::from_config(model_cfg);
+        match service.estimate(messages, DRY_RUN_SAMPLES).await {
+            Ok(estimate) => println!(
+                "  {name} ({model}): ok, {} messages in {:.2}s; {} to embed in {} requests, ~{:.0}s",
+                estimate.trial_size,
+                estimate.trial_time.as_secs_f64(),
+                estimate.to_embed,
+                estimate.requests,
+                estimate.total.as_secs_f64()
+            ),
+            Err(e) => {
+                ok = false;
+                println!("  {name} ({model}): FAILED: {e}");
+            }
         }
     }
+
+    let embedding_dim = config.embedding.output_dim();
+    let embedder = Arc::new(EmbeddingService::from_config(config.embedding.clone()));
+    let control = Arc::new(Control::default());
+    let extra_dims: BTreeMap<String, usize> = config
+        .extra_embeddings
+        .iter()
+        .map(|(name, extra)| (name.clone(), extra.output_dim()))
+        .collect();
+    for pipeline in pipelines {
+        println!(
+            "  pipeline {}: {}",
+            pipeline.name,
+            PipelinePlan::new(pipeline, duration)
+        );
+        // each on its own, so one failing doesn't hide the rest
+        for spec in &pipeline.sinks {
+            // building a dashboard starts its server, so it's only reported
+            #[cfg(feature = "dashboard")]
+            if let SinkConfig::Dashboard(dashboard_cfg) = &spec.sink {
+                println!(
+                    "    sink {}: not started (would serve on port {})",
+                    spec.name(),
+                    dashboard_cfg.port
+                );
+                continue;
+            }
+            let built = build_sinks(
+                std::slice::from_ref(spec),
+                embedding_dim,
+                &embedder,
+                &control,
+                &extra_dims,
+                Setup::DryRun,
+                Some(&pipeline.name),
+            )
+            .await;
+            match built.map(|_| ()) {
+                Ok(()) => println!("    sink {}: ok", spec.name()),
+                Err(e) => {
+                    ok = false;
+                    println!("    sink {}: FAILED: {e}", spec.name());
+                }
+            }
+        }
+    }
+    ok
+}
+
+/// Skip a sink that failed to initialize when `fail_open` is set, otherwise
+/// turn its error into one naming the sink.
+fn skip_or_fail(
+    sink: &str,
+    error: impl std::fmt::Display,
+    fail_open: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if fail_open {
+        warn!("{sink} sink failed to initialize, continuing without it: {error}");
+        Ok(())
+    } else {
+        Err(format!("{sink} sink failed to initialize: {error}").into())
+    }
+}
+
+/// How far `build_sinks` takes each sink
+#[derive(Clone, Copy)]
+enum Setup {
+    /// Initialize each sink, skipping any that fail when `fail_open` is set
+    Init { fail_open: bool },
+    /// Only build each sink, creating nothing in its backend
+    DryRun,
 }
 
+/// The configured sinks, each with the tally of what it accepted
+type Sinks = (Vec<Box<dyn Sink>>, Vec<Arc<SinkTally>>);
+
 #[allow(unused_variables)]
-async fn build_sinks(sink_configs: &[SinkConfig], embedding_dim: usize) -> Vec<Box<dyn Sink>> {
+async fn build_sinks(
+    sink_configs: &[SinkSpec],
+    embedding_dim: usize,
+    embedder: &Arc<EmbeddingService>,
+    control: &Arc<Control>,
+    extra_dims: &BTreeMap<String, usize>,
+    setup: Setup,
+    pipeline: Option<&str>,
+) -> Result<Sinks, Box<dyn std::error::Error + Send + Sync>> {
+    let (init, fail_open) = match setup {
+        Setup::Init { fail_open } => (true, fail_open),
+        Setup::DryRun => (false, false),
+    };
     let mut sinks: Vec<Box<dyn Sink>> = Vec::new();
-    for cfg in sink_configs {
-        match cfg {
+    let mut tallies = Vec::new();
+    // the stores written, for dashboards to search
+    let mut searchables = Vec::new();
+    // each dashboard's streams, and the index of its own tally
+    #[cfg(feature = "dashboard")]
+    let mut dashboards = Vec::new();
+    for spec in sink_configs {
+        let built = sinks.len();
+        let mut search: Option<Arc<dyn SearchBackend>> = None;
+        // a sink embedding entries itself gets its model's dimensions
+        let full_dim = spec
+            .embedding
+            .as_ref()
+            .map_or(embedding_dim, |embedding| embedding.output_dim());
+        let embedding_dim = spec.truncate_dim.unwrap_or(full_dim).min(full_dim);
+        match &spec.sink {
             SinkConfig::Stdout {} => {
                 sinks.push(Box::new(StdoutSink));
             }
+            SinkConfig::Null(null_cfg) => {
+                sinks.push(Box::new(NullSink::from_config(null_cfg.to_owned())));
+            }
             #[cfg(feature = "qdrant")]
             SinkConfig::Qdrant(qdrant_cfg) => {
                 use logstorm::sink::qdrant::QdrantSink;
-                let qdrant_sink =
-                    QdrantSink::from_config(qdrant_cfg.to_owned(), embedding_dim).await;
-                info!(
-                    "Qdrant sink configured for collection '{}'",
-                    qdrant_cfg.collection_name
+                let sink = QdrantSink::from_config(
+                    qdrant_cfg.to_owned(),
+                    embedding_dim,
+                    extra_dims.clone(),
                 );
-                sinks.push(Box::new(qdrant_sink));
+                match sink.await {
+                    Ok(qdrant_sink) => {
+                        info!(
+                            "Qdrant sink configured for collection '{}'",
+                            qdrant_cfg.collection_name
+                        );
+                        let qdrant_sink = Arc::new(qdrant_sink);
+                        search = Some(Arc::clone(&qdrant_sink) as Arc<dyn SearchBackend>);
+                        sinks.push(Box::new(qdrant_sink));
+                    }
+                    Err(e) => skip_or_fail("Qdrant", e, fail_open)?,
+                }
             }
             #[cfg(feature = "elasticsearch")]
             SinkConfig::ElasticSearch(es_cfg) => {
                 use logstorm::sink::elasticsearch::ElasticSearchSink;
-                let es_sink =
-                    ElasticSearchSink::from_config(es_cfg.to_owned(), embedding_dim).await;
-                info!(
-                    "Elasticsearch sink configured for index '{}'",
-                    es_cfg.index_name
+                let sink = ElasticSearchSink::from_config(
+                    es_cfg.to_owned(),
+                    embedding_dim,
+                    extra_dims.clone(),
                 );
-                sinks.push(Box::new(es_sink));
+                match sink.await {
+                    Ok(es_sink) => {
+                        info!(
+                            "Elasticsearch sink configured for index '{}'",
+                            es_cfg.index_name
+                        );
+                        let es_sink = Arc::new(es_sink);
+                        search = Some(Arc::clone(&es_sink) as Arc<dyn SearchBackend>);
+                        sinks.push(Box::new(es_sink));
+                    }
+                    Err(e) => skip_or_fail("Elasticsearch", e, fail_open)?,
+                }
             }
             #[cfg(feature = "pgvector")]
             SinkConfig::Pgvector(pg_cfg) => {
                 use logstorm::sink::pgvector::PgvectorSink;
-                let pg_sink =
-                    PgvectorSink::from_config(pg_cfg.to_owned(), embedding_dim).await;
-                info!(
-                    "Pgvector sink configured for table '{}'",
-                    pg_cfg.table_name
-                );
-                sinks.push(Box::new(pg_sink));
+                let sink =
+                    PgvectorSink::from_config(pg_cfg.to_owned(), embedding_dim, extra_dims.clone());
+                match sink.await {
+                    Ok(pg_sink) => {
+                        info!("Pgvector sink configured for table '{}'", pg_cfg.table_name);
+                        let pg_sink = Arc::new(pg_sink);
+                        search = Some(Arc::clone(&pg_sink) as Arc<dyn SearchBackend>);
+                        sinks.push(Box::new(pg_sink));
+                    }
+                    Err(e) => skip_or_fail("Pgvector", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "lancedb")]
+            SinkConfig::Lancedb(lance_cfg) => {
+                use logstorm::sink::lancedb::LanceDbSink;
+                match LanceDbSink::from_config(lance_cfg.to_owned(), embedding_dim).await {
+                    Ok(lance_sink) => {
+                        info!(
+                            "LanceDB sink configured for table '{}' at '{}'",
+                            lance_cfg.table_name, lance_cfg.uri
+                        );
+                        sinks.push(Box::new(lance_sink));
+                    }
+                    Err(e) => skip_or_fail("LanceDB", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "vespa")]
+            SinkConfig::Vespa(vespa_cfg) => {
+                use logstorm::sink::vespa::VespaSink;
+                match VespaSink::from_config(vespa_cfg.to_owned(), embedding_dim).await {
+                    Ok(vespa_sink) => {
+                        info!(
+                            "Vespa sink configured for document type '{}'",
+                            vespa_cfg.document_type
+                        );
+                        sinks.push(Box::new(vespa_sink));
+                    }
+                    Err(e) => skip_or_fail("Vespa", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "fluentd")]
+            SinkConfig::Fluentd(fluentd_cfg) => {
+                use logstorm::sink::fluentd::FluentdSink;
+                match FluentdSink::from_config(fluentd_cfg.to_owned()).await {
+                    Ok(fluentd_sink) => {
+                        info!(
+                            "Fluentd sink configured for {}:{} (tag '{}')",
+                            fluentd_cfg.host, fluentd_cfg.port, fluentd_cfg.tag
+                        );
+                        sinks.push(Box::new(fluentd_sink));
+                    }
+                    Err(e) => skip_or_fail("Fluentd", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "syslog")]
+            SinkConfig::Syslog(syslog_cfg) => {
+                use logstorm::sink::syslog::SyslogSink;
+                match SyslogSink::from_config(syslog_cfg.to_owned()).await {
+                    Ok(syslog_sink) => {
+                        info!(
+                            "Syslog sink configured for {}:{} ({:?})",
+                            syslog_cfg.host, syslog_cfg.port, syslog_cfg.transport
+                        );
+                        sinks.push(Box::new(syslog_sink));
+                    }
+                    Err(e) => skip_or_fail("Syslog", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "otlp")]
+            SinkConfig::Otlp(otlp_cfg) => {
+                use logstorm::sink::otlp::OtlpSink;
+                match OtlpSink::from_config(otlp_cfg.to_owned()) {
+                    Ok(otlp_sink) => {
+                        info!(
+                            "OTLP sink configured for {} ({:?})",
+                            otlp_cfg.endpoint, otlp_cfg.protocol
+                        );
+                        sinks.push(Box::new(otlp_sink));
+                    }
+                    Err(e) => skip_or_fail("OTLP", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "splunk")]
+            SinkConfig::Splunk(splunk_cfg) => {
+                use logstorm::sink::splunk::SplunkSink;
+                match SplunkSink::from_config(splunk_cfg.to_owned()) {
+                    Ok(splunk_sink) => {
+                        info!("Splunk HEC sink configured for {}", splunk_cfg.url);
+                        sinks.push(Box::new(splunk_sink));
+                    }
+                    Err(e) => skip_or_fail("Splunk", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "datadog")]
+            SinkConfig::Datadog(datadog_cfg) => {
+                use logstorm::sink::datadog::DatadogSink;
+                match DatadogSink::from_config(datadog_cfg.to_owned()) {
+                    Ok(datadog_sink) => {
+                        info!("Datadog sink configured for site {}", datadog_cfg.site);
+                        sinks.push(Box::new(datadog_sink));
+                    }
+                    Err(e) => skip_or_fail("Datadog", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "gelf")]
+            SinkConfig::Gelf(gelf_cfg) => {
+                use logstorm::sink::gelf::GelfSink;
+                match GelfSink::from_config(gelf_cfg.to_owned()).await {
+                    Ok(gelf_sink) => {
+                        info!(
+                            "GELF sink configured for {}:{} ({:?})",
+                            gelf_cfg.host, gelf_cfg.port, gelf_cfg.transport
+                        );
+                        sinks.push(Box::new(gelf_sink));
+                    }
+                    Err(e) => skip_or_fail("GELF", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "cloudwatch")]
+            SinkConfig::Cloudwatch(cloudwatch_cfg) => {
+                use logstorm::sink::cloudwatch::CloudWatchSink;
+                match CloudWatchSink::from_config(cloudwatch_cfg.to_owned()).await {
+                    Ok(cloudwatch_sink) => {
+                        info!(
+                            "CloudWatch Logs sink configured for group '{}'",
+                            cloudwatch_cfg.log_group
+                        );
+                        sinks.push(Box::new(cloudwatch_sink));
+                    }
+                    Err(e) => skip_or_fail("CloudWatch", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "cassandra")]
+            SinkConfig::Cassandra(cassandra_cfg) => {
+                use logstorm::sink::cassandra::CassandraSink;
+                match CassandraSink::from_config(cassandra_cfg.to_owned()).await {
+                    Ok(cassandra_sink) => {
+                        info!(
+                            "Cassandra sink configured for table '{}.{}'",
+                            cassandra_cfg.keyspace, cassandra_cfg.table_name
+                        );
+                        sinks.push(Box::new(cassandra_sink));
+                    }
+                    Err(e) => skip_or_fail("Cassandra", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "socket")]
+            SinkConfig::Socket(socket_cfg) => {
+                use logstorm::sink::socket::SocketSink;
+                match SocketSink::from_config(socket_cfg.to_owned()).await {
+                    Ok(socket_sink) => {
+                        info!(
+                            "Socket sink configured for {}:{} ({:?})",
+                            socket_cfg.host, socket_cfg.port, socket_cfg.protocol
+                        );
+                        sinks.push(Box::new(socket_sink));
+                    }
+                    Err(e) => skip_or_fail("Socket", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "http")]
+            SinkConfig::Http(http_cfg) => {
+                use logstorm::sink::http::HttpSink;
+                match HttpSink::from_config(http_cfg.to_owned()) {
+                    Ok(http_sink) => {
+                        info!("HTTP sink configured for {}", http_cfg.url);
+                        sinks.push(Box::new(http_sink));
+                    }
+                    Err(e) => skip_or_fail("HTTP", e, fail_open)?,
+                }
+            }
+            #[cfg(feature = "exec")]
+            SinkConfig::Exec(exec_cfg) => {
+                use logstorm::sink::exec::ExecSink;
+                match ExecSink::from_config(exec_cfg.to_owned()) {
+                    Ok(exec_sink) => {
+                        info!(
+                            "Exec sink configured for '{}' ({:?})",
+                            exec_cfg.command, exec_cfg.mode
+                        );
+                        sinks.push(Box::new(exec_sink));
+                    }
+                    Err(e) => skip_or_fail("Exec", e, fail_open)?,
+                }
             }
             #[cfg(feature = "dashboard")]
             SinkConfig::Dashboard(dashboard_cfg) => {
-                use logstorm::sink::dashboard::{DashboardSink, start_dashboard_server};
-                let (tx, _rx) = tokio::sync::broadcast::channel(100);
-                tokio::spawn(start_dashboard_server(dashboard_cfg.port, tx.clone()));
+                use logstorm::sink::dashboard::runs::RunStore;
+                use logstorm::sink::dashboard::{DashboardSink, Streams, start_dashboard_server};
+                let runs = match &dashboard_cfg.database {
+                    Some(path) => match RunStore::open(path, pipeline).await {
+                        Ok(runs) => Some(runs),
+                        Err(e) => {
+                            skip_or_fail("Dashboard", e, fail_open)?;
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+                let streams = Streams::new(dashboard_cfg.history_secs, runs);
+                tokio::spawn(start_dashboard_server(
+                    dashboard_cfg.to_owned(),
+                    streams.clone(),
+                    Arc::clone(control),
+                ));
                 info!("Dashboard sink configured on port {}", dashboard_cfg.port);
-                sinks.push(Box::new(DashboardSink::new(tx)));
+                dashboards.push((streams.clone(), tallies.len()));
+                sinks.push(Box::new(DashboardSink::new(streams)));
+            }
+        }
+
+        // wrap the sink just built, unless it was skipped
+        if sinks.len() > built
+            && let Some(mut sink) = sinks.pop()
+        {
+            if init && let Err(e) = sink.init().await {
+                skip_or_fail(spec.name(), e, fail_open)?;
+                continue;
+            }
+            if embedding_dim < full_dim {
+                sink = Box::new(TruncateSink::new(sink, embedding_dim));
+            }
+            // queries are embedded by the model the sink's entries are
+            let mut query_embedder = Arc::clone(embedder);
+            if let Some(embedding) = &spec.embedding {
+                let service = Arc::new(EmbeddingService::from_config(embedding.clone()));
+                query_embedder = Arc::clone(&service);
+                let embedder =
+                    LazyEmbeddings::start(embedding.lazy.clone().unwrap_or_default(), service);
+                sink = Box::new(EmbedSink::new(sink, embedder));
+            }
+            // qualified by pipeline when there are several
+            let name = match pipeline {
+                Some(pipeline) => format!("{pipeline}/{}", spec.name()),
+                None => spec.name().to_string(),
+            };
+            if let Some(backend) = search.take() {
+                searchables.push(Searchable {
+                    name: name.clone(),
+                    backend,
+                    embedder: query_embedder,
+                });
+            }
+            let tally = SinkTally::new(name);
+            if let Some(retry) = &spec.retry {
+                sink = Box::new(RetrySink::new(Arc::clone(&tally), sink, retry.clone()));
+            }
+            if let Some(adaptive) = &spec.adaptive_batch {
+                sink = Box::new(AdaptiveBatchSink::new(sink, adaptive.clone()));
+            }
+            if let Some(rate_limit) = &spec.rate_limit {
+                sink = Box::new(RateLimitSink::new(sink, rate_limit.clone()));
+            }
+            tallies.push(Arc::clone(&tally));
+            sink = Box::new(MeteredSink::new(tally, sink));
+            if !spec.processors.is_empty() {
+                sink = Box::new(ProcessSink::new(
+                    sink,
+                    build_processors(&spec.processors, None)?,
+                ));
+            }
+            if let Some(route) = &spec.route {
+                sink = Box::new(RouteSink::new(sink, route.clone()));
+            }
+            sinks.push(sink);
+        }
+    }
+    // every dashboard shows how the pipeline's other sinks are doing, and
+    // searches what they wrote
+    #[cfg(feature = "dashboard")]
+    for (streams, own) in dashboards {
+        let _ = streams.searchables.set(searchables.clone());
+        let others = tallies
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != own)
+            .map(|(_, tally)| Arc::clone(tally))
+            .collect();
+        tokio::spawn(logstorm::sink::dashboard::watch_sinks(
+            streams.events,
+            others,
+        ));
+    }
+    Ok((sinks, tallies))
+}
+
+/// The processors configured, a pipeline's registering their settings
+/// with `live` (its control and name) where they can be changed while
+/// running
+fn build_processors(
+    processor_configs: &[ProcessorConfig],
+    live: Option<(&Control, &str)>,
+) -> Result<Vec<Box<dyn Processor>>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut processors: Vec<Box<dyn Processor>> = Vec::new();
+    for cfg in processor_configs {
+        match cfg {
+            ProcessorConfig::Filter(filter_cfg) => {
+                processors.push(Box::new(FilterProcessor::from_config(
+                    filter_cfg.to_owned(),
+                )));
+            }
+            ProcessorConfig::Transform(transform_cfg) => {
+                processors.push(Box::new(TransformProcessor::from_config(
+                    transform_cfg.to_owned(),
+                )));
+            }
+            ProcessorConfig::Enrich(enrich_cfg) => {
+                processors.push(Box::new(EnrichProcessor::from_config(
+                    enrich_cfg.to_owned(),
+                )));
+            }
+            ProcessorConfig::Redact(redact_cfg) => {
+                let redact = RedactProcessor::from_config(redact_cfg.to_owned())
+                    .map_err(|e| format!("Invalid redact pattern: {e}"))?;
+                processors.push(Box::new(redact));
+            }
+            ProcessorConfig::Sample(sample_cfg) => {
+                let sample = match live {
+                    Some((control, pipeline)) => {
+                        SampleProcessor::live(control.sample(pipeline, sample_cfg.to_owned()))
+                    }
+                    None => SampleProcessor::from_config(sample_cfg.to_owned()),
+                };
+                processors.push(Box::new(sample));
+            }
+            ProcessorConfig::Dedup(dedup_cfg) => {
+                processors.push(Box::new(DedupProcessor::from_config(dedup_cfg.to_owned())));
+            }
+            ProcessorConfig::Sparse(sparse_cfg) => {
+                processors.push(Box::new(SparseProcessor::from_config(
+                    sparse_cfg.to_owned(),
+                )));
             }
         }
     }
-    sinks
+    Ok(processors)
+}
+
+/// What the emitters of every pipeline share
+struct Generator {
+    duration: Duration,
+    pool: Arc<LivePool>,
+    progress: Arc<Progress>,
+    /// For replayed messages, embedded as they're read, and generated ones
+    /// when embedding lazily
+    embedder: Arc<LazyEmbeddings>,
+    lazy: bool,
+    extras: Arc<BTreeMap<String, Arc<LazyEmbeddings>>>,
+    stack_traces: Option<Arc<StackTraceConfig>>,
+    seed: Option<u64>,
+    popularity: Popularity,
+    backfill: Option<Backfill>,
+    duplicates: Option<Arc<DuplicateConfig>>,
+    metadata: Option<Arc<Metadata>>,
+    pii: Option<Arc<PiiConfig>>,
+    payload: Option<Arc<PayloadConfig>>,
+    jitter: JitterConfig,
+    control: Arc<Control>,
+}
+
+impl Generator {
+    /// The RNG for a service's or flow's emitter. Resumed runs pick a new
+    /// stream by how many entries were generated before, rather than
+    /// repeating the first run's.
+    fn rng(&self, pipeline: &str, source: &str, generated: &AtomicU64) -> StdRng {
+        let stream = format!("{pipeline}/{source}#{}", generated.load(Ordering::Relaxed));
+        stream_rng(self.seed, &stream)
+    }
+}
+
+/// Start a pipeline's emitters and run its buffer until they're done
+async fn run_pipeline(
+    pipeline: PipelineConfig,
+    processors: Vec<Box<dyn Processor>>,
+    sinks: Vec<Box<dyn Sink>>,
+    tallies: Vec<Arc<SinkTally>>,
+    generator: &Generator,
+) -> RunReport {
+    let (tx, rx) = channel::channel(pipeline.channel_capacity, pipeline.overflow);
+    let dropped = tx.dropped();
+    let generated = tx.generated();
+
+    let scenarios = generator.control.scenarios(
+        &pipeline.name,
+        pipeline.scenarios.clone(),
+        generator.progress.elapsed(),
+    );
+    // a backfill runs through its scenarios far faster than the wall clock
+    let announcer = generator.backfill.is_none().then(|| {
+        let scenarios = Arc::clone(&scenarios);
+        let name = pipeline.name.clone();
+        tokio::spawn(async move { scenarios.announce(&name).await })
+    });
+    let source = MessageSource {
+        pool: Arc::clone(&generator.pool),
+        scenarios,
+        cascades: Arc::new(Cascades::new(pipeline.cascades.clone(), &pipeline.services)),
+        pipeline: Arc::from(pipeline.name.as_str()),
+        control: Arc::clone(&generator.control),
+        stack_traces: generator.stack_traces.clone(),
+        popularity: generator.popularity,
+        backfill: generator.backfill,
+        duplicates: generator.duplicates.clone(),
+        metadata: generator.metadata.clone(),
+        pii: generator.pii.clone(),
+        payload: generator.payload.clone(),
+        jitter: generator.jitter,
+        lazy: generator.lazy.then(|| Arc::clone(&generator.embedder)),
+        extras: Arc::clone(&generator.extras),
+    };
+
+    for service in &pipeline.services {
+        let generated = generator.progress.counter(&pipeline.name, &service.name);
+        for worker in 0..service.workers.max(1) {
+            let tx = tx.clone();
+            let service = service.clone();
+            let duration = generator.duration;
+            let source = source.clone();
+            let generated = Arc::clone(&generated);
+            let stream = match worker {
+                0 => service.name.clone(),
+                _ => format!("{}/worker-{worker}", service.name),
+            };
+            let rng = generator.rng(&pipeline.name, &stream, &generated);
+            tokio::spawn(async move {
+                emit_logs(service, tx, duration, source, generated, rng).await;
+            });
+        }
+    }
+    for flow in &pipeline.flows {
+        let tx = tx.clone();
+        let flow = flow.clone();
+        let services = pipeline.services.clone();
+        let duration = generator.duration;
+        let source = source.clone();
+        let generated = generator.progress.counter(&pipeline.name, &flow.name);
+        let rng = generator.rng(&pipeline.name, &format!("flow:{}", flow.name), &generated);
+        tokio::spawn(async move {
+            emit_flow(flow, services, tx, duration, source, generated, rng).await;
+        });
+    }
+    if let Some(replay_cfg) = pipeline.replay.clone() {
+        let tx = tx.clone();
+        let duration = generator.duration;
+        let embedder = Arc::clone(&generator.embedder);
+        let extras = Arc::clone(&generator.extras);
+        let generated = generator.progress.counter(&pipeline.name, replay::SOURCE);
+        let control = Arc::clone(&generator.control);
+        tokio::spawn(async move {
+            replay::run(
+                replay_cfg, tx, duration, embedder, extras, generated, control,
+            )
+            .await;
+        });
+    }
+    drop(tx);
+
+    // with a WAL, the buffer reads from a small channel fed by the spill task
+    let rx = match pipeline.wal.clone() {
+        Some(wal_cfg) => {
+            let (wal_tx, wal_rx) = mpsc::channel(wal_cfg.memory_entries.max(1));
+            tokio::spawn(async move {
+                if let Err(e) = wal::spill(wal_cfg, rx, wal_tx).await {
+                    error!("WAL error: {e}");
+                }
+            });
+            wal_rx
+        }
+        None => rx,
+    };
+
+    let mut buffer = Buffer::new(
+        rx,
+        sinks,
+        pipeline.buffer_size,
+        Duration::from_millis(pipeline.flush_interval_ms),
+        pipeline.sink_timeout_ms.map(Duration::from_millis),
+        pipeline.max_batch_bytes,
+    )
+    .with_processors(processors);
+    buffer.run().await;
+    if let Some(announcer) = announcer {
+        announcer.abort();
+    }
+
+    RunReport::new(
+        pipeline.name,
+        generated.load(Ordering::Relaxed),
+        dropped.load(Ordering::Relaxed),
+        buffer.dropped_by_processors(),
+        &tallies,
+    )
+}
+
+/// Stop the run on Ctrl-C or SIGTERM, letting the emitters wind down and
+/// the buffers drain; a second Ctrl-C exits at once
+async fn stop_on_signal(control: Arc<Control>) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+
+    info!("Stopping: flushing what's been generated (Ctrl-C again to exit now)");
+    control.stop();
+    let _ = tokio::signal::ctrl_c().await;
+    std::process::exit(130);
 }
 
 #[tokio::main]
@@ -119,67 +1014,342 @@ async fn main() {
         .with(EnvFilter::from_default_env())
         .init();
 
-    let args = Args::parse();
-    let mut config = load_config(&args.config);
-    if let Some(d) = args.duration_secs {
-        config.run_duration_secs = d;
+    let cli = Cli::parse();
+    let (args, validate_only) = match cli.command {
+        Some(Command::Run(args)) => {
+            let validate = args.validate;
+            (args, validate)
+        }
+        Some(Command::ValidateConfig(args)) => (args, true),
+        Some(Command::Init(init_args)) => {
+            if let Err(e) = init(&init_args) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Schema) => {
+            let schema = schemars::schema_for!(EmitterConfig);
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema).expect("schemas serialize")
+            );
+            return;
+        }
+        None => {
+            let validate = cli.run.validate;
+            (cli.run, validate)
+        }
+    };
+    if validate_only && args.preset.is_none() && !std::path::Path::new(&args.config).exists() {
+        eprintln!("Invalid config: {} not found", args.config);
+        std::process::exit(1);
     }
-    let duration = Duration::from_secs(config.run_duration_secs);
+    let mut config = match args.load() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Invalid config: {e}");
+            std::process::exit(1);
+        }
+    };
+    let problems = validate::validate(&config);
+    if !problems.is_empty() {
+        error!("Invalid config {}:", args.source());
+        for problem in &problems {
+            error!("  {problem}");
+        }
+        std::process::exit(1);
+    }
+    if validate_only {
+        summarize(&args.source(), &config);
+        return;
+    }
+    let mut duration = Duration::from_secs(config.run_duration_secs);
 
+    let resumed = match &config.checkpoint {
+        Some(cp_cfg) => match Checkpoint::load(&cp_cfg.path) {
+            Ok(resumed) => resumed,
+            Err(e) => {
+                error!("Failed to read checkpoint {}: {e}", cp_cfg.path.display());
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    if let Some(cp) = &resumed {
+        info!(
+            "Resuming from checkpoint: {}s in, {} entries generated",
+            cp.elapsed_secs,
+            cp.total_generated()
+        );
+        if !duration.is_zero() {
+            duration = duration
+                .saturating_sub(Duration::from_secs(cp.elapsed_secs))
+                .max(Duration::from_secs(1));
+        }
+    }
+
+    let control = Arc::new(Control::default());
+    if let Some(control_cfg) = config.control.clone() {
+        #[cfg(feature = "control")]
+        tokio::spawn(logstorm::control::serve(control_cfg, Arc::clone(&control)));
+        #[cfg(not(feature = "control"))]
+        warn!(
+            "Control API configured on port {} but logstorm was built without the control feature",
+            control_cfg.port
+        );
+    }
+    if let Some(metrics_cfg) = config.metrics.clone() {
+        #[cfg(feature = "metrics")]
+        tokio::spawn(logstorm::metrics::serve(metrics_cfg));
+        #[cfg(not(feature = "metrics"))]
+        warn!(
+            "Metrics configured on port {} but logstorm was built without the metrics feature",
+            metrics_cfg.port
+        );
+    }
+    let pipelines = config.pipelines();
+    let mut processors = Vec::with_capacity(pipelines.len());
+    for pipeline in &pipelines {
+        match build_processors(&pipeline.processors, Some((&control, &pipeline.name))) {
+            Ok(built) => processors.push(built),
+            Err(e) => {
+                error!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    for pipeline in &pipelines {
+        info!(
+            "Starting pipeline '{}': {} services, {} flows, {} sinks, {}s duration, buffer={}",
+            pipeline.name,
+            pipeline.services.len(),
+            pipeline.flows.len(),
+            pipeline.sinks.len(),
+            duration.as_secs(),
+            pipeline.buffer_size,
+        );
+    }
+
+    let progress = Progress::new(&pipelines, resumed.as_ref());
+
+    // Build message pool from combinatorial generator, or reuse the one the
+    // checkpoint was taken with
+    let pool = if let Some(cp) = resumed {
+        cp.pool
+    } else {
+        let mut rng = stream_rng(config.seed, "pool");
+        match MessagePool::build(
+            &mut rng,
+            &config.vocabulary,
+            &config.vocabularies,
+            config.multilingual.as_ref(),
+            pipelines.iter().flat_map(|pipeline| &pipeline.services),
+            config.message_pool_size,
+        ) {
+            Ok(pool) => pool,
+            Err(e) => {
+                error!("{e}");
+                std::process::exit(1);
+            }
+        }
+    };
     info!(
-        "Starting emitter: {} services, {} sinks, {}s duration, buffer={}",
-        config.services.len(),
-        config.sinks.len(),
-        config.run_duration_secs,
-        config.buffer_size,
+        "Generated message pool of {} messages ({} services with their own)",
+        pool.messages().count(),
+        pool.services.len()
     );
 
-    // Build message pool from combinatorial generator
-    let pool = {
-        use rand::SeedableRng;
-        let mut rng = rand::rngs::StdRng::from_entropy();
-        build_message_pool(&mut rng, config.message_pool_size)
+    if args.dry_run {
+        let messages: Vec<String> = pool
+            .to_embed(&pipelines, &config.jitter)
+            .into_iter()
+            .collect();
+        let ok = dry_run(&config, &pipelines, &messages, duration).await;
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    // Embed all pool messages via OpenAI, along with those scenarios and
+    // cascades log instead; or, lazily, each as it's first logged
+    if !config.embedding.enabled() && config.embedding.lazy.is_some() {
+        warn!("embedding.lazy is ignored with embedding switched off");
+        config.embedding.lazy = None;
+    }
+    let embedding_service = Arc::new(EmbeddingService::from_config(config.embedding.clone()));
+    let embedding_dim = config.embedding.output_dim();
+    let messages: Vec<String> = match &config.embedding.lazy {
+        Some(_) => Vec::new(),
+        None => pool
+            .to_embed(&pipelines, &config.jitter)
+            .into_iter()
+            .collect(),
     };
-    info!("Generated message pool of {} unique messages", pool.len());
-
-    // Embed all pool messages via OpenAI
-    let embedding_service = EmbeddingService::from_config(config.embedding.clone());
-    let embedding_dim = config.embedding.dimensions as usize;
-    let embeddings = Arc::new(
-        embedding_service
-            .embed_all(&pool)
+    let embeddings = match &config.embedding.lazy {
+        Some(lazy_cfg) => {
+            info!(
+                "Embedding messages as they're generated (cache of {})",
+                lazy_cfg.cache_entries
+            );
+            HashMap::new()
+        }
+        None if !config.embedding.enabled() => {
+            info!("Embedding is switched off, entries carry no embeddings");
+            messages
+                .iter()
+                .map(|message| (message.clone(), Vec::new()))
+                .collect()
+        }
+        None => embedding_service
+            .embed_all(&messages)
             .await
             .expect("Failed to generate embeddings"),
-    );
-    let pool = Arc::new(pool);
+    };
+    let pool = Arc::new(LivePool::new(pool, embeddings));
+    let lazy_cfg = config.embedding.lazy.clone().unwrap_or_default();
+    let lazy_embeddings = LazyEmbeddings::start(lazy_cfg.clone(), Arc::clone(&embedding_service));
+
+    // the extra models serve every entry through their caches, filled with
+    // the pool up front unless embedding lazily
+    let mut extras = BTreeMap::new();
+    let mut extra_dims = BTreeMap::new();
+    for (name, extra_cfg) in &config.extra_embeddings {
+        info!("Also embedding as {name} with model={}", extra_cfg.model);
+        let service = Arc::new(EmbeddingService::from_config(extra_cfg.clone()));
+        let extra = LazyEmbeddings::start(lazy_cfg.clone(), Arc::clone(&service));
+        if !messages.is_empty() {
+            let embeddings = service
+                .embed_all(&messages)
+                .await
+                .expect("Failed to generate embeddings");
+            extra.warm(embeddings);
+        }
+        extras.insert(name.clone(), extra);
+        extra_dims.insert(name.clone(), extra_cfg.output_dim());
+    }
+
+    let checkpointer = config.checkpoint.clone().map(|cp_cfg| {
+        tokio::spawn(checkpoint::run(
+            cp_cfg,
+            Arc::clone(&progress),
+            Arc::clone(&pool),
+        ))
+    });
 
     info!("Embedding dimension: {}", embedding_dim);
-    let sinks = build_sinks(&config.sinks, embedding_dim).await;
-    let (tx, rx) = mpsc::channel(10_000);
+    let backfill = config.backfill.as_ref().map(|backfill| {
+        let window = backfill.window(Utc::now());
+        info!(
+            "Backfilling {} to {}",
+            window.start,
+            window.start + chrono::Duration::from_std(window.span).unwrap_or_default()
+        );
+        window
+    });
+    let drifter = match (&config.drift, &backfill) {
+        (Some(_), Some(_)) => {
+            warn!("drift is ignored when backfilling");
+            None
+        }
+        (Some(drift_cfg), None) => Some(tokio::spawn(drift::run(
+            drift_cfg.clone(),
+            config.clone(),
+            Arc::clone(&pool),
+            Arc::clone(&embedding_service),
+            stream_rng(config.seed, "drift"),
+        ))),
+        (None, _) => None,
+    };
+    let generator = Generator {
+        duration,
+        pool,
+        progress,
+        stack_traces: config.stack_traces.clone().map(Arc::new),
+        seed: config.seed,
+        popularity: config.popularity,
+        backfill,
+        duplicates: config.duplicates.clone().map(Arc::new),
+        metadata: config
+            .metadata
+            .clone()
+            .map(|metadata| Arc::new(Metadata::new(metadata, config.seed))),
+        pii: config.pii.clone().map(Arc::new),
+        payload: config.payload.clone().map(Arc::new),
+        jitter: config.jitter,
+        control: Arc::clone(&control),
+        embedder: lazy_embeddings,
+        lazy: config.embedding.lazy.is_some(),
+        extras: Arc::new(extras),
+    };
+    let several = pipelines.len() > 1;
+    let mut runs = Vec::with_capacity(pipelines.len());
+    for (pipeline, processors) in pipelines.into_iter().zip(processors) {
+        let label = several.then_some(pipeline.name.as_str());
+        let (sinks, tallies) = match build_sinks(
+            &pipeline.sinks,
+            embedding_dim,
+            &embedding_service,
+            &control,
+            &extra_dims,
+            Setup::Init {
+                fail_open: config.fail_open,
+            },
+            label,
+        )
+        .await
+        {
+            Ok(built) => built,
+            Err(e) => {
+                error!("{e}");
+                std::process::exit(1);
+            }
+        };
+        runs.push(run_pipeline(
+            pipeline, processors, sinks, tallies, &generator,
+        ));
+    }
 
-    for service in &config.services {
-        let tx = tx.clone();
-        let service = service.clone();
-        let pool = Arc::clone(&pool);
-        let embeddings = Arc::clone(&embeddings);
-        tokio::spawn(async move {
-            emit_logs(service, tx, duration, pool, embeddings).await;
-        });
+    if duration.is_zero() && generator.backfill.is_none() {
+        info!("Emitter running until stopped (Ctrl-C)...");
+    } else {
+        info!("Emitter running for {} seconds...", duration.as_secs());
     }
-    drop(tx);
+    tokio::spawn(stop_on_signal(Arc::clone(&control)));
+    if args.watch {
+        let path = std::path::PathBuf::from(&args.config);
+        let load = move || args.load();
+        tokio::spawn(reload::watch(
+            path,
+            Arc::clone(&control),
+            config.clone(),
+            load,
+        ));
+    }
+    let reports = futures::future::join_all(runs).await;
 
-    let mut buffer = Buffer::new(
-        rx,
-        sinks,
-        config.buffer_size,
-        Duration::from_millis(config.flush_interval_ms),
-    );
+    if let Some(checkpointer) = checkpointer {
+        checkpointer.abort();
+    }
+    if let Some(drifter) = drifter {
+        drifter.abort();
+    }
+    // a run stopped early can pick up from its last checkpoint
+    if let Some(cp_cfg) = &config.checkpoint
+        && !control.is_stopping()
+        && let Err(e) = checkpoint::clear(&cp_cfg.path)
+    {
+        warn!("Failed to remove checkpoint {}: {e}", cp_cfg.path.display());
+    }
 
-    info!(
-        "Emitter running for {} seconds...",
-        config.run_duration_secs
-    );
-    buffer.run().await;
+    for report in &reports {
+        report.log();
+    }
+    if let Some(path) = &config.report_path {
+        match report::write_json(&reports, path) {
+            Ok(()) => info!("Run report written to {}", path.display()),
+            Err(e) => error!("Failed to write run report to {}: {e}", path.display()),
+        }
+    }
 
     info!("Done.");
 }
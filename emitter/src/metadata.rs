@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::emitter::fnv1a;
+use crate::log_entry::LogEntry;
+
+/// The characters Kubernetes draws generated name suffixes from
+const NAME_ALPHABET: &[u8] = b"bcdfghjklmnpqrstvwxz2456789";
+
+fn default_namespaces() -> Vec<String> {
+    vec!["prod".to_string()]
+}
+
+fn default_pods_per_service() -> usize {
+    3
+}
+
+fn default_nodes() -> usize {
+    12
+}
+
+fn default_regions() -> Vec<String> {
+    vec!["us-east-1".to_string()]
+}
+
+fn default_zones_per_region() -> usize {
+    3
+}
+
+/// Kubernetes and host attributes on every generated entry, as
+/// `pod_name`, `namespace`, `node`, `container_id`, `host`, `region` and
+/// `az` fields. The counts set how many distinct values each takes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MetadataConfig {
+    /// Each service is deployed to one of these, picked by its name
+    #[serde(default = "default_namespaces")]
+    pub namespaces: Vec<String>,
+    /// Replicas of each service
+    #[serde(default = "default_pods_per_service")]
+    pub pods_per_service: usize,
+    /// Nodes the pods are scheduled on, spread over the regions' zones
+    #[serde(default = "default_nodes")]
+    pub nodes: usize,
+    #[serde(default = "default_regions")]
+    pub regions: Vec<String>,
+    #[serde(default = "default_zones_per_region")]
+    pub zones_per_region: usize,
+    /// Replace each pod (new name, container and node) about this often, so
+    /// pod and container cardinality keeps growing over a run; 0 keeps the
+    /// same pods throughout
+    #[serde(default)]
+    pub pod_churn_secs: u64,
+}
+
+/// Where entries from one pod come from
+struct Placement {
+    pod_name: String,
+    container_id: String,
+    node: usize,
+}
+
+/// Metadata for a run: the configuration, plus a salt so that unseeded
+/// runs don't name their pods alike
+pub struct Metadata {
+    config: MetadataConfig,
+    salt: u64,
+}
+
+impl Metadata {
+    pub fn new(config: MetadataConfig, seed: Option<u64>) -> Self {
+        Self {
+            config,
+            salt: seed.unwrap_or_else(rand::random),
+        }
+    }
+
+    fn hash(&self, key: &str) -> u64 {
+        // FNV's low bits barely change between keys differing in their last
+        // character, so finish with splitmix64's mixer
+        let mut hash = fnv1a(self.salt, key);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^ (hash >> 31)
+    }
+
+    /// `len` characters of a generated Kubernetes name, drawn from `hash`
+    fn name_suffix(mut hash: u64, len: usize) -> String {
+        (0..len)
+            .map(|_| {
+                let c = NAME_ALPHABET[(hash % NAME_ALPHABET.len() as u64) as usize];
+                hash /= NAME_ALPHABET.len() as u64;
+                c as char
+            })
+            .collect()
+    }
+
+    /// The pod in `slot` of `service`'s deployment, `elapsed` into the run
+    fn placement(&self, service: &str, slot: usize, elapsed: Duration) -> Placement {
+        // pods are replaced in turn rather than all at once
+        let generation = match self.config.pod_churn_secs {
+            0 => 0,
+            churn => {
+                let offset = self.hash(&format!("{service}/{slot}")) % churn;
+                (elapsed.as_secs() + offset) / churn
+            }
+        };
+        let pod = self.hash(&format!("{service}/{slot}/{generation}"));
+        let container: String = (0..4)
+            .map(|part| format!("{:016x}", self.hash(&format!("{pod}/container/{part}"))))
+            .collect();
+        Placement {
+            pod_name: format!(
+                "{service}-{}-{}",
+                Self::name_suffix(self.hash(service), 10),
+                Self::name_suffix(pod, 5)
+            ),
+            container_id: format!("containerd://{container}"),
+            node: (self.hash(&format!("{pod}/node")) % self.config.nodes.max(1) as u64) as usize,
+        }
+    }
+
+    /// Stamp `entry` with the attributes of a pod of its service picked at
+    /// random, overriding any `region` it had
+    pub fn apply(&self, entry: &mut LogEntry, elapsed: Duration, rng: &mut impl Rng) {
+        let slot = rng.gen_range(0..self.config.pods_per_service.max(1));
+        let placement = self.placement(&entry.service, slot, elapsed);
+
+        let host = {
+            let address = self.hash(&format!("node/{}", placement.node));
+            format!(
+                "ip-10-{}-{}-{}",
+                address % 256,
+                (address >> 8) % 256,
+                (address >> 16) % 254 + 1
+            )
+        };
+        let regions = self.config.regions.len().max(1);
+        let region = self
+            .config
+            .regions
+            .get(placement.node % regions)
+            .map_or("us-east-1", String::as_str);
+        let zone = (placement.node / regions) % self.config.zones_per_region.clamp(1, 26);
+        let namespace = match self.config.namespaces.len() {
+            0 => "default",
+            len => {
+                let index = self.hash(&format!("{}/namespace", entry.service)) % len as u64;
+                &self.config.namespaces[index as usize]
+            }
+        };
+
+        for (key, value) in [
+            ("pod_name", json!(placement.pod_name)),
+            ("namespace", json!(namespace)),
+            ("node", json!(format!("{host}.{region}.compute.internal"))),
+            ("container_id", json!(placement.container_id)),
+            ("host", json!(host)),
+            ("region", json!(region)),
+            (
+                "az",
+                json!(format!("{region}{}", (b'a' + zone as u8) as char)),
+            ),
+        ] {
+            entry.fields.insert(key.to_string(), value);
+        }
+    }
+}
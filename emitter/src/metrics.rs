@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Prometheus metrics for the pipeline, served on `/metrics`. Without the
+/// `metrics` feature, recording them is a no-op.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MetricsConfig {
+    pub port: u16,
+}
+
+#[cfg(feature = "metrics")]
+mod prom {
+    use std::sync::LazyLock;
+
+    use prometheus::{
+        HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, exponential_buckets,
+    };
+
+    pub struct Metrics {
+        pub registry: Registry,
+        pub generated: IntCounterVec,
+        pub buffered: IntGauge,
+        pub dropped: IntCounterVec,
+        pub flushed: IntCounterVec,
+        pub failed: IntCounterVec,
+        pub write_seconds: HistogramVec,
+        pub retries: IntCounterVec,
+    }
+
+    pub static METRICS: LazyLock<Metrics> = LazyLock::new(|| {
+        let registry =
+            Registry::new_custom(Some("logstorm".to_string()), None).expect("valid metrics prefix");
+        let metrics = Metrics {
+            generated: IntCounterVec::new(
+                Opts::new("entries_generated_total", "Entries generated, by service"),
+                &["service"],
+            )
+            .unwrap(),
+            buffered: IntGauge::new("entries_buffered", "Entries waiting for the next flush")
+                .unwrap(),
+            dropped: IntCounterVec::new(
+                Opts::new("entries_dropped_total", "Entries dropped before any sink"),
+                &["reason"],
+            )
+            .unwrap(),
+            flushed: IntCounterVec::new(
+                Opts::new("sink_entries_written_total", "Entries written, by sink"),
+                &["sink"],
+            )
+            .unwrap(),
+            failed: IntCounterVec::new(
+                Opts::new(
+                    "sink_entries_failed_total",
+                    "Entries in failed writes, by sink",
+                ),
+                &["sink"],
+            )
+            .unwrap(),
+            write_seconds: HistogramVec::new(
+                HistogramOpts::new("sink_write_seconds", "Batch write latency, by sink")
+                    .buckets(exponential_buckets(0.001, 2.0, 16).unwrap()),
+                &["sink"],
+            )
+            .unwrap(),
+            retries: IntCounterVec::new(
+                Opts::new("sink_retries_total", "Write retries, by sink"),
+                &["sink"],
+            )
+            .unwrap(),
+            registry,
+        };
+        let registry = &metrics.registry;
+        registry
+            .register(Box::new(metrics.generated.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.buffered.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.dropped.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.flushed.clone()))
+            .unwrap();
+        registry.register(Box::new(metrics.failed.clone())).unwrap();
+        registry
+            .register(Box::new(metrics.write_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.retries.clone()))
+            .unwrap();
+        metrics
+    });
+}
+
+/// Serve `/metrics` on the configured port
+#[cfg(feature = "metrics")]
+pub async fn serve(config: MetricsConfig) {
+    use axum::{Router, routing::get};
+    use prometheus::{Encoder, TextEncoder};
+    use tracing::{error, info};
+
+    async fn metrics_handler() -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&prom::METRICS.registry.gather(), &mut buffer) {
+            error!("Failed to encode metrics: {e}");
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+
+    let app = Router::new().route("/metrics", get(metrics_handler));
+    let addr = format!("0.0.0.0:{}", config.port);
+    info!(
+        "Metrics available at http://localhost:{}/metrics",
+        config.port
+    );
+
+    match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!("Metrics server error: {e}");
+            }
+        }
+        Err(e) => error!("Failed to bind metrics server to {addr}: {e}"),
+    }
+}
+
+#[allow(unused_variables)]
+pub fn entry_generated(service: &str) {
+    #[cfg(feature = "metrics")]
+    prom::METRICS.generated.with_label_values(&[service]).inc();
+}
+
+#[allow(unused_variables)]
+pub fn set_buffered(entries: usize) {
+    #[cfg(feature = "metrics")]
+    prom::METRICS.buffered.set(entries as i64);
+}
+
+/// Entries dropped before reaching the sinks, e.g. `overflow` or `processor`
+#[allow(unused_variables)]
+pub fn entries_dropped(reason: &str, entries: usize) {
+    #[cfg(feature = "metrics")]
+    prom::METRICS
+        .dropped
+        .with_label_values(&[reason])
+        .inc_by(entries as u64);
+}
+
+#[allow(unused_variables)]
+pub fn sink_write(sink: &str, entries: usize, elapsed: Duration, ok: bool) {
+    #[cfg(feature = "metrics")]
+    {
+        let counter = if ok {
+            &prom::METRICS.flushed
+        } else {
+            &prom::METRICS.failed
+        };
+        counter.with_label_values(&[sink]).inc_by(entries as u64);
+        prom::METRICS
+            .write_seconds
+            .with_label_values(&[sink])
+            .observe(elapsed.as_secs_f64());
+    }
+}
+
+#[allow(unused_variables)]
+pub fn sink_retry(sink: &str) {
+    #[cfg(feature = "metrics")]
+    prom::METRICS.retries.with_label_values(&[sink]).inc();
+}
@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+
+/// Prefix of environment variables overriding config values, e.g.
+/// `EMITTER__BUFFER_SIZE=5000` or `EMITTER__EMBEDDING__MODEL=...`
+pub const ENV_PREFIX: &str = "EMITTER__";
+
+/// Expand `${VAR_NAME}` patterns in a string with environment variable
+/// values, and `${VAR_NAME:-fallback}` with the fallback when the variable
+/// is unset or empty. Unknown vars without one become empty strings.
+pub fn expand_env_vars(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let expr = &rest[start + 2..start + end];
+        let (name, fallback) = match expr.split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (expr, None),
+        };
+        match (std::env::var(name), fallback) {
+            (Ok(value), Some(fallback)) if value.is_empty() => result.push_str(fallback),
+            (Ok(value), _) => result.push_str(&value),
+            (Err(_), Some(fallback)) => result.push_str(fallback),
+            (Err(_), None) => {}
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// A config file's contents, with `${VAR}`s expanded: TOML for a `.toml`
+/// path, JSON for a `.json` one, YAML otherwise. All three end up as the
+/// same tree, for the same `EmitterConfig`.
+pub fn parse_file(path: &Path, contents: &str) -> Result<Value, String> {
+    let expanded = expand_env_vars(contents);
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    match extension.to_ascii_lowercase().as_str() {
+        "toml" => {
+            let table: toml::Table = toml::from_str(&expanded).map_err(|e| e.to_string())?;
+            serde_yaml::to_value(table).map_err(|e| e.to_string())
+        }
+        "json" => serde_json::from_str(&expanded).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str(&expanded).map_err(|e| e.to_string()),
+    }
+}
+
+/// A config file with the files it `include`s merged under it: each
+/// included file (relative to the one naming it, and with its own includes
+/// merged in) over the ones before it, and the file itself over them all
+pub fn read_file(path: &Path) -> Result<Value, String> {
+    read_including(path, &mut Vec::new())
+}
+
+/// `path` read as by [`read_file`], `chain` being the files including it
+fn read_including(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Value, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let value = parse_file(path, &contents).map_err(|e| format!("{}: {e}", path.display()))?;
+    let includes = match value.get("include") {
+        None | Some(Value::Null) => return Ok(value),
+        Some(Value::Sequence(includes)) => includes
+            .iter()
+            .map(|include| include.as_str().map(PathBuf::from))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format!("{}: include: expected a list of paths", path.display()))?,
+        Some(_) => {
+            return Err(format!(
+                "{}: include: expected a list of paths",
+                path.display()
+            ));
+        }
+    };
+
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    if chain.contains(&canonical) {
+        return Err(format!("{} includes itself", path.display()));
+    }
+    chain.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut merged = Value::Null;
+    for include in includes {
+        let mut included = read_including(&dir.join(include), chain)?;
+        if let Value::Mapping(included) = &mut included {
+            included.remove("include");
+        }
+        merge(&mut merged, included);
+    }
+    chain.pop();
+    merge(&mut merged, value);
+    Ok(merged)
+}
+
+/// Merge `over` into `base`: mappings key by key, so `over` only needs the
+/// keys it changes; anything else, lists included, replaced whole
+pub fn merge(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Mapping(base), Value::Mapping(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+/// Apply every `EMITTER__`-prefixed variable in `vars` to `config`, the
+/// rest of the name being the path to the value, split on `__`
+pub fn apply_env(config: &mut Value, vars: impl IntoIterator<Item = (String, String)>) {
+    // applied in order, so a list's whole value can be set before one of
+    // its items
+    let mut vars: Vec<_> = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let path = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+            Some((path, value))
+        })
+        .collect();
+    vars.sort();
+    for (path, value) in vars {
+        let path: Vec<&str> = path.split("__").collect();
+        set(config, &path, &value);
+    }
+}
+
+/// Apply a `path.to.key=value` override, as given to `--set`
+pub fn apply_set(config: &mut Value, assignment: &str) -> Result<(), String> {
+    let Some((path, value)) = assignment.split_once('=') else {
+        return Err(format!("--set {assignment}: expected key.path=value"));
+    };
+    let path: Vec<&str> = path.trim().split('.').collect();
+    if path.iter().any(|key| key.is_empty()) {
+        return Err(format!("--set {assignment}: empty key in path"));
+    }
+    set(config, &path, value);
+    Ok(())
+}
+
+/// Set the value at `path` to `raw` read as YAML (so numbers, booleans and
+/// inline lists keep their type), creating mappings along the way. Keys
+/// match existing ones regardless of case; numeric ones index lists.
+fn set(config: &mut Value, path: &[&str], raw: &str) {
+    let Some((key, rest)) = path.split_first() else {
+        *config = parse(raw);
+        return;
+    };
+    if let Value::Sequence(items) = config
+        && let Ok(index) = key.parse::<usize>()
+    {
+        if index >= items.len() {
+            items.resize(index + 1, Value::Mapping(Mapping::new()));
+        }
+        set(&mut items[index], rest, raw);
+        return;
+    }
+    if !config.is_mapping() {
+        *config = Value::Mapping(Mapping::new());
+    }
+    let mapping = config.as_mapping_mut().expect("made a mapping above");
+    let existing = mapping
+        .keys()
+        .find(|existing| {
+            existing
+                .as_str()
+                .is_some_and(|k| k.eq_ignore_ascii_case(key))
+        })
+        .cloned();
+    let entry = mapping
+        .entry(existing.unwrap_or_else(|| Value::String(key.to_string())))
+        .or_insert(Value::Null);
+    set(entry, rest, raw);
+}
+
+fn parse(raw: &str) -> Value {
+    if raw.trim().is_empty() {
+        return Value::String(raw.to_string());
+    }
+    serde_yaml::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
@@ -0,0 +1,96 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::log_entry::LogEntry;
+
+const WAREHOUSES: &[&str] = &["iad-2", "pdx-1", "fra-3", "sin-1", "nrt-2"];
+
+fn default_rate() -> f64 {
+    0.01
+}
+
+fn default_min_bytes() -> usize {
+    1024
+}
+
+fn default_max_bytes() -> usize {
+    65_536
+}
+
+/// How often entries are oversized, and by how much: sizes are spread
+/// log-uniformly between `min_bytes` and `max_bytes`, so most are near the
+/// bottom with a long tail
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SizeConfig {
+    /// Share of entries affected (0.0 - 1.0)
+    #[serde(default = "default_rate")]
+    pub rate: f64,
+    #[serde(default = "default_min_bytes")]
+    pub min_bytes: usize,
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+}
+
+impl SizeConfig {
+    /// A size to grow an entry to, if the dice say this one grows
+    fn draw(&self, rng: &mut impl Rng) -> Option<usize> {
+        if !rng.gen_bool(self.rate.clamp(0.0, 1.0)) {
+            return None;
+        }
+        let min = self.min_bytes.max(1) as f64;
+        let max = (self.max_bytes as f64).max(min);
+        Some(rng.gen_range(min.ln()..=max.ln()).exp() as usize)
+    }
+}
+
+/// Oversized entries, for showing how sinks cope with documents past their
+/// limits (Elasticsearch's `http.max_content_length`, Qdrant's payload
+/// size, Kafka's `max.message.bytes`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PayloadConfig {
+    /// Messages with a request body dumped into them, to this length
+    pub long_messages: Option<SizeConfig>,
+    /// A `body` field holding a JSON document of this size
+    pub blobs: Option<SizeConfig>,
+}
+
+impl PayloadConfig {
+    /// Grow `entry`, if the dice say so. The embedding stays that of the
+    /// original message.
+    pub fn apply(&self, entry: &mut LogEntry, rng: &mut impl Rng) {
+        if let Some(len) = self.long_messages.as_ref().and_then(|size| size.draw(rng)) {
+            let room = len.saturating_sub(entry.message.len() + " body=".len());
+            let body = body(room, rng);
+            entry.message.push_str(&format!(" body={body}"));
+        }
+        if let Some(len) = self.blobs.as_ref().and_then(|size| size.draw(rng)) {
+            entry.fields.insert("body".to_string(), body(len, rng));
+        }
+    }
+}
+
+/// An order-like JSON document of at least `len` bytes serialized, with a
+/// fixed set of keys so it doesn't blow up index mappings
+fn body(len: usize, rng: &mut impl Rng) -> Value {
+    let mut items = Vec::new();
+    // the envelope around the items, roughly
+    let mut size = 64;
+    while size < len {
+        let item = json!({
+            "sku": format!("SKU-{:05}", rng.gen_range(0..100_000)),
+            "qty": rng.gen_range(1..10),
+            "price_cents": rng.gen_range(99..100_000),
+            "warehouse": WAREHOUSES.choose(rng).unwrap(),
+        });
+        size += item.to_string().len() + 1;
+        items.push(item);
+    }
+    json!({
+        "order_id": format!("o-{:08x}", rng.r#gen::<u32>()),
+        "currency": "USD",
+        "items": items,
+    })
+}
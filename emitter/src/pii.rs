@@ -0,0 +1,181 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::log_entry::LogEntry;
+
+const FIRST_NAMES: &[&str] = &[
+    "alice", "bob", "carmen", "deepak", "elena", "farid", "grace", "hiro", "ines", "jamal",
+    "katya", "liam", "mei", "noah", "olga", "priya",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "nguyen",
+    "smith",
+    "garcia",
+    "kowalski",
+    "okafor",
+    "tanaka",
+    "muller",
+    "silva",
+    "haddad",
+    "johansson",
+    "patel",
+    "rossi",
+];
+
+const EMAIL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "outlook.com",
+    "yahoo.com",
+    "proton.me",
+    "example.org",
+    "corp.example.com",
+];
+
+/// Issuer prefixes of the card numbers generated
+const CARD_PREFIXES: &[&str] = &["4", "51", "55", "37", "6011"];
+
+fn default_rate() -> f64 {
+    0.1
+}
+
+fn default_kinds() -> Vec<PiiKind> {
+    vec![PiiKind::Email, PiiKind::Ip, PiiKind::CreditCard]
+}
+
+fn default_in_message() -> bool {
+    true
+}
+
+/// A kind of personal data, generated into entries and recognised by the
+/// redact processor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    /// e.g. `grace.tanaka42@gmail.com`
+    Email,
+    /// IPv4 addresses
+    Ip,
+    /// Luhn-valid 15/16-digit card numbers, grouped by spaces or dashes or
+    /// not at all
+    CreditCard,
+}
+
+impl PiiKind {
+    /// The field a generated value is stored under
+    pub fn field(self) -> &'static str {
+        match self {
+            PiiKind::Email => "email",
+            PiiKind::Ip => "client_ip",
+            PiiKind::CreditCard => "card_number",
+        }
+    }
+
+    /// A regular expression matching values of this kind
+    pub fn pattern(self) -> &'static str {
+        match self {
+            PiiKind::Email => r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+            PiiKind::Ip => r"\b(25[0-5]|2[0-4]\d|1?\d?\d)(\.(25[0-5]|2[0-4]\d|1?\d?\d)){3}\b",
+            PiiKind::CreditCard => r"\b\d{4}[ -]?\d{4,6}[ -]?\d{4,5}([ -]?\d{1,4})?\b",
+        }
+    }
+
+    fn generate(self, rng: &mut impl Rng) -> String {
+        match self {
+            PiiKind::Email => format!(
+                "{}.{}{}@{}",
+                FIRST_NAMES.choose(rng).unwrap(),
+                LAST_NAMES.choose(rng).unwrap(),
+                rng.gen_range(1..100),
+                EMAIL_DOMAINS.choose(rng).unwrap()
+            ),
+            PiiKind::Ip => format!(
+                "{}.{}.{}.{}",
+                rng.gen_range(11..224),
+                rng.gen_range(0..256),
+                rng.gen_range(0..256),
+                rng.gen_range(1..255)
+            ),
+            PiiKind::CreditCard => card_number(rng),
+        }
+    }
+}
+
+/// A card number with a valid Luhn check digit, grouped as printed
+fn card_number(rng: &mut impl Rng) -> String {
+    let prefix = CARD_PREFIXES.choose(rng).unwrap();
+    // American Express numbers are 15 digits, the rest 16
+    let len = if prefix.starts_with('3') { 15 } else { 16 };
+    let mut digits: Vec<u32> = prefix.chars().filter_map(|c| c.to_digit(10)).collect();
+    while digits.len() < len - 1 {
+        digits.push(rng.gen_range(0..10));
+    }
+    // doubling every second digit from the right, starting with the one
+    // left of the check digit
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2 == 0, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    digits.push((10 - sum % 10) % 10);
+
+    let number: String = digits
+        .iter()
+        .map(|d| char::from_digit(*d, 10).unwrap())
+        .collect();
+    let separator = *[" ", "-", ""].choose(rng).unwrap();
+    let groups: &[usize] = if len == 15 { &[4, 6, 5] } else { &[4, 4, 4, 4] };
+    let mut start = 0;
+    groups
+        .iter()
+        .map(|&group| {
+            let part = &number[start..start + group];
+            start += group;
+            part
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Personal data in a share of generated entries, for demonstrating
+/// redaction
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PiiConfig {
+    /// Share of entries that carry some (0.0 - 1.0)
+    #[serde(default = "default_rate")]
+    pub rate: f64,
+    /// Kinds to pick from, one per entry
+    #[serde(default = "default_kinds")]
+    pub kinds: Vec<PiiKind>,
+    /// Also mention the value in the message, not only in its field
+    #[serde(default = "default_in_message")]
+    pub in_message: bool,
+}
+
+impl PiiConfig {
+    /// Give `entry` a piece of personal data, if the dice say so. The
+    /// embedding is left alone: it stays that of the original message.
+    pub fn apply(&self, entry: &mut LogEntry, rng: &mut impl Rng) {
+        if !rng.gen_bool(self.rate.clamp(0.0, 1.0)) {
+            return;
+        }
+        let Some(&kind) = self.kinds.choose(rng) else {
+            return;
+        };
+        let value = kind.generate(rng);
+        if self.in_message {
+            entry
+                .message
+                .push_str(&format!(" {}={value}", kind.field()));
+        }
+        entry.fields.insert(kind.field().to_string(), json!(value));
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::PipelineConfig;
+
+/// What a pipeline is expected to generate and write, at its services' and
+/// flows' base rates (traffic shapes and scenarios move them around)
+#[derive(Debug, Clone)]
+pub struct PipelinePlan {
+    pub name: String,
+    /// Entries per second from services
+    pub service_rate: f64,
+    /// Entries per second from flows, one per hop of each request
+    pub flow_rate: f64,
+    /// Entries over the run, unless it runs until stopped
+    pub total: Option<u64>,
+    /// Entries per flush
+    pub batch_size: usize,
+    /// Time between flushes
+    pub flush_every: Duration,
+    pub replays: bool,
+}
+
+impl PipelinePlan {
+    pub fn new(pipeline: &PipelineConfig, duration: Duration) -> Self {
+        let service_rate: f64 = pipeline.services.iter().map(|s| s.rate_per_sec).sum();
+        let flow_rate: f64 = pipeline
+            .flows
+            .iter()
+            .map(|flow| flow.rate_per_sec * flow.path.len() as f64)
+            .sum();
+        let rate = service_rate + flow_rate;
+        let interval = Duration::from_millis(pipeline.flush_interval_ms);
+        // the buffer flushes when it fills or when the interval is up,
+        // whichever comes first
+        let per_interval = rate * interval.as_secs_f64();
+        let (batch_size, flush_every) = if per_interval >= pipeline.buffer_size as f64 {
+            let fill = Duration::from_secs_f64(pipeline.buffer_size as f64 / rate);
+            (pipeline.buffer_size, fill)
+        } else {
+            (per_interval.round() as usize, interval)
+        };
+        Self {
+            name: pipeline.name.clone(),
+            service_rate,
+            flow_rate,
+            total: (!duration.is_zero()).then_some((rate * duration.as_secs_f64()) as u64),
+            batch_size,
+            flush_every,
+            replays: pipeline.replay.is_some(),
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.service_rate + self.flow_rate
+    }
+}
+
+impl fmt::Display for PipelinePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "~{:.0} entries/s", self.rate())?;
+        if self.flow_rate > 0.0 {
+            write!(
+                f,
+                " ({:.0}/s from services, {:.0}/s from flows)",
+                self.service_rate, self.flow_rate
+            )?;
+        }
+        if self.replays {
+            write!(f, " plus what's replayed")?;
+        }
+        match self.total {
+            Some(total) => write!(f, ", ~{total} over the run")?,
+            None => write!(f, " until stopped")?,
+        }
+        write!(
+            f,
+            "; batches of ~{} every {:.1}s",
+            self.batch_size,
+            self.flush_every.as_secs_f64()
+        )
+    }
+}
@@ -0,0 +1,54 @@
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// How often each pool message is picked, by its place in the pool
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Popularity {
+    /// Every message equally likely
+    #[default]
+    Uniform,
+    /// The k-th message picked in proportion to 1/k^s: a handful dominate,
+    /// with a long tail
+    Zipf { s: f64 },
+    /// `hot_share` of the pool takes `traffic_share` of the picks, the rest
+    /// spread evenly over the others
+    HotSet { hot_share: f64, traffic_share: f64 },
+}
+
+impl Popularity {
+    /// Index of the message to pick from a pool of `len`
+    pub fn pick(&self, len: usize, rng: &mut impl Rng) -> usize {
+        if len <= 1 {
+            return 0;
+        }
+        match *self {
+            Popularity::Uniform => rng.gen_range(0..len),
+            Popularity::Zipf { s } => {
+                // inverse CDF of the continuous power law x^-s on [1, len + 1),
+                // floored to a rank: a close, table-free stand-in for Zipf
+                let u: f64 = rng.r#gen();
+                let n = len as f64 + 1.0;
+                let x = if (s - 1.0).abs() < 1e-9 {
+                    n.powf(u)
+                } else {
+                    let a = 1.0 - s;
+                    ((n.powf(a) - 1.0) * u + 1.0).powf(1.0 / a)
+                };
+                (x as usize).saturating_sub(1).min(len - 1)
+            }
+            Popularity::HotSet {
+                hot_share,
+                traffic_share,
+            } => {
+                let hot = ((len as f64 * hot_share.clamp(0.0, 1.0)).ceil() as usize).clamp(1, len);
+                if hot == len || rng.gen_bool(traffic_share.clamp(0.0, 1.0)) {
+                    rng.gen_range(0..hot)
+                } else {
+                    rng.gen_range(hot..len)
+                }
+            }
+        }
+    }
+}
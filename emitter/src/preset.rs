@@ -0,0 +1,222 @@
+/// A config built into the binary, to run or start a config file from
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Annotated YAML, its `sinks` last
+    pub source: &'static str,
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "quickstart-stdout",
+        description: "a few services logging to the terminal, nothing to set up",
+        source: include_str!("../presets/quickstart-stdout.yaml"),
+    },
+    Preset {
+        name: "es-vs-qdrant",
+        description: "the same embedded entries written to Elasticsearch and Qdrant side by side",
+        source: include_str!("../presets/es-vs-qdrant.yaml"),
+    },
+    Preset {
+        name: "high-volume-soak",
+        description: "thousands of entries a second until stopped, shedding load when sinks fall behind",
+        source: include_str!("../presets/high-volume-soak.yaml"),
+    },
+    Preset {
+        name: "incident-demo",
+        description: "an outage and a latency storm, with cascading errors and traced requests",
+        source: include_str!("../presets/incident-demo.yaml"),
+    },
+];
+
+/// The preset called `name`
+pub fn find(name: &str) -> Result<&'static Preset, String> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| format!("no preset named {name}; choose one of:\n{}", list()))
+}
+
+/// Every preset, a line each
+pub fn list() -> String {
+    PRESETS
+        .iter()
+        .map(|preset| format!("  {:<18} {}", preset.name, preset.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An example of each type of sink, with the settings it needs and the
+/// ones most often changed, whether or not this build has its feature
+const SINK_EXAMPLES: &[(&str, &str)] = &[
+    ("stdout", "  - type: stdout\n"),
+    (
+        "blackhole",
+        r#"  - type: blackhole # accepts and discards, to measure the emitter alone
+    # latency_ms: 20 # pretend each write takes this long
+"#,
+    ),
+    (
+        "qdrant",
+        r#"  - type: qdrant # build with --features qdrant
+    url: ${QDRANT_URL:-http://localhost:6334}
+    # api_key: ${QDRANT_API_KEY}
+    collection_name: logs # or e.g. logs-%Y.%m.%d / logs-{service} for a collection per day / service
+    # quantization: scalar # or binary
+    # distance: cosine # or dot, euclidean
+"#,
+    ),
+    (
+        "elasticsearch",
+        r#"  - type: elasticsearch # build with --features elasticsearch
+    url: ${ELASTIC_URL:-http://localhost:9200}
+    user: ${ELASTIC_USER:-elastic}
+    password: "${ELASTIC_PASSWORD}"
+    # or api_key: ${ELASTIC_API_KEY} / bearer_token, and cloud_id instead of url
+    index_name: logs # or e.g. logs-%Y.%m.%d / logs-{service} for an index per day / service
+    # vector_index_type: hnsw # or int8_hnsw, bbq_hnsw
+"#,
+    ),
+    (
+        "pgvector",
+        r#"  - type: pgvector # build with --features pgvector
+    host: ${PGVECTOR_HOST:-localhost}
+    user: ${PGVECTOR_USER:-postgres}
+    password: "${PGVECTOR_PASSWORD}"
+    table_name: logs
+    # vector_type: halfvec # vector (default), halfvec or bit
+    # index_type: hnsw # or ivfflat
+"#,
+    ),
+    (
+        "lancedb",
+        r#"  - type: lancedb # build with --features lancedb
+    uri: ./data/lancedb
+    table_name: logs
+"#,
+    ),
+    (
+        "vespa",
+        r#"  - type: vespa # build with --features vespa
+    url: http://localhost:8080
+    document_type: logs
+"#,
+    ),
+    (
+        "fluentd",
+        r#"  - type: fluentd # build with --features fluentd
+    host: localhost
+    port: 24224
+    tag: logstorm.{service}
+"#,
+    ),
+    (
+        "syslog",
+        r#"  - type: syslog # build with --features syslog
+    host: localhost
+    port: 514
+    transport: udp # or tcp
+"#,
+    ),
+    (
+        "otlp",
+        r#"  - type: otlp # build with --features otlp
+    endpoint: http://localhost:4317
+    protocol: grpc # or http/protobuf (port 4318)
+"#,
+    ),
+    (
+        "splunk",
+        r#"  - type: splunk # build with --features splunk
+    url: https://localhost:8088
+    token: "${SPLUNK_HEC_TOKEN}"
+    # index: logs
+"#,
+    ),
+    (
+        "datadog",
+        r#"  - type: datadog # build with --features datadog
+    api_key: "${DD_API_KEY}"
+    site: datadoghq.com # or datadoghq.eu, ...
+    # tags: [env:demo]
+"#,
+    ),
+    (
+        "gelf",
+        r#"  - type: gelf # build with --features gelf
+    host: localhost
+    port: 12201
+    transport: udp # or tcp
+"#,
+    ),
+    (
+        "cloudwatch",
+        r#"  - type: cloudwatch # build with --features cloudwatch; credentials from the AWS environment
+    log_group: /logstorm/{service}
+    log_stream: logstorm
+    # region: us-east-1
+"#,
+    ),
+    (
+        "cassandra",
+        r#"  - type: cassandra # build with --features cassandra
+    nodes: [localhost:9042]
+    keyspace: logstorm
+    table_name: logs
+"#,
+    ),
+    (
+        "socket",
+        r#"  - type: socket # build with --features socket
+    host: localhost
+    port: 9000
+    protocol: tcp # or udp
+"#,
+    ),
+    (
+        "http",
+        r#"  - type: http # build with --features http
+    url: http://localhost:8080/ingest
+    # headers: { Authorization: Bearer ${INGEST_TOKEN} }
+    # compression: gzip
+"#,
+    ),
+    (
+        "exec",
+        r#"  - type: exec # build with --features exec
+    command: jq
+    args: [-c, .]
+"#,
+    ),
+    (
+        "dashboard",
+        r#"  - type: dashboard # build with --features dashboard; open http://localhost:3000
+    port: 3000
+"#,
+    ),
+];
+
+/// `preset` as a config file to start from, writing to an example of each
+/// of `sinks` (by type) instead of its own sinks when any are given
+pub fn starter(preset: &Preset, sinks: &[String]) -> Result<String, String> {
+    let mut config = String::from(preset.source);
+    if sinks.is_empty() {
+        return Ok(config);
+    }
+    let Some(at) = config.rfind("\nsinks:") else {
+        return Err(format!("preset {} has no sinks to replace", preset.name));
+    };
+    config.truncate(at + 1);
+    config.push_str("sinks:\n");
+    for kind in sinks {
+        let Some((_, example)) = SINK_EXAMPLES.iter().find(|(name, _)| name == kind) else {
+            let kinds: Vec<&str> = SINK_EXAMPLES.iter().map(|(name, _)| *name).collect();
+            return Err(format!(
+                "no sink of type {kind}; choose from {}",
+                kinds.join(", ")
+            ));
+        };
+        config.push_str(example);
+    }
+    Ok(config)
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::LogEntry;
+use crate::processor::Processor;
+
+fn default_window_secs() -> u64 {
+    10
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    /// Drop repeats within the window
+    #[default]
+    Drop,
+    /// Count repeats on the entry kept: repeats in the same batch are added
+    /// to its `repeat_count`, later ones to that of the next entry let
+    /// through once the window has passed
+    Collapse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DedupConfig {
+    /// How long after an entry the same (service, message) counts as a repeat
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    #[serde(default)]
+    pub mode: DedupMode,
+}
+
+struct Seen {
+    /// Timestamp of the entry that opened the window
+    since: DateTime<Utc>,
+    /// Repeats dropped since, not yet counted on a kept entry
+    suppressed: u32,
+}
+
+/// Drops or collapses entries repeating a (service, message) pair within a
+/// sliding window of entry timestamps
+pub struct DedupProcessor {
+    config: DedupConfig,
+    window: TimeDelta,
+    seen: Mutex<HashMap<(String, String), Seen>>,
+}
+
+impl DedupProcessor {
+    pub fn from_config(config: DedupConfig) -> Self {
+        Self {
+            window: TimeDelta::seconds(config.window_secs as i64),
+            config,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Processor for DedupProcessor {
+    fn process(&self, batch: Vec<LogEntry>) -> Vec<LogEntry> {
+        let collapse = matches!(self.config.mode, DedupMode::Collapse);
+        let mut seen = self.seen.lock().unwrap();
+        let mut kept: Vec<LogEntry> = Vec::with_capacity(batch.len());
+        // where each key's entry sits in `kept`, for repeats within the batch
+        let mut kept_at: HashMap<(String, String), usize> = HashMap::new();
+
+        for mut entry in batch {
+            let key = (entry.service.clone(), entry.message.clone());
+            match seen.get_mut(&key) {
+                Some(previous) if entry.timestamp - previous.since < self.window => {
+                    match kept_at.get(&key) {
+                        Some(&i) if collapse => {
+                            let count = kept[i].repeat_count.get_or_insert(1);
+                            *count += 1;
+                        }
+                        _ => previous.suppressed += 1,
+                    }
+                }
+                _ => {
+                    let suppressed = seen
+                        .insert(
+                            key.clone(),
+                            Seen {
+                                since: entry.timestamp,
+                                suppressed: 0,
+                            },
+                        )
+                        .map_or(0, |previous| previous.suppressed);
+                    if collapse && suppressed > 0 {
+                        entry.repeat_count = Some(suppressed + 1);
+                    }
+                    kept_at.insert(key, kept.len());
+                    kept.push(entry);
+                }
+            }
+        }
+
+        // forget windows that have closed, so the map doesn't grow unbounded
+        if let Some(latest) = kept.iter().map(|entry| entry.timestamp).max() {
+            seen.retain(|_, s| latest - s.since < self.window || s.suppressed > 0);
+        }
+        kept
+    }
+}
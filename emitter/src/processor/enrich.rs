@@ -0,0 +1,39 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::LogEntry;
+use crate::processor::Processor;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EnrichConfig {
+    /// Static key/value pairs added to every entry, e.g. `env: demo`
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Tags entries with static fields, appended to the message in logfmt style
+/// (`... env=demo region=eu`) as entries have no structured fields
+pub struct EnrichProcessor {
+    suffix: String,
+}
+
+impl EnrichProcessor {
+    pub fn from_config(config: EnrichConfig) -> Self {
+        let suffix = config
+            .fields
+            .iter()
+            .map(|(key, value)| format!(" {key}={value}"))
+            .collect();
+        Self { suffix }
+    }
+}
+
+impl Processor for EnrichProcessor {
+    fn process(&self, mut batch: Vec<LogEntry>) -> Vec<LogEntry> {
+        for entry in &mut batch {
+            entry.message.push_str(&self.suffix);
+        }
+        batch
+    }
+}
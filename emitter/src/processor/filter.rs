@@ -0,0 +1,32 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::LogEntry;
+use crate::processor::{EntryMatch, Processor};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FilterConfig {
+    #[serde(flatten)]
+    pub matching: EntryMatch,
+    /// Drop matching entries instead of keeping only them
+    #[serde(default)]
+    pub exclude: bool,
+}
+
+/// Keeps (or with `exclude`, drops) the entries matching its conditions
+pub struct FilterProcessor {
+    config: FilterConfig,
+}
+
+impl FilterProcessor {
+    pub fn from_config(config: FilterConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Processor for FilterProcessor {
+    fn process(&self, mut batch: Vec<LogEntry>) -> Vec<LogEntry> {
+        batch.retain(|entry| self.config.matching.matches(entry) != self.config.exclude);
+        batch
+    }
+}
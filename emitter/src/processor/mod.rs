@@ -0,0 +1,54 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::{LogEntry, LogLevel};
+
+pub mod dedup;
+pub mod enrich;
+pub mod filter;
+pub mod redact;
+pub mod sample;
+pub mod sparse;
+pub mod transform;
+
+/// A stage applied to each batch after it leaves the buffer and before it
+/// reaches the sinks. Processors run in the order they're configured.
+pub trait Processor: Send + Sync {
+    fn process(&self, batch: Vec<LogEntry>) -> Vec<LogEntry>;
+}
+
+/// Conditions an entry has to meet; unset conditions match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct EntryMatch {
+    pub levels: Option<Vec<LogLevel>>,
+    pub services: Option<Vec<String>>,
+    /// Substring the message must contain
+    pub message_contains: Option<String>,
+}
+
+impl EntryMatch {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.levels
+            .as_ref()
+            .is_none_or(|levels| levels.contains(&entry.level))
+            && self
+                .services
+                .as_ref()
+                .is_none_or(|services| services.contains(&entry.service))
+            && self
+                .message_contains
+                .as_ref()
+                .is_none_or(|needle| entry.message.contains(needle.as_str()))
+    }
+}
+
+/// Run a batch through each processor in turn
+pub fn apply(processors: &[Box<dyn Processor>], mut batch: Vec<LogEntry>) -> Vec<LogEntry> {
+    for processor in processors {
+        if batch.is_empty() {
+            break;
+        }
+        batch = processor.process(batch);
+    }
+    batch
+}
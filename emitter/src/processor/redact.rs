@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::log_entry::LogEntry;
+use crate::pii::PiiKind;
+use crate::processor::Processor;
+
+fn default_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RedactConfig {
+    /// Regular expressions whose matches are masked
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Built-in patterns for these kinds of personal data, on top of
+    /// `patterns`
+    #[serde(default)]
+    pub pii: Vec<PiiKind>,
+    #[serde(default = "default_replacement")]
+    pub replacement: String,
+}
+
+/// Masks parts of the message and of string fields matching any of the
+/// configured patterns. Embeddings are left alone, having been computed
+/// from the original text.
+pub struct RedactProcessor {
+    patterns: Vec<Regex>,
+    replacement: String,
+}
+
+impl RedactProcessor {
+    pub fn from_config(config: RedactConfig) -> Result<Self, regex::Error> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(String::as_str)
+            .chain(config.pii.iter().map(|kind| kind.pattern()))
+            .map(Regex::new)
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            patterns,
+            replacement: config.replacement,
+        })
+    }
+
+    fn redact(&self, text: &mut String) {
+        for pattern in &self.patterns {
+            if let Cow::Owned(redacted) = pattern.replace_all(text, self.replacement.as_str()) {
+                *text = redacted;
+            }
+        }
+    }
+}
+
+impl Processor for RedactProcessor {
+    fn process(&self, mut batch: Vec<LogEntry>) -> Vec<LogEntry> {
+        for entry in &mut batch {
+            self.redact(&mut entry.message);
+            for value in entry.fields.values_mut() {
+                if let Value::String(text) = value {
+                    self.redact(text);
+                }
+            }
+        }
+        batch
+    }
+}
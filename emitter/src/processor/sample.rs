@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::{LogEntry, LogLevel};
+use crate::processor::Processor;
+
+fn default_rate() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SampleConfig {
+    /// Fraction of entries kept (0.0 - 1.0) for levels without their own rate
+    #[serde(default = "default_rate")]
+    pub rate: f64,
+    /// Per-level rates, e.g. `{ debug: 0.05, info: 0.2 }` to keep every
+    /// warning and error but only a share of the rest
+    #[serde(default)]
+    pub levels: HashMap<LogLevel, f64>,
+    /// Keep at most this many entries per service per second (by entry
+    /// timestamp), dropping the rest
+    pub max_per_service_per_sec: Option<u32>,
+}
+
+/// Keeps a random fraction of entries, by level, and optionally caps each
+/// service's rate. Kept entries are annotated with the rate they were
+/// sampled at. The settings can be changed while it runs.
+pub struct SampleProcessor {
+    config: Arc<RwLock<SampleConfig>>,
+    /// Per service: the second being counted and entries kept within it
+    windows: Mutex<HashMap<String, (i64, u32)>>,
+}
+
+impl SampleProcessor {
+    pub fn from_config(config: SampleConfig) -> Self {
+        Self::live(Arc::new(RwLock::new(config)))
+    }
+
+    /// Sample by settings that may be changed while it runs
+    pub fn live(config: Arc<RwLock<SampleConfig>>) -> Self {
+        Self {
+            config,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn rate(config: &SampleConfig, level: &LogLevel) -> f64 {
+        config
+            .levels
+            .get(level)
+            .copied()
+            .unwrap_or(config.rate)
+            .clamp(0.0, 1.0)
+    }
+
+    fn under_cap(
+        config: &SampleConfig,
+        windows: &mut HashMap<String, (i64, u32)>,
+        entry: &LogEntry,
+    ) -> bool {
+        let Some(cap) = config.max_per_service_per_sec else {
+            return true;
+        };
+        let second = entry.timestamp.timestamp();
+        let (window, kept) = windows.entry(entry.service.clone()).or_insert((second, 0));
+        if *window != second {
+            *window = second;
+            *kept = 0;
+        }
+        if *kept >= cap {
+            return false;
+        }
+        *kept += 1;
+        true
+    }
+}
+
+impl Processor for SampleProcessor {
+    fn process(&self, mut batch: Vec<LogEntry>) -> Vec<LogEntry> {
+        let mut rng = rand::thread_rng();
+        let config = self.config.read().unwrap().clone();
+        let mut windows = self.windows.lock().unwrap();
+        batch.retain_mut(|entry| {
+            let rate = Self::rate(&config, &entry.level);
+            if rate < 1.0 {
+                if !rng.gen_bool(rate) {
+                    return false;
+                }
+                entry.sample_rate = Some(entry.sample_rate.unwrap_or(1.0) * rate);
+            }
+            Self::under_cap(&config, &mut windows, entry)
+        });
+        batch
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::{LogEntry, SparseEmbedding};
+use crate::processor::Processor;
+
+fn default_k1() -> f32 {
+    1.2
+}
+
+fn default_b() -> f32 {
+    0.75
+}
+
+fn default_avg_len() -> f32 {
+    12.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SparseConfig {
+    /// Term frequency saturation
+    #[serde(default = "default_k1")]
+    pub k1: f32,
+    /// How much longer messages' terms are discounted (0.0 - 1.0)
+    #[serde(default = "default_b")]
+    pub b: f32,
+    /// Typical message length in terms, the length `b` discounts against
+    #[serde(default = "default_avg_len")]
+    pub avg_len: f32,
+}
+
+/// Attaches a BM25 sparse embedding of the message to each entry: one
+/// dimension per term, hashed, weighted by its saturated frequency. IDF
+/// needs the whole corpus, so it's left to the store (Qdrant's IDF
+/// modifier) or the query.
+pub struct SparseProcessor {
+    config: SparseConfig,
+}
+
+impl SparseProcessor {
+    pub fn from_config(config: SparseConfig) -> Self {
+        Self { config }
+    }
+
+    fn embed(&self, message: &str) -> SparseEmbedding {
+        let mut counts: BTreeMap<u32, f32> = BTreeMap::new();
+        let mut len = 0;
+        for term in terms(message) {
+            *counts.entry(term_index(&term)).or_default() += 1.0;
+            len += 1;
+        }
+        let SparseConfig { k1, b, avg_len } = self.config;
+        let norm = k1 * (1.0 - b + b * len as f32 / avg_len.max(1.0));
+        let (indices, values) = counts
+            .into_iter()
+            .map(|(index, tf)| (index, tf * (k1 + 1.0) / (tf + norm)))
+            .unzip();
+        SparseEmbedding { indices, values }
+    }
+}
+
+impl Processor for SparseProcessor {
+    fn process(&self, mut batch: Vec<LogEntry>) -> Vec<LogEntry> {
+        for entry in &mut batch {
+            entry.sparse = Some(self.embed(&entry.message));
+        }
+        batch
+    }
+}
+
+/// The lowercased alphanumeric terms of `message`
+pub fn terms(message: &str) -> impl Iterator<Item = String> + '_ {
+    message
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// A term's sparse dimension: its 32-bit FNV-1a hash, so indices are stable
+/// across runs and builds
+pub fn term_index(term: &str) -> u32 {
+    term.bytes().fold(0x811c9dc5, |hash: u32, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
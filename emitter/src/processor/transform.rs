@@ -0,0 +1,49 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::log_entry::LogEntry;
+use crate::processor::Processor;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TransformConfig {
+    /// New message, e.g. `"[{level}] {service}: {message}"`; `{message}`,
+    /// `{service}` and `{level}` are replaced with the entry's values
+    pub message: Option<String>,
+    /// New service name, with the same placeholders
+    pub service: Option<String>,
+}
+
+/// Rewrites entries' message and/or service from templates
+pub struct TransformProcessor {
+    config: TransformConfig,
+}
+
+impl TransformProcessor {
+    pub fn from_config(config: TransformConfig) -> Self {
+        Self { config }
+    }
+}
+
+fn render(template: &str, entry: &LogEntry) -> String {
+    template
+        .replace("{message}", &entry.message)
+        .replace("{service}", &entry.service)
+        .replace("{level}", &entry.level.to_string())
+}
+
+impl Processor for TransformProcessor {
+    fn process(&self, mut batch: Vec<LogEntry>) -> Vec<LogEntry> {
+        for entry in &mut batch {
+            // both templates see the entry as it came in
+            let message = self.config.message.as_deref().map(|t| render(t, entry));
+            let service = self.config.service.as_deref().map(|t| render(t, entry));
+            if let Some(message) = message {
+                entry.message = message;
+            }
+            if let Some(service) = service {
+                entry.service = service;
+            }
+        }
+        batch
+    }
+}
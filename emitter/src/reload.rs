@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde_yaml::Value;
+use tracing::{info, warn};
+
+use crate::config::{EmitterConfig, PipelineConfig, ProcessorConfig};
+use crate::control::Control;
+use crate::validate;
+
+/// How long to let a changed config file settle before reading it, as
+/// editors save in several steps
+const SETTLE: Duration = Duration::from_millis(250);
+
+/// Apply what can be changed without restarting from `new`, the config
+/// file as reloaded, to the run going on with `current`: services' and
+/// flows' rates, level weights, pipelines' sample processors and their
+/// scenarios. Returns what was changed, or, changing nothing, everything
+/// else that differs, which needs a restart.
+pub fn apply(
+    control: &Control,
+    current: &EmitterConfig,
+    new: &EmitterConfig,
+) -> Result<Vec<String>, Vec<String>> {
+    let mut rest = new.clone();
+    for (pipeline, current) in pipelines_mut(&mut rest)
+        .into_iter()
+        .zip(current.pipelines())
+    {
+        keep_live_settings(pipeline, &current);
+    }
+    let mut rejected = Vec::new();
+    if let (Ok(current), Ok(rest)) = (serde_yaml::to_value(current), serde_yaml::to_value(&rest)) {
+        differences(&current, &rest, "", &mut rejected);
+    }
+    rejected = rejected
+        .into_iter()
+        .map(|path| format!("{path} changed, which needs a restart"))
+        .collect();
+
+    // messages are embedded up front, so new scenarios can only log those
+    // already known
+    if new.embedding.enabled() && new.embedding.lazy.is_none() {
+        let pipelines = current.pipelines();
+        let known: HashSet<&str> = pipelines
+            .iter()
+            .flat_map(|pipeline| &pipeline.scenarios)
+            .flat_map(|scenario| scenario.vocabulary())
+            .collect();
+        for pipeline in new.pipelines() {
+            for scenario in &pipeline.scenarios {
+                if scenario
+                    .vocabulary()
+                    .any(|message| !known.contains(message))
+                {
+                    rejected.push(format!(
+                        "scenario '{}' of pipeline '{}' logs messages that weren't embedded at startup; \
+                         restart, or embed lazily, to use them",
+                        scenario.name, pipeline.name
+                    ));
+                }
+            }
+        }
+    }
+    if !rejected.is_empty() {
+        return Err(rejected);
+    }
+
+    let mut changes = Vec::new();
+    for (current, new) in current.pipelines().iter().zip(new.pipelines()) {
+        let name = new.name.as_str();
+        for (current, new) in current.services.iter().zip(&new.services) {
+            if current.rate_per_sec != new.rate_per_sec {
+                control.set_rate(Some(name), &new.name, new.rate_per_sec);
+                changes.push(format!(
+                    "{name}/{}: rate_per_sec {} -> {}",
+                    new.name, current.rate_per_sec, new.rate_per_sec
+                ));
+            }
+            if differs(&current.level_weights, &new.level_weights) {
+                control.set_level_weights(name, &new.name, new.level_weights.clone());
+                changes.push(format!("{name}/{}: level_weights", new.name));
+            }
+        }
+        for (current, new) in current.flows.iter().zip(&new.flows) {
+            if current.rate_per_sec != new.rate_per_sec {
+                control.set_rate(Some(name), &format!("flow:{}", new.name), new.rate_per_sec);
+                changes.push(format!(
+                    "{name}/flow:{}: rate_per_sec {} -> {}",
+                    new.name, current.rate_per_sec, new.rate_per_sec
+                ));
+            }
+        }
+        let samples = |pipeline: &PipelineConfig| {
+            pipeline
+                .processors
+                .iter()
+                .filter_map(|processor| match processor {
+                    ProcessorConfig::Sample(sample) => Some(sample.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        for (i, (current, new)) in samples(current).iter().zip(samples(&new)).enumerate() {
+            if differs(current, &new) {
+                control.set_sample(name, i, new);
+                changes.push(format!("{name}: sample processor {i}"));
+            }
+        }
+        if differs(&current.scenarios, &new.scenarios) {
+            control.set_scenarios(name, new.scenarios.clone());
+            changes.push(format!("{name}: scenarios"));
+        }
+    }
+    Ok(changes)
+}
+
+/// Watch the config file at `path`, applying what can be changed while
+/// running whenever it's saved. `load` reads it as the run was started
+/// with it, overrides and all; the run started with `current`.
+pub async fn watch(
+    path: PathBuf,
+    control: Arc<Control>,
+    mut current: EmitterConfig,
+    load: impl Fn() -> Result<EmitterConfig, String> + Send + 'static,
+) {
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    let name = path.file_name().map(|name| name.to_os_string());
+    let mut watcher =
+        match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event
+                && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == name.as_deref())
+            {
+                let _ = tx.try_send(());
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                warn!("Can't watch {} for changes: {e}", path.display());
+                return;
+            }
+        };
+    // the directory rather than the file, which editors often replace
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+        warn!("Can't watch {} for changes: {e}", path.display());
+        return;
+    }
+    info!("Watching {} for changes", path.display());
+
+    while rx.recv().await.is_some() {
+        tokio::time::sleep(SETTLE).await;
+        while rx.try_recv().is_ok() {}
+
+        let new = match load() {
+            Ok(new) => new,
+            Err(e) => {
+                warn!("Not reloading {}: {e}", path.display());
+                continue;
+            }
+        };
+        let problems = validate::validate(&new);
+        if !problems.is_empty() {
+            warn!("Not reloading {}, it's invalid:", path.display());
+            for problem in &problems {
+                warn!("  {problem}");
+            }
+            continue;
+        }
+        match apply(&control, &current, &new) {
+            Ok(changes) if changes.is_empty() => {}
+            Ok(changes) => {
+                info!("Reloaded {}:", path.display());
+                for change in &changes {
+                    info!("  {change}");
+                }
+                current = new;
+            }
+            Err(rejected) => {
+                warn!("Not reloading {}:", path.display());
+                for reason in &rejected {
+                    warn!("  {reason}");
+                }
+            }
+        }
+    }
+}
+
+fn pipelines_mut(config: &mut EmitterConfig) -> Vec<&mut PipelineConfig> {
+    if config.pipelines.is_empty() {
+        vec![&mut config.pipeline]
+    } else {
+        config.pipelines.iter_mut().collect()
+    }
+}
+
+/// Put `current`'s settings that can change while running back into
+/// `pipeline`, so what's left to tell them apart needs a restart
+fn keep_live_settings(pipeline: &mut PipelineConfig, current: &PipelineConfig) {
+    for (service, current) in pipeline.services.iter_mut().zip(&current.services) {
+        service.rate_per_sec = current.rate_per_sec;
+        service.level_weights = current.level_weights.clone();
+    }
+    for (flow, current) in pipeline.flows.iter_mut().zip(&current.flows) {
+        flow.rate_per_sec = current.rate_per_sec;
+    }
+    for (processor, current) in pipeline.processors.iter_mut().zip(&current.processors) {
+        if let (ProcessorConfig::Sample(sample), ProcessorConfig::Sample(current)) =
+            (processor, current)
+        {
+            *sample = current.clone();
+        }
+    }
+    pipeline.scenarios = current.scenarios.clone();
+}
+
+fn differs<T: serde::Serialize>(a: &T, b: &T) -> bool {
+    serde_yaml::to_value(a).ok() != serde_yaml::to_value(b).ok()
+}
+
+/// The paths at which `a` and `b` differ
+fn differences(a: &Value, b: &Value, path: &str, found: &mut Vec<String>) {
+    match (a, b) {
+        (Value::Mapping(a), Value::Mapping(b)) => {
+            let keys = a.keys().chain(b.keys().filter(|key| !a.contains_key(*key)));
+            for key in keys {
+                let key_path = match (path, key.as_str()) {
+                    ("", Some(key)) => key.to_string(),
+                    (_, Some(key)) => format!("{path}.{key}"),
+                    (_, None) => format!("{path}.?"),
+                };
+                let missing = Value::Null;
+                differences(
+                    a.get(key).unwrap_or(&missing),
+                    b.get(key).unwrap_or(&missing),
+                    &key_path,
+                    found,
+                );
+            }
+        }
+        (Value::Sequence(a), Value::Sequence(b)) if a.len() == b.len() => {
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                differences(a, b, &format!("{path}[{i}]"), found);
+            }
+        }
+        _ if a != b => found.push(if path.is_empty() { "the config" } else { path }.to_string()),
+        _ => {}
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::channel::EntrySender;
+use crate::control::Control;
+use crate::embedding::lazy::LazyEmbeddings;
+use crate::log_entry::{LogEntry, LogLevel, TraceContext};
+use crate::metrics;
+
+/// The name a pipeline's replay is counted under in progress and
+/// checkpoints
+pub const SOURCE: &str = "replay";
+
+/// Lines read and embedded together
+const CHUNK_LINES: usize = 256;
+
+/// How long to wait for more lines when following a file
+const FOLLOW_POLL: Duration = Duration::from_millis(500);
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+fn default_rewrite_timestamps() -> bool {
+    true
+}
+
+fn default_service() -> String {
+    "replay".to_string()
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayFormat {
+    /// Lines that parse as JSON objects are read as such, the rest as text
+    #[default]
+    Auto,
+    Jsonl,
+    Text,
+}
+
+/// Entries read from an existing log file instead of (or alongside) the
+/// generated ones
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub format: ReplayFormat,
+    /// Playback speed relative to the gaps between the original
+    /// timestamps; 0 replays as fast as the pipeline takes entries
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// Pace for lines without a timestamp (default: no pacing)
+    pub lines_per_sec: Option<f64>,
+    /// Stamp entries with the time they're replayed rather than their
+    /// original timestamp
+    #[serde(default = "default_rewrite_timestamps")]
+    pub rewrite_timestamps: bool,
+    /// Keep reading lines appended to the file once its end is reached,
+    /// like `tail -f`
+    #[serde(default)]
+    pub follow: bool,
+    /// Service for entries that don't name one
+    #[serde(default = "default_service")]
+    pub service: String,
+}
+
+/// A line parsed, before it's embedded
+struct Line {
+    timestamp: Option<DateTime<Utc>>,
+    entry: LogEntry,
+}
+
+impl ReplayConfig {
+    fn parse(&self, line: &str) -> Option<Line> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() {
+            return None;
+        }
+        let json = match self.format {
+            ReplayFormat::Text => None,
+            ReplayFormat::Jsonl | ReplayFormat::Auto => {
+                match serde_json::from_str::<Map<String, Value>>(line) {
+                    Ok(object) => Some(object),
+                    Err(_) if matches!(self.format, ReplayFormat::Auto) => None,
+                    Err(e) => {
+                        error!("Skipping malformed line in {}: {e}", self.path.display());
+                        return None;
+                    }
+                }
+            }
+        };
+        Some(match json {
+            Some(object) => self.parse_object(object),
+            None => Line {
+                timestamp: None,
+                entry: self.entry(line.to_string(), guess_level(line)),
+            },
+        })
+    }
+
+    fn parse_object(&self, mut object: Map<String, Value>) -> Line {
+        let mut take_str = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| object.remove(*key))
+                .map(|value| match value {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                })
+        };
+        let message = take_str(&["message", "msg", "log", "text"]).unwrap_or_default();
+        let level = take_str(&["level", "severity", "lvl"])
+            .and_then(|level| parse_level(&level))
+            .unwrap_or_else(|| guess_level(&message));
+        let service = take_str(&["service", "app", "service.name"]);
+        let id = take_str(&["id"]);
+        let trace_id = take_str(&["trace_id"]);
+        let span_id = take_str(&["span_id"]);
+        let parent_span_id = take_str(&["parent_span_id"]);
+        let timestamp = ["timestamp", "@timestamp", "ts", "time"]
+            .iter()
+            .find_map(|key| object.remove(*key))
+            .and_then(|value| parse_timestamp(&value));
+
+        let mut entry = self.entry(message, level);
+        if let Some(service) = service {
+            entry.service = service;
+        }
+        if let Some(id) = id {
+            entry.id = id;
+        }
+        if let (Some(trace_id), Some(span_id)) = (trace_id, span_id) {
+            entry.trace = Some(TraceContext {
+                trace_id,
+                span_id,
+                parent_span_id,
+            });
+        }
+        // whatever else the line carries becomes structured fields
+        entry.fields = object.into_iter().collect();
+        Line { timestamp, entry }
+    }
+
+    fn entry(&self, message: String, level: LogLevel) -> LogEntry {
+        LogEntry {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            service: self.service.clone(),
+            level,
+            message,
+            embedding: Vec::new(),
+            sparse: None,
+            extra_embeddings: BTreeMap::new(),
+            fields: HashMap::new(),
+            trace: None,
+            sample_rate: None,
+            repeat_count: None,
+        }
+    }
+}
+
+fn parse_level(level: &str) -> Option<LogLevel> {
+    match level.to_ascii_lowercase().as_str() {
+        "trace" | "debug" => Some(LogLevel::Debug),
+        "info" | "notice" | "information" => Some(LogLevel::Info),
+        "warn" | "warning" => Some(LogLevel::Warn),
+        "error" | "err" | "fatal" | "critical" | "crit" | "alert" | "emerg" => {
+            Some(LogLevel::Error)
+        }
+        _ => None,
+    }
+}
+
+/// The level a plain-text line mentions, INFO if none
+fn guess_level(line: &str) -> LogLevel {
+    let upper = line.to_ascii_uppercase();
+    if ["ERROR", "FATAL", "CRITICAL", "EXCEPTION"]
+        .iter()
+        .any(|word| upper.contains(word))
+    {
+        LogLevel::Error
+    } else if upper.contains("WARN") {
+        LogLevel::Warn
+    } else if upper.contains("DEBUG") || upper.contains("TRACE") {
+        LogLevel::Debug
+    } else {
+        LogLevel::Info
+    }
+}
+
+/// RFC 3339 strings, or epoch seconds or milliseconds
+fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|t| t.with_timezone(&Utc)),
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            let millis = if n > 1e11 { n } else { n * 1000.0 };
+            Utc.timestamp_millis_opt(millis as i64).single()
+        }
+        _ => None,
+    }
+}
+
+/// Where the replay is in the file's timeline, to pace entries by
+struct Clock {
+    started: Instant,
+    /// The first original timestamp seen
+    origin: Option<DateTime<Utc>>,
+    /// Lines replayed without a timestamp
+    untimed: u64,
+}
+
+impl Clock {
+    /// How long after the start of the replay `line` is due
+    fn due(&mut self, config: &ReplayConfig, line: &Line) -> Option<Duration> {
+        match line.timestamp {
+            Some(timestamp) if config.speed > 0.0 => {
+                let origin = *self.origin.get_or_insert(timestamp);
+                let offset = (timestamp - origin).to_std().unwrap_or_default();
+                Some(offset.div_f64(config.speed))
+            }
+            Some(_) => None,
+            None => {
+                let rate = config.lines_per_sec.filter(|&rate| rate > 0.0)?;
+                self.untimed += 1;
+                Some(Duration::from_secs_f64(self.untimed as f64 / rate))
+            }
+        }
+    }
+}
+
+/// The embeddings of a chunk's messages, by message; None (after logging
+/// why) if they couldn't be made
+async fn embed_chunk(
+    embedder: &LazyEmbeddings,
+    messages: &[String],
+) -> Option<HashMap<String, Vec<f32>>> {
+    match embedder.embed(messages).await {
+        Ok(embeddings) => Some(messages.iter().cloned().zip(embeddings).collect()),
+        Err(e) => {
+            error!("Failed to embed replayed messages: {e}");
+            None
+        }
+    }
+}
+
+/// Read the file line by line into the pipeline, embedding messages as
+/// they come, until its end (or, following it, until `duration` is up).
+/// The first `generated` lines are skipped, having been replayed by the run
+/// a checkpoint was taken from. Replay ends early when the run is stopped.
+pub async fn run(
+    config: ReplayConfig,
+    tx: EntrySender,
+    duration: Duration,
+    embedder: Arc<LazyEmbeddings>,
+    extras: Arc<BTreeMap<String, Arc<LazyEmbeddings>>>,
+    generated: Arc<AtomicU64>,
+    control: Arc<Control>,
+) {
+    let file = match tokio::fs::File::open(&config.path).await {
+        Ok(file) => file,
+        Err(e) => {
+            error!("Failed to open replay file {}: {e}", config.path.display());
+            return;
+        }
+    };
+    let mut reader = BufReader::new(file);
+    let mut skip = generated.load(Ordering::Relaxed);
+    let mut clock = Clock {
+        started: Instant::now(),
+        origin: None,
+        untimed: 0,
+    };
+    let mut partial = String::new();
+    let mut at_end = false;
+    info!("Replaying {}", config.path.display());
+
+    let running = |clock: &Clock| {
+        !control.is_stopping() && (duration.is_zero() || clock.started.elapsed() < duration)
+    };
+    while !at_end && running(&clock) {
+        let mut chunk = Vec::with_capacity(CHUNK_LINES);
+        while chunk.len() < CHUNK_LINES {
+            match reader.read_line(&mut partial).await {
+                Ok(0) if config.follow => {
+                    if chunk.is_empty() {
+                        tokio::time::sleep(FOLLOW_POLL).await;
+                    }
+                    break;
+                }
+                // with follow, a line without its newline is still being written
+                Ok(n) if n > 0 && config.follow && !partial.ends_with('\n') => continue,
+                Ok(n) => {
+                    // a last line without a newline still counts
+                    at_end = n == 0;
+                    let line = std::mem::take(&mut partial);
+                    if skip > 0 && !line.is_empty() {
+                        skip -= 1;
+                    } else {
+                        chunk.extend(config.parse(&line));
+                    }
+                    if at_end {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to read {}: {e}", config.path.display());
+                    return;
+                }
+            }
+        }
+        if chunk.is_empty() {
+            continue;
+        }
+
+        // messages seen before, here or in generated entries, come from
+        // the cache
+        let mut messages: Vec<String> = chunk
+            .iter()
+            .map(|line| line.entry.message.clone())
+            .collect();
+        messages.sort();
+        messages.dedup();
+        let Some(embeddings) = embed_chunk(&embedder, &messages).await else {
+            return;
+        };
+        let mut extra_embeddings = BTreeMap::new();
+        for (name, extra) in extras.iter() {
+            let Some(embeddings) = embed_chunk(extra, &messages).await else {
+                return;
+            };
+            extra_embeddings.insert(name, embeddings);
+        }
+
+        for mut line in chunk {
+            // time spent paused pushes back when the rest are due
+            if control.is_paused() {
+                clock.started += control.resumed().await;
+            }
+            if let Some(due) = clock.due(&config, &line) {
+                tokio::time::sleep(due.saturating_sub(clock.started.elapsed())).await;
+            }
+            if !running(&clock) {
+                return;
+            }
+            line.entry.embedding = embeddings
+                .get(&line.entry.message)
+                .cloned()
+                .unwrap_or_default();
+            for (name, embeddings) in &extra_embeddings {
+                if let Some(embedding) = embeddings.get(&line.entry.message) {
+                    line.entry
+                        .extra_embeddings
+                        .insert((*name).clone(), embedding.clone());
+                }
+            }
+            line.entry.timestamp = match line.timestamp {
+                Some(original) if !config.rewrite_timestamps => original,
+                _ => Utc::now(),
+            };
+            metrics::entry_generated(&line.entry.service);
+            generated.fetch_add(1, Ordering::Relaxed);
+            if tx.send(line.entry).await.is_err() {
+                return;
+            }
+        }
+    }
+    info!("Finished replaying {}", config.path.display());
+}
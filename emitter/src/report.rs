@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::log_entry::LogEntry;
+
+/// How many of a sink's latest write latencies are kept, for the dashboard
+const RECENT_WRITES: usize = 60;
+
+/// Running totals of what one sink accepted. Counts are per write, so a
+/// partially failed batch counts as failed in full.
+pub struct SinkTally {
+    name: String,
+    accepted: AtomicU64,
+    failed: AtomicU64,
+    /// Order-independent digest of the accepted entry ids
+    checksum: AtomicU64,
+    writes: AtomicU64,
+    failed_writes: AtomicU64,
+    retries: AtomicU64,
+    /// The latest writes' latencies, oldest first, and the last write's
+    /// error if it failed
+    recent: Mutex<(VecDeque<Duration>, Option<String>)>,
+}
+
+impl SinkTally {
+    pub fn new(name: impl Into<String>) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.into(),
+            accepted: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            checksum: AtomicU64::new(0),
+            writes: AtomicU64::new(0),
+            failed_writes: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            recent: Mutex::new((VecDeque::with_capacity(RECENT_WRITES), None)),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn record(&self, batch: &[LogEntry], ok: bool) {
+        if !ok {
+            self.failed.fetch_add(batch.len() as u64, Ordering::Relaxed);
+            return;
+        }
+        self.accepted
+            .fetch_add(batch.len() as u64, Ordering::Relaxed);
+        let digest = batch
+            .iter()
+            .fold(0u64, |sum, entry| sum.wrapping_add(id_hash(&entry.id)));
+        // fetch_add wraps on overflow, keeping the sum order-independent
+        self.checksum.fetch_add(digest, Ordering::Relaxed);
+    }
+
+    /// Record a write taking `latency`, retries included, and its error if
+    /// it failed
+    pub fn record_write(&self, latency: Duration, error: Option<String>) {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        if error.is_some() {
+            self.failed_writes.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut recent = self.recent.lock().unwrap();
+        let (latencies, last_error) = &mut *recent;
+        if latencies.len() == RECENT_WRITES {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+        *last_error = error;
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How the sink's writes are going
+    pub fn health(&self) -> SinkHealth {
+        let recent = self.recent.lock().unwrap();
+        let (latencies, last_error) = &*recent;
+        SinkHealth {
+            name: self.name.clone(),
+            accepted: self.accepted.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            failed_writes: self.failed_writes.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            latency_ms: latencies
+                .iter()
+                .map(|latency| latency.as_secs_f64() * 1000.0)
+                .collect(),
+            last_error: last_error.clone(),
+        }
+    }
+
+    fn report(&self) -> SinkReport {
+        SinkReport {
+            name: self.name.clone(),
+            accepted: self.accepted.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            checksum: format!("{:016x}", self.checksum.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// 64-bit FNV-1a
+fn id_hash(id: &str) -> u64 {
+    id.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A sink's writes so far, as the dashboard shows them
+#[derive(Debug, Clone, Serialize)]
+pub struct SinkHealth {
+    pub name: String,
+    pub accepted: u64,
+    pub failed: u64,
+    pub writes: u64,
+    pub failed_writes: u64,
+    pub retries: u64,
+    /// The latest writes' latencies, oldest first
+    pub latency_ms: Vec<f64>,
+    /// Set while the last write failed
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SinkReport {
+    pub name: String,
+    pub accepted: u64,
+    pub failed: u64,
+    /// Equal checksums (and counts) mean two sinks received the same entries
+    pub checksum: String,
+}
+
+/// What a pipeline generated versus what each of its sinks ended up with
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub pipeline: String,
+    pub generated: u64,
+    /// Dropped by the overflow policy before reaching the buffer
+    pub dropped_overflow: u64,
+    /// Dropped by processors (filter, sample, dedup, ...)
+    pub dropped_processors: u64,
+    pub sinks: Vec<SinkReport>,
+}
+
+impl RunReport {
+    pub fn new(
+        pipeline: impl Into<String>,
+        generated: u64,
+        dropped_overflow: u64,
+        dropped_processors: u64,
+        tallies: &[Arc<SinkTally>],
+    ) -> Self {
+        Self {
+            pipeline: pipeline.into(),
+            generated,
+            dropped_overflow,
+            dropped_processors,
+            sinks: tallies.iter().map(|tally| tally.report()).collect(),
+        }
+    }
+
+    pub fn log(&self) {
+        info!(
+            "Run report ({}): {} generated, {} dropped on a full channel, {} dropped by processors",
+            self.pipeline, self.generated, self.dropped_overflow, self.dropped_processors
+        );
+        let expected = self
+            .generated
+            .saturating_sub(self.dropped_overflow + self.dropped_processors);
+        for sink in &self.sinks {
+            info!(
+                "  {}: {} accepted, {} failed (checksum {})",
+                sink.name, sink.accepted, sink.failed, sink.checksum
+            );
+            if sink.accepted != expected {
+                // expected with routing, otherwise entries went missing
+                warn!(
+                    "  {} accepted {} of {expected} entries past the processors",
+                    sink.name, sink.accepted
+                );
+            }
+        }
+    }
+}
+
+/// Write every pipeline's report to one JSON file
+pub fn write_json(reports: &[RunReport], path: &Path) -> std::io::Result<()> {
+    std::fs::write(path, serde_json::to_vec_pretty(reports)?)
+}
@@ -0,0 +1,320 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tracing::info;
+
+const ERROR_MESSAGES: &[&str] = &[
+    "PaymentProcessor: charge failed with upstream 503 (circuit=open)",
+    "ConnectionPool: exhausted all connections, request rejected",
+    "QueryExecutor: deadlock detected, transaction rolled back",
+    "AuthManager: token validation failed, signing key unavailable",
+    "CacheLayer: cluster unreachable, falling back to primary store",
+    "EventBus: publish failed after 5 retries, message dead-lettered",
+    "ReplicaManager: replica lag exceeded threshold, reads failing over",
+    "SessionStore: write rejected, quorum not reached",
+    "SchemaValidator: unexpected null in required field amount",
+    "CircuitBreaker: tripped after 50% failure rate on downstream calls",
+];
+
+const LATENCY_MESSAGES: &[&str] = &[
+    "QueryExecutor: slow query took 8200ms on orders table",
+    "LoadBalancer: upstream response time p99=4500ms exceeds SLO",
+    "ConnectionPool: waited 3100ms to acquire a connection",
+    "CacheLayer: cache miss storm, backend latency=2900ms",
+    "PartitionConsumer: consumer lag growing, lag=45000ms",
+    "DiskMonitor: write latency spiked to 900ms on /data volume",
+    "JobScheduler: task queue delay 12s, workers saturated",
+    "GarbageCollector: stop-the-world pause of 1800ms",
+    "TLSHandler: handshake took 2400ms from upstream host",
+    "HealthMonitor: readiness probe timed out after 5000ms",
+];
+
+/// Built-in vocabularies for scenario messages
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioTheme {
+    /// Failures: rejected requests, exhausted pools, open circuits
+    Errors,
+    /// Slowness: slow queries, timeouts, growing lag
+    Latency,
+}
+
+impl ScenarioTheme {
+    fn messages(self) -> &'static [&'static str] {
+        match self {
+            ScenarioTheme::Errors => ERROR_MESSAGES,
+            ScenarioTheme::Latency => LATENCY_MESSAGES,
+        }
+    }
+}
+
+/// An anomaly injected for part of a run, e.g. an error spike on one service
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioConfig {
+    pub name: String,
+    /// Seconds into the run the scenario begins
+    pub start_secs: u64,
+    pub duration_secs: u64,
+    /// Services affected; every service when empty
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Share of entries logged at ERROR while active (0.0 - 1.0)
+    pub error_rate: Option<f64>,
+    /// Multiplier on the affected services' entry rate
+    pub rate_multiplier: Option<f64>,
+    /// Multiplier on the generated `duration_ms` field
+    pub latency_multiplier: Option<f64>,
+    /// Draw messages from a built-in vocabulary instead of the pool
+    pub theme: Option<ScenarioTheme>,
+    /// Draw messages from these instead of the pool (combined with `theme`)
+    #[serde(default)]
+    pub messages: Vec<String>,
+}
+
+impl ScenarioConfig {
+    fn start(&self) -> Duration {
+        Duration::from_secs(self.start_secs)
+    }
+
+    fn end(&self) -> Duration {
+        self.start() + Duration::from_secs(self.duration_secs)
+    }
+
+    fn is_running(&self, elapsed: Duration) -> bool {
+        (self.start()..self.end()).contains(&elapsed)
+    }
+
+    fn is_active(&self, elapsed: Duration, service: &str) -> bool {
+        self.is_running(elapsed)
+            && (self.services.is_empty() || self.services.iter().any(|s| s == service))
+    }
+
+    /// Every message the scenario may log, which need embedding up front
+    pub fn vocabulary(&self) -> impl Iterator<Item = &str> {
+        self.theme
+            .map(ScenarioTheme::messages)
+            .unwrap_or_default()
+            .iter()
+            .copied()
+            .chain(self.messages.iter().map(String::as_str))
+    }
+}
+
+/// How the scenarios active for a service change what it generates
+#[derive(Debug, Clone)]
+pub struct Modifiers<'a> {
+    pub error_rate: Option<f64>,
+    pub rate_multiplier: f64,
+    pub latency_multiplier: f64,
+    /// Messages to draw from instead of the pool, if any
+    pub messages: Vec<&'a str>,
+}
+
+impl Default for Modifiers<'_> {
+    fn default() -> Self {
+        Self {
+            error_rate: None,
+            rate_multiplier: 1.0,
+            latency_multiplier: 1.0,
+            messages: Vec::new(),
+        }
+    }
+}
+
+/// A scenario of a pipeline as it stands, for the control API
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioStatus {
+    pub name: String,
+    /// Services affected; every service when empty
+    pub services: Vec<String>,
+    pub running: bool,
+    /// Seconds until it ends, while running
+    pub ends_in_secs: Option<u64>,
+}
+
+/// The scenarios planned for a pipeline
+#[derive(Debug)]
+pub struct Schedule(Vec<ScenarioConfig>);
+
+impl Schedule {
+    /// The combined effect of the scenarios active for `service` `elapsed`
+    /// into the run: the highest error rate, multipliers compounded,
+    /// vocabularies pooled
+    pub fn modifiers(&self, service: &str, elapsed: Duration) -> Modifiers<'_> {
+        let mut modifiers = Modifiers::default();
+        for scenario in self
+            .0
+            .iter()
+            .filter(|scenario| scenario.is_active(elapsed, service))
+        {
+            if let Some(rate) = scenario.error_rate {
+                modifiers.error_rate = Some(modifiers.error_rate.unwrap_or(0.0).max(rate));
+            }
+            modifiers.rate_multiplier *= scenario.rate_multiplier.unwrap_or(1.0);
+            modifiers.latency_multiplier *= scenario.latency_multiplier.unwrap_or(1.0);
+            modifiers.messages.extend(scenario.vocabulary());
+        }
+        modifiers
+    }
+
+    /// The names of the scenarios running `elapsed` into the run, for any
+    /// service
+    fn running(&self, elapsed: Duration) -> BTreeSet<String> {
+        self.0
+            .iter()
+            .filter(|scenario| scenario.is_running(elapsed))
+            .map(|scenario| scenario.name.clone())
+            .collect()
+    }
+
+    /// The next time after `elapsed` a scenario starts or ends
+    fn next_change(&self, elapsed: Duration) -> Option<Duration> {
+        self.0
+            .iter()
+            .flat_map(|scenario| [scenario.start(), scenario.end()])
+            .filter(|at| *at > elapsed)
+            .min()
+    }
+}
+
+/// A pipeline's scenario schedule, timed from the start of the run. The
+/// schedule can be replaced while the run goes on, e.g. when the config
+/// file is reloaded.
+#[derive(Debug)]
+pub struct Scenarios {
+    schedule: RwLock<Arc<Schedule>>,
+    /// Wakes `announce` to look at a replaced schedule
+    replaced: Notify,
+    start: Instant,
+    /// Run time already covered when resuming from a checkpoint
+    offset: Duration,
+}
+
+impl Scenarios {
+    pub fn new(configs: Vec<ScenarioConfig>, offset: Duration) -> Self {
+        Self {
+            schedule: RwLock::new(Arc::new(Schedule(configs))),
+            replaced: Notify::new(),
+            start: Instant::now(),
+            offset,
+        }
+    }
+
+    /// Time into the run, including any covered before resuming from a
+    /// checkpoint
+    pub fn elapsed(&self) -> Duration {
+        self.offset + self.start.elapsed()
+    }
+
+    /// The schedule as it stands
+    pub fn schedule(&self) -> Arc<Schedule> {
+        Arc::clone(&self.schedule.read().unwrap())
+    }
+
+    /// Swap in a new schedule, timed from the same start
+    pub fn replace(&self, configs: Vec<ScenarioConfig>) {
+        *self.schedule.write().unwrap() = Arc::new(Schedule(configs));
+        self.replaced.notify_one();
+    }
+
+    /// Start the scenario called `name` now, as configured but for
+    /// `duration` if given, alongside the schedule. Returns false if
+    /// there's no such scenario.
+    pub fn trigger(&self, name: &str, duration: Option<Duration>) -> bool {
+        let elapsed = self.elapsed();
+        let mut schedule = self.schedule.write().unwrap();
+        let Some(configured) = schedule.0.iter().find(|scenario| scenario.name == name) else {
+            return false;
+        };
+        let mut triggered = configured.clone();
+        // from the start of the current second, so it's running at once
+        triggered.start_secs = elapsed.as_secs();
+        if let Some(duration) = duration {
+            triggered.duration_secs = duration.as_secs().max(1);
+        }
+        let mut configs = schedule.0.clone();
+        configs.push(triggered);
+        *schedule = Arc::new(Schedule(configs));
+        self.replaced.notify_one();
+        true
+    }
+
+    /// End the scenario called `name` now, if it's running. Returns false
+    /// if it isn't.
+    pub fn end(&self, name: &str) -> bool {
+        let elapsed = self.elapsed();
+        let mut schedule = self.schedule.write().unwrap();
+        let mut configs = schedule.0.clone();
+        let mut ended = false;
+        for scenario in configs
+            .iter_mut()
+            .filter(|scenario| scenario.name == name && scenario.is_running(elapsed))
+        {
+            scenario.duration_secs = elapsed.as_secs().saturating_sub(scenario.start_secs);
+            ended = true;
+        }
+        if ended {
+            *schedule = Arc::new(Schedule(configs));
+            self.replaced.notify_one();
+        }
+        ended
+    }
+
+    /// Each scenario by name, and whether it's running
+    pub fn status(&self) -> Vec<ScenarioStatus> {
+        let elapsed = self.elapsed();
+        let schedule = self.schedule();
+        let mut status: Vec<ScenarioStatus> = Vec::new();
+        for scenario in &schedule.0 {
+            let ends_in = scenario
+                .is_running(elapsed)
+                .then(|| (scenario.end() - elapsed).as_secs());
+            match status.iter_mut().find(|known| known.name == scenario.name) {
+                Some(known) => {
+                    known.running |= ends_in.is_some();
+                    known.ends_in_secs = known.ends_in_secs.max(ends_in);
+                }
+                None => status.push(ScenarioStatus {
+                    name: scenario.name.clone(),
+                    services: scenario.services.clone(),
+                    running: ends_in.is_some(),
+                    ends_in_secs: ends_in,
+                }),
+            }
+        }
+        status
+    }
+
+    /// Log each scenario as it starts and ends, following the schedule as
+    /// it's replaced
+    pub async fn announce(&self, pipeline: &str) {
+        let mut running = BTreeSet::new();
+        loop {
+            let schedule = self.schedule();
+            let elapsed = self.elapsed();
+            let now = schedule.running(elapsed);
+            for name in now.difference(&running) {
+                info!("Scenario '{name}' started in pipeline '{pipeline}'");
+            }
+            for name in running.difference(&now) {
+                info!("Scenario '{name}' ended in pipeline '{pipeline}'");
+            }
+            running = now;
+
+            let next = schedule.next_change(elapsed);
+            tokio::select! {
+                _ = async {
+                    match next {
+                        Some(at) => tokio::time::sleep(at - elapsed).await,
+                        None => std::future::pending().await,
+                    }
+                } => {}
+                _ = self.replaced.notified() => {}
+            }
+        }
+    }
+}
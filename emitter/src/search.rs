@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::embedding::{self, EmbeddingService};
+use crate::log_entry::LogLevel;
+
+/// The `k` of reciprocal rank fusion, damping how much the top few ranks
+/// of either list count for
+const RRF_K: f64 = 60.0;
+
+fn default_limit() -> usize {
+    20
+}
+
+/// How a query is matched against what's been written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Full-text match on the message: BM25, or Postgres full-text search
+    Keyword,
+    /// Nearest neighbours of the query's embedding
+    Dense,
+    /// Both, their rankings fused
+    Hybrid,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchQuery {
+    pub text: String,
+    pub mode: SearchMode,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Only entries of these services, or of any when empty
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Only entries at these levels, or at any when empty
+    #[serde(default)]
+    pub levels: Vec<LogLevel>,
+}
+
+impl SearchQuery {
+    /// The levels to filter on, spelled as sinks store them
+    pub fn level_names(&self) -> Vec<String> {
+        self.levels
+            .iter()
+            .map(|level| format!("{level:?}"))
+            .collect()
+    }
+}
+
+/// An entry found, with its score from the backend (higher is better,
+/// though what it means differs by backend and mode)
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub score: f64,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub service: String,
+    pub level: String,
+    pub message: String,
+}
+
+pub type SearchError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A store a sink writes to, searched with what it was written
+#[async_trait]
+pub trait SearchBackend: Send + Sync {
+    /// The type of sink, e.g. `qdrant`
+    fn kind(&self) -> &'static str;
+
+    /// Dimensions of the embeddings stored, 0 with none
+    fn embedding_dim(&self) -> usize;
+
+    /// Whether the store embeds queries itself, with the model it embeds
+    /// entries with, rather than being sent the query's embedding
+    fn embeds_queries(&self) -> bool {
+        false
+    }
+
+    /// Up to `query.limit` entries, best first. `embedding` is the query's,
+    /// cut down to [`embedding_dim`](Self::embedding_dim), for dense and
+    /// hybrid queries of a backend that doesn't embed them itself.
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        embedding: Option<&[f32]>,
+    ) -> Result<Vec<SearchHit>, SearchError>;
+}
+
+/// A sink's backend, by the sink's name, with the model its entries are
+/// embedded with
+#[derive(Clone)]
+pub struct Searchable {
+    pub name: String,
+    pub backend: Arc<dyn SearchBackend>,
+    pub embedder: Arc<EmbeddingService>,
+}
+
+/// What one backend returned for a query
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub name: String,
+    pub kind: &'static str,
+    /// Time spent embedding the query, when it was embedded for this backend
+    pub embed_ms: Option<f64>,
+    pub search_ms: f64,
+    pub hits: Vec<SearchHit>,
+    pub error: Option<String>,
+}
+
+/// A model's embedding of a query, and how long it took
+struct QueryEmbedding<'a> {
+    embedder: &'a Arc<EmbeddingService>,
+    embedding: Result<Vec<f32>, String>,
+    millis: f64,
+}
+
+/// Run `query` against each of `backends` at once, embedding it no more
+/// than once per model
+pub async fn search_all(backends: &[Searchable], query: &SearchQuery) -> Vec<SearchResults> {
+    let mut embeddings: Vec<QueryEmbedding> = Vec::new();
+    if query.mode != SearchMode::Keyword {
+        for searchable in backends {
+            let embedder = &searchable.embedder;
+            if searchable.backend.embeds_queries()
+                || embeddings
+                    .iter()
+                    .any(|seen| Arc::ptr_eq(seen.embedder, embedder))
+            {
+                continue;
+            }
+            let started = Instant::now();
+            let embedding = searchable
+                .embedder
+                .embed_batch(std::slice::from_ref(&query.text))
+                .await
+                .map_err(|e| format!("can't embed the query: {e}"))
+                .and_then(|mut embedded| match embedded.pop() {
+                    Some(embedding) if !embedding.is_empty() => Ok(embedding),
+                    _ => Err("embedding is switched off, so there's nothing to compare".into()),
+                });
+            embeddings.push(QueryEmbedding {
+                embedder,
+                embedding,
+                millis: millis(started),
+            });
+        }
+    }
+
+    let searches = backends.iter().map(|searchable| {
+        let embedded = embeddings
+            .iter()
+            .find(|embedded| Arc::ptr_eq(embedded.embedder, &searchable.embedder))
+            .filter(|_| !searchable.backend.embeds_queries());
+        async move {
+            let backend = &searchable.backend;
+            let mut results = SearchResults {
+                name: searchable.name.clone(),
+                kind: backend.kind(),
+                embed_ms: embedded.map(|embedded| embedded.millis),
+                search_ms: 0.0,
+                hits: Vec::new(),
+                error: None,
+            };
+            let embedding = match embedded.map(|embedded| &embedded.embedding) {
+                Some(_) if backend.embedding_dim() == 0 => {
+                    results.error = Some("no embeddings are stored here".to_string());
+                    return results;
+                }
+                Some(Ok(embedding)) if embedding.len() > backend.embedding_dim() => {
+                    Some(embedding::truncate(embedding, backend.embedding_dim()))
+                }
+                Some(Ok(embedding)) => Some(embedding.clone()),
+                Some(Err(e)) => {
+                    results.error = Some(e.clone());
+                    return results;
+                }
+                None => None,
+            };
+            let started = Instant::now();
+            match backend.search(query, embedding.as_deref()).await {
+                Ok(hits) => results.hits = hits,
+                Err(e) => results.error = Some(e.to_string()),
+            }
+            results.search_ms = millis(started);
+            results
+        }
+    });
+    futures::future::join_all(searches).await
+}
+
+/// Fuse rankings, each best first, by reciprocal rank: an entry scores
+/// `1 / (k + rank)` for each ranking it's in
+pub fn fuse(rankings: Vec<Vec<SearchHit>>, limit: usize) -> Vec<SearchHit> {
+    let mut fused: Vec<SearchHit> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for ranking in rankings {
+        for (rank, mut hit) in ranking.into_iter().enumerate() {
+            let score = 1.0 / (RRF_K + rank as f64 + 1.0);
+            match positions.get(&hit.id) {
+                Some(&at) => fused[at].score += score,
+                None => {
+                    positions.insert(hit.id.clone(), fused.len());
+                    hit.score = score;
+                    fused.push(hit);
+                }
+            }
+        }
+    }
+    fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+    fused.truncate(limit);
+    fused
+}
+
+/// Merge rankings from several targets (tables, collections) by score
+pub fn merge(rankings: Vec<Vec<SearchHit>>, limit: usize) -> Vec<SearchHit> {
+    let mut merged: Vec<SearchHit> = rankings.into_iter().flatten().collect();
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged.truncate(limit);
+    merged
+}
+
+fn millis(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
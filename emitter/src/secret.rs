@@ -0,0 +1,97 @@
+use std::process::Command;
+
+use serde_yaml::Value;
+
+/// Keys of config values holding credentials, wherever they are (the
+/// embedding provider, any sink)
+pub const SECRET_KEYS: &[&str] = &["api_key", "auth_token", "bearer_token", "password", "token"];
+
+/// Replace each credential in `config` given as `file:<path>` with the
+/// file's contents, and each given as `cmd:<command>` with what the command
+/// prints (run by the shell, e.g. `cmd:op read op://demo/elastic/password`),
+/// without their trailing newline. Other values are left as they are.
+pub fn resolve(config: &mut Value) -> Result<(), String> {
+    resolve_at(config, "")
+}
+
+fn resolve_at(value: &mut Value, path: &str) -> Result<(), String> {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                let key_path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match value {
+                    Value::String(secret) if SECRET_KEYS.contains(&key) => {
+                        if let Some(resolved) =
+                            read(secret).map_err(|e| format!("{key_path}: {e}"))?
+                        {
+                            *secret = resolved;
+                        }
+                    }
+                    _ => resolve_at(value, &key_path)?,
+                }
+            }
+        }
+        Value::Sequence(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                resolve_at(item, &format!("{path}[{i}]"))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The secret `spec` refers to, if it refers to one rather than being it
+fn read(spec: &str) -> Result<Option<String>, String> {
+    if let Some(path) = spec.strip_prefix("file:") {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("can't read {path}: {e}"))?;
+        return Ok(Some(trim_newline(contents)));
+    }
+    if let Some(command) = spec.strip_prefix("cmd:") {
+        let output = shell(command)
+            .output()
+            .map_err(|e| format!("can't run `{command}`: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(match stderr.trim() {
+                "" => format!("`{command}` failed ({})", output.status),
+                stderr => format!("`{command}` failed ({}): {stderr}", output.status),
+            });
+        }
+        let stdout = String::from_utf8(output.stdout)
+            .map_err(|_| format!("`{command}` printed something other than UTF-8"))?;
+        return Ok(Some(trim_newline(stdout)));
+    }
+    Ok(None)
+}
+
+fn shell(command: &str) -> Command {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+fn trim_newline(mut secret: String) -> String {
+    let kept = secret.trim_end_matches(['\r', '\n']).len();
+    secret.truncate(kept);
+    secret
+}
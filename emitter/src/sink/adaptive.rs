@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+fn default_min_batch() -> usize {
+    100
+}
+
+fn default_max_batch() -> usize {
+    5_000
+}
+
+fn default_target_latency_ms() -> u64 {
+    500
+}
+
+fn default_increase() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdaptiveBatchConfig {
+    #[serde(default = "default_min_batch")]
+    pub min_batch: usize,
+    /// Batches can't grow past what the buffer flushes, so keep
+    /// `buffer_size` at least this large
+    #[serde(default = "default_max_batch")]
+    pub max_batch: usize,
+    /// Writes faster than this grow the batch, slower ones shrink it
+    #[serde(default = "default_target_latency_ms")]
+    pub target_latency_ms: u64,
+    /// Entries added to the batch size after each fast, successful write
+    #[serde(default = "default_increase")]
+    pub increase: usize,
+}
+
+/// Wraps another sink, splitting each flush into batches sized AIMD-style:
+/// the size grows additively while writes succeed within the target latency
+/// and halves when one is slow or fails
+pub struct AdaptiveBatchSink {
+    inner: Box<dyn Sink>,
+    config: AdaptiveBatchConfig,
+    target_latency: Duration,
+    batch_size: Mutex<usize>,
+}
+
+impl AdaptiveBatchSink {
+    pub fn new(inner: Box<dyn Sink>, config: AdaptiveBatchConfig) -> Self {
+        let min_batch = config.min_batch.max(1);
+        Self {
+            inner,
+            target_latency: Duration::from_millis(config.target_latency_ms),
+            batch_size: Mutex::new(min_batch),
+            config: AdaptiveBatchConfig {
+                min_batch,
+                max_batch: config.max_batch.max(min_batch),
+                ..config
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for AdaptiveBatchSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut batch_size = self.batch_size.lock().await;
+        let mut remaining = batch;
+        while !remaining.is_empty() {
+            let (chunk, rest) = remaining.split_at(remaining.len().min(*batch_size));
+            let start = Instant::now();
+            let result = self.inner.write(chunk).await;
+            let elapsed = start.elapsed();
+
+            *batch_size = if result.is_ok() && elapsed <= self.target_latency {
+                (*batch_size + self.config.increase).min(self.config.max_batch)
+            } else {
+                (*batch_size / 2).max(self.config.min_batch)
+            };
+            debug!(
+                "Wrote {} entries in {elapsed:?}, batch size now {}",
+                chunk.len(),
+                *batch_size
+            );
+
+            result?;
+            remaining = rest;
+        }
+        Ok(())
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
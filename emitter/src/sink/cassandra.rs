@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use schemars::JsonSchema;
+use scylla::client::session::Session;
+use scylla::client::session_builder::SessionBuilder;
+use scylla::statement::prepared::PreparedStatement;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+use crate::sink::{DEFAULT_INDEX_NAME, DENSE_EMBEDDING_NAME};
+
+fn default_keyspace() -> String {
+    "logstorm".to_string()
+}
+
+fn default_table_name() -> String {
+    DEFAULT_INDEX_NAME.to_string()
+}
+
+fn default_replication_factor() -> u32 {
+    1
+}
+
+fn default_concurrency() -> usize {
+    64
+}
+
+/// Width of the time bucket in the partition key
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeBucket {
+    Hour,
+    #[default]
+    Day,
+}
+
+impl TimeBucket {
+    fn key(&self, ts: &DateTime<Utc>) -> String {
+        match self {
+            TimeBucket::Hour => ts.format("%Y-%m-%dT%H").to_string(),
+            TimeBucket::Day => ts.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CassandraConfig {
+    /// Contact points as `host:port`
+    pub nodes: Vec<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    #[serde(default = "default_keyspace")]
+    pub keyspace: String,
+    #[serde(default = "default_table_name")]
+    pub table_name: String,
+    /// Replication factor used if the keyspace has to be created (SimpleStrategy)
+    #[serde(default = "default_replication_factor")]
+    pub replication_factor: u32,
+    #[serde(default)]
+    pub bucket: TimeBucket,
+    /// Maximum in-flight inserts per flush
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+/// Writes entries into a table partitioned by `(service, bucket)` and clustered
+/// by time, the classic wide-row layout for logs in Cassandra/ScyllaDB.
+pub struct CassandraSink {
+    config: CassandraConfig,
+    session: Session,
+    /// Prepared once the table exists
+    insert: OnceCell<PreparedStatement>,
+}
+
+impl CassandraSink {
+    pub async fn from_config(
+        config: CassandraConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut builder = SessionBuilder::new().known_nodes(&config.nodes);
+        if let (Some(user), Some(password)) = (&config.user, &config.password) {
+            builder = builder.user(user, password);
+        }
+        let session = builder.build().await?;
+
+        // the keyspace and table are left to `init`, so a dry run only checks
+        // the cluster can be reached
+        Ok(Self {
+            config,
+            session,
+            insert: OnceCell::new(),
+        })
+    }
+
+    async fn prepare_insert(
+        &self,
+    ) -> Result<PreparedStatement, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .session
+            .prepare(format!(
+                r#"INSERT INTO {}.{} (service, bucket, timestamp, id, level, message, fields, trace_id, span_id, parent_span_id, {})
+                   VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+                self.config.keyspace, self.config.table_name, DENSE_EMBEDDING_NAME,
+            ))
+            .await?)
+    }
+}
+
+#[async_trait]
+impl Sink for CassandraSink {
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let create_keyspace = format!(
+            r#"CREATE KEYSPACE IF NOT EXISTS {}
+               WITH replication = {{'class': 'SimpleStrategy', 'replication_factor': {}}}"#,
+            self.config.keyspace, self.config.replication_factor,
+        );
+        self.session.query_unpaged(create_keyspace, ()).await?;
+
+        // bucketed partitions keep any one partition from growing without bound
+        let create_table = format!(
+            r#"CREATE TABLE IF NOT EXISTS {}.{} (
+                service text,
+                bucket text,
+                timestamp timestamp,
+                id text,
+                level text,
+                message text,
+                fields map<text, text>,
+                trace_id text,
+                span_id text,
+                parent_span_id text,
+                {} list<float>,
+                PRIMARY KEY ((service, bucket), timestamp, id)
+            ) WITH CLUSTERING ORDER BY (timestamp DESC, id ASC)"#,
+            self.config.keyspace, self.config.table_name, DENSE_EMBEDDING_NAME,
+        );
+        self.session.query_unpaged(create_table, ()).await?;
+
+        self.insert
+            .get_or_try_init(|| self.prepare_insert())
+            .await?;
+        Ok(())
+    }
+
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // a multi-partition BATCH would funnel everything through one coordinator
+        // (and embeddings quickly exceed batch size limits), so run the prepared
+        // insert concurrently instead
+        let insert = self
+            .insert
+            .get_or_try_init(|| self.prepare_insert())
+            .await?;
+        let inserts: Vec<_> = batch
+            .iter()
+            .map(|entry| {
+                let trace = entry.trace.as_ref();
+                self.session.execute_unpaged(
+                    insert,
+                    (
+                        entry.service.as_str(),
+                        self.config.bucket.key(&entry.timestamp),
+                        entry.timestamp,
+                        entry.id.as_str(),
+                        format!("{:?}", entry.level),
+                        entry.message.as_str(),
+                        entry.fields_as_text(),
+                        trace.map(|t| t.trace_id.as_str()),
+                        trace.map(|t| t.span_id.as_str()),
+                        trace.and_then(|t| t.parent_span_id.as_deref()),
+                        &entry.embedding,
+                    ),
+                )
+            })
+            .collect();
+
+        stream::iter(inserts)
+            .buffer_unordered(self.config.concurrency.max(1))
+            .map_ok(|_| ())
+            .try_collect::<()>()
+            .await?;
+
+        Ok(())
+    }
+}
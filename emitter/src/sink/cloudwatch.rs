@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_cloudwatchlogs::Client;
+use aws_sdk_cloudwatchlogs::operation::put_log_events::PutLogEventsError;
+use aws_sdk_cloudwatchlogs::types::InputLogEvent;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+/// PutLogEvents limits: 10,000 events and 1,048,576 bytes per call, where
+/// each event counts as its UTF-8 message length plus 26 bytes of overhead
+const MAX_EVENTS_PER_CALL: usize = 10_000;
+const MAX_BYTES_PER_CALL: usize = 1_048_576;
+const EVENT_OVERHEAD_BYTES: usize = 26;
+/// Longest stretch of time the events of one call may span
+const MAX_SPAN_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+fn default_log_group() -> String {
+    "/logstorm/{service}".to_string()
+}
+
+fn default_log_stream() -> String {
+    "logstorm".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CloudWatchConfig {
+    /// Log group name; `{service}` is replaced with the entry's service name
+    #[serde(default = "default_log_group")]
+    pub log_group: String,
+    /// Log stream name; `{service}` is replaced with the entry's service name
+    #[serde(default = "default_log_stream")]
+    pub log_stream: String,
+    /// AWS region, falling back to the usual environment/profile resolution
+    pub region: Option<String>,
+    /// Custom endpoint, e.g. a LocalStack URL
+    pub endpoint_url: Option<String>,
+}
+
+/// Writes entries as JSON messages with `PutLogEvents`, creating each
+/// group/stream on first sighting and tracking per-stream sequence tokens.
+pub struct CloudWatchSink {
+    config: CloudWatchConfig,
+    client: Client,
+    /// Streams known to exist, with the sequence token for their next put
+    streams: Mutex<HashMap<(String, String), Option<String>>>,
+}
+
+impl CloudWatchSink {
+    pub async fn from_config(
+        config: CloudWatchConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut loader = aws_config::defaults(BehaviorVersion::latest());
+        if let Some(region) = &config.region {
+            loader = loader.region(Region::new(region.clone()));
+        }
+        let sdk_config = loader.load().await;
+        if sdk_config.region().is_none() {
+            return Err("no AWS region configured; set `region` or AWS_REGION".into());
+        }
+
+        let mut builder = aws_sdk_cloudwatchlogs::config::Builder::from(&sdk_config);
+        if let Some(endpoint_url) = &config.endpoint_url {
+            builder = builder.endpoint_url(endpoint_url);
+        }
+        let client = Client::from_conf(builder.build());
+
+        Ok(Self {
+            config,
+            client,
+            streams: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Create the log group and stream, tolerating either already existing.
+    async fn ensure_stream(
+        &self,
+        group: &str,
+        stream: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Err(e) = self
+            .client
+            .create_log_group()
+            .log_group_name(group)
+            .send()
+            .await
+            && !e
+                .as_service_error()
+                .is_some_and(|se| se.is_resource_already_exists_exception())
+        {
+            return Err(e.into());
+        }
+
+        if let Err(e) = self
+            .client
+            .create_log_stream()
+            .log_group_name(group)
+            .log_stream_name(stream)
+            .send()
+            .await
+            && !e
+                .as_service_error()
+                .is_some_and(|se| se.is_resource_already_exists_exception())
+        {
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Split time-ordered events into calls that respect the PutLogEvents limits.
+    fn split_calls(events: Vec<InputLogEvent>) -> Vec<Vec<InputLogEvent>> {
+        let mut calls = Vec::new();
+        let mut current: Vec<InputLogEvent> = Vec::new();
+        let mut current_bytes = 0;
+
+        for event in events {
+            let size = event.message().len() + EVENT_OVERHEAD_BYTES;
+            let span = current
+                .first()
+                .map_or(0, |first| event.timestamp() - first.timestamp());
+            if !current.is_empty()
+                && (current.len() == MAX_EVENTS_PER_CALL
+                    || current_bytes + size > MAX_BYTES_PER_CALL
+                    || span >= MAX_SPAN_MILLIS)
+            {
+                calls.push(std::mem::take(&mut current));
+                current_bytes = 0;
+            }
+            current_bytes += size;
+            current.push(event);
+        }
+        if !current.is_empty() {
+            calls.push(current);
+        }
+        calls
+    }
+
+    async fn put(
+        &self,
+        group: &str,
+        stream: &str,
+        events: Vec<InputLogEvent>,
+        sequence_token: &mut Option<String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // retry once with the expected token if another writer advanced the stream
+        for _ in 0..2 {
+            let result = self
+                .client
+                .put_log_events()
+                .log_group_name(group)
+                .log_stream_name(stream)
+                .set_log_events(Some(events.clone()))
+                .set_sequence_token(sequence_token.clone())
+                .send()
+                .await;
+
+            match result {
+                Ok(output) => {
+                    if let Some(rejected) = output.rejected_log_events_info() {
+                        return Err(format!(
+                            "CloudWatch rejected events in {group}/{stream}: {rejected:?}"
+                        )
+                        .into());
+                    }
+                    *sequence_token = output.next_sequence_token().map(str::to_string);
+                    return Ok(());
+                }
+                Err(e) => match e.as_service_error() {
+                    Some(PutLogEventsError::InvalidSequenceTokenException(invalid)) => {
+                        *sequence_token = invalid.expected_sequence_token().map(str::to_string);
+                    }
+                    Some(PutLogEventsError::DataAlreadyAcceptedException(accepted)) => {
+                        *sequence_token = accepted.expected_sequence_token().map(str::to_string);
+                        return Ok(());
+                    }
+                    _ => return Err(e.into()),
+                },
+            }
+        }
+        Err(format!("CloudWatch sequence token for {group}/{stream} kept changing").into())
+    }
+}
+
+#[async_trait]
+impl Sink for CloudWatchSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut by_stream: BTreeMap<(String, String), Vec<&LogEntry>> = BTreeMap::new();
+        for entry in batch {
+            let group = self.config.log_group.replace("{service}", &entry.service);
+            let stream = self.config.log_stream.replace("{service}", &entry.service);
+            by_stream.entry((group, stream)).or_default().push(entry);
+        }
+
+        let mut streams = self.streams.lock().await;
+        for ((group, stream), mut entries) in by_stream {
+            let key = (group.clone(), stream.clone());
+            if !streams.contains_key(&key) {
+                self.ensure_stream(&group, &stream).await?;
+                streams.insert(key.clone(), None);
+            }
+
+            // events within a call must be in chronological order
+            entries.sort_by_key(|entry| entry.timestamp);
+            let events = entries
+                .iter()
+                .map(|entry| {
+                    InputLogEvent::builder()
+                        .timestamp(entry.timestamp.timestamp_millis())
+                        .message(
+                            json!({
+                                "id": entry.id,
+                                "service": entry.service,
+                                "level": entry.level.to_string(),
+                                "message": entry.message,
+                                "fields": entry.fields,
+                                "trace": entry.trace,
+                            })
+                            .to_string(),
+                        )
+                        .build()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let sequence_token = streams.get_mut(&key).expect("stream was just registered");
+            for call in Self::split_calls(events) {
+                self.put(&group, &stream, call, sequence_token).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::Write as _;
+
+use flate2::write::GzEncoder;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Request body compression for the HTTP-based sinks
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    /// Only for receivers that accept `Content-Encoding: zstd`
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// The `Content-Encoding` header value, if the body is compressed
+    pub fn content_encoding(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gzip"),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Some("zstd"),
+        }
+    }
+
+    pub fn encode(self, body: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(body),
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&body)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::encode_all(body.as_slice(), 0),
+        }
+    }
+
+    /// Compress `body` and set the matching `Content-Encoding` header
+    pub fn apply(
+        self,
+        request: reqwest::RequestBuilder,
+        body: Vec<u8>,
+    ) -> std::io::Result<reqwest::RequestBuilder> {
+        let body = self.encode(body)?;
+        Ok(match self.content_encoding() {
+            Some(encoding) => request.header("Content-Encoding", encoding).body(body),
+            None => request.body(body),
+        })
+    }
+}
@@ -0,0 +1,918 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::{
+    Json, Router,
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{FromRef, Query},
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::info;
+
+use crate::control::{self, Control};
+use crate::log_entry::LogEntry;
+use crate::report::{SinkHealth, SinkTally};
+use crate::search::Searchable;
+use crate::sink::Sink;
+
+pub mod runs;
+mod search;
+
+use runs::{RUNS_HTML, RunDetail, RunStore, RunSummary};
+use search::{backends_handler, search_handler, search_page_handler};
+
+/// Flushes kept for the table, for a browser opening the dashboard
+const RECENT_FLUSHES: usize = 50;
+
+fn default_tail_per_sec() -> f64 {
+    20.0
+}
+
+fn default_history_secs() -> usize {
+    300
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DashboardConfig {
+    pub port: u16,
+    /// Most entries a second sent to each live tail, sampled from those
+    /// matching its filter
+    #[serde(default = "default_tail_per_sec")]
+    pub tail_per_sec: f64,
+    /// Seconds of entry counts kept for the charts, so a browser opening
+    /// the dashboard sees the recent past
+    #[serde(default = "default_history_secs")]
+    pub history_secs: usize,
+    /// Keep every run's flushes and entries per second in this SQLite file,
+    /// to browse and compare past runs at `/runs`
+    pub database: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlushEvent {
+    pub timestamp: DateTime<Utc>,
+    pub total_count: usize,
+    pub by_service: HashMap<String, usize>,
+    pub by_level: HashMap<String, usize>,
+}
+
+/// What the dashboard is sent: each batch, and now and then how the
+/// pipeline's other sinks are doing
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DashboardEvent {
+    Flush(FlushEvent),
+    Sinks {
+        sinks: Vec<SinkHealth>,
+    },
+    /// The seconds a batch added entries to, with their counts so far
+    History {
+        seconds: Vec<Second>,
+    },
+}
+
+/// Entries timestamped within one second, overall and by service and level
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Second {
+    /// Unix time
+    pub timestamp: i64,
+    pub total: usize,
+    pub by_service: BTreeMap<String, usize>,
+    pub by_level: BTreeMap<String, usize>,
+}
+
+/// What the run has written so far, for a browser opening the dashboard:
+/// totals, the latest flushes, and entry counts for the latest seconds of
+/// entry timestamps, dropping the oldest past `capacity`
+pub struct History {
+    capacity: usize,
+    total_flushes: usize,
+    total_entries: usize,
+    flushes: VecDeque<FlushEvent>,
+    seconds: BTreeMap<i64, Second>,
+}
+
+/// [`History`] as served on `/history`
+#[derive(Debug, Serialize)]
+pub struct HistorySnapshot {
+    pub total_flushes: usize,
+    pub total_entries: usize,
+    /// The latest flushes, oldest first
+    pub flushes: Vec<FlushEvent>,
+    pub seconds: Vec<Second>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            total_flushes: 0,
+            total_entries: 0,
+            flushes: VecDeque::with_capacity(RECENT_FLUSHES),
+            seconds: BTreeMap::new(),
+        }
+    }
+
+    pub fn record_flush(&mut self, flush: &FlushEvent) {
+        self.total_flushes += 1;
+        self.total_entries += flush.total_count;
+        if self.flushes.len() == RECENT_FLUSHES {
+            self.flushes.pop_front();
+        }
+        self.flushes.push_back(flush.clone());
+    }
+
+    /// Count `batch` in, returning the seconds it added to
+    pub fn record(&mut self, batch: &[LogEntry]) -> Vec<Second> {
+        let mut touched = Vec::new();
+        for entry in batch {
+            let timestamp = entry.timestamp.timestamp();
+            let second = self.seconds.entry(timestamp).or_insert_with(|| Second {
+                timestamp,
+                ..Second::default()
+            });
+            second.total += 1;
+            *second.by_service.entry(entry.service.clone()).or_default() += 1;
+            *second.by_level.entry(entry.level.to_string()).or_default() += 1;
+            if touched.last() != Some(&timestamp) {
+                touched.push(timestamp);
+            }
+        }
+        while self.seconds.len() > self.capacity {
+            self.seconds.pop_first();
+        }
+        touched.sort_unstable();
+        touched.dedup();
+        touched
+            .iter()
+            .filter_map(|timestamp| self.seconds.get(timestamp).cloned())
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> HistorySnapshot {
+        HistorySnapshot {
+            total_flushes: self.total_flushes,
+            total_entries: self.total_entries,
+            flushes: self.flushes.iter().cloned().collect(),
+            seconds: self.seconds.values().cloned().collect(),
+        }
+    }
+}
+
+/// What the dashboard's sink shares with its server: the aggregate events,
+/// every batch (without embeddings) for live tails to sample from, the
+/// history of the run so far, past runs if they're kept, and the stores
+/// the pipeline writes, to search
+#[derive(Clone)]
+pub struct Streams {
+    pub events: broadcast::Sender<DashboardEvent>,
+    pub batches: broadcast::Sender<Arc<Vec<LogEntry>>>,
+    pub history: Arc<Mutex<History>>,
+    pub runs: Option<Arc<RunStore>>,
+    /// Set once every sink of the pipeline is built
+    pub searchables: Arc<OnceLock<Vec<Searchable>>>,
+}
+
+impl Streams {
+    pub fn new(history_secs: usize, runs: Option<RunStore>) -> Self {
+        Self {
+            events: broadcast::channel(100).0,
+            batches: broadcast::channel(16).0,
+            history: Arc::new(Mutex::new(History::new(history_secs))),
+            runs: runs.map(Arc::new),
+            searchables: Arc::new(OnceLock::new()),
+        }
+    }
+}
+
+pub struct DashboardSink {
+    streams: Streams,
+}
+
+impl DashboardSink {
+    pub fn new(streams: Streams) -> Self {
+        Self { streams }
+    }
+}
+
+#[async_trait]
+impl Sink for DashboardSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut by_service: HashMap<String, usize> = HashMap::new();
+        let mut by_level: HashMap<String, usize> = HashMap::new();
+
+        for entry in batch {
+            *by_service.entry(entry.service.clone()).or_default() += 1;
+            *by_level.entry(format!("{}", entry.level)).or_default() += 1;
+        }
+
+        let flush = FlushEvent {
+            timestamp: Utc::now(),
+            total_count: batch.len(),
+            by_service,
+            by_level,
+        };
+        let seconds = {
+            let mut history = self.streams.history.lock().unwrap();
+            history.record_flush(&flush);
+            history.record(batch)
+        };
+        if let Some(runs) = &self.streams.runs {
+            runs.record(&flush, &seconds).await?;
+        }
+
+        // Ignore send errors — just means no clients are connected
+        let _ = self.streams.events.send(DashboardEvent::Flush(flush));
+        let _ = self
+            .streams
+            .events
+            .send(DashboardEvent::History { seconds });
+        if self.streams.batches.receiver_count() > 0 {
+            let entries = batch
+                .iter()
+                .map(|entry| {
+                    let mut entry = entry.clone();
+                    entry.embedding = Vec::new();
+                    entry.sparse = None;
+                    entry.extra_embeddings.clear();
+                    entry
+                })
+                .collect();
+            let _ = self.streams.batches.send(Arc::new(entries));
+        }
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(runs) = &self.streams.runs {
+            runs.finish().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Send the health of the sinks `tallies` count for to the dashboard every
+/// second: their write latencies, failures and retries
+pub async fn watch_sinks(tx: broadcast::Sender<DashboardEvent>, tallies: Vec<Arc<SinkTally>>) {
+    let mut ticks = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        ticks.tick().await;
+        if tx.receiver_count() == 0 {
+            continue;
+        }
+        let sinks = tallies.iter().map(|tally| tally.health()).collect();
+        let _ = tx.send(DashboardEvent::Sinks { sinks });
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    streams: Streams,
+    tail_per_sec: f64,
+}
+
+impl FromRef<AppState> for Streams {
+    fn from_ref(state: &AppState) -> Self {
+        state.streams.clone()
+    }
+}
+
+pub async fn start_dashboard_server(
+    config: DashboardConfig,
+    streams: Streams,
+    control: Arc<Control>,
+) {
+    let app = Router::new()
+        .route("/", get(index_handler))
+        .route("/ws", get(ws_handler))
+        .route("/ws/tail", get(tail_handler))
+        .route("/history", get(history_handler))
+        .route("/runs", get(runs_page_handler))
+        .route("/api/runs", get(runs_handler))
+        .route("/api/runs/{id}", get(run_handler))
+        .route("/search", get(search_page_handler))
+        .route("/api/search", get(backends_handler).post(search_handler))
+        .with_state(AppState {
+            streams,
+            tail_per_sec: config.tail_per_sec,
+        })
+        .nest("/api/control", control::router(control));
+
+    let addr = format!("0.0.0.0:{}", config.port);
+    info!("Dashboard server listening on http://{addr}");
+
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .expect("Failed to bind dashboard server");
+
+    axum::serve(listener, app)
+        .await
+        .expect("Dashboard server error");
+}
+
+async fn index_handler() -> impl IntoResponse {
+    Html(DASHBOARD_HTML)
+}
+
+async fn history_handler(State(streams): State<Streams>) -> Json<HistorySnapshot> {
+    Json(streams.history.lock().unwrap().snapshot())
+}
+
+async fn runs_page_handler() -> impl IntoResponse {
+    Html(RUNS_HTML)
+}
+
+/// Past runs aren't kept without a database
+fn no_runs() -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        "Past runs aren't kept; set the dashboard's database to keep them".to_string(),
+    )
+}
+
+async fn runs_handler(
+    State(streams): State<Streams>,
+) -> Result<Json<Vec<RunSummary>>, (StatusCode, String)> {
+    let runs = streams.runs.ok_or_else(no_runs)?;
+    runs.runs()
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn run_handler(
+    State(streams): State<Streams>,
+    Path(id): Path<i64>,
+) -> Result<Json<RunDetail>, (StatusCode, String)> {
+    let runs = streams.runs.ok_or_else(no_runs)?;
+    match runs.run_detail(id).await {
+        Ok(Some(run)) => Ok(Json(run)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("No run {id}"))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn ws_handler(ws: WebSocketUpgrade, State(streams): State<Streams>) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_ws(socket, streams.events))
+}
+
+async fn handle_ws(socket: WebSocket, tx: broadcast::Sender<DashboardEvent>) {
+    let mut rx = tx.subscribe();
+    let (mut sender, mut _receiver) = socket.split();
+
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            // a slow client skips what it missed
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let json = match serde_json::to_string(&event) {
+            Ok(j) => j,
+            Err(_) => continue,
+        };
+        if sender.send(Message::Text(json.into())).await.is_err() {
+            break; // client disconnected
+        }
+    }
+}
+
+/// Which entries a live tail shows, from its query string, e.g.
+/// `/ws/tail?service=api,auth&level=WARN,ERROR&q=timeout`
+#[derive(Debug, Default, Deserialize)]
+struct TailFilter {
+    /// Comma-separated service names
+    service: Option<String>,
+    /// Comma-separated levels
+    level: Option<String>,
+    /// Text the message contains, regardless of case
+    q: Option<String>,
+}
+
+impl TailFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        let listed = |list: &Option<String>, value: &str| {
+            list.as_deref().is_none_or(|list| {
+                list.split(',')
+                    .any(|item| item.trim().eq_ignore_ascii_case(value))
+            })
+        };
+        listed(&self.service, &entry.service)
+            && listed(&self.level, &entry.level.to_string())
+            && self
+                .q
+                .as_deref()
+                .is_none_or(|q| entry.message.to_lowercase().contains(&q.to_lowercase()))
+    }
+}
+
+/// A batch of a live tail: the entries sampled, out of how many matched
+#[derive(Serialize)]
+struct TailBatch<'a> {
+    matched: usize,
+    entries: Vec<&'a LogEntry>,
+}
+
+async fn tail_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(filter): Query<TailFilter>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_tail(socket, state, filter))
+}
+
+/// Send the entries of each batch matching `filter`, sampled evenly down to
+/// the tail's share of `tail_per_sec`
+async fn handle_tail(socket: WebSocket, state: AppState, filter: TailFilter) {
+    let mut rx = state.streams.batches.subscribe();
+    let (mut sender, mut _receiver) = socket.split();
+    let rate = state.tail_per_sec.max(0.0);
+    // entries this tail may still send, refilled at `rate` up to a second's
+    let mut allowance = rate;
+    let mut last = Instant::now();
+
+    loop {
+        let batch = match rx.recv().await {
+            Ok(batch) => batch,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        allowance = (allowance + last.elapsed().as_secs_f64() * rate).min(rate.max(1.0));
+        last = Instant::now();
+
+        let matching: Vec<&LogEntry> = batch.iter().filter(|entry| filter.matches(entry)).collect();
+        let take = (allowance.floor() as usize).min(matching.len());
+        if matching.is_empty() || take == 0 {
+            continue;
+        }
+        allowance -= take as f64;
+        let step = matching.len() as f64 / take as f64;
+        let entries = (0..take)
+            .map(|i| matching[(i as f64 * step) as usize])
+            .collect();
+        let tail = TailBatch {
+            matched: matching.len(),
+            entries,
+        };
+        let json = match serde_json::to_string(&tail) {
+            Ok(j) => j,
+            Err(_) => continue,
+        };
+        if sender.send(Message::Text(json.into())).await.is_err() {
+            break; // client disconnected
+        }
+    }
+}
+
+const DASHBOARD_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>Emitter Dashboard</title>
+<style>
+  * { margin: 0; padding: 0; box-sizing: border-box; }
+  body { font-family: system-ui, -apple-system, sans-serif; background: #0f172a; color: #e2e8f0; padding: 24px; }
+  h1 { font-size: 1.5rem; margin-bottom: 16px; color: #38bdf8; }
+  .status { margin-bottom: 16px; font-size: 0.875rem; color: #94a3b8; }
+  .status .dot { display: inline-block; width: 8px; height: 8px; border-radius: 50%; margin-right: 6px; }
+  .dot.connected { background: #22c55e; }
+  .dot.disconnected { background: #ef4444; }
+  .summary { display: flex; gap: 16px; margin-bottom: 24px; flex-wrap: wrap; }
+  .card { background: #1e293b; border-radius: 8px; padding: 16px 20px; min-width: 140px; }
+  .card .label { font-size: 0.75rem; color: #94a3b8; text-transform: uppercase; letter-spacing: 0.05em; }
+  .card .value { font-size: 1.75rem; font-weight: 700; margin-top: 4px; color: #f1f5f9; }
+  table { width: 100%; border-collapse: collapse; background: #1e293b; border-radius: 8px; overflow: hidden; }
+  th { text-align: left; padding: 10px 14px; background: #334155; font-size: 0.75rem; text-transform: uppercase; letter-spacing: 0.05em; color: #94a3b8; }
+  td { padding: 10px 14px; border-top: 1px solid #334155; font-size: 0.875rem; font-variant-numeric: tabular-nums; }
+  tr:hover td { background: #1e3a5f; }
+  .level-badge { padding: 2px 8px; border-radius: 4px; font-size: 0.75rem; font-weight: 600; }
+  .level-DEBUG { background: #164e63; color: #67e8f9; }
+  .level-INFO { background: #14532d; color: #86efac; }
+  .level-WARN { background: #713f12; color: #fde047; }
+  .level-ERROR { background: #7f1d1d; color: #fca5a5; }
+  h2 { font-size: 1rem; margin: 24px 0 12px; color: #94a3b8; }
+  .health { font-weight: 600; }
+  .health.ok { color: #86efac; }
+  .health.failing { color: #fca5a5; }
+  .health.idle { color: #94a3b8; }
+  .spark polyline { fill: none; stroke: #38bdf8; stroke-width: 1.5; }
+  .error { color: #fca5a5; max-width: 360px; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+  .filters { display: flex; gap: 12px; align-items: center; margin-bottom: 12px; flex-wrap: wrap; font-size: 0.875rem; color: #94a3b8; }
+  .filters input[type=text] { background: #1e293b; border: 1px solid #334155; border-radius: 4px; color: #e2e8f0; padding: 6px 10px; }
+  .filters button { background: #334155; border: none; border-radius: 4px; color: #e2e8f0; padding: 6px 12px; cursor: pointer; }
+  #tail { background: #1e293b; border-radius: 8px; padding: 8px 14px; height: 320px; overflow-y: auto; font-family: ui-monospace, monospace; font-size: 0.8rem; }
+  #tail .line { padding: 2px 0; white-space: pre-wrap; word-break: break-word; }
+  #tail .time, #tail .fields { color: #64748b; }
+  #tail .service { color: #38bdf8; }
+  .charts { display: grid; grid-template-columns: repeat(auto-fit, minmax(420px, 1fr)); gap: 16px; }
+  .chart { background: #1e293b; border-radius: 8px; padding: 12px 14px; }
+  .chart .title { font-size: 0.75rem; color: #94a3b8; text-transform: uppercase; letter-spacing: 0.05em; margin-bottom: 8px; }
+  .chart svg { width: 100%; height: 180px; display: block; }
+  .chart svg text { fill: #64748b; font-size: 10px; }
+  .legend { display: flex; gap: 12px; flex-wrap: wrap; font-size: 0.75rem; margin-top: 6px; color: #94a3b8; }
+  .controls { display: grid; grid-template-columns: repeat(auto-fit, minmax(420px, 1fr)); gap: 16px; align-items: start; }
+  .controls input[type=number] { width: 80px; background: #0f172a; border: 1px solid #334155; border-radius: 4px; color: #e2e8f0; padding: 4px 8px; }
+  .controls button, #pauseRun { background: #334155; border: none; border-radius: 4px; color: #e2e8f0; padding: 4px 12px; cursor: pointer; }
+  .controls button.fire { background: #7f1d1d; color: #fca5a5; }
+  .running { color: #fca5a5; font-weight: 600; }
+  .legend span::before { content: ''; display: inline-block; width: 10px; height: 3px; margin-right: 4px; vertical-align: middle; background: var(--color); }
+</style>
+</head>
+<body>
+<h1>Emitter Dashboard <a href="/runs" style="font-size: 0.875rem; color: #94a3b8;">past runs</a> <a href="/search" style="font-size: 0.875rem; color: #94a3b8;">search</a></h1>
+<div class="status" id="status"><span class="dot disconnected" id="dot"></span>Connecting...</div>
+
+<div class="summary">
+  <div class="card"><div class="label">Total Flushes</div><div class="value" id="totalFlushes">0</div></div>
+  <div class="card"><div class="label">Total Logs</div><div class="value" id="totalLogs">0</div></div>
+  <div class="card"><div class="label">Last Batch</div><div class="value" id="lastBatch">-</div></div>
+</div>
+
+<h2>Control <button id="pauseRun">Pause emission</button> <span id="controlStatus"></span></h2>
+<div class="controls">
+  <table>
+    <thead><tr><th>Pipeline</th><th>Emitter</th><th>Rate / s</th><th>New rate</th></tr></thead>
+    <tbody id="rates"><tr><td colspan="4">No emitters yet</td></tr></tbody>
+  </table>
+  <table>
+    <thead><tr><th>Pipeline</th><th>Scenario</th><th>Services</th><th>Status</th><th>Duration (s)</th></tr></thead>
+    <tbody id="scenarios"><tr><td colspan="5">No scenarios configured</td></tr></tbody>
+  </table>
+</div>
+
+<h2>Throughput</h2>
+<div class="charts">
+  <div class="chart"><div class="title">Entries / second</div><svg id="rateChart" viewBox="0 0 600 180" preserveAspectRatio="none"></svg><div class="legend" id="rateLegend"></div></div>
+  <div class="chart"><div class="title">Level mix</div><svg id="levelChart" viewBox="0 0 600 180" preserveAspectRatio="none"></svg><div class="legend" id="levelLegend"></div></div>
+</div>
+
+<h2>Sinks</h2>
+<table>
+  <thead>
+    <tr><th>Sink</th><th>Status</th><th>Writes</th><th>Failed</th><th>Retries</th><th>Latency (last / avg)</th><th>Recent writes</th><th>Last error</th></tr>
+  </thead>
+  <tbody id="sinks"><tr><td colspan="8">No other sinks in this pipeline yet</td></tr></tbody>
+</table>
+
+<h2>Live tail</h2>
+<div class="filters">
+  <input type="text" id="tailService" placeholder="services, e.g. api-gateway,auth">
+  <label><input type="checkbox" class="tailLevel" value="DEBUG" checked> DEBUG</label>
+  <label><input type="checkbox" class="tailLevel" value="INFO" checked> INFO</label>
+  <label><input type="checkbox" class="tailLevel" value="WARN" checked> WARN</label>
+  <label><input type="checkbox" class="tailLevel" value="ERROR" checked> ERROR</label>
+  <input type="text" id="tailQuery" placeholder="message contains...">
+  <button id="tailPause">Pause</button>
+  <span id="tailStatus"></span>
+</div>
+<div id="tail"></div>
+
+<h2>Batches</h2>
+<table>
+  <thead>
+    <tr><th>Time</th><th>Count</th><th>Services</th><th>Levels</th></tr>
+  </thead>
+  <tbody id="events"></tbody>
+</table>
+
+<script>
+  const MAX_ROWS = 50;
+  let totalFlushes = 0;
+  let totalLogs = 0;
+
+  function connect() {
+    const ws = new WebSocket(`ws://${location.host}/ws`);
+    const dot = document.getElementById('dot');
+    const status = document.getElementById('status');
+
+    ws.onopen = () => {
+      dot.className = 'dot connected';
+      status.innerHTML = '<span class="dot connected" id="dot"></span>Connected';
+      loadHistory();
+    };
+
+    ws.onclose = () => {
+      dot.className = 'dot disconnected';
+      status.innerHTML = '<span class="dot disconnected" id="dot"></span>Reconnecting...';
+      setTimeout(connect, 2000);
+    };
+
+    ws.onmessage = (msg) => {
+      const ev = JSON.parse(msg.data);
+      if (ev.type === 'sinks') {
+        renderSinks(ev.sinks);
+        return;
+      }
+      if (ev.type === 'history') {
+        mergeHistory(ev.seconds);
+        return;
+      }
+      totalFlushes++;
+      totalLogs += ev.total_count;
+
+      document.getElementById('totalFlushes').textContent = totalFlushes;
+      document.getElementById('totalLogs').textContent = totalLogs.toLocaleString();
+      document.getElementById('lastBatch').textContent = ev.total_count;
+      addFlushRow(ev);
+    };
+  }
+
+  function addFlushRow(ev) {
+    const tbody = document.getElementById('events');
+    const tr = document.createElement('tr');
+
+    const services = Object.entries(ev.by_service).map(([k,v]) => `${k}: ${v}`).join(', ');
+    const levels = Object.entries(ev.by_level)
+      .map(([k,v]) => `<span class="level-badge level-${k}">${k}: ${v}</span>`)
+      .join(' ');
+    const time = new Date(ev.timestamp).toLocaleTimeString();
+
+    tr.innerHTML = `<td>${time}</td><td>${ev.total_count}</td><td>${services}</td><td>${levels}</td>`;
+    tbody.prepend(tr);
+
+    // keep table bounded
+    while (tbody.children.length > MAX_ROWS) tbody.removeChild(tbody.lastChild);
+  }
+
+  function escape(text) {
+    const div = document.createElement('div');
+    div.textContent = text;
+    return div.innerHTML;
+  }
+
+  // a line through the latest write latencies, scaled to the slowest
+  function sparkline(values) {
+    if (values.length < 2) return '';
+    const width = 120, height = 24;
+    const max = Math.max(...values) || 1;
+    const points = values
+      .map((v, i) => `${(i / (values.length - 1) * width).toFixed(1)},${(height - v / max * height).toFixed(1)}`)
+      .join(' ');
+    return `<svg class="spark" width="${width}" height="${height}"><polyline points="${points}"/></svg>`;
+  }
+
+  function renderSinks(sinks) {
+    if (sinks.length === 0) return;
+    const tbody = document.getElementById('sinks');
+    tbody.innerHTML = sinks.map(s => {
+      const status = s.writes === 0 ? 'idle' : (s.last_error ? 'failing' : 'ok');
+      const last = s.latency_ms.length ? s.latency_ms[s.latency_ms.length - 1] : 0;
+      const avg = s.latency_ms.length ? s.latency_ms.reduce((a, b) => a + b, 0) / s.latency_ms.length : 0;
+      const latency = s.writes === 0 ? '-' : `${last.toFixed(1)}ms / ${avg.toFixed(1)}ms`;
+      const error = s.last_error ? escape(s.last_error) : '';
+      return `<tr><td>${escape(s.name)}</td><td class="health ${status}">${status}</td>`
+        + `<td>${s.writes.toLocaleString()}</td><td>${s.failed_writes.toLocaleString()} (${s.failed.toLocaleString()} logs)</td>`
+        + `<td>${s.retries.toLocaleString()}</td><td>${latency}</td><td>${sparkline(s.latency_ms)}</td>`
+        + `<td class="error" title="${error}">${error}</td></tr>`;
+    }).join('');
+  }
+
+  // entry counts by second (unix time), from /history and then as batches
+  // are written; counts only grow, so the larger of two copies is newer
+  const history = new Map();
+  const PALETTE = ['#38bdf8', '#a78bfa', '#f472b6', '#fb923c', '#4ade80', '#facc15', '#2dd4bf', '#f87171'];
+  const LEVEL_COLORS = { DEBUG: '#67e8f9', INFO: '#86efac', WARN: '#fde047', ERROR: '#fca5a5' };
+
+  function mergeHistory(seconds) {
+    for (const second of seconds) {
+      const known = history.get(second.timestamp);
+      if (!known || known.total <= second.total) history.set(second.timestamp, second);
+    }
+  }
+
+  // what the run has written so far, so a refresh loses nothing
+  function loadHistory() {
+    fetch('/history').then(r => r.json()).then(snapshot => {
+      mergeHistory(snapshot.seconds);
+      totalFlushes = snapshot.total_flushes;
+      totalLogs = snapshot.total_entries;
+      document.getElementById('totalFlushes').textContent = totalFlushes;
+      document.getElementById('totalLogs').textContent = totalLogs.toLocaleString();
+      const last = snapshot.flushes[snapshot.flushes.length - 1];
+      if (last) document.getElementById('lastBatch').textContent = last.total_count;
+      document.getElementById('events').innerHTML = '';
+      snapshot.flushes.forEach(addFlushRow);
+    }).catch(() => {});
+  }
+
+  // every second from the first in the history to the last, empty ones
+  // included, over the last hour at most
+  const MAX_SPAN = 3600;
+  function timeline() {
+    if (history.size === 0) return [];
+    const times = [...history.keys()];
+    const last = Math.max(...times);
+    for (const t of times) if (t <= last - MAX_SPAN) history.delete(t);
+    const first = Math.max(Math.min(...times), last - MAX_SPAN + 1);
+    const seconds = [];
+    for (let t = first; t <= last; t++) {
+      seconds.push(history.get(t) || { timestamp: t, total: 0, by_service: {}, by_level: {} });
+    }
+    return seconds;
+  }
+
+  function polyline(values, max, color, width) {
+    const n = Math.max(values.length - 1, 1);
+    const points = values.map((v, i) => `${(i / n * 600).toFixed(1)},${(170 - v / max * 160).toFixed(1)}`).join(' ');
+    return `<polyline points="${points}" fill="none" stroke="${color}" stroke-width="${width}" vector-effect="non-scaling-stroke"/>`;
+  }
+
+  function legend(id, items) {
+    document.getElementById(id).innerHTML = items
+      .map(([name, color]) => `<span style="--color:${color}">${escape(name)}</span>`).join('');
+  }
+
+  function renderCharts() {
+    const seconds = timeline();
+    if (seconds.length < 2) return;
+    const services = [...new Set(seconds.flatMap(s => Object.keys(s.by_service)))].sort();
+    const max = Math.max(1, ...seconds.map(s => s.total));
+    let svg = `<text x="2" y="10">${max}/s</text>`;
+    const items = [['all', '#e2e8f0']];
+    services.forEach((service, i) => {
+      const color = PALETTE[i % PALETTE.length];
+      svg += polyline(seconds.map(s => s.by_service[service] || 0), max, color, 1.2);
+      items.push([service, color]);
+    });
+    svg += polyline(seconds.map(s => s.total), max, '#e2e8f0', 2);
+    document.getElementById('rateChart').innerHTML = svg;
+    legend('rateLegend', items);
+
+    // stacked shares of each level, bottom up
+    const levels = ['ERROR', 'WARN', 'INFO', 'DEBUG'];
+    const n = seconds.length - 1;
+    const base = seconds.map(() => 0);
+    let areas = '';
+    for (const level of levels) {
+      const top = seconds.map((s, i) => base[i] + (s.total ? (s.by_level[level] || 0) / s.total : 0));
+      const upper = top.map((v, i) => `${(i / n * 600).toFixed(1)},${(170 - v * 160).toFixed(1)}`);
+      const lower = base.map((v, i) => `${(i / n * 600).toFixed(1)},${(170 - v * 160).toFixed(1)}`).reverse();
+      areas += `<polygon points="${upper.concat(lower).join(' ')}" fill="${LEVEL_COLORS[level]}" fill-opacity="0.7"/>`;
+      top.forEach((v, i) => base[i] = v);
+    }
+    document.getElementById('levelChart').innerHTML = areas;
+    legend('levelLegend', levels.map(level => [level, LEVEL_COLORS[level]]));
+  }
+
+  const MAX_TAIL_LINES = 200;
+  let tailSocket = null;
+  let tailPaused = false;
+  let tailTimer = null;
+
+  // reconnect with the filters in the query string whenever they change
+  function connectTail() {
+    if (tailSocket) {
+      tailSocket.onclose = null;
+      tailSocket.close();
+    }
+    const params = new URLSearchParams();
+    const service = document.getElementById('tailService').value.trim();
+    const levels = [...document.querySelectorAll('.tailLevel:checked')].map(box => box.value);
+    const query = document.getElementById('tailQuery').value.trim();
+    if (service) params.set('service', service);
+    if (levels.length < 4) params.set('level', levels.join(','));
+    if (query) params.set('q', query);
+    tailSocket = new WebSocket(`ws://${location.host}/ws/tail?${params}`);
+    tailSocket.onclose = () => { tailTimer = setTimeout(connectTail, 2000); };
+    tailSocket.onmessage = (msg) => {
+      if (tailPaused) return;
+      const batch = JSON.parse(msg.data);
+      document.getElementById('tailStatus').textContent =
+        `last batch: ${batch.entries.length} of ${batch.matched} matching`;
+      const tail = document.getElementById('tail');
+      const atBottom = tail.scrollTop + tail.clientHeight >= tail.scrollHeight - 4;
+      for (const entry of batch.entries) {
+        const line = document.createElement('div');
+        line.className = 'line';
+        const fields = Object.entries(entry.fields || {}).map(([k, v]) => `${k}=${JSON.stringify(v)}`).join(' ');
+        const trace = entry.trace ? ` trace=${entry.trace.trace_id}` : '';
+        const level = entry.level.toUpperCase();
+        line.innerHTML = `<span class="time">${new Date(entry.timestamp).toLocaleTimeString()}</span> `
+          + `<span class="level-badge level-${level}">${level}</span> `
+          + `<span class="service">${escape(entry.service)}</span> ${escape(entry.message)}`
+          + ` <span class="fields">${escape(fields + trace)}</span>`;
+        tail.appendChild(line);
+      }
+      while (tail.children.length > MAX_TAIL_LINES) tail.removeChild(tail.firstChild);
+      if (atBottom) tail.scrollTop = tail.scrollHeight;
+    };
+  }
+
+  function filtersChanged() {
+    clearTimeout(tailTimer);
+    tailTimer = setTimeout(connectTail, 300);
+  }
+  document.getElementById('tailService').addEventListener('input', filtersChanged);
+  document.getElementById('tailQuery').addEventListener('input', filtersChanged);
+  document.querySelectorAll('.tailLevel').forEach(box => box.addEventListener('change', filtersChanged));
+  document.getElementById('tailPause').addEventListener('click', (e) => {
+    tailPaused = !tailPaused;
+    e.target.textContent = tailPaused ? 'Resume' : 'Pause';
+  });
+
+  // the control API, nested under /api/control
+  let paused = false;
+  let rateKeys = '', scenarioKeys = '';
+
+  function control(method, path, pipeline, body) {
+    const query = pipeline ? `?pipeline=${encodeURIComponent(pipeline)}` : '';
+    const init = { method };
+    if (body) {
+      init.headers = { 'Content-Type': 'application/json' };
+      init.body = JSON.stringify(body);
+    }
+    return fetch(`/api/control/${path}${query}`, init)
+      .then(async r => {
+        document.getElementById('controlStatus').textContent = r.ok ? '' : await r.text();
+        loadControl();
+      });
+  }
+
+  // rows are only rebuilt when the emitters or scenarios change, so
+  // what's being typed into them survives the refresh
+  function renderControl(status) {
+    paused = status.paused;
+    document.getElementById('pauseRun').textContent = paused ? 'Resume emission' : 'Pause emission';
+    const rates = Object.entries(status.rates)
+      .flatMap(([pipeline, sources]) => Object.entries(sources).map(([source, rate]) => ({ pipeline, source, rate })));
+    const keys = rates.map(r => `${r.pipeline}/${r.source}`).join(',');
+    const ratesBody = document.getElementById('rates');
+    if (keys !== rateKeys && rates.length) {
+      rateKeys = keys;
+      ratesBody.innerHTML = rates.map((r, i) => `<tr>
+        <td>${escape(r.pipeline)}</td><td>${escape(r.source)}</td><td id="rate-${i}"></td>
+        <td><input type="number" min="0" step="any" id="newRate-${i}"> <button data-rate="${i}">Set</button></td></tr>`).join('');
+      ratesBody.querySelectorAll('button').forEach(button => button.addEventListener('click', () => {
+        const r = rates[button.dataset.rate];
+        const input = document.getElementById(`newRate-${button.dataset.rate}`);
+        if (input.value === '') return;
+        control('PUT', `rates/${encodeURIComponent(r.source)}`, r.pipeline, { rate_per_sec: Number(input.value) });
+        input.value = '';
+      }));
+    }
+    rates.forEach((r, i) => {
+      const cell = document.getElementById(`rate-${i}`);
+      if (cell) cell.textContent = r.rate.toFixed(1);
+    });
+
+    const scenarios = Object.entries(status.scenarios)
+      .flatMap(([pipeline, list]) => list.map(s => ({ pipeline, ...s })));
+    const names = scenarios.map(s => `${s.pipeline}/${s.name}`).join(',');
+    const scenariosBody = document.getElementById('scenarios');
+    if (names !== scenarioKeys && scenarios.length) {
+      scenarioKeys = names;
+      scenariosBody.innerHTML = scenarios.map((s, i) => `<tr>
+        <td>${escape(s.pipeline)}</td><td>${escape(s.name)}</td>
+        <td>${s.services.length ? s.services.map(escape).join(', ') : 'all'}</td><td id="scenario-${i}"></td>
+        <td><input type="number" min="1" placeholder="as configured" id="duration-${i}">
+          <button class="fire" data-trigger="${i}">Trigger</button> <button data-end="${i}">End</button></td></tr>`).join('');
+      scenariosBody.querySelectorAll('button[data-trigger]').forEach(button => button.addEventListener('click', () => {
+        const s = scenarios[button.dataset.trigger];
+        const duration = document.getElementById(`duration-${button.dataset.trigger}`).value;
+        control('POST', `scenarios/${encodeURIComponent(s.name)}/trigger`, s.pipeline,
+          duration === '' ? null : { duration_secs: Number(duration) });
+      }));
+      scenariosBody.querySelectorAll('button[data-end]').forEach(button => button.addEventListener('click', () => {
+        const s = scenarios[button.dataset.end];
+        control('POST', `scenarios/${encodeURIComponent(s.name)}/end`, s.pipeline);
+      }));
+    }
+    scenarios.forEach((s, i) => {
+      const cell = document.getElementById(`scenario-${i}`);
+      if (!cell) return;
+      cell.className = s.running ? 'running' : '';
+      cell.textContent = s.running ? `running, ${s.ends_in_secs}s left` : 'idle';
+    });
+  }
+
+  function loadControl() {
+    fetch('/api/control/status').then(r => r.json()).then(renderControl).catch(() => {});
+  }
+  document.getElementById('pauseRun').addEventListener('click', () => {
+    control('POST', paused ? 'resume' : 'pause');
+  });
+
+  connect();
+  connectTail();
+  loadControl();
+  setInterval(renderCharts, 1000);
+  setInterval(loadControl, 2000);
+</script>
+</body>
+</html>
+"##;
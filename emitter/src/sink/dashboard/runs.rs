@@ -0,0 +1,329 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use tokio::sync::OnceCell;
+
+use super::{FlushEvent, Second};
+
+/// Each run's flushes and entries per second, kept in a SQLite file so past
+/// runs can be browsed and compared once they're over. The run is recorded
+/// from its first write, so a dry run leaves nothing behind.
+pub struct RunStore {
+    pool: SqlitePool,
+    pipeline: Option<String>,
+    run: OnceCell<i64>,
+}
+
+/// A run as listed
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RunSummary {
+    pub id: i64,
+    pub pipeline: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// Unset while running, or when a run was cut short
+    pub ended_at: Option<DateTime<Utc>>,
+    pub entries: i64,
+    pub flushes: i64,
+    /// Most entries timestamped within one second
+    pub peak_per_sec: Option<i64>,
+    pub mean_per_sec: Option<f64>,
+}
+
+/// A run with its entries per second
+#[derive(Debug, Clone, Serialize)]
+pub struct RunDetail {
+    #[serde(flatten)]
+    pub summary: RunSummary,
+    pub seconds: Vec<Second>,
+}
+
+impl RunStore {
+    /// Open (or create) the file at `path`, for the runs of `pipeline`
+    pub async fn open(path: &Path, pipeline: Option<&str>) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                pipeline TEXT,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                entries INTEGER NOT NULL DEFAULT 0,
+                flushes INTEGER NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS flushes (
+                run_id INTEGER NOT NULL REFERENCES runs(id),
+                timestamp TEXT NOT NULL,
+                total_count INTEGER NOT NULL,
+                by_service TEXT NOT NULL,
+                by_level TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS seconds (
+                run_id INTEGER NOT NULL REFERENCES runs(id),
+                timestamp INTEGER NOT NULL,
+                total INTEGER NOT NULL,
+                by_service TEXT NOT NULL,
+                by_level TEXT NOT NULL,
+                PRIMARY KEY (run_id, timestamp)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self {
+            pool,
+            pipeline: pipeline.map(str::to_string),
+            run: OnceCell::new(),
+        })
+    }
+
+    /// This run's id, starting it on first use
+    async fn run(&self) -> Result<i64, sqlx::Error> {
+        self.run
+            .get_or_try_init(|| async {
+                let result = sqlx::query("INSERT INTO runs (pipeline, started_at) VALUES (?, ?)")
+                    .bind(&self.pipeline)
+                    .bind(Utc::now())
+                    .execute(&self.pool)
+                    .await?;
+                Ok(result.last_insert_rowid())
+            })
+            .await
+            .copied()
+    }
+
+    /// Record a flush, and the seconds it added entries to with their
+    /// counts so far
+    pub async fn record(&self, flush: &FlushEvent, seconds: &[Second]) -> Result<(), sqlx::Error> {
+        let run = self.run().await?;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO flushes (run_id, timestamp, total_count, by_service, by_level)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(run)
+        .bind(flush.timestamp)
+        .bind(flush.total_count as i64)
+        .bind(json(&flush.by_service))
+        .bind(json(&flush.by_level))
+        .execute(&mut *tx)
+        .await?;
+        for second in seconds {
+            sqlx::query(
+                "INSERT INTO seconds (run_id, timestamp, total, by_service, by_level)
+                 VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT (run_id, timestamp) DO UPDATE SET
+                    total = excluded.total,
+                    by_service = excluded.by_service,
+                    by_level = excluded.by_level",
+            )
+            .bind(run)
+            .bind(second.timestamp)
+            .bind(second.total as i64)
+            .bind(json(&second.by_service))
+            .bind(json(&second.by_level))
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query("UPDATE runs SET entries = entries + ?, flushes = flushes + 1 WHERE id = ?")
+            .bind(flush.total_count as i64)
+            .bind(run)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+
+    /// Mark the run as ended, if it started
+    pub async fn finish(&self) -> Result<(), sqlx::Error> {
+        let Some(run) = self.run.get() else {
+            return Ok(());
+        };
+        sqlx::query("UPDATE runs SET ended_at = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(run)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Every run recorded, the latest first
+    pub async fn runs(&self) -> Result<Vec<RunSummary>, sqlx::Error> {
+        sqlx::query_as(SUMMARY)
+            .bind(None::<i64>)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// The run with this id, if there is one
+    pub async fn run_detail(&self, id: i64) -> Result<Option<RunDetail>, sqlx::Error> {
+        let Some(summary) = sqlx::query_as::<_, RunSummary>(SUMMARY)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let rows: Vec<(i64, i64, String, String)> = sqlx::query_as(
+            "SELECT timestamp, total, by_service, by_level FROM seconds
+             WHERE run_id = ? ORDER BY timestamp",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        let seconds = rows
+            .into_iter()
+            .map(|(timestamp, total, by_service, by_level)| Second {
+                timestamp,
+                total: total as usize,
+                by_service: serde_json::from_str(&by_service).unwrap_or_default(),
+                by_level: serde_json::from_str(&by_level).unwrap_or_default(),
+            })
+            .collect();
+        Ok(Some(RunDetail { summary, seconds }))
+    }
+}
+
+/// Runs with their peak and mean entries per second, all of them or the
+/// one whose id is bound
+const SUMMARY: &str = "SELECT runs.id, runs.pipeline, runs.started_at, runs.ended_at,
+        runs.entries, runs.flushes,
+        MAX(seconds.total) AS peak_per_sec, AVG(seconds.total) AS mean_per_sec
+    FROM runs LEFT JOIN seconds ON seconds.run_id = runs.id
+    WHERE ?1 IS NULL OR runs.id = ?1
+    GROUP BY runs.id
+    ORDER BY runs.id DESC";
+
+fn json(counts: &impl Serialize) -> String {
+    serde_json::to_string(counts).unwrap_or_default()
+}
+
+pub(super) const RUNS_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>Emitter Runs</title>
+<style>
+  * { margin: 0; padding: 0; box-sizing: border-box; }
+  body { font-family: system-ui, -apple-system, sans-serif; background: #0f172a; color: #e2e8f0; padding: 24px; }
+  h1 { font-size: 1.5rem; margin-bottom: 16px; color: #38bdf8; }
+  h2 { font-size: 1rem; margin: 24px 0 12px; color: #94a3b8; }
+  a { color: #38bdf8; }
+  .status { margin-bottom: 16px; font-size: 0.875rem; color: #94a3b8; }
+  table { width: 100%; border-collapse: collapse; background: #1e293b; border-radius: 8px; overflow: hidden; }
+  th { text-align: left; padding: 10px 14px; background: #334155; font-size: 0.75rem; text-transform: uppercase; letter-spacing: 0.05em; color: #94a3b8; }
+  td { padding: 10px 14px; border-top: 1px solid #334155; font-size: 0.875rem; font-variant-numeric: tabular-nums; }
+  tr:hover td { background: #1e3a5f; }
+  .chart { background: #1e293b; border-radius: 8px; padding: 12px 14px; }
+  .chart .title { font-size: 0.75rem; color: #94a3b8; text-transform: uppercase; letter-spacing: 0.05em; margin-bottom: 8px; }
+  .chart svg { width: 100%; height: 240px; display: block; }
+  .legend { display: flex; gap: 12px; flex-wrap: wrap; font-size: 0.75rem; margin-top: 6px; color: #94a3b8; }
+  .legend span::before { content: ''; display: inline-block; width: 10px; height: 3px; margin-right: 4px; vertical-align: middle; background: var(--color); }
+</style>
+</head>
+<body>
+<h1>Past Runs</h1>
+<div class="status"><a href="/">Back to the live dashboard</a> &middot; <span id="status">Loading...</span></div>
+
+<table>
+  <thead>
+    <tr><th>Compare</th><th>Run</th><th>Pipeline</th><th>Started</th><th>Duration</th><th>Entries</th><th>Flushes</th><th>Peak / s</th><th>Mean / s</th></tr>
+  </thead>
+  <tbody id="runs"></tbody>
+</table>
+
+<h2>Throughput profiles</h2>
+<div class="chart">
+  <div class="title">Entries / second, from each run's first second</div>
+  <svg id="compareChart" viewBox="0 0 600 240" preserveAspectRatio="none"></svg>
+  <div class="legend" id="compareLegend"></div>
+</div>
+
+<script>
+  const PALETTE = ['#38bdf8', '#a78bfa', '#f472b6', '#fb923c', '#4ade80', '#facc15', '#2dd4bf', '#f87171'];
+  const details = new Map();
+
+  function escape(text) {
+    const div = document.createElement('div');
+    div.textContent = text;
+    return div.innerHTML;
+  }
+
+  function duration(run) {
+    if (!run.ended_at) return 'running or cut short';
+    const secs = Math.round((new Date(run.ended_at) - new Date(run.started_at)) / 1000);
+    return secs >= 60 ? `${Math.floor(secs / 60)}m ${secs % 60}s` : `${secs}s`;
+  }
+
+  async function load() {
+    const response = await fetch('/api/runs');
+    if (!response.ok) {
+      document.getElementById('status').textContent = await response.text();
+      return;
+    }
+    const runs = await response.json();
+    document.getElementById('status').textContent = `${runs.length} runs`;
+    document.getElementById('runs').innerHTML = runs.map(run => `<tr>`
+      + `<td><input type="checkbox" class="compare" value="${run.id}"></td>`
+      + `<td>#${run.id}</td><td>${escape(run.pipeline || '-')}</td>`
+      + `<td>${new Date(run.started_at).toLocaleString()}</td><td>${duration(run)}</td>`
+      + `<td>${run.entries.toLocaleString()}</td><td>${run.flushes.toLocaleString()}</td>`
+      + `<td>${run.peak_per_sec ?? '-'}</td><td>${run.mean_per_sec == null ? '-' : run.mean_per_sec.toFixed(1)}</td>`
+      + `</tr>`).join('');
+    document.querySelectorAll('.compare').forEach(box => box.addEventListener('change', compare));
+    // the latest two, to start with
+    [...document.querySelectorAll('.compare')].slice(0, 2).forEach(box => box.checked = true);
+    compare();
+  }
+
+  async function compare() {
+    const ids = [...document.querySelectorAll('.compare:checked')].map(box => Number(box.value));
+    for (const id of ids) {
+      if (!details.has(id)) details.set(id, await (await fetch(`/api/runs/${id}`)).json());
+    }
+    // each run as entries per second since its first second, gaps as 0
+    const series = ids.map(id => {
+      const seconds = details.get(id).seconds;
+      if (seconds.length === 0) return { id, values: [] };
+      const first = seconds[0].timestamp;
+      const values = new Array(seconds[seconds.length - 1].timestamp - first + 1).fill(0);
+      for (const s of seconds) values[s.timestamp - first] = s.total;
+      return { id, values };
+    });
+    const length = Math.max(2, ...series.map(s => s.values.length));
+    const max = Math.max(1, ...series.flatMap(s => s.values));
+    let svg = `<text x="2" y="10" fill="#64748b" font-size="10">${max}/s</text>`;
+    const legend = [];
+    series.forEach((s, i) => {
+      const color = PALETTE[i % PALETTE.length];
+      const points = s.values
+        .map((v, x) => `${(x / (length - 1) * 600).toFixed(1)},${(230 - v / max * 215).toFixed(1)}`)
+        .join(' ');
+      svg += `<polyline points="${points}" fill="none" stroke="${color}" stroke-width="1.5" vector-effect="non-scaling-stroke"/>`;
+      legend.push(`<span style="--color:${color}">#${s.id} (${s.values.length}s)</span>`);
+    });
+    document.getElementById('compareChart').innerHTML = svg;
+    document.getElementById('compareLegend').innerHTML = legend.join('');
+  }
+
+  load();
+</script>
+</body>
+</html>
+"##;
@@ -0,0 +1,211 @@
+use axum::Json;
+use axum::extract::State;
+use axum::response::{Html, IntoResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::search::{SearchQuery, SearchResults, Searchable};
+
+use super::Streams;
+
+pub(super) async fn search_page_handler() -> impl IntoResponse {
+    Html(SEARCH_HTML)
+}
+
+/// A backend to search, as the search page lists it
+#[derive(Debug, Serialize)]
+pub(super) struct Backend {
+    name: String,
+    kind: &'static str,
+    /// Whether it has embeddings to search by, with the emitter's or its own model
+    dense: bool,
+}
+
+pub(super) async fn backends_handler(State(streams): State<Streams>) -> Json<Vec<Backend>> {
+    let searchables = streams
+        .searchables
+        .get()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Json(
+        searchables
+            .iter()
+            .map(|searchable| Backend {
+                name: searchable.name.clone(),
+                kind: searchable.backend.kind(),
+                dense: searchable.backend.embedding_dim() > 0
+                    || searchable.backend.embeds_queries(),
+            })
+            .collect(),
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct SearchRequest {
+    /// Names of the backends to search, or every one when empty
+    #[serde(default)]
+    backends: Vec<String>,
+    #[serde(flatten)]
+    query: SearchQuery,
+}
+
+pub(super) async fn search_handler(
+    State(streams): State<Streams>,
+    Json(request): Json<SearchRequest>,
+) -> Json<Vec<SearchResults>> {
+    let searchables: Vec<Searchable> = streams
+        .searchables
+        .get()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|searchable| {
+            request.backends.is_empty() || request.backends.contains(&searchable.name)
+        })
+        .cloned()
+        .collect();
+    Json(crate::search::search_all(&searchables, &request.query).await)
+}
+
+pub(super) const SEARCH_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>Search</title>
+<style>
+  * { margin: 0; padding: 0; box-sizing: border-box; }
+  body { font-family: system-ui, -apple-system, sans-serif; background: #0f172a; color: #e2e8f0; padding: 24px; }
+  h1 { font-size: 1.5rem; margin-bottom: 16px; color: #38bdf8; }
+  a { color: #94a3b8; }
+  .status { margin-bottom: 16px; font-size: 0.875rem; color: #94a3b8; }
+  form { display: flex; flex-direction: column; gap: 10px; margin-bottom: 20px; font-size: 0.875rem; color: #94a3b8; }
+  form .row { display: flex; gap: 16px; align-items: center; flex-wrap: wrap; }
+  input[type=text], input[type=number] { background: #1e293b; border: 1px solid #334155; border-radius: 4px; color: #e2e8f0; padding: 6px 10px; }
+  #text { flex: 1; min-width: 320px; font-size: 1rem; }
+  button { background: #0369a1; border: none; border-radius: 4px; color: #e2e8f0; padding: 8px 16px; cursor: pointer; font-size: 0.875rem; }
+  .results { display: grid; grid-template-columns: repeat(auto-fit, minmax(360px, 1fr)); gap: 16px; }
+  .column { background: #1e293b; border-radius: 8px; padding: 12px 14px; }
+  .column h2 { font-size: 1rem; color: #f1f5f9; }
+  .column .timing { font-size: 0.75rem; color: #64748b; margin: 2px 0 10px; }
+  .column .error { color: #fca5a5; font-size: 0.875rem; }
+  .hit { padding: 6px 4px; border-top: 1px solid #334155; font-size: 0.8rem; }
+  .hit.same { background: #1e3a5f; }
+  .hit .meta { color: #64748b; font-family: ui-monospace, monospace; }
+  .hit .service { color: #38bdf8; }
+  .hit .message { margin-top: 2px; word-break: break-word; }
+  .level-badge { padding: 1px 6px; border-radius: 4px; font-size: 0.7rem; font-weight: 600; }
+  .level-DEBUG { background: #164e63; color: #67e8f9; }
+  .level-INFO { background: #14532d; color: #86efac; }
+  .level-WARN { background: #713f12; color: #fde047; }
+  .level-ERROR { background: #7f1d1d; color: #fca5a5; }
+</style>
+</head>
+<body>
+<h1>Search</h1>
+<div class="status"><a href="/">Back to the live dashboard</a> &middot; <span id="status">Loading...</span></div>
+
+<form id="search">
+  <div class="row">
+    <input type="text" id="text" placeholder="e.g. connection refused to the payments database" autofocus>
+    <button type="submit">Search</button>
+  </div>
+  <div class="row">
+    <span>Mode:</span>
+    <label><input type="radio" name="mode" value="keyword"> keyword</label>
+    <label><input type="radio" name="mode" value="dense"> dense</label>
+    <label><input type="radio" name="mode" value="hybrid" checked> hybrid</label>
+    <label>Results <input type="number" id="limit" value="10" min="1" max="100" style="width: 64px"></label>
+  </div>
+  <div class="row">
+    <span>Backends:</span>
+    <span id="backends"></span>
+  </div>
+  <div class="row">
+    <label>Services <input type="text" id="services" placeholder="api-gateway, auth (all)"></label>
+    <span>Levels:</span>
+    <label><input type="checkbox" class="level" value="DEBUG"> DEBUG</label>
+    <label><input type="checkbox" class="level" value="INFO"> INFO</label>
+    <label><input type="checkbox" class="level" value="WARN"> WARN</label>
+    <label><input type="checkbox" class="level" value="ERROR"> ERROR</label>
+  </div>
+</form>
+
+<div class="results" id="results"></div>
+
+<script>
+  function escape(text) {
+    const div = document.createElement('div');
+    div.textContent = text;
+    return div.innerHTML;
+  }
+
+  async function loadBackends() {
+    const backends = await (await fetch('/api/search')).json();
+    document.getElementById('status').textContent = backends.length === 0
+      ? 'Nothing to search: this pipeline writes to no Qdrant, Elasticsearch or pgvector sink'
+      : `${backends.length} backends`;
+    document.getElementById('backends').innerHTML = backends.map(b =>
+      `<label title="${b.dense ? 'keyword, dense and hybrid' : 'keyword only, no embeddings stored'}">`
+      + `<input type="checkbox" class="backend" value="${escape(b.name)}" checked> ${escape(b.name)} (${b.kind})</label>`
+    ).join(' ');
+  }
+
+  function column(result) {
+    let html = `<div class="column"><h2>${escape(result.name)} <span class="meta">${result.kind}</span></h2>`;
+    const timing = [];
+    if (result.embed_ms != null) timing.push(`embedded in ${result.embed_ms.toFixed(0)} ms`);
+    if (!result.error) timing.push(`searched in ${result.search_ms.toFixed(0)} ms`, `${result.hits.length} results`);
+    html += `<div class="timing">${timing.join(' &middot; ')}</div>`;
+    if (result.error) return html + `<div class="error">${escape(result.error)}</div></div>`;
+    html += result.hits.map((hit, i) => {
+      const level = hit.level.toUpperCase();
+      const time = hit.timestamp ? new Date(hit.timestamp).toLocaleTimeString() : '';
+      return `<div class="hit" data-id="${escape(hit.id)}">`
+        + `<div class="meta">#${i + 1} score ${hit.score.toPrecision(4)} ${time} `
+        + `<span class="level-badge level-${level}">${level}</span> <span class="service">${escape(hit.service)}</span></div>`
+        + `<div class="message">${escape(hit.message)}</div></div>`;
+    }).join('');
+    return html + '</div>';
+  }
+
+  document.getElementById('search').addEventListener('submit', async event => {
+    event.preventDefault();
+    const text = document.getElementById('text').value.trim();
+    if (!text) return;
+    const request = {
+      text,
+      mode: document.querySelector('input[name=mode]:checked').value,
+      limit: Number(document.getElementById('limit').value) || 10,
+      backends: [...document.querySelectorAll('.backend:checked')].map(box => box.value),
+      services: document.getElementById('services').value.split(',').map(s => s.trim()).filter(Boolean),
+      levels: [...document.querySelectorAll('.level:checked')].map(box => box.value),
+    };
+    if (request.backends.length === 0) return;
+    document.getElementById('status').textContent = 'Searching...';
+    const response = await fetch('/api/search', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(request),
+    });
+    if (!response.ok) {
+      document.getElementById('status').textContent = await response.text();
+      return;
+    }
+    const results = await response.json();
+    document.getElementById('status').textContent = `"${text}", ${request.mode}`;
+    document.getElementById('results').innerHTML = results.map(column).join('');
+  });
+
+  // the same entry in every column, when hovering one
+  const results = document.getElementById('results');
+  results.addEventListener('mouseover', event => {
+    const hit = event.target.closest('.hit');
+    results.querySelectorAll('.hit').forEach(other =>
+      other.classList.toggle('same', hit != null && other.dataset.id === hit.dataset.id));
+  });
+
+  loadBackends();
+</script>
+</body>
+</html>
+"##;
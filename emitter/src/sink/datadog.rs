@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+use crate::sink::compression::Compression;
+
+/// The logs intake accepts at most this many entries per request
+const MAX_ENTRIES_PER_REQUEST: usize = 1000;
+
+/// ...and at most this many bytes per request, before compression
+const MAX_REQUEST_BYTES: usize = 5_000_000;
+
+/// Larger entries are rejected by the intake
+const MAX_ENTRY_BYTES: usize = 1_000_000;
+
+fn default_site() -> String {
+    "datadoghq.com".to_string()
+}
+
+fn default_ddsource() -> String {
+    "logstorm".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DatadogConfig {
+    pub api_key: String,
+    /// Datadog site, e.g. `datadoghq.com`, `datadoghq.eu`, `us5.datadoghq.com`
+    #[serde(default = "default_site")]
+    pub site: String,
+    /// Full intake URL, overriding the one derived from `site` (proxies, mocks)
+    pub url: Option<String>,
+    #[serde(default = "default_ddsource")]
+    pub ddsource: String,
+    /// Tags attached to every entry, e.g. `env:demo`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub hostname: Option<String>,
+    /// Request body compression (the intake accepts gzip)
+    #[serde(default)]
+    pub compression: Compression,
+}
+
+pub struct DatadogSink {
+    config: DatadogConfig,
+    client: reqwest::Client,
+    url: String,
+    /// Ids of entries already sent from a batch whose write then failed on
+    /// a later request, so retrying the batch doesn't send them again
+    delivered: Mutex<HashSet<String>>,
+}
+
+impl DatadogSink {
+    pub fn from_config(config: DatadogConfig) -> Result<Self, reqwest::Error> {
+        let url = config
+            .url
+            .clone()
+            .unwrap_or_else(|| format!("https://http-intake.logs.{}/api/v2/logs", config.site));
+        let client = reqwest::Client::builder().build()?;
+
+        Ok(Self {
+            config,
+            client,
+            url,
+            delivered: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// POST one request's worth of already serialized entries
+    async fn send(&self, logs: &[Vec<u8>]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut body = vec![b'['];
+        body.extend(logs.join(&b','));
+        body.push(b']');
+
+        let request = self
+            .client
+            .post(&self.url)
+            .header("DD-API-KEY", &self.config.api_key)
+            .header("Content-Type", "application/json");
+        let response = self.config.compression.apply(request, body)?.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Datadog logs intake error {status}: {body}").into());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for DatadogSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let ddtags = self.config.tags.join(",");
+        let mut delivered = self.delivered.lock().await;
+
+        // requests are cut at whichever of the intake's entry and byte
+        // limits comes first ("[", "]" and a comma between entries included)
+        let mut logs: Vec<Vec<u8>> = Vec::new();
+        let mut ids: Vec<&str> = Vec::new();
+        let mut bytes = 2;
+        let pending: Vec<&LogEntry> = batch
+            .iter()
+            .filter(|entry| !delivered.contains(&entry.id))
+            .collect();
+        for entry in pending {
+            let log = serde_json::to_vec(&json!({
+                "ddsource": self.config.ddsource,
+                "ddtags": ddtags,
+                "hostname": self.config.hostname,
+                "service": entry.service,
+                "status": entry.level.to_string().to_lowercase(),
+                "message": entry.message,
+                "timestamp": entry.timestamp,
+                "log_id": entry.id,
+                "fields": entry.fields,
+                "trace": entry.trace,
+            }))?;
+            if log.len() > MAX_ENTRY_BYTES {
+                warn!(
+                    "Dropping entry {}: {} bytes is over the intake's {MAX_ENTRY_BYTES} byte limit",
+                    entry.id,
+                    log.len()
+                );
+                continue;
+            }
+            if !logs.is_empty()
+                && (logs.len() == MAX_ENTRIES_PER_REQUEST
+                    || bytes + 1 + log.len() > MAX_REQUEST_BYTES)
+            {
+                self.send(&logs).await?;
+                delivered.extend(ids.drain(..).map(str::to_string));
+                logs.clear();
+                bytes = 2;
+            }
+            bytes += log.len() + usize::from(!logs.is_empty());
+            logs.push(log);
+            ids.push(&entry.id);
+        }
+        if !logs.is_empty() {
+            self.send(&logs).await?;
+        }
+        // the whole batch is through, so a retry of it won't come
+        delivered.clear();
+        Ok(())
+    }
+}
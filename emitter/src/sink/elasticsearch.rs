@@ -1,54 +1,399 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use elasticsearch::{
-    BulkOperation, BulkParts, Elasticsearch as EsClient,
+    BulkOperation, BulkParts, Elasticsearch as EsClient, SearchParts,
     auth::Credentials,
-    http::transport::{SingleNodeConnectionPool, TransportBuilder},
+    cert::{Certificate, CertificateValidation},
+    http::transport::{CloudConnectionPool, SingleNodeConnectionPool, TransportBuilder},
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
+use tracing::warn;
 
+use crate::embedding::Distance;
+use crate::embedding::quantize::{self, ClientQuantization};
 use crate::log_entry::LogEntry;
+use crate::search::{self, SearchBackend, SearchError, SearchHit, SearchMode, SearchQuery};
 use crate::sink::Sink;
-use crate::sink::{DEFAULT_INDEX_NAME, DENSE_EMBEDDING_NAME};
+use crate::sink::compression::Compression;
+use crate::sink::target::TargetName;
+use crate::sink::{DEFAULT_INDEX_NAME, DENSE_EMBEDDING_NAME, SPARSE_EMBEDDING_NAME};
 
 fn default_index_name() -> String {
     DEFAULT_INDEX_NAME.to_string()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorIndexType {
+    #[default]
+    Hnsw,
+    /// HNSW over int8-quantized vectors
+    Int8Hnsw,
+    /// HNSW over better binary quantized vectors
+    BbqHnsw,
+}
+
+/// An ingest pipeline installed (or replaced) at startup and applied to every
+/// bulk request, for enrichment on the Elasticsearch side
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IngestPipelineConfig {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Processor definitions as in the ingest API, e.g. `{ set: { field: event.ingested, value: "{{_ingest.timestamp}}" } }`
+    pub processors: Vec<Value>,
+}
+
+/// A model Elasticsearch embeds messages with at ingest, through an
+/// `inference` processor
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EsInferenceConfig {
+    /// A deployed model (e.g. `.multilingual-e5-small`) or inference endpoint id
+    pub model_id: String,
+    /// What the model returns, for the dense_vector mapping
+    pub dimensions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ElasticSearchConfig {
+    /// Ignored when `cloud_id` is set
+    #[serde(default)]
     pub url: String,
-    pub user: String,
-    pub password: String,
+    /// Elastic Cloud deployment id, used instead of `url`
+    pub cloud_id: Option<String>,
+    // credentials: an API key or bearer token takes precedence over basic auth
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// Base64-encoded `id:api_key`, as shown when creating a key
+    pub api_key: Option<String>,
+    pub bearer_token: Option<String>,
+    /// PEM CA certificate to verify the cluster against
+    pub ca_cert_path: Option<String>,
+    /// Skip TLS certificate verification entirely
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+    /// May contain a date pattern (e.g. `logs-%Y.%m.%d`) to roll over to a new
+    /// index (or data stream) per day and/or `{service}` for one per service,
+    /// each created on first write
     #[serde(default = "default_index_name")]
     pub index_name: String,
+    /// Write to a data stream named `index_name` (backed by an index template)
+    /// using `create` operations and an `@timestamp` field
+    #[serde(default)]
+    pub data_stream: bool,
+    /// `index_options.type` of the dense_vector mapping
+    #[serde(default)]
+    pub vector_index_type: VectorIndexType,
+    /// Quantize embeddings before they're sent, into a `byte` (int8) or
+    /// `bit` (binary, Hamming distance) dense_vector, indexed with plain HNSW
+    pub client_quantization: Option<ClientQuantization>,
+    /// `similarity` of the dense_vector mapping; dot product needs
+    /// normalized embeddings (`embedding.normalize`)
+    #[serde(default)]
+    pub distance: Distance,
+    /// HNSW graph degree (server default 16)
+    pub hnsw_m: Option<u32>,
+    /// HNSW candidates considered during index build (server default 100)
+    pub hnsw_ef_construction: Option<u32>,
+    /// Retries for bulk requests and individually rejected (429/5xx) items
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each further attempt
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    pub ingest_pipeline: Option<IngestPipelineConfig>,
+    /// Send only messages and have Elasticsearch embed them, the inference
+    /// processor running first in `ingest_pipeline` (or a pipeline of its own)
+    pub inference: Option<EsInferenceConfig>,
+    /// Install an index template matching every index the sink may write
+    /// (`index_name` with date and `{service}` parts as wildcards), so indices
+    /// created by anything else pick up the same mappings and pipeline
+    #[serde(default)]
+    pub index_template: bool,
+    /// Request body compression (Elasticsearch accepts gzip, not zstd)
+    #[serde(default)]
+    pub compression: Compression,
 }
 
 pub struct ElasticSearchSink {
     config: ElasticSearchConfig,
     client: EsClient,
+    embedding_dim: usize,
+    /// Dimensions of each of `extra_embeddings`, mapped as further
+    /// dense_vector fields
+    extra_dims: BTreeMap<String, usize>,
+    index: TargetName,
 }
 
 impl ElasticSearchSink {
-    pub async fn from_config(config: ElasticSearchConfig, embedding_dim: usize) -> Self {
+    pub async fn from_config(
+        mut config: ElasticSearchConfig,
+        embedding_dim: usize,
+        extra_dims: BTreeMap<String, usize>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         // build the Elasticsearch client
-        let credentials = Credentials::Basic(config.user.clone(), config.password.clone());
-        let conn_pool = SingleNodeConnectionPool::new(config.url.clone().parse().unwrap());
-        let transport = TransportBuilder::new(conn_pool)
-            .auth(credentials)
-            .build()
-            .expect("Failed to create Elasticsearch transport");
+        let mut builder = match &config.cloud_id {
+            Some(cloud_id) => TransportBuilder::new(CloudConnectionPool::new(cloud_id)?),
+            None => TransportBuilder::new(SingleNodeConnectionPool::new(config.url.parse()?)),
+        };
+
+        if let Some(api_key) = &config.api_key {
+            builder = builder.auth(Credentials::EncodedApiKey(api_key.clone()));
+        } else if let Some(token) = &config.bearer_token {
+            builder = builder.auth(Credentials::Bearer(token.clone()));
+        } else if let (Some(user), Some(password)) = (&config.user, &config.password) {
+            builder = builder.auth(Credentials::Basic(user.clone(), password.clone()));
+        }
+
+        if config.insecure_skip_verify {
+            builder = builder.cert_validation(CertificateValidation::None);
+        } else if let Some(path) = &config.ca_cert_path {
+            let ca = Certificate::from_pem(&std::fs::read(path)?)?;
+            builder = builder.cert_validation(CertificateValidation::Full(ca));
+        }
+
+        let gzip = matches!(config.compression, Compression::Gzip);
+        let transport = builder.request_body_compression(gzip).build()?;
         let client = EsClient::new(transport);
 
+        // the embedding is made by the pipeline, ahead of any other processing
+        let mut embedding_dim = embedding_dim;
+        if let Some(inference) = &config.inference {
+            if config.client_quantization.is_some() {
+                warn!("client_quantization is ignored with inference");
+                config.client_quantization = None;
+            }
+            let pipeline = config
+                .ingest_pipeline
+                .get_or_insert_with(|| IngestPipelineConfig {
+                    name: "logstorm-inference".to_string(),
+                    description: "Embeds log messages at ingest".to_string(),
+                    processors: Vec::new(),
+                });
+            pipeline.processors.insert(
+                0,
+                json!({
+                    "inference": {
+                        "model_id": inference.model_id,
+                        "input_output": [{
+                            "input_field": "message",
+                            "output_field": DENSE_EMBEDDING_NAME,
+                        }],
+                    }
+                }),
+            );
+            embedding_dim = inference.dimensions;
+        }
+
+        if config.client_quantization.is_some()
+            && !matches!(config.vector_index_type, VectorIndexType::Hnsw)
+        {
+            warn!(
+                "vector_index_type is ignored with client_quantization, which is indexed with hnsw"
+            );
+        }
+
+        let sink = Self {
+            index: TargetName::new(&config.index_name),
+            config,
+            client,
+            embedding_dim,
+            extra_dims,
+        };
+
+        // the pipeline, template and index are left to `init`, so a dry run
+        // only checks the cluster answers
+        sink.client.ping().send().await?.error_for_status_code()?;
+
+        Ok(sink)
+    }
+
+    async fn ensure_target(&self, name: &str) -> Result<(), elasticsearch::Error> {
+        if self.config.data_stream {
+            Self::ensure_data_stream(&self.client, name, self.mappings("@timestamp")).await
+        } else {
+            Self::ensure_index(&self.client, name, self.mappings("timestamp")).await
+        }
+    }
+
+    async fn put_pipeline(
+        &self,
+        pipeline: &IngestPipelineConfig,
+    ) -> Result<(), elasticsearch::Error> {
+        self.client
+            .ingest()
+            .put_pipeline(elasticsearch::ingest::IngestPutPipelineParts::Id(
+                &pipeline.name,
+            ))
+            .body(json!({
+                "description": pipeline.description,
+                "processors": pipeline.processors,
+            }))
+            .send()
+            .await?
+            .error_for_status_code()?;
+        Ok(())
+    }
+
+    async fn put_index_template(&self) -> Result<(), elasticsearch::Error> {
+        let pattern = self.index.pattern();
+        let template_name = format!(
+            "{}-template",
+            pattern.trim_end_matches(['*', '-', '.', '_'])
+        );
+
+        let mut template = json!({ "mappings": self.mappings("timestamp") });
+        if let Some(pipeline) = &self.config.ingest_pipeline {
+            template["settings"] = json!({ "index.default_pipeline": pipeline.name });
+        }
+
+        self.client
+            .indices()
+            .put_index_template(elasticsearch::indices::IndicesPutIndexTemplateParts::Name(
+                &template_name,
+            ))
+            .body(json!({
+                "index_patterns": [pattern],
+                "priority": 500,
+                "template": template,
+            }))
+            .send()
+            .await?
+            .error_for_status_code()?;
+        Ok(())
+    }
+
+    fn mappings(&self, timestamp_field: &str) -> Value {
+        let mut index_options = match self.config.client_quantization {
+            Some(_) => json!({ "type": VectorIndexType::Hnsw }),
+            None => json!({ "type": self.config.vector_index_type }),
+        };
+        if let Some(m) = self.config.hnsw_m {
+            index_options["m"] = json!(m);
+        }
+        if let Some(ef_construction) = self.config.hnsw_ef_construction {
+            index_options["ef_construction"] = json!(ef_construction);
+        }
+
+        let similarity = match self.config.distance {
+            Distance::Cosine => "cosine",
+            Distance::Dot => "dot_product",
+            Distance::Euclidean => "l2_norm",
+        };
+        let mut dense_vector = json!({
+            "type": "dense_vector",
+            "dims": self.embedding_dim,
+            "index": true,
+            "index_options": index_options,
+            "similarity": similarity,
+        });
+        match self.config.client_quantization {
+            Some(ClientQuantization::Int8) => dense_vector["element_type"] = json!("byte"),
+            Some(ClientQuantization::Binary) => {
+                dense_vector["element_type"] = json!("bit");
+                // Hamming distance, the only one bit vectors have
+                dense_vector["similarity"] = json!("l2_norm");
+            }
+            None => {}
+        }
+
+        let mut mappings = json!({
+            "properties": {
+                timestamp_field: { "type": "date" },
+                "service": { "type": "keyword" },
+                "level": { "type": "keyword" },
+                "message": { "type": "text" },
+                "fields": {
+                    "properties": {
+                        "request_id": { "type": "keyword" },
+                        "user_id": { "type": "keyword" },
+                        "duration_ms": { "type": "long" },
+                        "status_code": { "type": "short" },
+                        "region": { "type": "keyword" },
+                        "pod_name": { "type": "keyword" },
+                        "namespace": { "type": "keyword" },
+                        "node": { "type": "keyword" },
+                        "container_id": { "type": "keyword" },
+                        "host": { "type": "keyword" },
+                        "az": { "type": "keyword" },
+                        "exception": { "type": "text" },
+                        // stored as sent, not indexed: large dumps would
+                        // otherwise add mapped fields by the hundred
+                        "body": { "type": "object", "enabled": false },
+                    }
+                },
+                "trace": {
+                    "properties": {
+                        "trace_id": { "type": "keyword" },
+                        "span_id": { "type": "keyword" },
+                        "parent_span_id": { "type": "keyword" },
+                    }
+                },
+                DENSE_EMBEDDING_NAME: dense_vector,
+                // filled by a `sparse` processor, keyed by term hash
+                SPARSE_EMBEDDING_NAME: { "type": "sparse_vector" },
+            }
+        });
+        // with embedding switched off, entries have no dense vector
+        if self.embedding_dim == 0 {
+            mappings["properties"]
+                .as_object_mut()
+                .expect("mappings have properties")
+                .remove(DENSE_EMBEDDING_NAME);
+        }
+        for (name, dim) in &self.extra_dims {
+            mappings["properties"][name] = json!({
+                "type": "dense_vector",
+                "dims": dim,
+                "index": true,
+                "index_options": index_options,
+                "similarity": similarity,
+            });
+        }
+        mappings
+    }
+
+    /// What's sent of `embedding`: floats, int8 values, or the bits packed
+    /// into a hex string
+    fn stored(&self, embedding: &[f32]) -> Value {
+        match self.config.client_quantization {
+            None => json!(embedding),
+            Some(ClientQuantization::Int8) => json!(quantize::int8(embedding)),
+            Some(ClientQuantization::Binary) => json!(
+                quantize::packed_bits(embedding)
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<String>()
+            ),
+        }
+    }
+
+    async fn ensure_index(
+        client: &EsClient,
+        index_name: &str,
+        mappings: Value,
+    ) -> Result<(), elasticsearch::Error> {
         // create the index if it doesn't exist
         let index_exists = client
             .indices()
             .exists(elasticsearch::indices::IndicesExistsParts::Index(&[
-                &config.index_name,
+                index_name,
             ]))
             .send()
-            .await
-            .expect("Failed to check if index exists")
+            .await?
             .status_code()
             == 200;
 
@@ -56,65 +401,373 @@ impl ElasticSearchSink {
             client
                 .indices()
                 .create(elasticsearch::indices::IndicesCreateParts::Index(
-                    &config.index_name,
+                    index_name,
                 ))
                 .body(json!({
-                    "mappings": {
-                        "properties": {
-                            "timestamp": { "type": "date" },
-                            "service": { "type": "keyword" },
-                            "level": { "type": "keyword" },
-                            "message": { "type": "text" },
-                            DENSE_EMBEDDING_NAME: {
-                                "type": "dense_vector",
-                                "dims": embedding_dim,
-                                "index": true,
-                                "index_options": {
-                                    "type": "hnsw",
-                                }
-                            }
-                        }
-                    }
+                    "mappings": mappings,
                 }))
                 .send()
-                .await
-                .expect("Failed to create index");
+                .await?
+                .error_for_status_code()?;
         }
+        Ok(())
+    }
+
+    /// Send `docs` as a bulk request, retrying items rejected with 429/5xx (and
+    /// whole requests failing in transport or with 429/5xx) with backoff. Items
+    /// rejected for any other reason, e.g. mapping conflicts, fail the batch.
+    async fn bulk(
+        &self,
+        index_name: &str,
+        mut pending: Vec<(String, Value)>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut rejected = Vec::new();
+        let mut backoff = Duration::from_millis(self.config.retry_backoff_ms);
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.client.bulk(BulkParts::Index(index_name));
+            if let Some(pipeline) = &self.config.ingest_pipeline {
+                request = request.pipeline(&pipeline.name);
+            }
 
-        Self { config, client }
+            let operations = pending
+                .iter()
+                .map(|(id, doc)| {
+                    if self.config.data_stream {
+                        // data streams are append-only and only accept `create` ops
+                        BulkOperation::create(id, doc.clone()).into()
+                    } else {
+                        BulkOperation::index(doc.clone()).id(id).routing(id).into()
+                    }
+                })
+                .collect::<Vec<BulkOperation<_>>>();
+
+            let error = match request.body(operations).send().await {
+                Ok(response) if response.status_code().is_success() => {
+                    let body: Value = response.json().await?;
+                    if !body["errors"].as_bool().unwrap_or(false) {
+                        break;
+                    }
+
+                    // items come back in request order, keyed by op type
+                    let items = body["items"].as_array().cloned().unwrap_or_default();
+                    let mut retry = Vec::new();
+                    for (item, doc) in items.iter().zip(pending) {
+                        let result = item.as_object().and_then(|op| op.values().next());
+                        let status = result.and_then(|r| r["status"].as_u64()).unwrap_or(0);
+                        match status {
+                            // 409: a `create` retried after it already went through
+                            200..=299 | 409 => {}
+                            429 | 500..=599 => retry.push(doc),
+                            _ => rejected.push(format!(
+                                "{}: {}",
+                                doc.0,
+                                result.map(|r| r["error"].to_string()).unwrap_or_default()
+                            )),
+                        }
+                    }
+                    pending = retry;
+                    if pending.is_empty() {
+                        break;
+                    }
+                    format!("{} items rejected", pending.len())
+                }
+                Ok(response)
+                    if response.status_code().as_u16() == 429
+                        || response.status_code().is_server_error() =>
+                {
+                    format!("bulk request failed with {}", response.status_code())
+                }
+                Ok(response) => {
+                    // other 4xx won't succeed on retry
+                    let status = response.status_code();
+                    let text = response.text().await.unwrap_or_default();
+                    return Err(
+                        format!("Elasticsearch rejected bulk request: {status} {text}").into(),
+                    );
+                }
+                Err(e) => e.to_string(),
+            };
+
+            if attempt >= self.config.max_retries {
+                return Err(format!(
+                    "Elasticsearch bulk to {index_name} gave up after {} attempts: {error}",
+                    attempt + 1
+                )
+                .into());
+            }
+            attempt += 1;
+            warn!("Elasticsearch bulk to {index_name} failed ({error}), retrying in {backoff:?}");
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+
+        if !rejected.is_empty() {
+            return Err(format!(
+                "Elasticsearch rejected {} documents, first: {}",
+                rejected.len(),
+                rejected[0]
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Install an index template matching the data stream name, then create the
+    /// data stream itself if it doesn't exist yet.
+    async fn ensure_data_stream(
+        client: &EsClient,
+        name: &str,
+        mappings: Value,
+    ) -> Result<(), elasticsearch::Error> {
+        let template_name = format!("{name}-template");
+        client
+            .indices()
+            .put_index_template(elasticsearch::indices::IndicesPutIndexTemplateParts::Name(
+                &template_name,
+            ))
+            .body(json!({
+                "index_patterns": [name],
+                "data_stream": {},
+                // outrank the built-in `logs-*-*` template (priority 100)
+                "priority": 500,
+                "template": {
+                    "mappings": mappings,
+                }
+            }))
+            .send()
+            .await?
+            .error_for_status_code()?;
+
+        let stream_exists = client
+            .indices()
+            .get_data_stream(elasticsearch::indices::IndicesGetDataStreamParts::Name(&[
+                name,
+            ]))
+            .send()
+            .await?
+            .status_code()
+            == 200;
+
+        if !stream_exists {
+            client
+                .indices()
+                .create_data_stream(elasticsearch::indices::IndicesCreateDataStreamParts::Name(
+                    name,
+                ))
+                .send()
+                .await?
+                .error_for_status_code()?;
+        }
+        Ok(())
     }
 }
 
 #[async_trait]
 impl Sink for ElasticSearchSink {
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(pipeline) = &self.config.ingest_pipeline {
+            self.put_pipeline(pipeline).await?;
+        }
+        if self.config.index_template && !self.config.data_stream {
+            self.put_index_template().await?;
+        }
+
+        // templated indices are created as their first entries arrive
+        if self.index.is_static() {
+            self.ensure_target(&self.config.index_name).await?;
+            self.index.mark_created(&self.config.index_name).await;
+        }
+        Ok(())
+    }
+
     async fn write(
         &self,
         batch: &[LogEntry],
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let logs = batch
-            .iter()
-            .map(|entry| {
-                let id = entry.id.clone();
-                BulkOperation::index(json!({
-                    "timestamp": entry.timestamp,
-                    "service": entry.service,
-                    "level": format!("{:?}", entry.level),
-                    "message": entry.message,
-                    DENSE_EMBEDDING_NAME: entry.embedding,
-                }))
-                .id(&id)
-                .routing(&id)
-                .into()
-            })
-            .collect::<Vec<BulkOperation<_>>>();
+        let timestamp_field = self.timestamp_field();
 
+        for (index_name, entries) in self.index.group(batch) {
+            if !self.index.is_created(&index_name).await {
+                self.ensure_target(&index_name).await?;
+                self.index.mark_created(&index_name).await;
+            }
+
+            let docs = entries
+                .into_iter()
+                .map(|entry| {
+                    let mut doc = json!({
+                        timestamp_field: entry.timestamp,
+                        "service": entry.service,
+                        "level": format!("{:?}", entry.level),
+                        "message": entry.message,
+                        "fields": entry.fields,
+                        "trace": entry.trace,
+                    });
+                    if self.embedding_dim > 0 && self.config.inference.is_none() {
+                        doc[DENSE_EMBEDDING_NAME] = self.stored(&entry.embedding);
+                    }
+                    for (name, embedding) in &entry.extra_embeddings {
+                        doc[name] = json!(embedding);
+                    }
+                    if let Some(sparse) = &entry.sparse {
+                        doc[SPARSE_EMBEDDING_NAME] = sparse
+                            .indices
+                            .iter()
+                            .zip(&sparse.values)
+                            .map(|(index, value)| (index.to_string(), json!(value)))
+                            .collect::<serde_json::Map<_, _>>()
+                            .into();
+                    }
+                    (entry.id.clone(), doc)
+                })
+                .collect();
+            self.bulk(&index_name, docs).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // make everything written searchable before the run is reported done
+        let names = self.index.created().await;
+        if names.is_empty() {
+            return Ok(());
+        }
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
         self.client
-            .bulk(BulkParts::Index(&self.config.index_name))
-            .body(logs)
+            .indices()
+            .refresh(elasticsearch::indices::IndicesRefreshParts::Index(&names))
             .send()
-            .await
-            .expect("Failed to write logs to Elasticsearch");
-
+            .await?
+            .error_for_status_code()?;
         Ok(())
     }
 }
+
+impl ElasticSearchSink {
+    fn timestamp_field(&self) -> &'static str {
+        if self.config.data_stream {
+            "@timestamp"
+        } else {
+            "timestamp"
+        }
+    }
+
+    /// Entries matching the query's words by BM25 (`keyword`), or else
+    /// nearest its embedding
+    async fn search_ranked(
+        &self,
+        query: &SearchQuery,
+        embedding: Option<&[f32]>,
+        keyword: bool,
+    ) -> Result<Vec<SearchHit>, SearchError> {
+        let mut filter = Vec::new();
+        if !query.services.is_empty() {
+            filter.push(json!({ "terms": { "service": query.services } }));
+        }
+        if !query.levels.is_empty() {
+            filter.push(json!({ "terms": { "level": query.level_names() } }));
+        }
+        let mut body = json!({
+            "size": query.limit,
+            "_source": [self.timestamp_field(), "service", "level", "message"],
+        });
+        if keyword {
+            body["query"] = json!({
+                "bool": { "must": { "match": { "message": query.text } }, "filter": filter }
+            });
+        } else {
+            let mut knn = json!({
+                "field": DENSE_EMBEDDING_NAME,
+                "k": query.limit,
+                "num_candidates": (query.limit * 5).max(100),
+                "filter": filter,
+            });
+            match (&self.config.inference, embedding) {
+                // embedded by the model that embedded the entries
+                (Some(inference), _) => {
+                    knn["query_vector_builder"] = json!({
+                        "text_embedding": { "model_id": inference.model_id, "model_text": query.text }
+                    });
+                }
+                (None, Some(embedding)) => knn["query_vector"] = self.stored(embedding),
+                (None, None) => return Err("dense search needs the query's embedding".into()),
+            }
+            body["knn"] = knn;
+        }
+
+        let pattern = self.index.pattern();
+        let response: Value = self
+            .client
+            .search(SearchParts::Index(&[&pattern]))
+            .ignore_unavailable(true)
+            .allow_no_indices(true)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status_code()?
+            .json()
+            .await?;
+        let hits = response["hits"]["hits"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        Ok(hits
+            .iter()
+            .map(|hit| {
+                let source = &hit["_source"];
+                let text = |key: &str| source[key].as_str().unwrap_or_default().to_string();
+                SearchHit {
+                    id: hit["_id"].as_str().unwrap_or_default().to_string(),
+                    score: hit["_score"].as_f64().unwrap_or_default(),
+                    timestamp: source[self.timestamp_field()]
+                        .as_str()
+                        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+                        .map(|timestamp| timestamp.with_timezone(&Utc)),
+                    service: text("service"),
+                    level: text("level"),
+                    message: text("message"),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Searches every index the sink may write (its name with date and
+/// `{service}` parts as wildcards), keyword queries by BM25 on `message`, and
+/// fuses hybrid queries' rankings by reciprocal rank, as reciprocal rank
+/// fusion in Elasticsearch itself needs a paid license
+#[async_trait]
+impl SearchBackend for ElasticSearchSink {
+    fn kind(&self) -> &'static str {
+        "elasticsearch"
+    }
+
+    fn embedding_dim(&self) -> usize {
+        self.embedding_dim
+    }
+
+    fn embeds_queries(&self) -> bool {
+        self.config.inference.is_some()
+    }
+
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        embedding: Option<&[f32]>,
+    ) -> Result<Vec<SearchHit>, SearchError> {
+        match query.mode {
+            SearchMode::Keyword => self.search_ranked(query, embedding, true).await,
+            SearchMode::Dense => self.search_ranked(query, embedding, false).await,
+            SearchMode::Hybrid => Ok(search::fuse(
+                vec![
+                    self.search_ranked(query, embedding, false).await?,
+                    self.search_ranked(query, embedding, true).await?,
+                ],
+                query.limit,
+            )),
+        }
+    }
+}
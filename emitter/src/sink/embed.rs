@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::embedding::lazy::LazyEmbeddings;
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+/// Wraps another sink, embedding entries' messages on their way to it
+/// rather than in the emitter, as a shipper-side embedding stage would
+pub struct EmbedSink {
+    inner: Box<dyn Sink>,
+    embedder: Arc<LazyEmbeddings>,
+}
+
+impl EmbedSink {
+    pub fn new(inner: Box<dyn Sink>, embedder: Arc<LazyEmbeddings>) -> Self {
+        Self { inner, embedder }
+    }
+}
+
+#[async_trait]
+impl Sink for EmbedSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let messages: Vec<String> = batch.iter().map(|entry| entry.message.clone()).collect();
+        let embeddings = self.embedder.embed(&messages).await?;
+        let embedded: Vec<LogEntry> = batch
+            .iter()
+            .zip(embeddings)
+            .map(|(entry, embedding)| LogEntry {
+                embedding,
+                ..entry.clone()
+            })
+            .collect();
+        self.inner.write(&embedded).await
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,168 @@
+use std::path::Path;
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnMode {
+    /// One long-lived process receives every batch on its stdin
+    #[default]
+    Once,
+    /// A fresh process per flush; stdin is closed after the batch and the exit code checked
+    PerFlush,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExecConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub mode: SpawnMode,
+}
+
+/// Pipes each batch as JSONL into the stdin of an external command, so any
+/// tool can act as a sink without changes to this crate.
+pub struct ExecSink {
+    config: ExecConfig,
+    /// The running child in `once` mode
+    child: Mutex<Option<Child>>,
+}
+
+impl ExecSink {
+    pub fn from_config(config: ExecConfig) -> std::io::Result<Self> {
+        if !Self::runnable(&config.command) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("command '{}' not found", config.command),
+            ));
+        }
+        Ok(Self {
+            config,
+            child: Mutex::new(None),
+        })
+    }
+
+    /// Whether `command` is a path to a file, or a name found on `PATH`
+    fn runnable(command: &str) -> bool {
+        if command.contains(std::path::MAIN_SEPARATOR) {
+            return Path::new(command).is_file();
+        }
+        std::env::var_os("PATH").is_some_and(|paths| {
+            std::env::split_paths(&paths).any(|dir| dir.join(command).is_file())
+        })
+    }
+
+    fn spawn(&self) -> std::io::Result<Child> {
+        Command::new(&self.config.command)
+            .args(&self.config.args)
+            .stdin(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+    }
+
+    fn jsonl(batch: &[LogEntry]) -> Result<Vec<u8>, serde_json::Error> {
+        let mut buf = Vec::new();
+        for entry in batch {
+            serde_json::to_writer(&mut buf, entry)?;
+            buf.push(b'\n');
+        }
+        Ok(buf)
+    }
+
+    async fn write_per_flush(
+        &self,
+        payload: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut child = self.spawn()?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        stdin.write_all(payload).await?;
+        // closing stdin signals end of input
+        drop(stdin);
+
+        let status = child.wait().await?;
+        if !status.success() {
+            return Err(format!("'{}' exited with {status}", self.config.command).into());
+        }
+        Ok(())
+    }
+
+    async fn write_once(
+        &self,
+        payload: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut guard = self.child.lock().await;
+
+        // surface an exit since the last flush; the next write respawns
+        if let Some(child) = guard.as_mut()
+            && let Some(status) = child.try_wait()?
+        {
+            *guard = None;
+            if !status.success() {
+                return Err(format!("'{}' exited with {status}", self.config.command).into());
+            }
+            info!("'{}' exited cleanly, respawning", self.config.command);
+        }
+
+        if guard.is_none() {
+            *guard = Some(self.spawn()?);
+        }
+        let child = guard.as_mut().expect("child was just spawned");
+        let stdin = child.stdin.as_mut().expect("stdin is piped");
+
+        if let Err(e) = async {
+            stdin.write_all(payload).await?;
+            stdin.flush().await
+        }
+        .await
+        {
+            // a broken pipe usually means the process died; report its exit status if so
+            let status = child.try_wait()?;
+            *guard = None;
+            return Err(match status {
+                Some(status) => format!("'{}' exited with {status}", self.config.command).into(),
+                None => e.into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for ExecSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let payload = Self::jsonl(batch)?;
+        match self.config.mode {
+            SpawnMode::Once => self.write_once(&payload).await,
+            SpawnMode::PerFlush => self.write_per_flush(&payload).await,
+        }
+    }
+
+    /// Close the long-lived child's stdin and wait for it to finish with
+    /// what it was sent, rather than killing it when the sink is dropped
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(mut child) = self.child.lock().await.take() else {
+            return Ok(());
+        };
+        // closing stdin signals end of input
+        drop(child.stdin.take());
+        let status = child.wait().await?;
+        if !status.success() {
+            return Err(format!("'{}' exited with {status}", self.config.command).into());
+        }
+        Ok(())
+    }
+}
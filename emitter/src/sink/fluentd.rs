@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rmpv::Value;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+fn default_port() -> u16 {
+    24224
+}
+
+fn default_tag() -> String {
+    "logstorm.{service}".to_string()
+}
+
+fn default_ack_timeout_ms() -> u64 {
+    5000
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FluentdConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Tag for each event; `{service}` is replaced with the entry's service name
+    #[serde(default = "default_tag")]
+    pub tag: String,
+    /// Ask the server to acknowledge each chunk (at-least-once delivery)
+    #[serde(default)]
+    pub require_ack: bool,
+    #[serde(default = "default_ack_timeout_ms")]
+    pub ack_timeout_ms: u64,
+    /// Ship the embedding vector as part of the record
+    #[serde(default)]
+    pub include_embedding: bool,
+}
+
+/// Writes batches using the Fluentd forward protocol (msgpack over TCP), one
+/// `Forward` mode message per tag so fluentd/fluent-bit can route by service.
+pub struct FluentdSink {
+    config: FluentdConfig,
+    stream: Mutex<Option<TcpStream>>,
+    /// Ids of entries already sent from a batch whose write then failed on
+    /// a later tag, so retrying the batch doesn't send them again
+    delivered: Mutex<HashSet<String>>,
+}
+
+impl FluentdSink {
+    pub async fn from_config(config: FluentdConfig) -> std::io::Result<Self> {
+        super::resolve(&config.host, config.port).await?;
+        Ok(Self {
+            config,
+            stream: Mutex::new(None),
+            delivered: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn tag_for(&self, service: &str) -> String {
+        self.config.tag.replace("{service}", service)
+    }
+
+    /// Encode the timestamp as the forward protocol's `EventTime` extension
+    /// (type 0: big-endian u32 seconds followed by u32 nanoseconds).
+    fn event_time(ts: &DateTime<Utc>) -> Value {
+        let mut buf = Vec::with_capacity(8);
+        buf.extend_from_slice(&(ts.timestamp() as u32).to_be_bytes());
+        buf.extend_from_slice(&ts.timestamp_subsec_nanos().to_be_bytes());
+        Value::Ext(0, buf)
+    }
+
+    /// Convert a structured field's JSON value to its msgpack equivalent
+    fn field_value(value: &serde_json::Value) -> Value {
+        match value {
+            serde_json::Value::Null => Value::Nil,
+            serde_json::Value::Bool(b) => Value::from(*b),
+            serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => Value::from(i),
+                (None, Some(u)) => Value::from(u),
+                _ => Value::from(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Value::from(s.as_str()),
+            serde_json::Value::Array(items) => {
+                Value::Array(items.iter().map(Self::field_value).collect())
+            }
+            serde_json::Value::Object(map) => Value::Map(
+                map.iter()
+                    .map(|(key, value)| (Value::from(key.as_str()), Self::field_value(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn record(&self, entry: &LogEntry) -> Value {
+        let mut record = vec![
+            (Value::from("id"), Value::from(entry.id.as_str())),
+            (Value::from("service"), Value::from(entry.service.as_str())),
+            (Value::from("level"), Value::from(entry.level.to_string())),
+            (Value::from("message"), Value::from(entry.message.as_str())),
+        ];
+        if let Some(trace) = &entry.trace {
+            for (key, id) in trace.ids() {
+                record.push((Value::from(key), Value::from(id)));
+            }
+        }
+        if !entry.fields.is_empty() {
+            record.push((
+                Value::from("fields"),
+                Value::Map(
+                    entry
+                        .fields
+                        .iter()
+                        .map(|(key, value)| (Value::from(key.as_str()), Self::field_value(value)))
+                        .collect(),
+                ),
+            ));
+        }
+        if self.config.include_embedding {
+            record.push((
+                Value::from("embedding"),
+                Value::Array(entry.embedding.iter().map(|&v| Value::F32(v)).collect()),
+            ));
+        }
+        Value::Map(record)
+    }
+
+    /// Encode one forward-mode message: `[tag, [[time, record], ...], option]`.
+    /// Returns the chunk id when an ack was requested.
+    fn encode(
+        &self,
+        tag: String,
+        entries: &[&LogEntry],
+    ) -> Result<(Vec<u8>, Option<String>), rmpv::encode::Error> {
+        let events = entries
+            .iter()
+            .map(|entry| Value::Array(vec![Self::event_time(&entry.timestamp), self.record(entry)]))
+            .collect();
+
+        let mut option = vec![(Value::from("size"), Value::from(entries.len()))];
+        let chunk = self
+            .config
+            .require_ack
+            .then(|| Uuid::new_v4().simple().to_string());
+        if let Some(chunk) = &chunk {
+            option.push((Value::from("chunk"), Value::from(chunk.as_str())));
+        }
+
+        let message = Value::Array(vec![
+            Value::from(tag),
+            Value::Array(events),
+            Value::Map(option),
+        ]);
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, &message)?;
+        Ok((buf, chunk))
+    }
+
+    async fn send(
+        &self,
+        stream: &mut TcpStream,
+        message: &[u8],
+        chunk: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        stream.write_all(message).await?;
+
+        let Some(chunk) = chunk else {
+            return Ok(());
+        };
+
+        // the ack is a small msgpack map `{"ack": chunk}`; keep reading until it decodes
+        let timeout = Duration::from_millis(self.config.ack_timeout_ms);
+        let mut buf = Vec::new();
+        let response = tokio::time::timeout(timeout, async {
+            loop {
+                let mut read = [0u8; 256];
+                let n = stream.read(&mut read).await?;
+                if n == 0 {
+                    return Err("fluentd closed the connection before acking".into());
+                }
+                buf.extend_from_slice(&read[..n]);
+                if let Ok(value) = rmpv::decode::read_value(&mut buf.as_slice()) {
+                    return Ok::<_, Box<dyn std::error::Error + Send + Sync>>(value);
+                }
+            }
+        })
+        .await
+        .map_err(|_| format!("timed out waiting for fluentd ack of chunk {chunk}"))??;
+
+        let acked = response
+            .as_map()
+            .and_then(|m| m.iter().find(|(k, _)| k.as_str() == Some("ack")))
+            .and_then(|(_, v)| v.as_str());
+        if acked != Some(chunk) {
+            return Err(format!("unexpected fluentd ack for chunk {chunk}: {response}").into());
+        }
+        Ok(())
+    }
+
+    /// Send one tag's message, reusing the open connection and reconnecting
+    /// once if it has gone away
+    async fn deliver(
+        &self,
+        stream: &mut Option<TcpStream>,
+        message: &[u8],
+        chunk: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut attempts = 0;
+        loop {
+            if stream.is_none() {
+                // a tuple rather than `host:port`, so IPv6 literals like `::1` work
+                let addr = (self.config.host.as_str(), self.config.port);
+                *stream = Some(TcpStream::connect(addr).await?);
+            }
+            let conn = stream.as_mut().expect("connection was just established");
+            match self.send(conn, message, chunk).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempts == 0 => {
+                    warn!("Fluentd write failed, reconnecting: {e}");
+                    *stream = None;
+                    attempts += 1;
+                }
+                Err(e) => {
+                    *stream = None;
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for FluentdSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut delivered = self.delivered.lock().await;
+        let mut by_tag: BTreeMap<String, Vec<&LogEntry>> = BTreeMap::new();
+        for entry in batch.iter().filter(|entry| !delivered.contains(&entry.id)) {
+            by_tag
+                .entry(self.tag_for(&entry.service))
+                .or_default()
+                .push(entry);
+        }
+
+        let mut stream = self.stream.lock().await;
+        for (tag, entries) in by_tag {
+            let (message, chunk) = self.encode(tag, &entries)?;
+            self.deliver(&mut stream, &message, chunk.as_deref())
+                .await?;
+            delivered.extend(entries.iter().map(|entry| entry.id.clone()));
+        }
+        // the whole batch is through, so a retry of it won't come
+        delivered.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use std::io::Write as _;
+
+use async_trait::async_trait;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::log_entry::{LogEntry, LogLevel};
+use crate::sink::Sink;
+
+/// Magic bytes prefixing every chunk of a chunked GELF UDP message
+const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+/// magic (2) + message id (8) + sequence number (1) + sequence count (1)
+const CHUNK_HEADER_LEN: usize = 12;
+/// Graylog discards messages split into more chunks than this
+const MAX_CHUNKS: usize = 128;
+
+fn default_port() -> u16 {
+    12201
+}
+
+fn default_host_field() -> String {
+    "logstorm".to_string()
+}
+
+fn default_chunk_size() -> usize {
+    // Graylog's recommended size for LAN setups; use ~1420 across a WAN
+    8192
+}
+
+fn default_compress() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GelfTransport {
+    Udp,
+    Tcp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GelfConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub transport: GelfTransport,
+    /// Value of the GELF `host` field
+    #[serde(default = "default_host_field")]
+    pub source: String,
+    /// Maximum UDP datagram size, including the chunk header
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Gzip UDP payloads (TCP inputs don't support compression)
+    #[serde(default = "default_compress")]
+    pub compress: bool,
+}
+
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+pub struct GelfSink {
+    config: GelfConfig,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl GelfSink {
+    pub async fn from_config(config: GelfConfig) -> std::io::Result<Self> {
+        super::resolve(&config.host, config.port).await?;
+        Ok(Self {
+            config,
+            connection: Mutex::new(None),
+        })
+    }
+
+    /// GELF levels are syslog severities
+    fn level(level: &LogLevel) -> u8 {
+        match level {
+            LogLevel::Debug => 7,
+            LogLevel::Info => 6,
+            LogLevel::Warn => 4,
+            LogLevel::Error => 3,
+        }
+    }
+
+    fn encode(&self, entry: &LogEntry) -> Vec<u8> {
+        let mut message = json!({
+            "version": "1.1",
+            "host": self.config.source,
+            "short_message": entry.message,
+            "timestamp": entry.timestamp.timestamp_micros() as f64 / 1_000_000.0,
+            "level": Self::level(&entry.level),
+            // `_id` is reserved, and dropped by Graylog
+            "_log_id": entry.id,
+            "_service": entry.service,
+            "_level_name": entry.level.to_string(),
+        });
+        // stack traces are what full_message is for
+        if let Some(exception) = entry.fields.get("exception") {
+            message["full_message"] = exception.clone();
+        }
+        // GELF only takes flat additional fields, prefixed with an underscore;
+        // those that would clash with ours or the reserved `_id` are left out
+        let additional = message.as_object_mut().expect("message is an object");
+        let trace_ids = entry.trace.iter().flat_map(|trace| trace.ids());
+        let fields = entry
+            .fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()));
+        for (key, value) in trace_ids.map(|(key, id)| (key, json!(id))).chain(fields) {
+            if key != "id" {
+                additional.entry(format!("_{key}")).or_insert(value);
+            }
+        }
+        message.to_string().into_bytes()
+    }
+
+    /// Split a UDP payload into GELF chunks if it doesn't fit in one datagram.
+    fn chunk(&self, payload: Vec<u8>) -> Result<Vec<Vec<u8>>, String> {
+        if payload.len() <= self.config.chunk_size {
+            return Ok(vec![payload]);
+        }
+
+        let body_size = self
+            .config
+            .chunk_size
+            .saturating_sub(CHUNK_HEADER_LEN)
+            .max(1);
+        let count = payload.len().div_ceil(body_size);
+        if count > MAX_CHUNKS {
+            return Err(format!(
+                "GELF message of {} bytes needs {count} chunks (max {MAX_CHUNKS})",
+                payload.len()
+            ));
+        }
+
+        let message_id: [u8; 8] = rand::random();
+        Ok(payload
+            .chunks(body_size)
+            .enumerate()
+            .map(|(seq, body)| {
+                let mut chunk = Vec::with_capacity(CHUNK_HEADER_LEN + body.len());
+                chunk.extend_from_slice(&CHUNK_MAGIC);
+                chunk.extend_from_slice(&message_id);
+                chunk.push(seq as u8);
+                chunk.push(count as u8);
+                chunk.extend_from_slice(body);
+                chunk
+            })
+            .collect())
+    }
+
+    async fn connect(&self) -> std::io::Result<Connection> {
+        // a tuple rather than `host:port`, so IPv6 literals like `::1` work
+        let (host, port) = (self.config.host.as_str(), self.config.port);
+        match self.config.transport {
+            GelfTransport::Udp => Ok(Connection::Udp(super::connect_udp(host, port).await?)),
+            GelfTransport::Tcp => Ok(Connection::Tcp(TcpStream::connect((host, port)).await?)),
+        }
+    }
+
+    async fn send(
+        &self,
+        connection: &mut Connection,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match connection {
+            Connection::Udp(socket) => {
+                for entry in batch {
+                    let mut payload = self.encode(entry);
+                    if self.config.compress {
+                        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+                        encoder.write_all(&payload)?;
+                        payload = encoder.finish()?;
+                    }
+                    // too big for GELF's chunking: drop it rather than fail
+                    // (and resend) the whole batch
+                    match self.chunk(payload) {
+                        Ok(chunks) => {
+                            for chunk in chunks {
+                                socket.send(&chunk).await?;
+                            }
+                        }
+                        Err(e) => warn!("Dropping entry {}: {e}", entry.id),
+                    }
+                }
+            }
+            Connection::Tcp(stream) => {
+                // TCP frames are null-byte delimited and must not be compressed
+                let mut framed = Vec::new();
+                for entry in batch {
+                    framed.extend_from_slice(&self.encode(entry));
+                    framed.push(0);
+                }
+                stream.write_all(&framed).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for GelfSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut connection = self.connection.lock().await;
+        if connection.is_none() {
+            *connection = Some(self.connect().await?);
+        }
+
+        let conn = connection
+            .as_mut()
+            .expect("connection was just established");
+        if let Err(e) = self.send(conn, batch).await {
+            // the input may have dropped the connection; retry once on a fresh one
+            warn!("GELF write failed, reconnecting: {e}");
+            *connection = None;
+            let mut conn = self.connect().await?;
+            self.send(&mut conn, batch).await?;
+            *connection = Some(conn);
+        }
+        Ok(())
+    }
+}
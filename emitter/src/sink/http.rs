@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+use crate::sink::compression::Compression;
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+fn default_timeout_ms() -> u64 {
+    30_000
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HttpConfig {
+    pub url: String,
+    /// Extra request headers, e.g. `Authorization`
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Request body compression, sent with the matching `Content-Encoding`
+    #[serde(default)]
+    pub compression: Compression,
+    /// Retries on 5xx responses and transport errors
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each further attempt
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+/// POSTs each flushed batch as a JSON array of entries to a configurable URL.
+pub struct HttpSink {
+    config: HttpConfig,
+    client: reqwest::Client,
+}
+
+impl HttpSink {
+    pub fn from_config(config: HttpConfig) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+        Ok(Self { config, client })
+    }
+}
+
+#[async_trait]
+impl Sink for HttpSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let body = self.config.compression.encode(serde_json::to_vec(batch)?)?;
+        let mut backoff = Duration::from_millis(self.config.retry_backoff_ms);
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self
+                .client
+                .post(&self.config.url)
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+            if let Some(encoding) = self.config.compression.content_encoding() {
+                request = request.header("Content-Encoding", encoding);
+            }
+            for (key, value) in &self.config.headers {
+                request = request.header(key, value);
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) if response.status().is_server_error() => {
+                    format!("server error {}", response.status())
+                }
+                Ok(response) => {
+                    // 4xx won't succeed on retry
+                    let status = response.status();
+                    let text = response.text().await.unwrap_or_default();
+                    return Err(format!("HTTP sink request rejected: {status} {text}").into());
+                }
+                Err(e) => e.to_string(),
+            };
+
+            if attempt >= self.config.max_retries {
+                return Err(
+                    format!("HTTP sink gave up after {} attempts: {error}", attempt + 1).into(),
+                );
+            }
+            attempt += 1;
+            warn!(
+                "HTTP sink POST to {} failed ({error}), retrying in {backoff:?}",
+                self.config.url
+            );
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, FixedSizeListArray, Float32Array, RecordBatch, RecordBatchIterator, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, TimeUnit};
+use async_trait::async_trait;
+use lancedb::{Connection, Table};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+use crate::sink::{DEFAULT_INDEX_NAME, DENSE_EMBEDDING_NAME};
+
+fn default_table_name() -> String {
+    DEFAULT_INDEX_NAME.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LanceDbConfig {
+    /// Directory (or object store URI) holding the LanceDB database
+    pub uri: String,
+    #[serde(default = "default_table_name")]
+    pub table_name: String,
+}
+
+pub struct LanceDbSink {
+    config: LanceDbConfig,
+    connection: Connection,
+    schema: Arc<Schema>,
+    table: OnceCell<Table>,
+}
+
+impl LanceDbSink {
+    pub async fn from_config(
+        config: LanceDbConfig,
+        embedding_dim: usize,
+    ) -> Result<Self, lancedb::Error> {
+        let connection = lancedb::connect(&config.uri).execute().await?;
+
+        let mut fields = vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                false,
+            ),
+            Field::new("service", DataType::Utf8, false),
+            Field::new("level", DataType::Utf8, false),
+            Field::new("message", DataType::Utf8, false),
+            // structured fields as a JSON object
+            Field::new("fields", DataType::Utf8, false),
+            Field::new("trace_id", DataType::Utf8, true),
+            Field::new("span_id", DataType::Utf8, true),
+            Field::new("parent_span_id", DataType::Utf8, true),
+        ];
+        // no embedding column when embedding is switched off
+        if embedding_dim > 0 {
+            fields.push(Field::new(
+                DENSE_EMBEDDING_NAME,
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    embedding_dim as i32,
+                ),
+                true,
+            ));
+        }
+        let schema = Arc::new(Schema::new(fields));
+
+        Ok(Self {
+            config,
+            connection,
+            schema,
+            table: OnceCell::new(),
+        })
+    }
+
+    /// Open the table if it already exists, otherwise create it empty with the
+    /// sink's schema. Deferred to the first write so that simply configuring
+    /// the sink never touches the database directory.
+    async fn table(&self) -> Result<&Table, lancedb::Error> {
+        self.table
+            .get_or_try_init(|| async {
+                let existing = self.connection.table_names().execute().await?;
+                if existing.contains(&self.config.table_name) {
+                    self.connection
+                        .open_table(&self.config.table_name)
+                        .execute()
+                        .await
+                } else {
+                    self.connection
+                        .create_empty_table(&self.config.table_name, self.schema.clone())
+                        .execute()
+                        .await
+                }
+            })
+            .await
+    }
+
+    fn to_record_batch(&self, batch: &[LogEntry]) -> Result<RecordBatch, ArrowError> {
+        let ids = StringArray::from_iter_values(batch.iter().map(|e| e.id.as_str()));
+        let timestamps = TimestampMicrosecondArray::from_iter_values(
+            batch.iter().map(|e| e.timestamp.timestamp_micros()),
+        )
+        .with_timezone("UTC");
+        let services = StringArray::from_iter_values(batch.iter().map(|e| e.service.as_str()));
+        let levels = StringArray::from_iter_values(batch.iter().map(|e| format!("{:?}", e.level)));
+        let messages = StringArray::from_iter_values(batch.iter().map(|e| e.message.as_str()));
+        let fields = StringArray::from_iter_values(
+            batch
+                .iter()
+                .map(|e| serde_json::to_string(&e.fields).unwrap_or_default()),
+        );
+
+        let traces = || batch.iter().map(|e| e.trace.as_ref());
+        let trace_ids = StringArray::from_iter(traces().map(|t| t.map(|t| t.trace_id.as_str())));
+        let span_ids = StringArray::from_iter(traces().map(|t| t.map(|t| t.span_id.as_str())));
+        let parent_span_ids =
+            StringArray::from_iter(traces().map(|t| t.and_then(|t| t.parent_span_id.as_deref())));
+
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(ids),
+            Arc::new(timestamps),
+            Arc::new(services),
+            Arc::new(levels),
+            Arc::new(messages),
+            Arc::new(fields),
+            Arc::new(trace_ids),
+            Arc::new(span_ids),
+            Arc::new(parent_span_ids),
+        ];
+        if let Ok(dense) = self.schema.field_with_name(DENSE_EMBEDDING_NAME) {
+            let DataType::FixedSizeList(item_field, dim) = dense.data_type() else {
+                unreachable!("embedding column is always a fixed size list");
+            };
+            // flatten the embeddings; try_new rejects any entry whose length doesn't match the table
+            let values = Float32Array::from_iter_values(
+                batch.iter().flat_map(|e| e.embedding.iter().copied()),
+            );
+            columns.push(Arc::new(FixedSizeListArray::try_new(
+                item_field.clone(),
+                *dim,
+                Arc::new(values),
+                None,
+            )?));
+        }
+
+        RecordBatch::try_new(self.schema.clone(), columns)
+    }
+}
+
+#[async_trait]
+impl Sink for LanceDbSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let record_batch = self.to_record_batch(batch)?;
+        let reader = RecordBatchIterator::new(vec![Ok(record_batch)], self.schema.clone());
+
+        self.table().await?.add(Box::new(reader)).execute().await?;
+
+        Ok(())
+    }
+}
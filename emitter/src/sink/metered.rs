@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::log_entry::LogEntry;
+use crate::metrics;
+use crate::report::SinkTally;
+use crate::sink::Sink;
+
+/// Wraps another sink, recording written/failed entries and write latency
+/// under the sink's name, as metrics, for the dashboard and for the
+/// end-of-run report
+pub struct MeteredSink {
+    tally: Arc<SinkTally>,
+    inner: Box<dyn Sink>,
+}
+
+impl MeteredSink {
+    pub fn new(tally: Arc<SinkTally>, inner: Box<dyn Sink>) -> Self {
+        Self { tally, inner }
+    }
+}
+
+#[async_trait]
+impl Sink for MeteredSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let start = Instant::now();
+        let result = self.inner.write(batch).await;
+        let latency = start.elapsed();
+        metrics::sink_write(self.tally.name(), batch.len(), latency, result.is_ok());
+        self.tally.record(batch, result.is_ok());
+        self.tally
+            .record_write(latency, result.as_ref().err().map(|e| e.to_string()));
+        result
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
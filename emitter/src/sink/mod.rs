@@ -1,20 +1,113 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use tracing::{debug, error, info, warn};
 
 use crate::log_entry::LogEntry;
 
+// shared by the feature-gated sinks, so unused in a default build
+#[allow(dead_code)]
 const DEFAULT_INDEX_NAME: &str = "logs";
+#[allow(dead_code)]
 const DENSE_EMBEDDING_NAME: &str = "dense";
+#[allow(dead_code)]
 const SPARSE_EMBEDDING_NAME: &str = "bm25";
 
+pub mod adaptive;
+pub mod embed;
+pub mod metered;
+pub mod null;
+pub mod process;
+pub mod rate_limit;
+pub mod retry;
+pub mod route;
+pub mod truncate;
+
 #[cfg(feature = "dashboard")]
 pub mod dashboard;
 #[cfg(feature = "elasticsearch")]
 pub mod elasticsearch;
+#[cfg(feature = "lancedb")]
+pub mod lancedb;
 #[cfg(feature = "qdrant")]
 pub mod qdrant;
 #[cfg(feature = "pgvector")]
 pub mod pgvector;
+#[cfg(feature = "vespa")]
+pub mod vespa;
+#[cfg(feature = "fluentd")]
+pub mod fluentd;
+#[cfg(feature = "syslog")]
+pub mod syslog;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+#[cfg(feature = "splunk")]
+pub mod splunk;
+#[cfg(feature = "datadog")]
+pub mod datadog;
+#[cfg(feature = "gelf")]
+pub mod gelf;
+#[cfg(feature = "cloudwatch")]
+pub mod cloudwatch;
+#[cfg(feature = "cassandra")]
+pub mod cassandra;
+#[cfg(feature = "socket")]
+pub mod socket;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "exec")]
+pub mod exec;
+#[cfg(any(feature = "elasticsearch", feature = "pgvector", feature = "qdrant"))]
+mod target;
+#[cfg(any(
+    feature = "datadog",
+    feature = "elasticsearch",
+    feature = "http",
+    feature = "otlp",
+    feature = "splunk"
+))]
+pub mod compression;
+
+/// Check `host` resolves, so a sink that only connects on its first write
+/// still fails to initialize on a bad address
+#[cfg(any(
+    feature = "fluentd",
+    feature = "gelf",
+    feature = "socket",
+    feature = "syslog"
+))]
+async fn resolve(host: &str, port: u16) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+    let mut addrs = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| Error::new(e.kind(), format!("can't resolve {host}:{port}: {e}")))?;
+    match addrs.next() {
+        Some(_) => Ok(()),
+        None => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("{host}:{port} resolves to no address"),
+        )),
+    }
+}
+
+/// A UDP socket connected to `host`, bound to the unspecified address of
+/// the same family as the first address `host` resolves to
+#[cfg(any(feature = "gelf", feature = "socket", feature = "syslog"))]
+async fn connect_udp(host: &str, port: u16) -> std::io::Result<tokio::net::UdpSocket> {
+    let target = tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{host}:{port} resolves to no address"),
+            )
+        })?;
+    let local = if target.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+    let socket = tokio::net::UdpSocket::bind(local).await?;
+    socket.connect(target).await?;
+    Ok(socket)
+}
 
 #[async_trait]
 pub trait Sink: Send + Sync {
@@ -22,6 +115,46 @@ pub trait Sink: Send + Sync {
         &self,
         batch: &[LogEntry],
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Called once before the first write
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+
+    /// Called once the final batch has been written, to make everything
+    /// written durable and visible (e.g. refresh an index)
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+
+    /// Called once after `flush`, for work deferred to the end of a run
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+/// A sink shared with something else that needs it, e.g. to search what it
+/// wrote
+#[async_trait]
+impl<T: Sink + ?Sized> Sink for Arc<T> {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        (**self).write(batch).await
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        (**self).init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        (**self).flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        (**self).close().await
+    }
 }
 
 /// A simple sink that writes logs to stdout using the `tracing` crate. Its
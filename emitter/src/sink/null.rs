@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NullConfig {
+    /// Simulated write latency per batch
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Uniform random extra latency in `[0, latency_jitter_ms]` added per batch
+    #[serde(default)]
+    pub latency_jitter_ms: u64,
+}
+
+/// Discards every batch, only counting what it was given. Useful for
+/// benchmarking the emitter/buffer pipeline without a backend (or the logging
+/// overhead of `StdoutSink`) in the loop.
+pub struct NullSink {
+    config: NullConfig,
+    batches: AtomicU64,
+    entries: AtomicU64,
+}
+
+impl NullSink {
+    pub fn from_config(config: NullConfig) -> Self {
+        Self {
+            config,
+            batches: AtomicU64::new(0),
+            entries: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of batches written so far
+    pub fn batches(&self) -> u64 {
+        self.batches.load(Ordering::Relaxed)
+    }
+
+    /// Number of entries written so far
+    pub fn entries(&self) -> u64 {
+        self.entries.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl Sink for NullSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut latency_ms = self.config.latency_ms;
+        if self.config.latency_jitter_ms > 0 {
+            latency_ms += rand::thread_rng().gen_range(0..=self.config.latency_jitter_ms);
+        }
+        if latency_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+        }
+
+        let batches = self.batches.fetch_add(1, Ordering::Relaxed) + 1;
+        let entries = self
+            .entries
+            .fetch_add(batch.len() as u64, Ordering::Relaxed)
+            + batch.len() as u64;
+        debug!(
+            "Null sink discarded {} entries ({entries} in {batches} batches so far)",
+            batch.len()
+        );
+        Ok(())
+    }
+}
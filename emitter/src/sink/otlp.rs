@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::tonic::collector::logs::v1::logs_service_client::LogsServiceClient;
+use opentelemetry_proto::tonic::common::v1::{
+    AnyValue, InstrumentationScope, KeyValue, any_value::Value,
+};
+use opentelemetry_proto::tonic::logs::v1::{LogRecord, ResourceLogs, ScopeLogs, SeverityNumber};
+use opentelemetry_proto::tonic::resource::v1::Resource;
+use prost::Message;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::transport::Channel;
+
+use crate::log_entry::{LogEntry, LogLevel};
+use crate::sink::Sink;
+use crate::sink::compression::Compression;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+pub enum OtlpProtocol {
+    #[default]
+    #[serde(rename = "grpc")]
+    Grpc,
+    #[serde(rename = "http/protobuf")]
+    HttpProtobuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OtlpConfig {
+    /// Collector endpoint, e.g. `http://localhost:4317` (gRPC) or `http://localhost:4318` (HTTP)
+    pub endpoint: String,
+    #[serde(default)]
+    pub protocol: OtlpProtocol,
+    /// Extra headers (or gRPC metadata) sent with every export, e.g. auth tokens
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Request body compression, `http/protobuf` only
+    #[serde(default)]
+    pub compression: Compression,
+}
+
+enum Exporter {
+    Grpc(LogsServiceClient<Channel>),
+    Http(reqwest::Client),
+}
+
+/// Exports each batch as OTLP LogRecords, grouped into one `ResourceLogs`
+/// per service so collectors see a distinct `service.name` resource.
+pub struct OtlpSink {
+    config: OtlpConfig,
+    exporter: Exporter,
+}
+
+impl OtlpSink {
+    pub fn from_config(
+        config: OtlpConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let exporter = match config.protocol {
+            OtlpProtocol::Grpc => {
+                let channel = Channel::from_shared(config.endpoint.clone())
+                    .map_err(|e| format!("invalid endpoint '{}': {e}", config.endpoint))?
+                    .connect_lazy();
+                Exporter::Grpc(LogsServiceClient::new(channel))
+            }
+            OtlpProtocol::HttpProtobuf => Exporter::Http(reqwest::Client::builder().build()?),
+        };
+        Ok(Self { config, exporter })
+    }
+
+    fn severity(level: &LogLevel) -> SeverityNumber {
+        match level {
+            LogLevel::Debug => SeverityNumber::Debug,
+            LogLevel::Info => SeverityNumber::Info,
+            LogLevel::Warn => SeverityNumber::Warn,
+            LogLevel::Error => SeverityNumber::Error,
+        }
+    }
+
+    fn string_attribute(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue {
+                value: Some(Value::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    /// A structured field as a log attribute, keeping numbers and booleans typed
+    fn field_attribute(key: &str, value: &serde_json::Value) -> KeyValue {
+        let value = match value {
+            serde_json::Value::Bool(b) => Value::BoolValue(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::IntValue(i),
+                None => Value::DoubleValue(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Value::StringValue(s.clone()),
+            other => Value::StringValue(other.to_string()),
+        };
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue { value: Some(value) }),
+        }
+    }
+
+    /// Trace and span ids go over the wire as raw bytes
+    fn hex_bytes(id: &str) -> Vec<u8> {
+        (0..id.len() / 2)
+            .filter_map(|i| u8::from_str_radix(id.get(i * 2..i * 2 + 2)?, 16).ok())
+            .collect()
+    }
+
+    fn build_request(batch: &[LogEntry]) -> ExportLogsServiceRequest {
+        let observed = Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+
+        let mut by_service: BTreeMap<&str, Vec<LogRecord>> = BTreeMap::new();
+        for entry in batch {
+            let severity = Self::severity(&entry.level);
+            let mut attributes: Vec<KeyValue> =
+                std::iter::once(Self::string_attribute("log.record.uid", &entry.id))
+                    .chain(
+                        entry
+                            .fields
+                            .iter()
+                            .map(|(key, value)| Self::field_attribute(key, value)),
+                    )
+                    .collect();
+            let (trace_id, span_id) = match &entry.trace {
+                Some(trace) => {
+                    if let Some(parent) = &trace.parent_span_id {
+                        attributes.push(Self::string_attribute("parent_span_id", parent));
+                    }
+                    (
+                        Self::hex_bytes(&trace.trace_id),
+                        Self::hex_bytes(&trace.span_id),
+                    )
+                }
+                None => (Vec::new(), Vec::new()),
+            };
+            by_service
+                .entry(entry.service.as_str())
+                .or_default()
+                .push(LogRecord {
+                    time_unix_nano: entry.timestamp.timestamp_nanos_opt().unwrap_or_default()
+                        as u64,
+                    observed_time_unix_nano: observed,
+                    severity_number: severity as i32,
+                    severity_text: entry.level.to_string(),
+                    body: Some(AnyValue {
+                        value: Some(Value::StringValue(entry.message.clone())),
+                    }),
+                    attributes,
+                    trace_id,
+                    span_id,
+                    ..Default::default()
+                });
+        }
+
+        let resource_logs = by_service
+            .into_iter()
+            .map(|(service, log_records)| ResourceLogs {
+                resource: Some(Resource {
+                    attributes: vec![Self::string_attribute("service.name", service)],
+                    ..Default::default()
+                }),
+                scope_logs: vec![ScopeLogs {
+                    scope: Some(InstrumentationScope {
+                        name: env!("CARGO_PKG_NAME").to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        ..Default::default()
+                    }),
+                    log_records,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+            .collect();
+
+        ExportLogsServiceRequest { resource_logs }
+    }
+}
+
+#[async_trait]
+impl Sink for OtlpSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let request = Self::build_request(batch);
+
+        match &self.exporter {
+            Exporter::Grpc(client) => {
+                let mut request = tonic::Request::new(request);
+                for (key, value) in &self.config.headers {
+                    request.metadata_mut().insert(
+                        MetadataKey::from_bytes(key.to_lowercase().as_bytes())?,
+                        MetadataValue::try_from(value.as_str())?,
+                    );
+                }
+                let response = client.clone().export(request).await?.into_inner();
+                if let Some(partial) = response.partial_success
+                    && partial.rejected_log_records > 0
+                {
+                    return Err(format!(
+                        "OTLP collector rejected {} log records: {}",
+                        partial.rejected_log_records, partial.error_message
+                    )
+                    .into());
+                }
+            }
+            Exporter::Http(client) => {
+                let url = format!("{}/v1/logs", self.config.endpoint.trim_end_matches('/'));
+                let mut http_request = client
+                    .post(url)
+                    .header("Content-Type", "application/x-protobuf");
+                for (key, value) in &self.config.headers {
+                    http_request = http_request.header(key, value);
+                }
+                self.config
+                    .compression
+                    .apply(http_request, request.encode_to_vec())?
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,24 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
 use async_trait::async_trait;
-use pgvector::Vector;
+use chrono::{Days, NaiveDate, Utc};
+use pgvector::{Bit, HalfVector, SparseVector, Vector};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
+use sqlx::postgres::{PgPoolOptions, PgRow};
+use sqlx::{PgPool, Row};
+use tokio::sync::Mutex;
+use tracing::info;
 
+use crate::embedding::Distance;
+use crate::embedding::quantize::{self, ClientQuantization};
 use crate::log_entry::LogEntry;
+use crate::processor::sparse;
+use crate::search::{self, SearchBackend, SearchError, SearchHit, SearchMode, SearchQuery};
 use crate::sink::Sink;
 use crate::sink::DEFAULT_INDEX_NAME;
+use crate::sink::target::TargetName;
 
 fn default_table_name() -> String {
     DEFAULT_INDEX_NAME.to_string()
@@ -28,7 +40,87 @@ fn default_database() -> String {
     "postgres".to_string()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What searches filter on, given services as `$2` and levels as `$3`
+const SEARCH_FILTERS: &str = "(cardinality($2::text[]) = 0 OR service = ANY($2)) \
+     AND (cardinality($3::text[]) = 0 OR level = ANY($3))";
+
+/// 2000-01-01T00:00:00Z, the Postgres timestamp epoch, in Unix microseconds
+const PG_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+fn default_partitions_ahead() -> u32 {
+    1
+}
+
+fn default_sparse_dim() -> u32 {
+    1 << 16
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorType {
+    /// 32-bit floats
+    #[default]
+    Vector,
+    /// 16-bit floats, half the storage
+    Halfvec,
+    /// One bit per dimension (sign of each component), compared by Hamming distance
+    Bit,
+}
+
+impl VectorType {
+    fn column_type(self) -> &'static str {
+        match self {
+            VectorType::Vector => "vector",
+            VectorType::Halfvec => "halfvec",
+            VectorType::Bit => "bit",
+        }
+    }
+
+    /// Operator class indexing the column for `distance`; bits are always
+    /// compared by Hamming distance
+    fn index_ops(self, distance: Distance) -> &'static str {
+        match (self, distance) {
+            (VectorType::Vector, Distance::Cosine) => "vector_cosine_ops",
+            (VectorType::Vector, Distance::Dot) => "vector_ip_ops",
+            (VectorType::Vector, Distance::Euclidean) => "vector_l2_ops",
+            (VectorType::Halfvec, Distance::Cosine) => "halfvec_cosine_ops",
+            (VectorType::Halfvec, Distance::Dot) => "halfvec_ip_ops",
+            (VectorType::Halfvec, Distance::Euclidean) => "halfvec_l2_ops",
+            (VectorType::Bit, _) => "bit_hamming_ops",
+        }
+    }
+}
+
+/// For the optional `sparsevec` column: the entry's sparse embedding (from a
+/// `sparse` processor) folded into `dim` dimensions, or else hashed term
+/// frequencies of its message
+fn sparse_vector(entry: &LogEntry, dim: u32) -> SparseVector {
+    let mut weights: HashMap<i32, f32> = HashMap::new();
+    match &entry.sparse {
+        Some(embedding) => {
+            for (index, value) in embedding.indices.iter().zip(&embedding.values) {
+                *weights.entry((index % dim) as i32).or_default() += value;
+            }
+        }
+        None => {
+            for term in sparse::terms(&entry.message) {
+                *weights.entry((sparse::term_index(&term) % dim) as i32).or_default() += 1.0;
+            }
+        }
+    }
+    SparseVector::from_map(&weights, dim as i32)
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexType {
+    #[default]
+    Hnsw,
+    /// Cluster-based; needs data in the table to pick good centroids
+    Ivfflat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PgvectorConfig {
     pub host: String,
     #[serde(default = "default_port")]
@@ -39,17 +131,73 @@ pub struct PgvectorConfig {
     pub password: String,
     #[serde(default = "default_database")]
     pub database: String,
+    /// May contain a date pattern (e.g. `logs_%Y_%m_%d`) to roll over to a new
+    /// table per day and/or `{service}` for a table per service, each created
+    /// on first write. Must stay a valid unquoted identifier, so avoid `-` and
+    /// `.` (service names are substituted with those replaced by `_`)
     #[serde(default = "default_table_name")]
     pub table_name: String,
+    /// Column type for the dense embedding
+    #[serde(default)]
+    pub vector_type: VectorType,
+    /// Quantize embeddings before they're sent. Binary ones go in a `bit`
+    /// column whatever `vector_type` says; pgvector has no int8 type, so
+    /// int8 values go in the `vector_type` column, showing the recall cost
+    /// without the storage saving.
+    pub client_quantization: Option<ClientQuantization>,
+    /// Also store a `sparsevec` column: entries' sparse embeddings, or hashed
+    /// term frequencies of the message for entries without one
+    #[serde(default)]
+    pub sparse: bool,
+    /// Dimensions of the sparse column (number of hash buckets)
+    #[serde(default = "default_sparse_dim")]
+    pub sparse_dim: u32,
+    /// Batches of at least this many entries (per table) are written with
+    /// binary `COPY` instead of a parameterised `INSERT ... UNNEST`
+    pub copy_min_batch: Option<usize>,
+    /// Index method for the embedding column
+    #[serde(default)]
+    pub index_type: IndexType,
+    /// What the embedding indexes are built for, and so which operator
+    /// (`<=>`, `<#>` or `<->`) queries need to use them
+    #[serde(default)]
+    pub distance: Distance,
+    /// HNSW graph degree (server default 16)
+    pub hnsw_m: Option<u32>,
+    /// HNSW candidates considered during index build (server default 64)
+    pub hnsw_ef_construction: Option<u32>,
+    /// IVFFlat cluster count (server default 100)
+    pub ivfflat_lists: Option<u32>,
+    /// Build the embedding index once at the end of the run instead of
+    /// maintaining it during ingestion
+    #[serde(default)]
+    pub defer_index: bool,
+    /// Range-partition each table by day on `timestamp`, creating partitions
+    /// as needed so old days can be dropped wholesale
+    #[serde(default)]
+    pub partition_by_day: bool,
+    /// Days past today to pre-create partitions for
+    #[serde(default = "default_partitions_ahead")]
+    pub partitions_ahead: u32,
 }
 
 pub struct PgvectorSink {
     config: PgvectorConfig,
     pool: PgPool,
+    embedding_dim: usize,
+    /// Dimensions of each of `extra_embeddings`, in columns of their own
+    extra_dims: BTreeMap<String, usize>,
+    table: TargetName,
+    /// Daily partitions created so far
+    partitions: Mutex<HashSet<String>>,
 }
 
 impl PgvectorSink {
-    pub async fn from_config(config: PgvectorConfig, embedding_dim: usize) -> Self {
+    pub async fn from_config(
+        config: PgvectorConfig,
+        embedding_dim: usize,
+        extra_dims: BTreeMap<String, usize>,
+    ) -> Result<Self, sqlx::Error> {
         let url = format!(
             "postgres://{}:{}@{}:{}/{}",
             config.user, config.password, config.host, config.port, config.database,
@@ -58,99 +206,651 @@ impl PgvectorSink {
         let pool = PgPoolOptions::new()
             .max_connections(8)
             .connect(&url)
-            .await
-            .expect("Failed to connect to Postgres");
+            .await?;
 
-        // ensure pgvector extension is available
-        sqlx::query("CREATE EXTENSION IF NOT EXISTS vector")
-            .execute(&pool)
-            .await
-            .expect("Failed to create vector extension");
+        // the extension and table are left to `init`, so a dry run only
+        // checks the database can be connected to
+        Ok(Self {
+            table: TargetName::identifier(&config.table_name),
+            config,
+            pool,
+            embedding_dim,
+            extra_dims,
+            partitions: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// The column type embeddings are stored in
+    fn vector_type(&self) -> VectorType {
+        if self.embedding_dim == 0 {
+            return VectorType::Vector;
+        }
+        match self.config.client_quantization {
+            Some(ClientQuantization::Binary) => VectorType::Bit,
+            _ => self.config.vector_type,
+        }
+    }
+
+    /// What's stored of `embedding` in a `vector` or `halfvec` column
+    fn stored<'a>(&self, embedding: &'a [f32]) -> Cow<'a, [f32]> {
+        match self.config.client_quantization {
+            Some(ClientQuantization::Int8) => {
+                Cow::Owned(quantize::int8(embedding).into_iter().map(f32::from).collect())
+            }
+            _ => Cow::Borrowed(embedding),
+        }
+    }
+
+    fn conflict_target(&self) -> &'static str {
+        if self.config.partition_by_day {
+            "(id, timestamp)"
+        } else {
+            "(id)"
+        }
+    }
+
+    /// Create the daily partitions covering `entries`, plus `partitions_ahead`
+    /// days from today so writes around midnight never miss a partition.
+    async fn ensure_partitions(
+        &self,
+        table_name: &str,
+        entries: &[&LogEntry],
+    ) -> Result<(), sqlx::Error> {
+        let today = Utc::now().date_naive();
+        let mut days: BTreeSet<NaiveDate> = entries
+            .iter()
+            .map(|entry| entry.timestamp.date_naive())
+            .collect();
+        days.extend(
+            (0..=self.config.partitions_ahead as u64)
+                .filter_map(|ahead| today.checked_add_days(Days::new(ahead))),
+        );
+
+        let mut partitions = self.partitions.lock().await;
+        for day in days {
+            let partition = format!("{table_name}_p{}", day.format("%Y%m%d"));
+            if partitions.contains(&partition) {
+                continue;
+            }
+            let create_partition = format!(
+                r#"CREATE TABLE IF NOT EXISTS {partition} PARTITION OF {table_name}
+                   FOR VALUES FROM ('{day} 00:00:00+00') TO ('{next} 00:00:00+00')"#,
+                next = day + Days::new(1),
+            );
+            sqlx::query(&create_partition).execute(&self.pool).await?;
+            partitions.insert(partition);
+        }
+        Ok(())
+    }
+
+    async fn ensure_table(&self, table_name: &str) -> Result<(), sqlx::Error> {
+        let sparse_column = if self.config.sparse {
+            format!(", sparse_embedding sparsevec({})", self.config.sparse_dim)
+        } else {
+            String::new()
+        };
+
+        // with embedding switched off, the column is left NULL
+        let embedding_column = match self.embedding_dim {
+            0 => self.vector_type().column_type().to_string(),
+            dim => format!("{}({dim})", self.vector_type().column_type()),
+        };
+
+        // partitioned tables need the partition key in the primary key
+        let partitioning = if self.config.partition_by_day {
+            " PARTITION BY RANGE (timestamp)"
+        } else {
+            ""
+        };
 
         // create table if it doesn't exist
         let create_table = format!(
             r#"CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY,
+                id TEXT NOT NULL,
                 timestamp TIMESTAMPTZ NOT NULL,
                 service TEXT NOT NULL,
                 level TEXT NOT NULL,
                 message TEXT NOT NULL,
+                fields JSONB NOT NULL DEFAULT '{{}}',
+                trace_id TEXT,
+                span_id TEXT,
+                parent_span_id TEXT,
                 message_tsv TSVECTOR GENERATED ALWAYS AS (to_tsvector('english', message)) STORED,
-                embedding vector({})
-            )"#,
-            config.table_name, embedding_dim,
+                embedding {}{},
+                PRIMARY KEY {}
+            ){}"#,
+            table_name,
+            embedding_column,
+            sparse_column,
+            self.conflict_target(),
+            partitioning,
         );
-        sqlx::query(&create_table)
-            .execute(&pool)
-            .await
-            .expect("Failed to create table");
+        sqlx::query(&create_table).execute(&self.pool).await?;
 
-        // create an HNSW index on the embedding column for cosine similarity
-        let create_index = format!(
-            r#"CREATE INDEX IF NOT EXISTS {table}_embedding_idx
-               ON {table} USING hnsw (embedding vector_cosine_ops)"#,
-            table = config.table_name,
+        // tables created before structured fields and trace context existed
+        let add_columns = format!(
+            r#"ALTER TABLE {table_name}
+               ADD COLUMN IF NOT EXISTS fields JSONB NOT NULL DEFAULT '{{}}',
+               ADD COLUMN IF NOT EXISTS trace_id TEXT,
+               ADD COLUMN IF NOT EXISTS span_id TEXT,
+               ADD COLUMN IF NOT EXISTS parent_span_id TEXT"#
         );
-        sqlx::query(&create_index)
-            .execute(&pool)
-            .await
-            .expect("Failed to create HNSW index");
+        sqlx::query(&add_columns).execute(&self.pool).await?;
+        for (name, dim) in &self.extra_dims {
+            sqlx::query(&format!(
+                "ALTER TABLE {table_name} ADD COLUMN IF NOT EXISTS {name} vector({dim})"
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+
+        if !self.config.defer_index {
+            self.create_embedding_index(table_name).await?;
+        }
 
         // create a GIN index on the message column for full-text search
         let create_fts_index = format!(
             r#"CREATE INDEX IF NOT EXISTS {table}_message_idx
                ON {table} USING GIN (message_tsv)"#,
-            table = config.table_name,
+            table = table_name,
+        );
+        sqlx::query(&create_fts_index).execute(&self.pool).await?;
+
+        // and one on the structured fields for containment (@>) filters
+        let create_fields_index = format!(
+            r#"CREATE INDEX IF NOT EXISTS {table}_fields_idx
+               ON {table} USING GIN (fields jsonb_path_ops)"#,
+            table = table_name,
         );
-        sqlx::query(&create_fts_index)
-            .execute(&pool)
-            .await
-            .expect("Failed to create GIN index");
+        sqlx::query(&create_fields_index).execute(&self.pool).await?;
 
-        Self { config, pool }
+        // for pulling up every entry of a trace
+        let create_trace_index = format!(
+            r#"CREATE INDEX IF NOT EXISTS {table}_trace_idx
+               ON {table} (trace_id) WHERE trace_id IS NOT NULL"#,
+            table = table_name,
+        );
+        sqlx::query(&create_trace_index).execute(&self.pool).await?;
+
+        Ok(())
     }
-}
 
-#[async_trait]
-impl Sink for PgvectorSink {
-    async fn write(
+    /// Index the embedding column for the configured distance (Hamming distance for bit vectors)
+    async fn create_embedding_index(&self, table_name: &str) -> Result<(), sqlx::Error> {
+        let (method, options) = match self.config.index_type {
+            IndexType::Hnsw => (
+                "hnsw",
+                [
+                    ("m", self.config.hnsw_m),
+                    ("ef_construction", self.config.hnsw_ef_construction),
+                ]
+                .iter()
+                .filter_map(|(key, value)| value.map(|value| format!("{key} = {value}")))
+                .collect::<Vec<_>>(),
+            ),
+            IndexType::Ivfflat => (
+                "ivfflat",
+                self.config
+                    .ivfflat_lists
+                    .map(|lists| format!("lists = {lists}"))
+                    .into_iter()
+                    .collect(),
+            ),
+        };
+        let with = if options.is_empty() {
+            String::new()
+        } else {
+            format!(" WITH ({})", options.join(", "))
+        };
+
+        let create_index = format!(
+            r#"CREATE INDEX IF NOT EXISTS {table}_embedding_idx
+               ON {table} USING {method} (embedding {ops}){with}"#,
+            table = table_name,
+            ops = self.vector_type().index_ops(self.config.distance),
+        );
+        if self.embedding_dim > 0 {
+            sqlx::query(&create_index).execute(&self.pool).await?;
+        }
+
+        // extra embeddings are stored as plain floats, whatever the main one is
+        for name in self.extra_dims.keys() {
+            let create_index = format!(
+                r#"CREATE INDEX IF NOT EXISTS {table}_{name}_idx
+                   ON {table} USING {method} ({name} {ops}){with}"#,
+                table = table_name,
+                ops = VectorType::Vector.index_ops(self.config.distance),
+            );
+            sqlx::query(&create_index).execute(&self.pool).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn insert_unnest(
         &self,
-        batch: &[LogEntry],
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        table_name: &str,
+        entries: &[&LogEntry],
+    ) -> Result<(), sqlx::Error> {
         // build a batch insert using UNNEST for efficiency
-        let mut ids = Vec::with_capacity(batch.len());
-        let mut timestamps = Vec::with_capacity(batch.len());
-        let mut services = Vec::with_capacity(batch.len());
-        let mut levels = Vec::with_capacity(batch.len());
-        let mut messages = Vec::with_capacity(batch.len());
-        let mut embeddings: Vec<Vector> = Vec::with_capacity(batch.len());
-
-        for entry in batch {
+        let mut ids = Vec::with_capacity(entries.len());
+        let mut timestamps = Vec::with_capacity(entries.len());
+        let mut services = Vec::with_capacity(entries.len());
+        let mut levels = Vec::with_capacity(entries.len());
+        let mut messages = Vec::with_capacity(entries.len());
+        let mut fields = Vec::with_capacity(entries.len());
+        let mut trace_ids = Vec::with_capacity(entries.len());
+        let mut span_ids = Vec::with_capacity(entries.len());
+        let mut parent_span_ids = Vec::with_capacity(entries.len());
+
+        for entry in entries {
             ids.push(entry.id.clone());
             timestamps.push(entry.timestamp);
             services.push(entry.service.clone());
             levels.push(format!("{:?}", entry.level));
             messages.push(entry.message.clone());
-            embeddings.push(Vector::from(entry.embedding.clone()));
+            fields.push(serde_json::to_value(&entry.fields).unwrap_or_default());
+            let trace = entry.trace.as_ref();
+            trace_ids.push(trace.map(|t| t.trace_id.clone()));
+            span_ids.push(trace.map(|t| t.span_id.clone()));
+            parent_span_ids.push(trace.and_then(|t| t.parent_span_id.clone()));
         }
 
+        let vector_type = self.vector_type().column_type();
+        let (mut extra_columns, mut extra_params) = if self.config.sparse {
+            (", sparse_embedding".to_string(), ", $11::sparsevec[]".to_string())
+        } else {
+            (String::new(), String::new())
+        };
+        let first_extra = if self.config.sparse { 12 } else { 11 };
+        for (param, name) in (first_extra..).zip(self.extra_dims.keys()) {
+            extra_columns.push_str(&format!(", {name}"));
+            extra_params.push_str(&format!(", ${param}::vector[]"));
+        }
         let query = format!(
-            r#"INSERT INTO {} (id, timestamp, service, level, message, embedding)
-               SELECT * FROM UNNEST($1::text[], $2::timestamptz[], $3::text[], $4::text[], $5::text[], $6::vector[])
-               ON CONFLICT (id) DO NOTHING"#,
-            self.config.table_name,
+            r#"INSERT INTO {} (id, timestamp, service, level, message, fields, trace_id, span_id, parent_span_id, embedding{})
+               SELECT * FROM UNNEST($1::text[], $2::timestamptz[], $3::text[], $4::text[], $5::text[], $6::jsonb[], $7::text[], $8::text[], $9::text[], $10::{}[]{})
+               ON CONFLICT {} DO NOTHING"#,
+            table_name,
+            extra_columns,
+            vector_type,
+            extra_params,
+            self.conflict_target(),
         );
 
-        sqlx::query(&query)
+        let mut insert = sqlx::query(&query)
             .bind(&ids)
             .bind(&timestamps)
             .bind(&services)
             .bind(&levels)
             .bind(&messages)
-            .bind(&embeddings)
+            .bind(&fields)
+            .bind(&trace_ids)
+            .bind(&span_ids)
+            .bind(&parent_span_ids);
+        insert = match self.vector_type() {
+            _ if self.embedding_dim == 0 => insert.bind(vec![None::<Vector>; entries.len()]),
+            VectorType::Vector => insert.bind(
+                entries
+                    .iter()
+                    .map(|entry| Vector::from(self.stored(&entry.embedding).into_owned()))
+                    .collect::<Vec<_>>(),
+            ),
+            VectorType::Halfvec => insert.bind(
+                entries
+                    .iter()
+                    .map(|entry| HalfVector::from_f32_slice(&self.stored(&entry.embedding)))
+                    .collect::<Vec<_>>(),
+            ),
+            VectorType::Bit => insert.bind(
+                entries
+                    .iter()
+                    .map(|entry| Bit::new(&quantize::bits(&entry.embedding)))
+                    .collect::<Vec<_>>(),
+            ),
+        };
+        if self.config.sparse {
+            insert = insert.bind(
+                entries
+                    .iter()
+                    .map(|entry| sparse_vector(entry, self.config.sparse_dim))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        for name in self.extra_dims.keys() {
+            insert = insert.bind(
+                entries
+                    .iter()
+                    .map(|entry| entry.extra_embeddings.get(name).cloned().map(Vector::from))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        insert.execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    /// Stream entries with `COPY ... (FORMAT BINARY)` into a temp table, then move
+    /// them over with `INSERT ... SELECT` so duplicate ids are still skipped.
+    async fn insert_copy(
+        &self,
+        table_name: &str,
+        entries: &[&LogEntry],
+    ) -> Result<(), sqlx::Error> {
+        let mut columns =
+            "id, timestamp, service, level, message, fields, trace_id, span_id, parent_span_id, embedding"
+                .to_string();
+        if self.config.sparse {
+            columns.push_str(", sparse_embedding");
+        }
+        for name in self.extra_dims.keys() {
+            columns.push_str(&format!(", {name}"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!(
+            "CREATE TEMP TABLE {table_name}_staging (LIKE {table_name} INCLUDING DEFAULTS) ON COMMIT DROP"
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        let mut copy = tx
+            .copy_in_raw(&format!(
+                "COPY {table_name}_staging ({columns}) FROM STDIN (FORMAT BINARY)"
+            ))
+            .await?;
+        copy.send(self.copy_data(entries)).await?;
+        copy.finish().await?;
+
+        sqlx::query(&format!(
+            "INSERT INTO {table_name} ({columns}) SELECT {columns} FROM {table_name}_staging ON CONFLICT {} DO NOTHING",
+            self.conflict_target(),
+        ))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Encode entries in the Postgres binary COPY format
+    fn copy_data(&self, entries: &[&LogEntry]) -> Vec<u8> {
+        fn field(buf: &mut Vec<u8>, data: &[u8]) {
+            buf.extend_from_slice(&(data.len() as i32).to_be_bytes());
+            buf.extend_from_slice(data);
+        }
+
+        let field_count =
+            if self.config.sparse { 11 } else { 10 } + self.extra_dims.len() as i16;
+        let mut buf = Vec::new();
+        // signature, flags, header extension length
+        buf.extend_from_slice(b"PGCOPY\n\xff\r\n\0");
+        buf.extend_from_slice(&0i32.to_be_bytes());
+        buf.extend_from_slice(&0i32.to_be_bytes());
+
+        for entry in entries {
+            buf.extend_from_slice(&field_count.to_be_bytes());
+            field(&mut buf, entry.id.as_bytes());
+            field(
+                &mut buf,
+                &(entry.timestamp.timestamp_micros() - PG_EPOCH_MICROS).to_be_bytes(),
+            );
+            field(&mut buf, entry.service.as_bytes());
+            field(&mut buf, format!("{:?}", entry.level).as_bytes());
+            field(&mut buf, entry.message.as_bytes());
+
+            // jsonb's binary format: a version byte, then the JSON text
+            let mut json = vec![1u8];
+            serde_json::to_writer(&mut json, &entry.fields).expect("fields serialize to JSON");
+            field(&mut buf, &json);
+
+            let trace = entry.trace.as_ref();
+            for id in [
+                trace.map(|t| &t.trace_id),
+                trace.map(|t| &t.span_id),
+                trace.and_then(|t| t.parent_span_id.as_ref()),
+            ] {
+                match id {
+                    Some(id) => field(&mut buf, id.as_bytes()),
+                    // NULL
+                    None => buf.extend_from_slice(&(-1i32).to_be_bytes()),
+                }
+            }
+
+            // pgvector's binary send formats, or NULL with embedding switched off
+            if self.embedding_dim == 0 {
+                buf.extend_from_slice(&(-1i32).to_be_bytes());
+            } else {
+                let mut vector = Vec::new();
+                let dim = entry.embedding.len();
+                match self.vector_type() {
+                    VectorType::Vector => {
+                        vector.extend_from_slice(&(dim as i16).to_be_bytes());
+                        vector.extend_from_slice(&0i16.to_be_bytes());
+                        for x in self.stored(&entry.embedding).iter() {
+                            vector.extend_from_slice(&x.to_be_bytes());
+                        }
+                    }
+                    VectorType::Halfvec => {
+                        vector.extend_from_slice(&(dim as i16).to_be_bytes());
+                        vector.extend_from_slice(&0i16.to_be_bytes());
+                        let half = HalfVector::from_f32_slice(&self.stored(&entry.embedding));
+                        for x in half.as_slice() {
+                            vector.extend_from_slice(&x.to_be_bytes());
+                        }
+                    }
+                    VectorType::Bit => {
+                        vector.extend_from_slice(&(dim as i32).to_be_bytes());
+                        vector.extend_from_slice(&quantize::packed_bits(&entry.embedding));
+                    }
+                }
+                field(&mut buf, &vector);
+            }
+
+            if self.config.sparse {
+                let sparse = sparse_vector(entry, self.config.sparse_dim);
+                let mut data = Vec::new();
+                data.extend_from_slice(&sparse.dimensions().to_be_bytes());
+                data.extend_from_slice(&(sparse.indices().len() as i32).to_be_bytes());
+                data.extend_from_slice(&0i32.to_be_bytes());
+                for index in sparse.indices() {
+                    data.extend_from_slice(&index.to_be_bytes());
+                }
+                for value in sparse.values() {
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+                field(&mut buf, &data);
+            }
+
+            for name in self.extra_dims.keys() {
+                match entry.extra_embeddings.get(name) {
+                    Some(embedding) => {
+                        let mut vector = Vec::new();
+                        vector.extend_from_slice(&(embedding.len() as i16).to_be_bytes());
+                        vector.extend_from_slice(&0i16.to_be_bytes());
+                        for x in embedding {
+                            vector.extend_from_slice(&x.to_be_bytes());
+                        }
+                        field(&mut buf, &vector);
+                    }
+                    None => buf.extend_from_slice(&(-1i32).to_be_bytes()),
+                }
+            }
+        }
+
+        // file trailer
+        buf.extend_from_slice(&(-1i16).to_be_bytes());
+        buf
+    }
+}
+
+#[async_trait]
+impl Sink for PgvectorSink {
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // ensure pgvector extension is available
+        sqlx::query("CREATE EXTENSION IF NOT EXISTS vector")
             .execute(&self.pool)
             .await?;
 
+        // templated tables are created as their first entries arrive
+        if self.table.is_static() {
+            self.ensure_table(&self.config.table_name).await?;
+            self.table.mark_created(&self.config.table_name).await;
+        }
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for (table_name, entries) in self.table.group(batch) {
+            if !self.table.is_created(&table_name).await {
+                self.ensure_table(&table_name).await?;
+                self.table.mark_created(&table_name).await;
+            }
+            if self.config.partition_by_day {
+                self.ensure_partitions(&table_name, &entries).await?;
+            }
+
+            match self.config.copy_min_batch {
+                Some(min) if entries.len() >= min => {
+                    self.insert_copy(&table_name, &entries).await?
+                }
+                _ => self.insert_unnest(&table_name, &entries).await?,
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.config.defer_index {
+            for table_name in self.table.created().await {
+                info!("Building deferred embedding index on {table_name}");
+                self.create_embedding_index(&table_name).await?;
+            }
+        }
+        Ok(())
+    }
+}
+impl PgvectorSink {
+    /// Entries of `table_name` matching the query's words, ranked by
+    /// Postgres full-text search
+    async fn search_keyword(
+        &self,
+        table_name: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        let select = format!(
+            r#"SELECT id, timestamp, service, level, message, ts_rank(message_tsv, query)::float8 AS score
+               FROM {table_name}, plainto_tsquery('english', $1) query
+               WHERE message_tsv @@ query AND {SEARCH_FILTERS}
+               ORDER BY score DESC LIMIT $4"#
+        );
+        sqlx::query(&select)
+            .bind(&query.text)
+            .bind(&query.services)
+            .bind(query.level_names())
+            .bind(query.limit as i64)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| search_hit(row, row.try_get("score")?))
+            .collect()
+    }
+
+    /// Entries of `table_name` nearest `embedding`, by the operator the
+    /// embedding index was built for
+    async fn search_dense(
+        &self,
+        table_name: &str,
+        query: &SearchQuery,
+        embedding: &[f32],
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        let operator = match (self.vector_type(), self.config.distance) {
+            (VectorType::Bit, _) => "<~>",
+            (_, Distance::Cosine) => "<=>",
+            (_, Distance::Dot) => "<#>",
+            (_, Distance::Euclidean) => "<->",
+        };
+        let select = format!(
+            r#"SELECT id, timestamp, service, level, message, (embedding {operator} $1)::float8 AS distance
+               FROM {table_name}
+               WHERE embedding IS NOT NULL AND {SEARCH_FILTERS}
+               ORDER BY embedding {operator} $1 LIMIT $4"#
+        );
+        let select = sqlx::query(&select);
+        let select = match self.vector_type() {
+            VectorType::Vector => select.bind(Vector::from(self.stored(embedding).into_owned())),
+            VectorType::Halfvec => select.bind(HalfVector::from_f32_slice(&self.stored(embedding))),
+            VectorType::Bit => select.bind(Bit::new(&quantize::bits(embedding))),
+        };
+        select
+            .bind(&query.services)
+            .bind(query.level_names())
+            .bind(query.limit as i64)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| {
+                let distance: f64 = row.try_get("distance")?;
+                // cosine distance is 1 - similarity, `<#>` the negated inner product
+                let score = match (self.vector_type(), self.config.distance) {
+                    (VectorType::Bit, _) => -distance,
+                    (_, Distance::Cosine) => 1.0 - distance,
+                    _ => -distance,
+                };
+                search_hit(row, score)
+            })
+            .collect()
+    }
+}
+
+fn search_hit(row: &PgRow, score: f64) -> Result<SearchHit, sqlx::Error> {
+    Ok(SearchHit {
+        id: row.try_get("id")?,
+        score,
+        timestamp: Some(row.try_get("timestamp")?),
+        service: row.try_get("service")?,
+        level: row.try_get("level")?,
+        message: row.try_get("message")?,
+    })
+}
+
+/// Searches every table written so far, keyword queries with Postgres
+/// full-text search over `message_tsv`, and fuses hybrid queries' rankings
+/// by reciprocal rank
+#[async_trait]
+impl SearchBackend for PgvectorSink {
+    fn kind(&self) -> &'static str {
+        "pgvector"
+    }
+
+    fn embedding_dim(&self) -> usize {
+        self.embedding_dim
+    }
+
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        embedding: Option<&[f32]>,
+    ) -> Result<Vec<SearchHit>, SearchError> {
+        let mut rankings = Vec::new();
+        for table_name in self.table.created().await {
+            rankings.push(match (query.mode, embedding) {
+                (SearchMode::Keyword, _) => self.search_keyword(&table_name, query).await?,
+                (SearchMode::Dense, Some(embedding)) => {
+                    self.search_dense(&table_name, query, embedding).await?
+                }
+                (SearchMode::Hybrid, Some(embedding)) => search::fuse(
+                    vec![
+                        self.search_dense(&table_name, query, embedding).await?,
+                        self.search_keyword(&table_name, query).await?,
+                    ],
+                    query.limit,
+                ),
+                (_, None) => return Err("dense search needs the query's embedding".into()),
+            });
+        }
+        Ok(search::merge(rankings, query.limit))
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+use crate::log_entry::LogEntry;
+use crate::processor::{self, Processor};
+use crate::sink::Sink;
+
+/// Wraps another sink, running each batch through processors of its own
+/// first, e.g. redacting what one sink receives while another gets the
+/// original entries
+pub struct ProcessSink {
+    inner: Box<dyn Sink>,
+    processors: Vec<Box<dyn Processor>>,
+}
+
+impl ProcessSink {
+    pub fn new(inner: Box<dyn Sink>, processors: Vec<Box<dyn Processor>>) -> Self {
+        Self { inner, processors }
+    }
+}
+
+#[async_trait]
+impl Sink for ProcessSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let processed = processor::apply(&self.processors, batch.to_vec());
+        if processed.is_empty() {
+            return Ok(());
+        }
+        self.inner.write(&processed).await
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
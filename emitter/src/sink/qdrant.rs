@@ -1,36 +1,212 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use qdrant_client::qdrant::point_id::PointIdOptions;
 use qdrant_client::qdrant::{
-    CreateCollectionBuilder, CreateFieldIndexCollection, Distance, DocumentBuilder, FieldType,
-    Modifier, NamedVectors, PointStruct, SparseVectorParamsBuilder, SparseVectorsConfigBuilder,
-    UpsertPointsBuilder, VectorParamsBuilder, VectorsConfigBuilder,
+    BinaryQuantizationBuilder, CollectionStatus, Condition, CreateCollectionBuilder,
+    CreateFieldIndexCollectionBuilder, Datatype, Distance, Document, DocumentBuilder, FieldType,
+    Filter, Fusion, HnswConfigDiffBuilder, Modifier, NamedVectors, PointId, PointStruct,
+    PrefetchQueryBuilder, QuantizationType, Query, QueryPointsBuilder, ScalarQuantizationBuilder,
+    ScoredPoint, SparseVectorParamsBuilder, SparseVectorsConfigBuilder, UpsertPointsBuilder,
+    Vector, VectorInput, VectorParamsBuilder, VectorsConfigBuilder, WriteOrdering,
+    WriteOrderingType,
 };
-use qdrant_client::{Payload, Qdrant};
+use qdrant_client::{Payload, Qdrant, QdrantError};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tracing::{info, warn};
 
+use crate::embedding::quantize::{self, ClientQuantization};
 use crate::log_entry::LogEntry;
+use crate::search::{self, SearchBackend, SearchError, SearchHit, SearchMode, SearchQuery};
 use crate::sink::Sink;
+use crate::sink::target::TargetName;
 use crate::sink::{DEFAULT_INDEX_NAME, DENSE_EMBEDDING_NAME, SPARSE_EMBEDDING_NAME};
 
 fn default_collection_name() -> String {
     DEFAULT_INDEX_NAME.to_string()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_payload_indexes() -> Vec<PayloadIndexConfig> {
+    vec![
+        PayloadIndexConfig {
+            field: "level".to_string(),
+            field_type: PayloadFieldType::Keyword,
+        },
+        PayloadIndexConfig {
+            field: "service".to_string(),
+            field_type: PayloadFieldType::Keyword,
+        },
+    ]
+}
+
+fn default_chunk_size() -> usize {
+    1000
+}
+
+fn default_wait() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantization {
+    /// int8 scalar quantization
+    Scalar,
+    /// 1 bit per dimension
+    Binary,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpsertOrdering {
+    Weak,
+    Medium,
+    Strong,
+}
+
+impl From<UpsertOrdering> for WriteOrdering {
+    fn from(ordering: UpsertOrdering) -> Self {
+        let ordering_type = match ordering {
+            UpsertOrdering::Weak => WriteOrderingType::Weak,
+            UpsertOrdering::Medium => WriteOrderingType::Medium,
+            UpsertOrdering::Strong => WriteOrderingType::Strong,
+        };
+        WriteOrdering {
+            r#type: ordering_type.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFieldType {
+    Keyword,
+    Integer,
+    Float,
+    Bool,
+    Datetime,
+    /// Full-text index, e.g. on `message`
+    Text,
+}
+
+impl From<PayloadFieldType> for FieldType {
+    fn from(field_type: PayloadFieldType) -> Self {
+        match field_type {
+            PayloadFieldType::Keyword => FieldType::Keyword,
+            PayloadFieldType::Integer => FieldType::Integer,
+            PayloadFieldType::Float => FieldType::Float,
+            PayloadFieldType::Bool => FieldType::Bool,
+            PayloadFieldType::Datetime => FieldType::Datetime,
+            PayloadFieldType::Text => FieldType::Text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PayloadIndexConfig {
+    pub field: String,
+    #[serde(rename = "type")]
+    pub field_type: PayloadFieldType,
+}
+
+/// A model Qdrant embeds messages with itself
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QdrantInferenceConfig {
+    /// e.g. `sentence-transformers/all-minilm-l6-v2`
+    pub model: String,
+    /// What the model returns, for the collection's dense vector
+    pub dimensions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QdrantConfig {
     pub url: String,
     pub api_key: Option<String>,
+    /// May contain a date pattern (e.g. `logs-%Y.%m.%d`) to roll over to a new
+    /// collection per day and/or `{service}` for a collection per service,
+    /// each created on first write
     #[serde(default = "default_collection_name")]
     pub collection_name: String,
+    // collection tuning, applied when a collection is created; unset values
+    // fall back to the server defaults
+    /// HNSW graph degree
+    pub hnsw_m: Option<u64>,
+    /// HNSW neighbours considered during index build
+    pub hnsw_ef_construct: Option<u64>,
+    /// Keep payloads on disk rather than in memory
+    pub on_disk_payload: Option<bool>,
+    pub quantization: Option<Quantization>,
+    /// Keep quantized vectors in RAM (originals may then live on disk)
+    #[serde(default)]
+    pub quantization_always_ram: bool,
+    /// Quantize embeddings before they're sent instead, storing one byte
+    /// per dimension: int8 values shifted into `uint8` and compared by
+    /// Euclidean distance, or binary ones as 0/1 compared by Manhattan
+    /// (Hamming) distance, Qdrant having no bit datatype
+    pub client_quantization: Option<ClientQuantization>,
+    /// How the dense vectors are compared, unless `client_quantization`
+    /// sets it
+    #[serde(default)]
+    pub distance: crate::embedding::Distance,
+    /// Send messages for Qdrant to embed (cloud inference, or a deployment
+    /// with FastEmbed behind it) instead of the emitter's embeddings
+    pub inference: Option<QdrantInferenceConfig>,
+    pub shard_number: Option<u32>,
+    pub replication_factor: Option<u32>,
+    /// Max points per upsert request; larger batches are split
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Wait for each upsert to be applied before sending the next
+    #[serde(default = "default_wait")]
+    pub wait: bool,
+    /// Write ordering guarantee across replicas (server default weak)
+    pub ordering: Option<UpsertOrdering>,
+    /// Payload indexes created with each collection
+    #[serde(default = "default_payload_indexes")]
+    pub payload_indexes: Vec<PayloadIndexConfig>,
+    /// At the end of the run, wait up to this long for every collection to
+    /// finish optimizing, so query benchmarks start from a settled index
+    pub optimizer_wait_secs: Option<u64>,
 }
 
 pub struct QdrantSink {
     config: QdrantConfig,
     client: Qdrant,
+    embedding_dim: usize,
+    /// Dimensions of each of `extra_embeddings`, stored as named vectors
+    extra_dims: BTreeMap<String, usize>,
+    collection: TargetName,
+}
+
+fn distance(distance: crate::embedding::Distance) -> Distance {
+    match distance {
+        crate::embedding::Distance::Cosine => Distance::Cosine,
+        crate::embedding::Distance::Dot => Distance::Dot,
+        crate::embedding::Distance::Euclidean => Distance::Euclid,
+    }
+}
+
+/// The entry's own sparse embedding, from a `sparse` processor, or else its
+/// message for Qdrant to embed with BM25
+fn sparse_vector(entry: &LogEntry) -> Vector {
+    match &entry.sparse {
+        Some(sparse) => Vector::new_sparse(sparse.indices.clone(), sparse.values.clone()),
+        None => DocumentBuilder::new(entry.message.clone(), "qdrant/bm25")
+            .build()
+            .into(),
+    }
 }
 
 impl QdrantSink {
-    pub async fn from_config(config: QdrantConfig, embedding_dim: usize) -> Self {
+    pub async fn from_config(
+        config: QdrantConfig,
+        embedding_dim: usize,
+        extra_dims: BTreeMap<String, usize>,
+    ) -> Result<Self, QdrantError> {
         let mut qbuilder = Qdrant::from_url(&config.url);
 
         // grab api key from config if provided and set it on the builder
@@ -38,25 +214,86 @@ impl QdrantSink {
             qbuilder = qbuilder.api_key(api_key.to_string());
         }
 
-        let client = qbuilder.build().expect("Failed to create Qdrant client");
+        let client = qbuilder.build()?;
+
+        if config.inference.is_some() && config.client_quantization.is_some() {
+            warn!("client_quantization is ignored with inference, Qdrant making the embeddings");
+        }
+        let embedding_dim = config
+            .inference
+            .as_ref()
+            .map_or(embedding_dim, |inference| inference.dimensions);
+
+        // the collection is left to `init`, so a dry run only checks the
+        // server answers
+        client.health_check().await?;
+
+        Ok(Self {
+            collection: TargetName::new(&config.collection_name),
+            config,
+            client,
+            embedding_dim,
+            extra_dims,
+        })
+    }
+
+    /// What's sent of `embedding`, as the collection's datatype holds it
+    fn stored(&self, embedding: &[f32]) -> Vec<f32> {
+        match self.config.client_quantization {
+            None => embedding.to_vec(),
+            Some(ClientQuantization::Int8) => quantize::int8(embedding)
+                .into_iter()
+                .map(|v| f32::from(v) + 128.0)
+                .collect(),
+            Some(ClientQuantization::Binary) => quantize::bits(embedding)
+                .into_iter()
+                .map(f32::from)
+                .collect(),
+        }
+    }
 
+    async fn ensure_collection(&self, collection_name: &str) -> Result<(), QdrantError> {
         // check if the collection exists by listing collections and looking for a match on the name
-        let collection_exists = client
+        let collection_exists = self
+            .client
             .list_collections()
-            .await
-            .unwrap()
+            .await?
             .collections
             .iter()
-            .any(|c| c.name == config.collection_name);
+            .any(|c| c.name == collection_name);
 
         // build collection if it doesn't exist
-        // (creating a payload index on "level" and "service" for querying)
+        // (along with the configured payload indexes for querying)
         if !collection_exists {
             let mut vectors_config = VectorsConfigBuilder::default();
-            vectors_config.add_named_vector_params(
-                DENSE_EMBEDDING_NAME,
-                VectorParamsBuilder::new(embedding_dim as u64, Distance::Cosine),
-            );
+            let client_quantization = self
+                .config
+                .client_quantization
+                .filter(|_| self.config.inference.is_none());
+            let dense = match client_quantization {
+                None => VectorParamsBuilder::new(
+                    self.embedding_dim as u64,
+                    distance(self.config.distance),
+                ),
+                Some(ClientQuantization::Int8) => {
+                    VectorParamsBuilder::new(self.embedding_dim as u64, Distance::Euclid)
+                        .datatype(Datatype::Uint8)
+                }
+                Some(ClientQuantization::Binary) => {
+                    VectorParamsBuilder::new(self.embedding_dim as u64, Distance::Manhattan)
+                        .datatype(Datatype::Uint8)
+                }
+            };
+            // with embedding switched off, only the sparse vector is kept
+            if self.embedding_dim > 0 {
+                vectors_config.add_named_vector_params(DENSE_EMBEDDING_NAME, dense);
+            }
+            for (name, dim) in &self.extra_dims {
+                vectors_config.add_named_vector_params(
+                    name,
+                    VectorParamsBuilder::new(*dim as u64, distance(self.config.distance)),
+                );
+            }
 
             let mut sparse_vectors_config = SparseVectorsConfigBuilder::default();
             sparse_vectors_config.add_named_vector_params(
@@ -65,79 +302,287 @@ impl QdrantSink {
                 SparseVectorParamsBuilder::default().modifier(Modifier::Idf),
             );
 
-            client
-                .create_collection(
-                    CreateCollectionBuilder::new(config.collection_name.clone())
-                        // todo: make these vector params configurable???
-                        .vectors_config(vectors_config)
-                        .sparse_vectors_config(sparse_vectors_config),
-                )
-                .await
-                .unwrap();
-
-            // payload index on "level" field
-            let payload_index = CreateFieldIndexCollection {
-                collection_name: config.collection_name.clone(),
-                field_name: "level".to_string(),
-                field_type: Some(FieldType::Keyword.into()),
-                field_index_params: None, // use optional parameters
-                wait: Some(true),         // wait for index creation to complete
-                ordering: None,           // default ordering
-            };
-            client.create_field_index(payload_index).await.unwrap();
-
-            // payload index on "service" field
-            let payload_index = CreateFieldIndexCollection {
-                collection_name: config.collection_name.clone(),
-                field_name: "service".to_string(),
-                field_type: Some(FieldType::Keyword.into()),
-                field_index_params: None, // use optional parameters
-                wait: Some(true),         // wait for index creation to complete
-                ordering: None,           // default ordering
+            let mut collection = CreateCollectionBuilder::new(collection_name)
+                .vectors_config(vectors_config)
+                .sparse_vectors_config(sparse_vectors_config);
+
+            if self.config.hnsw_m.is_some() || self.config.hnsw_ef_construct.is_some() {
+                let mut hnsw = HnswConfigDiffBuilder::default();
+                if let Some(m) = self.config.hnsw_m {
+                    hnsw = hnsw.m(m);
+                }
+                if let Some(ef_construct) = self.config.hnsw_ef_construct {
+                    hnsw = hnsw.ef_construct(ef_construct);
+                }
+                collection = collection.hnsw_config(hnsw);
+            }
+            if let Some(on_disk_payload) = self.config.on_disk_payload {
+                collection = collection.on_disk_payload(on_disk_payload);
+            }
+            collection = match self.config.quantization {
+                Some(Quantization::Scalar) => collection.quantization_config(
+                    ScalarQuantizationBuilder::default()
+                        .r#type(QuantizationType::Int8.into())
+                        .always_ram(self.config.quantization_always_ram),
+                ),
+                Some(Quantization::Binary) => collection.quantization_config(
+                    BinaryQuantizationBuilder::new(self.config.quantization_always_ram),
+                ),
+                None => collection,
             };
-            client.create_field_index(payload_index).await.unwrap();
+            if let Some(shard_number) = self.config.shard_number {
+                collection = collection.shard_number(shard_number);
+            }
+            if let Some(replication_factor) = self.config.replication_factor {
+                collection = collection.replication_factor(replication_factor);
+            }
+
+            self.client.create_collection(collection).await?;
+
+            for index in &self.config.payload_indexes {
+                // wait for index creation to complete
+                let payload_index = CreateFieldIndexCollectionBuilder::new(
+                    collection_name,
+                    index.field.as_str(),
+                    FieldType::from(index.field_type),
+                )
+                .wait(true);
+                self.client.create_field_index(payload_index).await?;
+            }
         }
 
-        Self { config, client }
+        Ok(())
+    }
+
+    async fn wait_for_optimizers(
+        &self,
+        collection_name: &str,
+        timeout: Duration,
+    ) -> Result<(), QdrantError> {
+        let start = Instant::now();
+        loop {
+            let status = self
+                .client
+                .collection_info(collection_name)
+                .await?
+                .result
+                .map(|info| info.status);
+            if status == Some(CollectionStatus::Green as i32) {
+                info!("Collection '{collection_name}' optimized");
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                warn!("Collection '{collection_name}' still optimizing after {timeout:?}");
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
     }
 }
 
 #[async_trait]
 impl Sink for QdrantSink {
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // templated collections are created as their first entries arrive
+        if self.collection.is_static() {
+            self.ensure_collection(&self.config.collection_name).await?;
+            self.collection
+                .mark_created(&self.config.collection_name)
+                .await;
+        }
+        Ok(())
+    }
+
     async fn write(
         &self,
         batch: &[LogEntry],
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // upsert all of these logs into the collection
-        self.client
-            .upsert_points(UpsertPointsBuilder::new(
-                // todo: do I have to clone this?
-                self.config.collection_name.clone(),
-                batch
+        for (collection_name, entries) in self.collection.group(batch) {
+            if !self.collection.is_created(&collection_name).await {
+                self.ensure_collection(&collection_name).await?;
+                self.collection.mark_created(&collection_name).await;
+            }
+
+            // upsert the logs into the collection, a chunk at a time
+            for chunk in entries.chunks(self.config.chunk_size.max(1)) {
+                let points = chunk
                     .iter()
                     .map(|entry| {
+                        let mut vectors = NamedVectors::default()
+                            .add_vector(SPARSE_EMBEDDING_NAME, sparse_vector(entry));
+                        if let Some(inference) = &self.config.inference {
+                            let document: Vector =
+                                DocumentBuilder::new(entry.message.clone(), &inference.model)
+                                    .build()
+                                    .into();
+                            vectors = vectors.add_vector(DENSE_EMBEDDING_NAME, document);
+                        } else if self.embedding_dim > 0 {
+                            vectors = vectors
+                                .add_vector(DENSE_EMBEDDING_NAME, self.stored(&entry.embedding));
+                        }
+                        for (name, embedding) in &entry.extra_embeddings {
+                            vectors = vectors.add_vector(name.clone(), embedding.clone());
+                        }
                         PointStruct::new(
                             entry.id.clone(),
-                            NamedVectors::default()
-                                .add_vector(DENSE_EMBEDDING_NAME, entry.embedding.clone())
-                                .add_vector(
-                                    SPARSE_EMBEDDING_NAME,
-                                    DocumentBuilder::new(entry.message.clone(), "qdrant/bm25")
-                                        .build(),
-                                ),
+                            vectors,
                             Payload::try_from(json!({
                                 "service": entry.service.clone(),
                                 "level": format!("{:?}", entry.level),
                                 "message": entry.message.clone(),
                                 "timestamp": entry.timestamp,
+                                "fields": entry.fields,
+                                "trace": entry.trace,
                             }))
                             .unwrap(),
                         )
                     })
-                    .collect::<Vec<PointStruct>>(),
-            ))
-            .await
-            .unwrap();
+                    .collect::<Vec<PointStruct>>();
+
+                let mut upsert = UpsertPointsBuilder::new(collection_name.clone(), points)
+                    .wait(self.config.wait);
+                if let Some(ordering) = self.config.ordering {
+                    upsert = upsert.ordering(WriteOrdering::from(ordering));
+                }
+                self.client.upsert_points(upsert).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(secs) = self.config.optimizer_wait_secs {
+            for collection_name in self.collection.created().await {
+                self.wait_for_optimizers(&collection_name, Duration::from_secs(secs))
+                    .await?;
+            }
+        }
         Ok(())
     }
 }
+
+impl QdrantSink {
+    /// The query as the dense vector is searched: a document for Qdrant to
+    /// embed, or `embedding` as the collection holds it
+    fn dense_query(&self, query: &SearchQuery, embedding: Option<&[f32]>) -> Option<VectorInput> {
+        match (&self.config.inference, embedding) {
+            (Some(inference), _) => Some(
+                DocumentBuilder::new(query.text.clone(), &inference.model)
+                    .build()
+                    .into(),
+            ),
+            (None, Some(embedding)) => Some(self.stored(embedding).into()),
+            (None, None) => None,
+        }
+    }
+
+    async fn search_collection(
+        &self,
+        collection_name: &str,
+        query: &SearchQuery,
+        embedding: Option<&[f32]>,
+    ) -> Result<Vec<SearchHit>, SearchError> {
+        let limit = query.limit as u64;
+        let keyword: Document = DocumentBuilder::new(query.text.clone(), "qdrant/bm25").build();
+        let mut request = QueryPointsBuilder::new(collection_name)
+            .limit(limit)
+            .with_payload(true);
+        let mut conditions = Vec::new();
+        if !query.services.is_empty() {
+            conditions.push(Condition::matches("service", query.services.clone()));
+        }
+        if !query.levels.is_empty() {
+            conditions.push(Condition::matches("level", query.level_names()));
+        }
+        if !conditions.is_empty() {
+            request = request.filter(Filter::must(conditions));
+        }
+        request = match (query.mode, self.dense_query(query, embedding)) {
+            (SearchMode::Keyword, _) => request
+                .query(Query::new_nearest(keyword))
+                .using(SPARSE_EMBEDDING_NAME),
+            (SearchMode::Dense, Some(dense)) => request
+                .query(Query::new_nearest(dense))
+                .using(DENSE_EMBEDDING_NAME),
+            // fused by Qdrant itself, from a prefetch of each
+            (SearchMode::Hybrid, Some(dense)) => request
+                .add_prefetch(
+                    PrefetchQueryBuilder::default()
+                        .query(Query::new_nearest(dense))
+                        .using(DENSE_EMBEDDING_NAME)
+                        .limit(limit),
+                )
+                .add_prefetch(
+                    PrefetchQueryBuilder::default()
+                        .query(Query::new_nearest(keyword))
+                        .using(SPARSE_EMBEDDING_NAME)
+                        .limit(limit),
+                )
+                .query(Query::new_fusion(Fusion::Rrf)),
+            (_, None) => return Err("dense search needs the query's embedding".into()),
+        };
+        let response = self.client.query(request).await?;
+        Ok(response.result.into_iter().map(search_hit).collect())
+    }
+}
+
+fn search_hit(point: ScoredPoint) -> SearchHit {
+    let text = |key: &str| {
+        point
+            .payload
+            .get(key)
+            .and_then(|value| value.as_str())
+            .cloned()
+            .unwrap_or_default()
+    };
+    SearchHit {
+        id: point_id(point.id.clone()),
+        score: f64::from(point.score),
+        timestamp: DateTime::parse_from_rfc3339(&text("timestamp"))
+            .ok()
+            .map(|timestamp| timestamp.with_timezone(&Utc)),
+        service: text("service"),
+        level: text("level"),
+        message: text("message"),
+    }
+}
+
+fn point_id(id: Option<PointId>) -> String {
+    match id.and_then(|id| id.point_id_options) {
+        Some(PointIdOptions::Uuid(uuid)) => uuid,
+        Some(PointIdOptions::Num(num)) => num.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Searches every collection written so far, keyword queries against the
+/// BM25 sparse vector, and has Qdrant fuse hybrid queries' prefetches
+#[async_trait]
+impl SearchBackend for QdrantSink {
+    fn kind(&self) -> &'static str {
+        "qdrant"
+    }
+
+    fn embedding_dim(&self) -> usize {
+        self.embedding_dim
+    }
+
+    fn embeds_queries(&self) -> bool {
+        self.config.inference.is_some()
+    }
+
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        embedding: Option<&[f32]>,
+    ) -> Result<Vec<SearchHit>, SearchError> {
+        let mut rankings = Vec::new();
+        for collection_name in self.collection.created().await {
+            rankings.push(
+                self.search_collection(&collection_name, query, embedding)
+                    .await?,
+            );
+        }
+        Ok(search::merge(rankings, query.limit))
+    }
+}
@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RateLimitConfig {
+    pub docs_per_sec: Option<f64>,
+    /// Estimated bytes per second, embeddings included
+    pub bytes_per_sec: Option<f64>,
+}
+
+/// A token bucket holding up to one second's worth of tokens. Taking more
+/// than are available leaves it in debt, which the caller waits out.
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            tokens: rate,
+            refilled: Instant::now(),
+        }
+    }
+
+    /// Take `amount` tokens, returning how long to wait before using them
+    fn take(&mut self, amount: f64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.refilled = now;
+
+        self.tokens -= amount;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+/// Wraps another sink, holding writes back to stay within a docs/sec and/or
+/// bytes/sec quota
+pub struct RateLimitSink {
+    inner: Box<dyn Sink>,
+    docs: Option<Mutex<TokenBucket>>,
+    bytes: Option<Mutex<TokenBucket>>,
+}
+
+impl RateLimitSink {
+    pub fn new(inner: Box<dyn Sink>, config: RateLimitConfig) -> Self {
+        let bucket = |rate: Option<f64>| {
+            rate.filter(|rate| *rate > 0.0)
+                .map(|rate| Mutex::new(TokenBucket::new(rate)))
+        };
+        Self {
+            inner,
+            docs: bucket(config.docs_per_sec),
+            bytes: bucket(config.bytes_per_sec),
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for RateLimitSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut wait = Duration::ZERO;
+        if let Some(docs) = &self.docs {
+            wait = wait.max(docs.lock().await.take(batch.len() as f64));
+        }
+        if let Some(bytes) = &self.bytes {
+            let size: usize = batch.iter().map(LogEntry::estimated_size).sum();
+            wait = wait.max(bytes.lock().await.take(size as f64));
+        }
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        self.inner.write(batch).await
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::log_entry::LogEntry;
+use crate::metrics;
+use crate::report::SinkTally;
+use crate::sink::Sink;
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_base_delay_ms() -> u64 {
+    200
+}
+
+fn default_max_delay_ms() -> u64 {
+    10_000
+}
+
+fn default_jitter() -> f64 {
+    0.2
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RetryConfig {
+    /// Total attempts per batch, including the first
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each one after
+    #[serde(default = "default_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Randomize each delay by up to this fraction either way (0.0 - 1.0)
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+    /// Only retry errors whose message contains one of these (case
+    /// insensitive), e.g. `[timed out, "429", connection]`; unset retries
+    /// every error
+    pub retry_on: Option<Vec<String>>,
+}
+
+/// Wraps another sink, retrying failed writes with exponential backoff
+/// before giving up on the batch, counting retries in the sink's tally
+pub struct RetrySink {
+    tally: Arc<SinkTally>,
+    inner: Box<dyn Sink>,
+    config: RetryConfig,
+}
+
+impl RetrySink {
+    pub fn new(tally: Arc<SinkTally>, inner: Box<dyn Sink>, config: RetryConfig) -> Self {
+        Self {
+            tally,
+            inner,
+            config,
+        }
+    }
+
+    fn is_retryable(&self, error: &(dyn std::error::Error + Send + Sync)) -> bool {
+        let Some(patterns) = &self.config.retry_on else {
+            return true;
+        };
+        let message = error.to_string().to_lowercase();
+        patterns
+            .iter()
+            .any(|pattern| message.contains(&pattern.to_lowercase()))
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self
+            .config
+            .base_delay_ms
+            .saturating_mul(1 << retry.min(20))
+            .min(self.config.max_delay_ms) as f64;
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            1.0 + rand::thread_rng().gen_range(-jitter..=jitter)
+        } else {
+            1.0
+        };
+        Duration::from_millis((backoff * factor) as u64)
+    }
+}
+
+#[async_trait]
+impl Sink for RetrySink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut attempt = 1;
+        loop {
+            match self.inner.write(batch).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.config.max_attempts && self.is_retryable(e.as_ref()) => {
+                    let delay = self.delay(attempt - 1);
+                    warn!(
+                        "{} write failed (attempt {attempt}/{}), retrying in {delay:?}: {e}",
+                        self.tally.name(),
+                        self.config.max_attempts
+                    );
+                    metrics::sink_retry(self.tally.name());
+                    self.tally.record_retry();
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+
+use crate::log_entry::LogEntry;
+use crate::processor::EntryMatch;
+use crate::sink::Sink;
+
+/// Wraps another sink, passing on only the entries that match its route
+pub struct RouteSink {
+    inner: Box<dyn Sink>,
+    route: EntryMatch,
+}
+
+impl RouteSink {
+    pub fn new(inner: Box<dyn Sink>, route: EntryMatch) -> Self {
+        Self { inner, route }
+    }
+}
+
+#[async_trait]
+impl Sink for RouteSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let routed: Vec<LogEntry> = batch
+            .iter()
+            .filter(|entry| self.route.matches(entry))
+            .cloned()
+            .collect();
+        if routed.is_empty() {
+            return Ok(());
+        }
+        self.inner.write(&routed).await
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+fn default_include_embedding() -> bool {
+    true
+}
+
+fn default_reconnect_attempts() -> u32 {
+    3
+}
+
+fn default_reconnect_backoff_ms() -> u64 {
+    500
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SocketProtocol {
+    Tcp,
+    Udp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SocketConfig {
+    pub host: String,
+    pub port: u16,
+    pub protocol: SocketProtocol,
+    #[serde(default = "default_include_embedding")]
+    pub include_embedding: bool,
+    /// How many times to reconnect and resend before failing the batch
+    #[serde(default = "default_reconnect_attempts")]
+    pub reconnect_attempts: u32,
+    /// Delay before the first reconnect, doubled on each further attempt
+    #[serde(default = "default_reconnect_backoff_ms")]
+    pub reconnect_backoff_ms: u64,
+}
+
+enum Connection {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+}
+
+/// Writes newline-delimited JSON entries to a TCP or UDP endpoint (Vector,
+/// Logstash tcp/udp inputs, `nc -l`, ...). UDP sends one datagram per entry.
+pub struct SocketSink {
+    config: SocketConfig,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl SocketSink {
+    pub async fn from_config(config: SocketConfig) -> std::io::Result<Self> {
+        super::resolve(&config.host, config.port).await?;
+        Ok(Self {
+            config,
+            connection: Mutex::new(None),
+        })
+    }
+
+    fn encode(&self, entry: &LogEntry) -> Result<Vec<u8>, serde_json::Error> {
+        let mut line = if self.config.include_embedding {
+            serde_json::to_vec(entry)?
+        } else {
+            let mut value = serde_json::to_value(entry)?;
+            if let Some(object) = value.as_object_mut() {
+                object.remove("embedding");
+            }
+            serde_json::to_vec(&value)?
+        };
+        line.push(b'\n');
+        Ok(line)
+    }
+
+    async fn connect(&self) -> std::io::Result<Connection> {
+        // a tuple rather than `host:port`, so IPv6 literals like `::1` work
+        let (host, port) = (self.config.host.as_str(), self.config.port);
+        match self.config.protocol {
+            SocketProtocol::Tcp => Ok(Connection::Tcp(TcpStream::connect((host, port)).await?)),
+            SocketProtocol::Udp => Ok(Connection::Udp(super::connect_udp(host, port).await?)),
+        }
+    }
+
+    async fn send(connection: &mut Connection, lines: &[Vec<u8>]) -> std::io::Result<()> {
+        match connection {
+            Connection::Tcp(stream) => {
+                stream.write_all(&lines.concat()).await?;
+                stream.flush().await
+            }
+            Connection::Udp(socket) => {
+                for line in lines {
+                    socket.send(line).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for SocketSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let lines = batch
+            .iter()
+            .map(|entry| self.encode(entry))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut connection = self.connection.lock().await;
+        let mut backoff = Duration::from_millis(self.config.reconnect_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            let result = match connection.as_mut() {
+                Some(conn) => Self::send(conn, &lines).await,
+                None => match self.connect().await {
+                    Ok(mut conn) => {
+                        let result = Self::send(&mut conn, &lines).await;
+                        *connection = Some(conn);
+                        result
+                    }
+                    Err(e) => Err(e),
+                },
+            };
+
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.config.reconnect_attempts => {
+                    attempt += 1;
+                    warn!(
+                        "Socket write to {}:{} failed ({e}), reconnecting in {backoff:?} (attempt {attempt}/{})",
+                        self.config.host, self.config.port, self.config.reconnect_attempts
+                    );
+                    *connection = None;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => {
+                    *connection = None;
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+}
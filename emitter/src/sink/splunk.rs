@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+use crate::sink::compression::Compression;
+
+fn default_sourcetype() -> String {
+    "_json".to_string()
+}
+
+fn default_ack_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_ack_poll_interval_ms() -> u64 {
+    500
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SplunkConfig {
+    /// Base URL of the HEC endpoint, e.g. `https://splunk:8088`
+    pub url: String,
+    pub token: String,
+    /// Target index; omitted to use the token's default index
+    pub index: Option<String>,
+    #[serde(default = "default_sourcetype")]
+    pub sourcetype: String,
+    /// Wait for indexer acknowledgment of every batch (the token must have ack enabled)
+    #[serde(default)]
+    pub use_ack: bool,
+    #[serde(default = "default_ack_timeout_ms")]
+    pub ack_timeout_ms: u64,
+    #[serde(default = "default_ack_poll_interval_ms")]
+    pub ack_poll_interval_ms: u64,
+    /// Skip TLS certificate verification (self-signed HEC certificates)
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+    /// Request body compression (HEC accepts gzip)
+    #[serde(default)]
+    pub compression: Compression,
+}
+
+#[derive(Debug, Deserialize)]
+struct HecResponse {
+    text: String,
+    code: i64,
+    #[serde(rename = "ackId")]
+    ack_id: Option<u64>,
+}
+
+pub struct SplunkSink {
+    config: SplunkConfig,
+    client: reqwest::Client,
+    /// HEC channel identifying this emitter; required when acks are enabled
+    channel: String,
+}
+
+impl SplunkSink {
+    pub fn from_config(config: SplunkConfig) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(config.insecure_skip_verify)
+            .build()?;
+
+        Ok(Self {
+            config,
+            client,
+            channel: Uuid::new_v4().to_string(),
+        })
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{path}", self.config.url.trim_end_matches('/'))
+    }
+
+    fn event(&self, entry: &LogEntry) -> Value {
+        let mut event = json!({
+            "time": entry.timestamp.timestamp_micros() as f64 / 1_000_000.0,
+            "source": entry.service,
+            "sourcetype": self.config.sourcetype,
+            "event": {
+                "id": entry.id,
+                "service": entry.service,
+                "level": entry.level.to_string(),
+                "message": entry.message,
+                "fields": entry.fields,
+                "trace": entry.trace,
+            },
+        });
+        if let Some(index) = &self.config.index {
+            event["index"] = json!(index);
+        }
+        event
+    }
+
+    /// Poll the ack endpoint until the indexer confirms `ack_id` or the timeout expires.
+    async fn wait_for_ack(
+        &self,
+        ack_id: u64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let deadline = Instant::now() + Duration::from_millis(self.config.ack_timeout_ms);
+        loop {
+            let response: Value = self
+                .client
+                .post(self.endpoint("/services/collector/ack"))
+                .query(&[("channel", &self.channel)])
+                .header("Authorization", format!("Splunk {}", self.config.token))
+                .header("X-Splunk-Request-Channel", &self.channel)
+                .json(&json!({ "acks": [ack_id] }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            if response["acks"][ack_id.to_string()].as_bool() == Some(true) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(format!("Splunk did not acknowledge batch {ack_id} in time").into());
+            }
+            tokio::time::sleep(Duration::from_millis(self.config.ack_poll_interval_ms)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for SplunkSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // HEC batches are concatenated JSON objects, not a JSON array
+        let mut body = String::new();
+        for entry in batch {
+            body.push_str(&self.event(entry).to_string());
+            body.push('\n');
+        }
+
+        let request = self
+            .client
+            .post(self.endpoint("/services/collector/event"))
+            .header("Authorization", format!("Splunk {}", self.config.token))
+            .header("X-Splunk-Request-Channel", &self.channel)
+            .header("Content-Type", "application/json");
+        let response = self
+            .config
+            .compression
+            .apply(request, body.into_bytes())?
+            .send()
+            .await?;
+
+        let status = response.status();
+        let hec: HecResponse = response.json().await?;
+        if !status.is_success() || hec.code != 0 {
+            return Err(format!(
+                "Splunk HEC error {status}: {} (code {})",
+                hec.text, hec.code
+            )
+            .into());
+        }
+
+        if self.config.use_ack {
+            let ack_id = hec.ack_id.ok_or(
+                "Splunk HEC returned no ackId; is indexer acknowledgment enabled on the token?",
+            )?;
+            self.wait_for_ack(ack_id).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::Write as _;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::log_entry::{LogEntry, LogLevel};
+use crate::sink::Sink;
+
+fn default_port() -> u16 {
+    514
+}
+
+fn default_facility() -> u8 {
+    // local0
+    16
+}
+
+fn default_app_name() -> String {
+    "logstorm".to_string()
+}
+
+fn default_hostname() -> String {
+    "logstorm".to_string()
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogTransport {
+    Udp,
+    Tcp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SyslogConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub transport: SyslogTransport,
+    /// Syslog facility code (0-23); defaults to local0
+    #[serde(default = "default_facility")]
+    pub facility: u8,
+    /// HOSTNAME field of each message
+    #[serde(default = "default_hostname")]
+    pub hostname: String,
+    /// APP-NAME used when `app_name_from_service` is false
+    #[serde(default = "default_app_name")]
+    pub app_name: String,
+    /// Use the entry's service as APP-NAME instead of `app_name`
+    #[serde(default)]
+    pub app_name_from_service: bool,
+}
+
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+/// Sends entries to a syslog collector as RFC 5424 messages. TCP uses
+/// octet-counting framing (RFC 6587) so multi-line messages stay intact.
+pub struct SyslogSink {
+    config: SyslogConfig,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl SyslogSink {
+    pub async fn from_config(config: SyslogConfig) -> std::io::Result<Self> {
+        super::resolve(&config.host, config.port).await?;
+        Ok(Self {
+            config,
+            connection: Mutex::new(None),
+        })
+    }
+
+    fn severity(level: &LogLevel) -> u8 {
+        match level {
+            LogLevel::Debug => 7,
+            LogLevel::Info => 6,
+            LogLevel::Warn => 4,
+            LogLevel::Error => 3,
+        }
+    }
+
+    /// Escape a structured data PARAM-VALUE (`"`, `\` and `]` must be escaped).
+    fn escape_param(value: &str) -> String {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            if matches!(c, '"' | '\\' | ']') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    /// Header fields must be printable US-ASCII without spaces; anything else is replaced.
+    fn header_field(value: &str, max_len: usize) -> String {
+        let field: String = value
+            .chars()
+            .map(|c| if c.is_ascii_graphic() { c } else { '_' })
+            .take(max_len)
+            .collect();
+        if field.is_empty() {
+            "-".to_string()
+        } else {
+            field
+        }
+    }
+
+    fn format(&self, entry: &LogEntry) -> String {
+        let pri = self.config.facility as u16 * 8 + Self::severity(&entry.level) as u16;
+        let app_name = if self.config.app_name_from_service {
+            &entry.service
+        } else {
+            &self.config.app_name
+        };
+
+        // trace ids and structured fields become extra SD-PARAMs (names are
+        // capped at 32 chars)
+        let mut params = String::new();
+        if let Some(trace) = &entry.trace {
+            for (key, id) in trace.ids() {
+                let _ = write!(params, " {key}=\"{id}\"");
+            }
+        }
+        for (key, value) in entry.fields_as_text() {
+            let _ = write!(
+                params,
+                " {}=\"{}\"",
+                Self::header_field(&key.replace(['=', ']', '"'], "_"), 32),
+                Self::escape_param(&value),
+            );
+        }
+
+        // <PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD] MSG
+        let mut message = String::with_capacity(entry.message.len() + params.len() + 160);
+        let _ = write!(
+            message,
+            "<{pri}>1 {} {} {} - - [logstorm@32473 id=\"{}\" service=\"{}\" level=\"{}\"{params}] {}",
+            entry
+                .timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            Self::header_field(&self.config.hostname, 255),
+            Self::header_field(app_name, 48),
+            Self::escape_param(&entry.id),
+            Self::escape_param(&entry.service),
+            entry.level,
+            entry.message,
+        );
+        message
+    }
+
+    async fn connect(&self) -> std::io::Result<Connection> {
+        // a tuple rather than `host:port`, so IPv6 literals like `::1` work
+        let (host, port) = (self.config.host.as_str(), self.config.port);
+        match self.config.transport {
+            SyslogTransport::Udp => Ok(Connection::Udp(super::connect_udp(host, port).await?)),
+            SyslogTransport::Tcp => Ok(Connection::Tcp(TcpStream::connect((host, port)).await?)),
+        }
+    }
+
+    async fn send(connection: &mut Connection, messages: &[String]) -> std::io::Result<()> {
+        match connection {
+            Connection::Udp(socket) => {
+                for message in messages {
+                    socket.send(message.as_bytes()).await?;
+                }
+            }
+            Connection::Tcp(stream) => {
+                let mut framed = Vec::new();
+                for message in messages {
+                    framed.extend_from_slice(format!("{} ", message.len()).as_bytes());
+                    framed.extend_from_slice(message.as_bytes());
+                }
+                stream.write_all(&framed).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for SyslogSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let messages: Vec<String> = batch.iter().map(|entry| self.format(entry)).collect();
+
+        let mut connection = self.connection.lock().await;
+        if connection.is_none() {
+            *connection = Some(self.connect().await?);
+        }
+
+        let conn = connection
+            .as_mut()
+            .expect("connection was just established");
+        if let Err(e) = Self::send(conn, &messages).await {
+            // the collector may have dropped the connection; retry once on a fresh one
+            warn!("Syslog write failed, reconnecting: {e}");
+            *connection = None;
+            let mut conn = self.connect().await?;
+            Self::send(&mut conn, &messages).await?;
+            *connection = Some(conn);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+
+use tokio::sync::Mutex;
+
+use crate::log_entry::LogEntry;
+
+const SERVICE_PLACEHOLDER: &str = "{service}";
+
+/// The name of an index, collection, or table written by a sink. Names may
+/// contain a strftime-style date pattern (e.g. `logs-%Y.%m.%d`), resolved
+/// from each entry's timestamp so a new target is started as the clock rolls
+/// over, and a `{service}` placeholder for a target per service. Keeps track
+/// of which resolved targets have been created already.
+pub struct TargetName {
+    template: String,
+    /// Restrict substituted service names to `[a-z0-9_]`, for SQL identifiers
+    identifier: bool,
+    created: Mutex<HashSet<String>>,
+}
+
+impl TargetName {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            identifier: false,
+            created: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// A name that must stay a valid unquoted SQL identifier
+    #[cfg_attr(not(feature = "pgvector"), allow(dead_code))]
+    pub fn identifier(template: impl Into<String>) -> Self {
+        Self {
+            identifier: true,
+            ..Self::new(template)
+        }
+    }
+
+    /// Whether every entry resolves to the same target
+    pub fn is_static(&self) -> bool {
+        !self.template.contains('%') && !self.template.contains(SERVICE_PLACEHOLDER)
+    }
+
+    /// A wildcard pattern matching every name this template can resolve to,
+    /// e.g. `logs-*` for `logs-{service}-%Y.%m.%d`
+    #[cfg_attr(not(feature = "elasticsearch"), allow(dead_code))]
+    pub fn pattern(&self) -> String {
+        let template = self.template.replace(SERVICE_PLACEHOLDER, "*");
+        let mut chars = template.chars().peekable();
+        let mut pattern = String::new();
+        while let Some(c) = chars.next() {
+            let c = if c == '%' {
+                // skip padding flags, then the specifier itself
+                while chars
+                    .next_if(|c| matches!(c, '-' | '_' | '0' | '^' | '#'))
+                    .is_some()
+                {}
+                chars.next();
+                '*'
+            } else {
+                c
+            };
+            pattern.push(c);
+        }
+
+        // everything between two wildcards is covered by a single one
+        match (pattern.find('*'), pattern.rfind('*')) {
+            (Some(first), Some(last)) => {
+                format!("{}*{}", &pattern[..first], &pattern[last + 1..])
+            }
+            _ => pattern,
+        }
+    }
+
+    pub fn resolve(&self, entry: &LogEntry) -> String {
+        if self.is_static() {
+            return self.template.clone();
+        }
+        let mut name = String::new();
+        if write!(name, "{}", entry.timestamp.format(&self.template)).is_err() {
+            // invalid format specifiers; fall back to the literal name
+            name = self.template.clone();
+        }
+        if name.contains(SERVICE_PLACEHOLDER) {
+            // lowercase, as Elasticsearch index names must be
+            let mut service = entry.service.to_lowercase();
+            if self.identifier {
+                service = service
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                    .collect();
+            }
+            name = name.replace(SERVICE_PLACEHOLDER, &service);
+        }
+        name
+    }
+
+    /// Split a batch by resolved target
+    pub fn group<'a>(&self, batch: &'a [LogEntry]) -> BTreeMap<String, Vec<&'a LogEntry>> {
+        let mut groups: BTreeMap<String, Vec<&LogEntry>> = BTreeMap::new();
+        for entry in batch {
+            groups.entry(self.resolve(entry)).or_default().push(entry);
+        }
+        groups
+    }
+
+    pub async fn is_created(&self, name: &str) -> bool {
+        self.created.lock().await.contains(name)
+    }
+
+    pub async fn mark_created(&self, name: &str) {
+        self.created.lock().await.insert(name.to_string());
+    }
+
+    /// Every target created (or found to exist) so far
+    pub async fn created(&self) -> Vec<String> {
+        self.created.lock().await.iter().cloned().collect()
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+
+use crate::embedding;
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+
+/// Wraps another sink, passing on entries with their embeddings cut down to
+/// the first `dim` dimensions
+pub struct TruncateSink {
+    inner: Box<dyn Sink>,
+    dim: usize,
+}
+
+impl TruncateSink {
+    pub fn new(inner: Box<dyn Sink>, dim: usize) -> Self {
+        Self { inner, dim }
+    }
+}
+
+#[async_trait]
+impl Sink for TruncateSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let truncated: Vec<LogEntry> = batch
+            .iter()
+            .map(|entry| LogEntry {
+                embedding: embedding::truncate(&entry.embedding, self.dim),
+                ..entry.clone()
+            })
+            .collect();
+        self.inner.write(&truncated).await
+    }
+
+    async fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.init().await
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,224 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::info;
+
+use crate::log_entry::LogEntry;
+use crate::sink::Sink;
+use crate::sink::{DEFAULT_INDEX_NAME, DENSE_EMBEDDING_NAME};
+
+fn default_namespace() -> String {
+    DEFAULT_INDEX_NAME.to_string()
+}
+
+fn default_document_type() -> String {
+    DEFAULT_INDEX_NAME.to_string()
+}
+
+fn default_concurrency() -> usize {
+    64
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VespaConfig {
+    /// Base URL of the Vespa container cluster, e.g. `http://localhost:8080`
+    pub url: String,
+    /// Data-plane token for Vespa Cloud endpoints
+    pub auth_token: Option<String>,
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+    #[serde(default = "default_document_type")]
+    pub document_type: String,
+    /// Maximum number of in-flight document/v1 requests per flush
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// If set, write a matching `.sd` schema to this path on startup so it can
+    /// be dropped into the application package before deploying
+    pub schema_path: Option<String>,
+}
+
+pub struct VespaSink {
+    config: VespaConfig,
+    client: reqwest::Client,
+    /// Dimensions of the dense embedding fed, 0 when embedding is off
+    embedding_dim: usize,
+}
+
+impl VespaSink {
+    pub async fn from_config(
+        config: VespaConfig,
+        embedding_dim: usize,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(path) = &config.schema_path {
+            std::fs::write(path, generate_schema(&config.document_type, embedding_dim))?;
+            info!(
+                "Wrote Vespa schema for '{}' to {path}",
+                config.document_type
+            );
+        }
+
+        let client = reqwest::Client::builder().build()?;
+
+        Ok(Self {
+            config,
+            client,
+            embedding_dim,
+        })
+    }
+
+    fn document_url(&self, id: &str) -> String {
+        format!(
+            "{}/document/v1/{}/{}/docid/{}",
+            self.config.url.trim_end_matches('/'),
+            self.config.namespace,
+            self.config.document_type,
+            id,
+        )
+    }
+
+    async fn feed(&self, entry: &LogEntry) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut document = json!({
+            "fields": {
+                "id": entry.id,
+                "timestamp": entry.timestamp.timestamp_millis(),
+                "service": entry.service,
+                "level": format!("{:?}", entry.level),
+                "message": entry.message,
+                // `fields` is a keyword in schemas, so structured fields go in `attributes`
+                "attributes": entry.fields_as_text(),
+            }
+        });
+        if self.embedding_dim > 0 {
+            document["fields"][DENSE_EMBEDDING_NAME] = json!({ "values": entry.embedding });
+        }
+        if let Some(trace) = &entry.trace {
+            for (key, id) in trace.ids() {
+                document["fields"][key] = json!(id);
+            }
+        }
+        let mut request = self
+            .client
+            .post(self.document_url(&entry.id))
+            .json(&document);
+        if let Some(token) = &self.config.auth_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Vespa rejected document {}: {status} {body}", entry.id).into());
+        }
+        Ok(())
+    }
+}
+
+/// Generate a Vespa schema (`.sd`) matching the documents written by [`VespaSink`]:
+/// attribute fields for `service`/`level` filtering, a BM25-enabled `message`
+/// index, a string map of the structured fields, trace ids for lookups by
+/// trace, and an HNSW-indexed tensor for the dense embedding, plus rank profiles
+/// for keyword, semantic, and hybrid queries. Without embeddings
+/// (`embedding_dim` 0) there's no tensor, and only the keyword profile.
+pub fn generate_schema(document_type: &str, embedding_dim: usize) -> String {
+    let (dense_field, dense_profiles) = if embedding_dim > 0 {
+        (
+            format!(
+                r#"
+        field {DENSE_EMBEDDING_NAME} type tensor<float>(x[{embedding_dim}]) {{
+            indexing: attribute | index
+            attribute {{
+                distance-metric: angular
+            }}
+        }}"#
+            ),
+            format!(
+                r#"
+
+    rank-profile semantic {{
+        inputs {{
+            query(q) tensor<float>(x[{embedding_dim}])
+        }}
+        first-phase {{
+            expression: closeness(field, {DENSE_EMBEDDING_NAME})
+        }}
+    }}
+
+    rank-profile hybrid inherits semantic {{
+        global-phase {{
+            expression: reciprocal_rank_fusion(bm25(message), closeness(field, {DENSE_EMBEDDING_NAME}))
+        }}
+    }}"#
+            ),
+        )
+    } else {
+        (String::new(), String::new())
+    };
+    format!(
+        r#"schema {document_type} {{
+    document {document_type} {{
+        field id type string {{
+            indexing: summary | attribute
+        }}
+        field timestamp type long {{
+            indexing: summary | attribute
+        }}
+        field service type string {{
+            indexing: summary | attribute
+            attribute: fast-search
+        }}
+        field level type string {{
+            indexing: summary | attribute
+            attribute: fast-search
+        }}
+        field message type string {{
+            indexing: summary | index
+            index: enable-bm25
+        }}
+        field attributes type map<string, string> {{
+            indexing: summary
+            struct-field key {{ indexing: attribute }}
+            struct-field value {{ indexing: attribute }}
+        }}
+        field trace_id type string {{
+            indexing: summary | attribute
+            attribute: fast-search
+        }}
+        field span_id type string {{
+            indexing: summary | attribute
+        }}
+        field parent_span_id type string {{
+            indexing: summary | attribute
+        }}{dense_field}
+    }}
+
+    fieldset default {{
+        fields: message
+    }}
+
+    rank-profile bm25 {{
+        first-phase {{
+            expression: bm25(message)
+        }}
+    }}{dense_profiles}
+}}
+"#
+    )
+}
+
+#[async_trait]
+impl Sink for VespaSink {
+    async fn write(
+        &self,
+        batch: &[LogEntry],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // document/v1 has no bulk endpoint, so fan out one request per document
+        let feeds: Vec<_> = batch.iter().map(|entry| self.feed(entry)).collect();
+        stream::iter(feeds)
+            .buffer_unordered(self.config.concurrency.max(1))
+            .try_collect::<()>()
+            .await
+    }
+}
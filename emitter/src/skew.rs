@@ -0,0 +1,45 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+fn default_max_lag_ms() -> u64 {
+    30_000
+}
+
+/// How far a service's timestamps stray from the time its entries are
+/// emitted: a steady clock offset, plus some entries arriving late
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SkewConfig {
+    /// Offset of the service's clock; negative runs behind
+    #[serde(default)]
+    pub clock_skew_ms: i64,
+    /// Share of entries stamped up to `max_lag_ms` before they're emitted,
+    /// so they land out of order (0.0 - 1.0)
+    #[serde(default)]
+    pub late_rate: f64,
+    #[serde(default = "default_max_lag_ms")]
+    pub max_lag_ms: u64,
+}
+
+impl Default for SkewConfig {
+    fn default() -> Self {
+        Self {
+            clock_skew_ms: 0,
+            late_rate: 0.0,
+            max_lag_ms: default_max_lag_ms(),
+        }
+    }
+}
+
+impl SkewConfig {
+    /// The timestamp the service would put on an entry emitted at `emitted`
+    pub fn apply(&self, emitted: DateTime<Utc>, rng: &mut impl Rng) -> DateTime<Utc> {
+        let mut timestamp = emitted + Duration::milliseconds(self.clock_skew_ms);
+        if self.late_rate > 0.0 && rng.gen_bool(self.late_rate.clamp(0.0, 1.0)) {
+            let lag = rng.gen_range(0..=self.max_lag_ms) as i64;
+            timestamp -= Duration::milliseconds(lag);
+        }
+        timestamp
+    }
+}
@@ -0,0 +1,291 @@
+use std::fmt::Write;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::log_entry::{LogEntry, LogLevel};
+
+const METHODS: &[&str] = &[
+    "handle", "process", "execute", "dispatch", "apply", "poll", "send", "fetch", "commit",
+    "acquire", "validate", "resolve", "flush", "decode",
+];
+
+const JAVA_EXCEPTIONS: &[&str] = &[
+    "java.net.SocketTimeoutException",
+    "java.lang.IllegalStateException",
+    "java.sql.SQLTransientConnectionException",
+    "java.util.concurrent.TimeoutException",
+    "java.io.IOException",
+    "java.lang.NullPointerException",
+];
+
+const JAVA_CAUSES: &[&str] = &[
+    "java.net.ConnectException: Connection refused",
+    "java.io.EOFException: Unexpected end of stream",
+    "javax.net.ssl.SSLHandshakeException: PKIX path validation failed",
+];
+
+const JAVA_FRAMEWORK: &[&str] = &[
+    "org.springframework.web.servlet.FrameworkServlet.service(FrameworkServlet.java:897)",
+    "io.netty.channel.AbstractChannelHandlerContext.invokeChannelRead(AbstractChannelHandlerContext.java:379)",
+    "java.base/java.util.concurrent.ThreadPoolExecutor.runWorker(ThreadPoolExecutor.java:1136)",
+    "java.base/java.util.concurrent.ThreadPoolExecutor$Worker.run(ThreadPoolExecutor.java:635)",
+    "java.base/java.lang.Thread.run(Thread.java:833)",
+];
+
+const PYTHON_EXCEPTIONS: &[&str] = &[
+    "ConnectionError",
+    "TimeoutError",
+    "KeyError",
+    "ValueError",
+    "RuntimeError",
+    "sqlalchemy.exc.OperationalError",
+];
+
+const PYTHON_FRAMEWORK: &[(&str, &str, &str)] = &[
+    (
+        "/usr/local/lib/python3.11/site-packages/starlette/routing.py",
+        "app",
+        "response = await func(request)",
+    ),
+    (
+        "/usr/local/lib/python3.11/site-packages/fastapi/routing.py",
+        "run_endpoint_function",
+        "return await dependant.call(**values)",
+    ),
+];
+
+const PYTHON_STATEMENTS: &[&str] = &[
+    "result = client.post(url, json=payload, timeout=timeout)",
+    "row = session.execute(query).one()",
+    "return self._pool.acquire()",
+    "response.raise_for_status()",
+    "value = cache[key]",
+    "await asyncio.wait_for(task, timeout=self.timeout)",
+];
+
+const RUST_RUNTIME: &[&str] = &[
+    "tokio::runtime::task::core::Core<T,S>::poll",
+    "tokio::runtime::task::harness::Harness<T,S>::poll",
+    "tokio::runtime::scheduler::multi_thread::worker::Context::run_task",
+    "std::sys::pal::unix::thread::Thread::new::thread_start",
+];
+
+const RUST_PANICS: &[&str] = &[
+    "called `Result::unwrap()` on an `Err` value: Os { code: 104, kind: ConnectionReset }",
+    "called `Option::unwrap()` on a `None` value",
+    "index out of bounds: the len is 0 but the index is 0",
+    "connection pool exhausted",
+];
+
+fn default_rate() -> f64 {
+    0.3
+}
+
+fn default_max_frames() -> usize {
+    12
+}
+
+fn default_languages() -> Vec<TraceLanguage> {
+    vec![
+        TraceLanguage::Java,
+        TraceLanguage::Python,
+        TraceLanguage::Rust,
+    ]
+}
+
+/// Which runtime's traces to imitate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceLanguage {
+    Java,
+    Python,
+    Rust,
+}
+
+/// Where an entry's stack trace goes
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TracePlacement {
+    /// An `exception` structured field, leaving the message as it was
+    #[default]
+    Field,
+    /// Appended to the message after a newline
+    Message,
+}
+
+/// Synthetic multi-line stack traces on a share of ERROR entries
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StackTraceConfig {
+    /// Share of ERROR entries that get a trace (0.0 - 1.0)
+    #[serde(default = "default_rate")]
+    pub rate: f64,
+    /// Languages to pick from for each trace
+    #[serde(default = "default_languages")]
+    pub languages: Vec<TraceLanguage>,
+    #[serde(default)]
+    pub placement: TracePlacement,
+    /// Most application frames in a trace, before runtime frames
+    #[serde(default = "default_max_frames")]
+    pub max_frames: usize,
+}
+
+impl StackTraceConfig {
+    /// Give `entry` a stack trace, if it's an ERROR and the dice say so.
+    /// The embedding is left alone: it stays that of the original message.
+    pub fn apply(&self, entry: &mut LogEntry, rng: &mut impl Rng) {
+        if entry.level != LogLevel::Error || !rng.gen_bool(self.rate.clamp(0.0, 1.0)) {
+            return;
+        }
+        let Some(&language) = self.languages.choose(rng) else {
+            return;
+        };
+        let trace = Frames::new(entry, self.max_frames, rng).render(language, rng);
+        match self.placement {
+            TracePlacement::Field => {
+                entry.fields.insert("exception".to_string(), json!(trace));
+            }
+            TracePlacement::Message => {
+                entry.message.push('\n');
+                entry.message.push_str(&trace);
+            }
+        }
+    }
+}
+
+/// The application frames of a trace, innermost first, named after the
+/// entry's service and the component its message mentions
+struct Frames {
+    /// e.g. `payment_service`
+    module: String,
+    /// e.g. `PaymentProcessor`
+    component: String,
+    /// The message without its component prefix
+    detail: String,
+    methods: Vec<(&'static str, u32)>,
+}
+
+impl Frames {
+    fn new(entry: &LogEntry, max_frames: usize, rng: &mut impl Rng) -> Self {
+        let (component, detail) = match entry.message.split_once(": ") {
+            Some((component, detail)) if !component.contains(' ') => {
+                (component.to_string(), detail.to_string())
+            }
+            _ => ("Handler".to_string(), entry.message.clone()),
+        };
+        let depth = rng.gen_range(1..=max_frames.max(1));
+        let methods = (0..depth)
+            .map(|_| (*METHODS.choose(rng).unwrap(), rng.gen_range(20..600)))
+            .collect();
+        Self {
+            module: entry.service.replace('-', "_"),
+            component,
+            detail,
+            methods,
+        }
+    }
+
+    fn render(&self, language: TraceLanguage, rng: &mut impl Rng) -> String {
+        match language {
+            TraceLanguage::Java => self.java(rng),
+            TraceLanguage::Python => self.python(rng),
+            TraceLanguage::Rust => self.rust(rng),
+        }
+    }
+
+    fn java(&self, rng: &mut impl Rng) -> String {
+        let package = format!("com.logstorm.{}", self.module.replace('_', ""));
+        let mut trace = format!("{}: {}", JAVA_EXCEPTIONS.choose(rng).unwrap(), self.detail);
+        for (method, line) in &self.methods {
+            let _ = write!(
+                trace,
+                "\n\tat {package}.{component}.{method}({component}.java:{line})",
+                component = self.component,
+            );
+        }
+        for frame in JAVA_FRAMEWORK {
+            let _ = write!(trace, "\n\tat {frame}");
+        }
+        if rng.gen_bool(0.5) {
+            let _ = write!(
+                trace,
+                "\nCaused by: {}\n\tat java.base/sun.nio.ch.NioSocketImpl.read(NioSocketImpl.java:{})\n\t... {} more",
+                JAVA_CAUSES.choose(rng).unwrap(),
+                rng.gen_range(200..400),
+                self.methods.len() + JAVA_FRAMEWORK.len(),
+            );
+        }
+        trace
+    }
+
+    fn python(&self, rng: &mut impl Rng) -> String {
+        let mut trace = "Traceback (most recent call last):".to_string();
+        for (file, function, statement) in PYTHON_FRAMEWORK {
+            let _ = write!(
+                trace,
+                "\n  File \"{file}\", line {}, in {function}\n    {statement}",
+                rng.gen_range(50..300),
+            );
+        }
+        let file = format!("/app/{}/{}.py", self.module, snake_case(&self.component));
+        // outermost call first
+        for (method, line) in self.methods.iter().rev() {
+            let _ = write!(
+                trace,
+                "\n  File \"{file}\", line {line}, in {method}\n    {}",
+                PYTHON_STATEMENTS.choose(rng).unwrap(),
+            );
+        }
+        let _ = write!(
+            trace,
+            "\n{}: {}",
+            PYTHON_EXCEPTIONS.choose(rng).unwrap(),
+            self.detail
+        );
+        trace
+    }
+
+    fn rust(&self, rng: &mut impl Rng) -> String {
+        let file = format!("src/{}.rs", snake_case(&self.component));
+        let (_, panic_line) = self.methods[0];
+        let mut trace = format!(
+            "thread 'tokio-runtime-worker' panicked at {file}:{panic_line}:{}:\n{}\nstack backtrace:\n   0: rust_begin_unwind\n   1: core::panicking::panic_fmt",
+            rng.gen_range(5..40),
+            RUST_PANICS.choose(rng).unwrap(),
+        );
+        let mut frame = 2;
+        for (method, line) in &self.methods {
+            let _ = write!(
+                trace,
+                "\n  {frame:>2}: {}::{}::{method}\n             at ./{file}:{line}:{}",
+                self.module,
+                snake_case(&self.component),
+                rng.gen_range(5..40),
+            );
+            frame += 1;
+        }
+        for function in RUST_RUNTIME {
+            let _ = write!(trace, "\n  {frame:>2}: {function}");
+            frame += 1;
+        }
+        trace.push_str("\nnote: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.");
+        trace
+    }
+}
+
+/// `PaymentProcessor` -> `payment_processor`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut after_lowercase = false;
+    for c in name.chars() {
+        if c.is_uppercase() && after_lowercase {
+            snake.push('_');
+        }
+        after_lowercase = c.is_lowercase();
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
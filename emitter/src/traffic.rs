@@ -0,0 +1,146 @@
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+fn default_period_secs() -> u64 {
+    86_400
+}
+
+fn default_amplitude() -> f64 {
+    0.5
+}
+
+/// A daily (or faster, for demos) sinusoidal swing in rate
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiurnalConfig {
+    #[serde(default = "default_period_secs")]
+    pub period_secs: u64,
+    /// How far the rate swings either way, as a fraction of `rate_per_sec`
+    /// (0.0 - 1.0)
+    #[serde(default = "default_amplitude")]
+    pub amplitude: f64,
+    /// Seconds into each period the rate peaks
+    #[serde(default)]
+    pub peak_secs: u64,
+}
+
+/// A window with the rate multiplied, e.g. a flash sale
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BurstConfig {
+    pub start_secs: u64,
+    pub duration_secs: u64,
+    pub multiplier: f64,
+}
+
+fn default_stddev() -> f64 {
+    0.2
+}
+
+fn default_every_secs() -> f64 {
+    5.0
+}
+
+/// How a service's entries are spaced out at a given rate
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Arrival {
+    /// Exponential gaps, as from many independent clients
+    #[default]
+    Poisson,
+    /// Evenly spaced, like a health check or a poller
+    Fixed,
+    /// Gaps around the mean, normally distributed with `stddev` as a
+    /// fraction of the mean
+    Gaussian {
+        #[serde(default = "default_stddev")]
+        stddev: f64,
+    },
+    /// Everything due over `every_secs` at once, like a buffered agent
+    /// flushing; entries keep the times they were due at
+    Batch {
+        #[serde(default = "default_every_secs")]
+        every_secs: f64,
+    },
+}
+
+/// How a service's rate varies over the run, around its `rate_per_sec`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TrafficShape {
+    /// How entries are spaced out at that rate
+    #[serde(default)]
+    pub arrival: Arrival,
+    pub diurnal: Option<DiurnalConfig>,
+    #[serde(default)]
+    pub bursts: Vec<BurstConfig>,
+    /// Climb linearly from zero to the full rate over this many seconds
+    pub ramp_up_secs: Option<u64>,
+    /// Fall linearly to zero over the last this many seconds of the run
+    pub ramp_down_secs: Option<u64>,
+}
+
+impl TrafficShape {
+    /// Factor to apply to the base rate `elapsed` into the run, with
+    /// `remaining` left of it (unknown when running indefinitely)
+    pub fn multiplier(&self, elapsed: Duration, remaining: Option<Duration>) -> f64 {
+        let t = elapsed.as_secs_f64();
+        let mut multiplier = 1.0;
+
+        if let Some(diurnal) = &self.diurnal {
+            let period = diurnal.period_secs.max(1) as f64;
+            let phase = (t - diurnal.peak_secs as f64) / period;
+            multiplier *= 1.0 + diurnal.amplitude.clamp(0.0, 1.0) * (TAU * phase).cos();
+        }
+
+        for burst in &self.bursts {
+            let start = burst.start_secs as f64;
+            if (start..start + burst.duration_secs as f64).contains(&t) {
+                multiplier *= burst.multiplier;
+            }
+        }
+
+        if let Some(ramp) = self.ramp_up_secs.filter(|&secs| secs > 0) {
+            multiplier *= (t / ramp as f64).min(1.0);
+        }
+        if let (Some(ramp), Some(remaining)) =
+            (self.ramp_down_secs.filter(|&secs| secs > 0), remaining)
+        {
+            multiplier *= (remaining.as_secs_f64() / ramp as f64).min(1.0);
+        }
+
+        multiplier.max(0.0)
+    }
+}
+
+/// Generate a past window's worth of entries as fast as the sinks take
+/// them, instead of in real time
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BackfillConfig {
+    /// Length of the window
+    pub days: f64,
+    /// How long before now the window ends (default: it ends now)
+    #[serde(default)]
+    pub end_secs_ago: u64,
+}
+
+impl BackfillConfig {
+    /// The window, ending `end_secs_ago` before `now`
+    pub fn window(&self, now: DateTime<Utc>) -> Backfill {
+        let span = Duration::from_secs_f64(self.days.max(0.0) * 86_400.0);
+        let end = now - chrono::Duration::seconds(self.end_secs_ago as i64);
+        Backfill {
+            start: end - chrono::Duration::from_std(span).unwrap_or_default(),
+            span,
+        }
+    }
+}
+
+/// The past window a backfill covers. Its start is the start of the run,
+/// as far as traffic shapes and scenarios are concerned.
+#[derive(Debug, Clone, Copy)]
+pub struct Backfill {
+    pub start: DateTime<Utc>,
+    pub span: Duration,
+}
@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::config::{EmbeddingConfig, EmitterConfig, PipelineConfig, SinkSpec};
+
+/// Something wrong with a config that would parse, at the path of the
+/// offending value (e.g. `pipelines[1].services[0].rate_per_sec`)
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// The problems found so far
+struct Checker {
+    problems: Vec<Problem>,
+}
+
+impl Checker {
+    fn report(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.problems.push(Problem {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+}
+
+/// Every problem with `config` at once, rather than only the first; empty
+/// when it's good to run
+pub fn validate(config: &EmitterConfig) -> Vec<Problem> {
+    let mut checker = Checker {
+        problems: Vec::new(),
+    };
+
+    if config.message_pool_size == 0 {
+        checker.report("message_pool_size", "must be above 0");
+    }
+    check_embedding(&mut checker, "embedding", &config.embedding);
+    for (name, extra) in &config.extra_embeddings {
+        let path = format!("extra_embeddings.{name}");
+        if !is_identifier(name) {
+            checker.report(
+                &path,
+                "names must be plain identifiers (letters, digits and _), as they become pgvector columns",
+            );
+        }
+        if !extra.enabled() {
+            checker.report(
+                format!("{path}.provider"),
+                "extra embeddings need a provider",
+            );
+        }
+        check_embedding(&mut checker, &path, extra);
+    }
+
+    if config.pipelines.is_empty() {
+        check_pipeline(&mut checker, "", &config.pipeline);
+    } else {
+        let mut names = HashSet::new();
+        for (i, pipeline) in config.pipelines.iter().enumerate() {
+            let prefix = format!("pipelines[{i}].");
+            if !names.insert(pipeline.name.as_str()) {
+                checker.report(
+                    format!("{prefix}name"),
+                    format!("another pipeline is already named {}", pipeline.name),
+                );
+            }
+            check_pipeline(&mut checker, &prefix, pipeline);
+        }
+    }
+
+    checker.problems
+}
+
+fn check_embedding(checker: &mut Checker, path: &str, embedding: &EmbeddingConfig) {
+    if !embedding.enabled() {
+        return;
+    }
+    if embedding.dimensions == 0 {
+        checker.report(format!("{path}.dimensions"), "must be above 0");
+    }
+    if let Some(dim) = embedding.truncate_dim
+        && (dim == 0 || dim > embedding.dimensions)
+    {
+        checker.report(
+            format!("{path}.truncate_dim"),
+            format!(
+                "must be between 1 and dimensions ({})",
+                embedding.dimensions
+            ),
+        );
+    }
+    if embedding.batch_size == Some(0) {
+        checker.report(format!("{path}.batch_size"), "must be above 0");
+    }
+    if embedding.concurrency == 0 {
+        checker.report(format!("{path}.concurrency"), "must be above 0");
+    }
+    if let Some(url) = &embedding.url {
+        check_url(checker, format!("{path}.url"), url);
+    }
+}
+
+fn check_pipeline(checker: &mut Checker, prefix: &str, pipeline: &PipelineConfig) {
+    if pipeline.buffer_size == 0 {
+        checker.report(format!("{prefix}buffer_size"), "must be above 0");
+    }
+    if pipeline.flush_interval_ms == 0 {
+        checker.report(format!("{prefix}flush_interval_ms"), "must be above 0");
+    }
+    if pipeline.channel_capacity == 0 {
+        checker.report(format!("{prefix}channel_capacity"), "must be above 0");
+    } else if pipeline.channel_capacity < pipeline.buffer_size {
+        checker.report(
+            format!("{prefix}channel_capacity"),
+            format!(
+                "is below buffer_size ({}), so emitters wait on every batch before it fills; \
+                 raise it or lower buffer_size",
+                pipeline.buffer_size
+            ),
+        );
+    }
+    if pipeline.services.is_empty() && pipeline.flows.is_empty() && pipeline.replay.is_none() {
+        checker.report(
+            format!("{prefix}services"),
+            "no services or flows to generate entries, and nothing to replay",
+        );
+    }
+
+    // services that log for others, so may have no traffic of their own
+    let downstream: HashSet<&str> = pipeline
+        .flows
+        .iter()
+        .flat_map(|flow| flow.path.iter())
+        .chain(
+            pipeline
+                .cascades
+                .iter()
+                .flat_map(|cascade| cascade.to.iter()),
+        )
+        .map(String::as_str)
+        .collect();
+
+    let mut services = HashSet::new();
+    for (i, service) in pipeline.services.iter().enumerate() {
+        let path = format!("{prefix}services[{i}]");
+        if !services.insert(service.name.as_str()) {
+            checker.report(
+                format!("{path}.name"),
+                format!("another service is already named {}", service.name),
+            );
+        }
+        if !(service.rate_per_sec.is_finite() && service.rate_per_sec >= 0.0) {
+            checker.report(
+                format!("{path}.rate_per_sec"),
+                format!("can't be negative, not {}", service.rate_per_sec),
+            );
+        } else if service.rate_per_sec == 0.0 && !downstream.contains(service.name.as_str()) {
+            checker.report(
+                format!("{path}.rate_per_sec"),
+                "is 0, and no flow or cascade reaches this service, so it logs nothing",
+            );
+        }
+        let weights = &service.level_weights;
+        let all = [weights.debug, weights.info, weights.warn, weights.error];
+        if all
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            checker.report(format!("{path}.level_weights"), "weights can't be negative");
+        } else if all.iter().sum::<f64>() <= 0.0 {
+            checker.report(
+                format!("{path}.level_weights"),
+                "at least one level needs a weight above 0",
+            );
+        }
+    }
+
+    for (i, flow) in pipeline.flows.iter().enumerate() {
+        let path = format!("{prefix}flows[{i}]");
+        if !(flow.rate_per_sec.is_finite() && flow.rate_per_sec > 0.0) {
+            checker.report(
+                format!("{path}.rate_per_sec"),
+                format!("must be above 0, not {}", flow.rate_per_sec),
+            );
+        }
+    }
+
+    if pipeline.sinks.is_empty() {
+        checker.report(format!("{prefix}sinks"), "no sinks to write to");
+    }
+    let mut sinks = HashSet::new();
+    for (i, spec) in pipeline.sinks.iter().enumerate() {
+        let path = format!("{prefix}sinks[{i}]");
+        check_sink(checker, &path, spec);
+        if !sinks.insert(spec.name()) {
+            checker.report(
+                format!("{path}.name"),
+                format!(
+                    "another sink is already called {}; name them apart to tell their reports and metrics apart",
+                    spec.name()
+                ),
+            );
+        }
+    }
+}
+
+fn check_sink(checker: &mut Checker, path: &str, spec: &SinkSpec) {
+    if let Some((key, url)) = spec.sink.url() {
+        check_url(checker, format!("{path}.{key}"), url);
+    }
+    if spec.truncate_dim == Some(0) {
+        checker.report(format!("{path}.truncate_dim"), "must be above 0");
+    }
+    if let Some(embedding) = &spec.embedding {
+        check_embedding(checker, &format!("{path}.embedding"), embedding);
+    }
+    #[cfg(all(
+        feature = "zstd",
+        any(feature = "elasticsearch", feature = "splunk", feature = "datadog")
+    ))]
+    check_gzip_only(checker, path, &spec.sink);
+}
+
+/// Flag zstd on sinks whose backends only take gzip request bodies
+#[cfg(all(
+    feature = "zstd",
+    any(feature = "elasticsearch", feature = "splunk", feature = "datadog")
+))]
+fn check_gzip_only(checker: &mut Checker, path: &str, sink: &crate::config::SinkConfig) {
+    use crate::config::SinkConfig;
+    use crate::sink::compression::Compression;
+
+    let compressed: Option<(&str, Compression)> = match sink {
+        #[cfg(feature = "elasticsearch")]
+        SinkConfig::ElasticSearch(config) => Some(("Elasticsearch", config.compression)),
+        #[cfg(feature = "splunk")]
+        SinkConfig::Splunk(config) => Some(("Splunk HEC", config.compression)),
+        #[cfg(feature = "datadog")]
+        SinkConfig::Datadog(config) => Some(("The Datadog intake", config.compression)),
+        _ => None,
+    };
+    if let Some((backend, Compression::Zstd)) = compressed {
+        checker.report(
+            format!("{path}.compression"),
+            format!("{backend} accepts gzip, not zstd"),
+        );
+    }
+}
+
+fn check_url(checker: &mut Checker, path: String, url: &str) {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if parsed.has_host() => {}
+        Ok(_) => checker.report(path, format!("{url} has no host")),
+        // most likely an unset `${VAR}`
+        Err(_) if url.is_empty() => checker.report(path, "is empty"),
+        Err(e) => checker.report(path, format!("{url} isn't a valid URL: {e}")),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,400 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::log_entry::LogLevel;
+
+// ---------------------------------------------------------------------------
+// Default slot lists for combinatorial message generation
+// 20 items per slot × 4 patterns × 20^4..20^5 = ~640K–12.8M unique messages
+// ---------------------------------------------------------------------------
+
+const COMPONENTS: &[&str] = &[
+    "ConnectionPool",
+    "QueryExecutor",
+    "AuthManager",
+    "CacheLayer",
+    "LoadBalancer",
+    "RateLimiter",
+    "SchemaValidator",
+    "EventBus",
+    "HealthMonitor",
+    "SessionStore",
+    "CircuitBreaker",
+    "JobScheduler",
+    "ReplicaManager",
+    "PartitionConsumer",
+    "TLSHandler",
+    "GarbageCollector",
+    "DiskMonitor",
+    "DeadLetterQueue",
+    "IdempotencyFilter",
+    "TraceContext",
+];
+
+const ACTIONS: &[&str] = &[
+    "detected threshold breach",
+    "completed successfully",
+    "failed after retries",
+    "initiated graceful recovery",
+    "rejected invalid request",
+    "triggered rebalance",
+    "exceeded soft limit",
+    "evicted stale entry",
+    "propagated context",
+    "acquired resource handle",
+    "flushed pending writes",
+    "rotated credentials",
+    "promoted fallback path",
+    "checkpointed at offset",
+    "enqueued background task",
+    "resolved after backoff",
+    "timed out waiting",
+    "received reset signal",
+    "applied migration",
+    "scheduled maintenance",
+];
+
+const METRICS: &[&str] = &[
+    "latency=2340ms",
+    "count=184302",
+    "ratio=0.94",
+    "depth=500",
+    "attempt=3/5",
+    "usage=85%",
+    "lag=500ms",
+    "size=1.2MB",
+    "rate=120/s",
+    "ttl=3600s",
+    "connections=980/1024",
+    "duration=30s",
+    "retries=3",
+    "offset=48291",
+    "queue_depth=1247",
+    "p99=450ms",
+    "batch_size=500",
+    "memory=2.4GB",
+    "iops=12000",
+    "threads=48",
+];
+
+const TARGETS: &[&str] = &[
+    "on orders table",
+    "for payments-api",
+    "from upstream host",
+    "in consumer group",
+    "on /data volume",
+    "for tenant af923c",
+    "to downstream service",
+    "across service boundary",
+    "in container cgroup",
+    "from replica node-3",
+    "on topic order.completed",
+    "for client session",
+    "in write-ahead log",
+    "on port 8443",
+    "from discovery endpoint",
+    "in ring buffer",
+    "for user session",
+    "on primary shard",
+    "to dead letter queue",
+    "from environment config",
+];
+
+const CONTEXTS: &[&str] = &[
+    "(retrying)",
+    "(non-blocking)",
+    "(scheduled)",
+    "(cached response)",
+    "(dark_launch=true)",
+    "(read-only)",
+    "(best-effort)",
+    "(idempotent)",
+    "(correlation_id=missing)",
+    "(circuit=open)",
+    "(degraded mode)",
+    "(cold start)",
+    "(warm path)",
+    "(fallback)",
+    "(async)",
+    "(batched)",
+    "(compressed)",
+    "(encrypted)",
+    "(sampled)",
+    "(throttled)",
+];
+
+const TEMPLATES: &[&str] = &[
+    "{component}: {action} {target} {context}",
+    "{component}: {action} [{metric}] {target}",
+    "{component}: {action} [{metric}]",
+    "{component}: {action} {target} [{metric}] {context}",
+];
+
+// ERROR and WARN entries get phrasing of their own, so they read (and
+// embed) differently from routine INFO chatter
+
+const FAILURES: &[&str] = &[
+    "request failed",
+    "write aborted",
+    "connection lost",
+    "commit rolled back",
+    "read failed",
+    "handshake failed",
+    "call timed out",
+    "retries exhausted",
+    "malformed response",
+    "operation rejected",
+    "lock acquisition failed",
+    "transaction aborted",
+];
+
+const CAUSES: &[&str] = &[
+    "connection refused",
+    "deadline exceeded",
+    "broken pipe",
+    "checksum mismatch",
+    "quorum not reached",
+    "out of memory",
+    "permission denied",
+    "upstream returned 503",
+    "certificate expired",
+    "unexpected end of stream",
+    "constraint violation",
+    "no healthy upstream",
+];
+
+const DEGRADATIONS: &[&str] = &[
+    "slow responses",
+    "retrying request",
+    "falling back",
+    "elevated error rate",
+    "nearing capacity",
+    "shedding load",
+    "stale read",
+    "backing off",
+    "skipped unhealthy replica",
+    "queue building up",
+];
+
+const ERROR_TEMPLATES: &[&str] = &[
+    "{component}: {failure} {target}: {cause}",
+    "{component}: {failure} {target} [{metric}], cause: {cause}",
+    "{component}: unrecoverable error, {cause} {context}",
+];
+
+const WARN_TEMPLATES: &[&str] = &[
+    "{component}: {degradation} {target} [{metric}]",
+    "{component}: {degradation} {target} {context}",
+];
+
+pub(crate) fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+pub(crate) fn slots(lists: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+    lists
+        .iter()
+        .map(|(name, items)| (name.to_string(), strings(items)))
+        .collect()
+}
+
+/// Slot lists and the templates messages are built from: each `{name}` in a
+/// template is filled with a random item of the slot of that name.
+///
+/// Vocabularies are layered: one only needs to give what it changes from
+/// the vocabulary beneath it (the built-in one, then the top-level one).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct VocabularyConfig {
+    /// Added to the slots beneath, replacing any of the same name
+    #[serde(default)]
+    pub slots: BTreeMap<String, Vec<String>>,
+    /// Replace the templates beneath, if any are given
+    #[serde(default)]
+    pub templates: Vec<String>,
+    /// Share of draws from a slot that pick each of these items (0.0 -
+    /// 1.0); the rest are spread evenly over the slot's other items. Items
+    /// not in the slot are drawn as if they were. Layered by slot, like
+    /// `slots`.
+    #[serde(default)]
+    pub shares: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Phrasing for entries at particular levels, layered over this
+    /// vocabulary
+    #[serde(default)]
+    pub levels: BTreeMap<LogLevel, VocabularyConfig>,
+}
+
+/// A service's vocabulary: the name of one under `vocabularies`, or its own
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum VocabularyRef {
+    Named(String),
+    Inline(VocabularyConfig),
+}
+
+impl VocabularyConfig {
+    /// The built-in infrastructure vocabulary, with distinct ERROR and WARN
+    /// phrasing
+    pub fn builtin() -> Self {
+        Self {
+            slots: slots(&[
+                ("component", COMPONENTS),
+                ("action", ACTIONS),
+                ("metric", METRICS),
+                ("target", TARGETS),
+                ("context", CONTEXTS),
+            ]),
+            templates: strings(TEMPLATES),
+            shares: BTreeMap::new(),
+            levels: BTreeMap::from([
+                (
+                    LogLevel::Error,
+                    Self {
+                        slots: slots(&[("failure", FAILURES), ("cause", CAUSES)]),
+                        templates: strings(ERROR_TEMPLATES),
+                        shares: BTreeMap::new(),
+                        levels: BTreeMap::new(),
+                    },
+                ),
+                (
+                    LogLevel::Warn,
+                    Self {
+                        slots: slots(&[("degradation", DEGRADATIONS)]),
+                        templates: strings(WARN_TEMPLATES),
+                        shares: BTreeMap::new(),
+                        levels: BTreeMap::new(),
+                    },
+                ),
+            ]),
+        }
+    }
+
+    /// This vocabulary with whatever it leaves out taken from `base`
+    pub fn over(&self, base: &Self) -> Self {
+        let mut slots = base.slots.clone();
+        slots.extend(self.slots.clone());
+        let mut shares = base.shares.clone();
+        shares.extend(self.shares.clone());
+        let templates = if self.templates.is_empty() {
+            base.templates.clone()
+        } else {
+            self.templates.clone()
+        };
+        let mut levels = base.levels.clone();
+        for (level, vocabulary) in &self.levels {
+            let layered = match levels.get(level) {
+                Some(beneath) => vocabulary.over(beneath),
+                None => vocabulary.clone(),
+            };
+            levels.insert(level.clone(), layered);
+        }
+        Self {
+            slots,
+            templates,
+            shares,
+            levels,
+        }
+    }
+
+    /// The vocabulary for entries at `level`: its own phrasing layered over
+    /// this one, or just this one
+    pub fn for_level(&self, level: &LogLevel) -> Self {
+        let general = Self {
+            levels: BTreeMap::new(),
+            ..self.clone()
+        };
+        match self.levels.get(level) {
+            Some(vocabulary) => Self {
+                levels: BTreeMap::new(),
+                ..vocabulary.over(&general)
+            },
+            None => general,
+        }
+    }
+
+    /// Fill a random template. Placeholders naming a missing or empty slot
+    /// are left as they are.
+    pub fn generate(&self, rng: &mut impl Rng) -> String {
+        if self.templates.is_empty() {
+            return String::new();
+        }
+        let template = &self.templates[rng.gen_range(0..self.templates.len())];
+
+        let mut message = String::with_capacity(template.len() * 2);
+        let mut rest = template.as_str();
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}').map(|i| open + i) else {
+                break;
+            };
+            message.push_str(&rest[..open]);
+            match self.pick(&rest[open + 1..close], rng) {
+                Some(item) => message.push_str(item),
+                None => message.push_str(&rest[open..=close]),
+            }
+            rest = &rest[close + 1..];
+        }
+        message.push_str(rest);
+        message
+    }
+
+    /// An item of `slot`, drawn by its `shares` if it has any
+    fn pick(&self, slot: &str, rng: &mut impl Rng) -> Option<&str> {
+        let items = self.slots.get(slot).map_or(&[][..], Vec::as_slice);
+        let Some(shares) = self.shares.get(slot).filter(|shares| !shares.is_empty()) else {
+            return items.choose(rng).map(String::as_str);
+        };
+        let rest: Vec<&String> = items
+            .iter()
+            .filter(|item| !shares.contains_key(*item))
+            .collect();
+        // shares adding up to more than everything, or with nothing else in
+        // the slot, are scaled to make up all of it
+        let total: f64 = shares.values().map(|share| share.max(0.0)).sum();
+        let scale = if total > 1.0 || (rest.is_empty() && total > 0.0) {
+            total.recip()
+        } else {
+            1.0
+        };
+        let mut roll: f64 = rng.r#gen();
+        for (item, share) in shares {
+            roll -= share.max(0.0) * scale;
+            if roll < 0.0 {
+                return Some(item);
+            }
+        }
+        rest.choose(rng)
+            .copied()
+            .or_else(|| shares.keys().next_back())
+            .map(String::as_str)
+    }
+
+    /// Generate `size` unique messages, or as many as the vocabulary allows.
+    /// Level phrasing is left out; see [`Self::for_level`].
+    pub fn build_pool(&self, rng: &mut impl Rng, size: usize) -> Vec<String> {
+        // kept in draw order too, so seeded runs get the same pool
+        let mut seen = std::collections::HashSet::with_capacity(size);
+        let mut pool = Vec::with_capacity(size);
+        // give up once new draws stop turning up unseen messages
+        let mut misses = 0;
+        while pool.len() < size && misses < 1000 {
+            let message = self.generate(rng);
+            if seen.insert(message.clone()) {
+                pool.push(message);
+                misses = 0;
+            } else {
+                misses += 1;
+            }
+        }
+        if pool.len() < size {
+            warn!(
+                "Vocabulary only produced {} of {size} unique messages",
+                pool.len()
+            );
+        }
+        pool
+    }
+}
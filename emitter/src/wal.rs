@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::log_entry::LogEntry;
+
+fn default_memory_entries() -> usize {
+    10_000
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WalConfig {
+    /// File that overflowing entries are appended to, one JSON entry per line
+    pub path: PathBuf,
+    /// Entries held in memory ahead of the buffer before spilling to disk
+    #[serde(default = "default_memory_entries")]
+    pub memory_entries: usize,
+}
+
+/// An append-only log of entries that didn't fit in memory, read back in
+/// order as the buffer frees up
+struct Wal {
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    /// Entries appended but not yet read back
+    pending: usize,
+    dirty: bool,
+}
+
+impl Wal {
+    async fn open(config: &WalConfig) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)
+            .await?;
+        let mut reader = BufReader::new(File::open(&config.path).await?);
+
+        // anything left over from an earlier run is replayed first
+        let mut pending = 0;
+        let mut line = String::new();
+        while reader.read_line(&mut line).await? > 0 {
+            pending += 1;
+            line.clear();
+        }
+        reader.rewind().await?;
+
+        Ok(Self {
+            writer: BufWriter::new(file),
+            reader,
+            pending,
+            dirty: false,
+        })
+    }
+
+    async fn append(&mut self, entry: &LogEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.writer.write_all(&line).await?;
+        self.pending += 1;
+        self.dirty = true;
+        Ok(())
+    }
+
+    async fn next(&mut self) -> std::io::Result<Option<LogEntry>> {
+        if self.pending == 0 {
+            return Ok(None);
+        }
+        if self.dirty {
+            self.writer.flush().await?;
+            self.dirty = false;
+        }
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            self.pending = 0;
+            return Ok(None);
+        }
+        self.pending -= 1;
+
+        // start the file over once it has been fully read back
+        if self.pending == 0 {
+            self.writer.get_ref().set_len(0).await?;
+            self.reader.rewind().await?;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(e) => {
+                // e.g. a line cut short when an earlier run was killed
+                warn!("Skipping unreadable WAL entry: {e}");
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Forward entries from `rx` to `tx`, appending them to the WAL instead
+/// whenever `tx` is full, so a slow buffer costs disk space rather than
+/// memory or backpressure on the emitters. Entries keep their order: once
+/// spilling starts, new entries go to disk until it has been drained.
+pub async fn spill(
+    config: WalConfig,
+    mut rx: mpsc::Receiver<LogEntry>,
+    tx: mpsc::Sender<LogEntry>,
+) -> std::io::Result<()> {
+    let mut wal = Wal::open(&config).await?;
+    if wal.pending > 0 {
+        info!(
+            "Replaying {} entries from {}",
+            wal.pending,
+            config.path.display()
+        );
+    }
+
+    loop {
+        if wal.pending == 0 {
+            let Some(entry) = rx.recv().await else {
+                return Ok(());
+            };
+            match tx.try_send(entry) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(entry)) => {
+                    warn!("Buffer full, spilling entries to {}", config.path.display());
+                    wal.append(&entry).await?;
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => return Ok(()),
+            }
+            continue;
+        }
+
+        tokio::select! {
+            entry = rx.recv() => match entry {
+                Some(entry) => wal.append(&entry).await?,
+                None => break,
+            },
+            permit = tx.reserve() => {
+                let Ok(permit) = permit else {
+                    return Ok(());
+                };
+                if let Some(entry) = wal.next().await? {
+                    permit.send(entry);
+                }
+            }
+        }
+    }
+
+    // the emitters are done; hand over whatever is still on disk
+    while wal.pending > 0 {
+        if let Some(entry) = wal.next().await?
+            && tx.send(entry).await.is_err()
+        {
+            break;
+        }
+    }
+    Ok(())
+}